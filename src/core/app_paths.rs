@@ -1,9 +1,260 @@
+use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 pub const APP_DIR_NAME: &str = "SGLoader-v2";
 
-#[cfg(target_os = "windows")]
+/// Overrides the data dir for this run (takes precedence over the relocation pointer).
+const DATA_DIR_ENV: &str = "SGLOADER_DATA_DIR";
+const RELOCATION_POINTER_FILE: &str = "data_dir.txt";
+
+/// A probe write slower than this means something (sync client, AV) is throttling the folder.
+const SLOW_WRITE_THRESHOLD: Duration = Duration::from_secs(2);
+
+/// Where launcher data lives: `SGLOADER_DATA_DIR`, then a user-chosen location, then the default.
 pub fn data_dir() -> Result<PathBuf, String> {
+    if let Some(dir) = std::env::var_os(DATA_DIR_ENV).filter(|v| !v.is_empty()) {
+        return Ok(PathBuf::from(dir));
+    }
+    if let Some(dir) = relocated_data_dir() {
+        return Ok(dir);
+    }
+    default_data_dir()
+}
+
+/// True when the user moved the data dir away from the default location.
+pub fn data_dir_relocated() -> bool {
+    relocated_data_dir().is_some()
+}
+
+fn relocated_data_dir() -> Option<PathBuf> {
+    let raw = fs::read_to_string(relocation_pointer_path()?).ok()?;
+    let trimmed = raw.trim();
+    (!trimmed.is_empty()).then(|| PathBuf::from(trimmed))
+}
+
+/// The pointer lives outside the data dir (and outside roaming/synced profile folders),
+/// so it survives the data dir being unwritable.
+fn relocation_pointer_path() -> Option<PathBuf> {
+    #[cfg(target_os = "windows")]
+    {
+        let local = std::env::var_os("LOCALAPPDATA")?;
+        Some(Path::new(&local).join(APP_DIR_NAME).join(RELOCATION_POINTER_FILE))
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        directories::ProjectDirs::from("com", "AZERBAIJAN-TECH", "SGLoader V2")
+            .map(|dirs| dirs.config_dir().join(RELOCATION_POINTER_FILE))
+    }
+}
+
+/// Folders in the data dir that are downloaded or unpacked again on demand, so moving the
+/// data dir does not copy them.
+pub(crate) const REDOWNLOADABLE_DIRS: &[&str] = &[
+    "engines",
+    "content",
+    "content_overlay_cache",
+    "content_blob_cache",
+    "loader",
+];
+
+/// Shown failures of a data dir copy; the rest are counted.
+const MAX_REPORTED_COPY_FAILURES: usize = 5;
+
+/// Points the launcher at `target` (or back to the default with `None`). Everything except
+/// re-downloadable caches is copied over (settings, accounts, patches, resource packs, logs);
+/// files already present in `target` are kept. If anything fails to copy, the pointer is not
+/// written and the failures are returned. Takes effect after a restart.
+pub fn relocate_data_dir(target: Option<&Path>) -> Result<(), String> {
+    crate::core::instance_lock::ensure_writable()?;
+    let pointer = relocation_pointer_path()
+        .ok_or_else(|| "не удалось определить, где хранить путь к каталогу данных".to_string())?;
+
+    let Some(target) = target else {
+        return match fs::remove_file(&pointer) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(format!("удаление {:?}: {e}", pointer)),
+        };
+    };
+
+    let health = check_data_dir(target);
+    if let Some(err) = health.write_error {
+        return Err(err);
+    }
+
+    if let Ok(current) = data_dir()
+        && current != target
+        && current.exists()
+    {
+        if target.starts_with(&current) {
+            return Err("новый каталог данных не может быть внутри текущего".to_string());
+        }
+        let mut failures = Vec::new();
+        copy_data_tree(&current, target, true, &mut failures);
+        if !failures.is_empty() {
+            let mut msg = format!(
+                "не удалось скопировать {} объект(ов) в новый каталог данных, перенос отменён:",
+                failures.len()
+            );
+            for failure in failures.iter().take(MAX_REPORTED_COPY_FAILURES) {
+                msg.push_str(&format!("\n- {failure}"));
+            }
+            if failures.len() > MAX_REPORTED_COPY_FAILURES {
+                msg.push_str(&format!(
+                    "\n… и ещё {}",
+                    failures.len() - MAX_REPORTED_COPY_FAILURES
+                ));
+            }
+            return Err(msg);
+        }
+    }
+
+    if let Some(parent) = pointer.parent() {
+        create_dir(parent, "каталог указателя")?;
+    }
+    fs::write(&pointer, target.to_string_lossy().as_bytes())
+        .map_err(|e| describe_io_error("запись", &pointer, &e))
+}
+
+/// Copies `src` into `dst` recursively, skipping files that already exist there. At the top
+/// level the instance lock and re-downloadable caches are left out.
+fn copy_data_tree(src: &Path, dst: &Path, top_level: bool, failures: &mut Vec<String>) {
+    if let Err(e) = create_dir(dst, "каталог данных") {
+        failures.push(e);
+        return;
+    }
+    let entries = match fs::read_dir(src) {
+        Ok(entries) => entries,
+        Err(e) => {
+            failures.push(describe_io_error("чтение", src, &e));
+            return;
+        }
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        if top_level
+            && (crate::core::instance_lock::is_lock_file(&name)
+                || REDOWNLOADABLE_DIRS.iter().any(|d| name == *d))
+        {
+            continue;
+        }
+        let path = entry.path();
+        let target = dst.join(&name);
+        match entry.file_type() {
+            Ok(t) if t.is_dir() => copy_data_tree(&path, &target, false, failures),
+            Ok(t) if t.is_file() => {
+                if target.exists() {
+                    continue;
+                }
+                if let Err(e) = fs::copy(&path, &target) {
+                    failures.push(describe_io_error("копирование", &target, &e));
+                }
+            }
+            Ok(_) => {}
+            Err(e) => failures.push(describe_io_error("чтение", &path, &e)),
+        }
+    }
+}
+
+/// Result of probing a data dir candidate for problems.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DataDirHealth {
+    pub path: PathBuf,
+    /// Name of the sync client whose folder contains `path`.
+    pub cloud_sync: Option<&'static str>,
+    pub write_error: Option<String>,
+    /// Set when the probe write succeeded but took suspiciously long.
+    pub slow_write: Option<Duration>,
+}
+
+impl DataDirHealth {
+    pub fn has_issues(&self) -> bool {
+        self.cloud_sync.is_some() || self.write_error.is_some() || self.slow_write.is_some()
+    }
+}
+
+/// Creates `dir` and writes/removes a probe file, timing it.
+pub fn check_data_dir(dir: &Path) -> DataDirHealth {
+    let mut health = DataDirHealth {
+        path: dir.to_path_buf(),
+        cloud_sync: cloud_sync_provider(dir),
+        ..Default::default()
+    };
+
+    if let Err(e) = create_dir(dir, "каталог данных") {
+        health.write_error = Some(e);
+        return health;
+    }
+
+    let probe = dir.join(".write_probe");
+    let started = Instant::now();
+    match fs::write(&probe, b"probe") {
+        Ok(()) => {
+            let _ = fs::remove_file(&probe);
+            let elapsed = started.elapsed();
+            if elapsed >= SLOW_WRITE_THRESHOLD {
+                health.slow_write = Some(elapsed);
+            }
+        }
+        Err(e) => health.write_error = Some(describe_io_error("запись", &probe, &e)),
+    }
+    health
+}
+
+/// Detects folders managed by OneDrive (the usual cause of locked or throttled writes).
+pub fn cloud_sync_provider(path: &Path) -> Option<&'static str> {
+    for var in ["OneDrive", "OneDriveConsumer", "OneDriveCommercial"] {
+        if let Some(root) = std::env::var_os(var).filter(|v| !v.is_empty())
+            && path.starts_with(Path::new(&root))
+        {
+            return Some("OneDrive");
+        }
+    }
+    path.components()
+        .any(|c| {
+            c.as_os_str()
+                .to_string_lossy()
+                .to_ascii_lowercase()
+                .starts_with("onedrive")
+        })
+        .then_some("OneDrive")
+}
+
+/// `fs::create_dir_all` with an error message that explains the likely cause.
+pub fn create_dir(path: &Path, what: &str) -> Result<(), String> {
+    fs::create_dir_all(path).map_err(|e| describe_io_error(&format!("mkdir {what}"), path, &e))
+}
+
+/// Formats an io error for `path`, adding a hint when access is denied in a folder
+/// that is synced by OneDrive or likely protected by Controlled Folder Access.
+pub fn describe_io_error(action: &str, path: &Path, e: &std::io::Error) -> String {
+    let base = format!("{action} {:?}: {e}", path);
+    if e.kind() != std::io::ErrorKind::PermissionDenied {
+        return base;
+    }
+
+    if let Some(provider) = cloud_sync_provider(path) {
+        return format!(
+            "{base}\nКаталог данных находится в папке {provider}: синхронизация блокирует файлы. \
+             Перенесите каталог данных в другое место (Настройки → Игра → Каталог данных)."
+        );
+    }
+
+    if cfg!(target_os = "windows") {
+        return format!(
+            "{base}\nЗапись запрещена. Возможно, включён «Контролируемый доступ к папкам» Защитника Windows: \
+             разрешите SGLoader-V2.exe в настройках защиты от программ-шантажистов \
+             или перенесите каталог данных (Настройки → Игра → Каталог данных)."
+        );
+    }
+
+    format!("{base}\nНет прав на запись. Проверьте владельца каталога или перенесите каталог данных.")
+}
+
+#[cfg(target_os = "windows")]
+fn default_data_dir() -> Result<PathBuf, String> {
     let appdata =
         std::env::var("APPDATA").map_err(|_| "APPDATA не найден (Windows)".to_string())?;
     Ok(Path::new(&appdata).join(APP_DIR_NAME))
@@ -39,7 +290,7 @@ pub fn open_in_file_manager(path: &Path) -> Result<(), String> {
 }
 
 #[cfg(not(target_os = "windows"))]
-pub fn data_dir() -> Result<PathBuf, String> {
+fn default_data_dir() -> Result<PathBuf, String> {
     use directories::ProjectDirs;
 
     ProjectDirs::from("com", "AZERBAIJAN-TECH", "SGLoader V2")
@@ -1,11 +1,44 @@
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Temp files untouched for this long belong to no running download or build.
+pub const ORPHANED_TEMP_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// How often `prune_expired_data` runs while the launcher is open.
+pub const RETENTION_PRUNE_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// What `prune_expired_data` removed.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RetentionSweep {
+    pub log_files: u64,
+    pub log_bytes: u64,
+    pub history_entries: usize,
+}
+
+/// What `remove_orphaned_temp_files` removed.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TempSweep {
+    pub files: u64,
+    pub bytes: u64,
+}
 
 pub fn clear_engines_cache(data_dir: &Path) -> Result<(), String> {
+    crate::core::instance_lock::ensure_writable()?;
     clear_dir_if_exists(data_dir.join("engines"), "движки")
 }
 
+/// Removes a single installed engine version so the next connect downloads it again.
+pub fn clear_engine_version(data_dir: &Path, version: &str) -> Result<(), String> {
+    crate::core::instance_lock::ensure_writable()?;
+    clear_dir_if_exists(
+        crate::client_install::engine_dir(data_dir, version),
+        &format!("движок {version}"),
+    )
+}
+
 pub fn clear_server_content_cache(data_dir: &Path) -> Result<(), String> {
+    crate::core::instance_lock::ensure_writable()?;
     clear_dir_if_exists(data_dir.join("content"), "контент серверов")?;
     clear_dir_if_exists(
         data_dir.join("content_overlay_cache"),
@@ -15,6 +48,181 @@ pub fn clear_server_content_cache(data_dir: &Path) -> Result<(), String> {
     Ok(())
 }
 
+/// Removes temp files left in the launcher's cache folders by crashed or killed downloads and
+/// builds (`*.tmp.*` zips, `*.json.tmp`, unfinished blobs) that were not written to for at
+/// least `min_age`. Patches, resource packs and anything else the user put in the data dir are
+/// not touched; files that cannot be removed are skipped. Blocking.
+pub fn remove_orphaned_temp_files(data_dir: &Path, min_age: Duration) -> TempSweep {
+    let mut sweep = crate::acz_content::remove_stale_blob_temps(
+        &crate::acz_content::blob_cache_root(data_dir),
+    );
+    let mut dirs: Vec<PathBuf> = crate::app_paths::REDOWNLOADABLE_DIRS
+        .iter()
+        .filter(|name| **name != "content_blob_cache")
+        .map(|name| data_dir.join(name))
+        .collect();
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                dirs.push(entry.path());
+                continue;
+            }
+            if !file_type.is_file() || !is_temp_file_name(&entry.file_name().to_string_lossy()) {
+                continue;
+            }
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            let stale = meta
+                .modified()
+                .ok()
+                .and_then(|t| t.elapsed().ok())
+                .is_some_and(|age| age >= min_age);
+            if stale && fs::remove_file(entry.path()).is_ok() {
+                sweep.files += 1;
+                sweep.bytes += meta.len();
+            }
+        }
+    }
+    sweep
+}
+
+/// Removes orphaned temp files; anything removed is also reported in the tasks panel.
+/// Blocking.
+pub fn sweep_temp_files(data_dir: &Path) -> Result<TempSweep, String> {
+    // Another instance's downloads may still own these files.
+    crate::core::instance_lock::ensure_writable()?;
+    let sweep = remove_orphaned_temp_files(data_dir, ORPHANED_TEMP_AGE);
+    if sweep.files > 0 {
+        let task = crate::tasks::register(
+            crate::tasks::TaskKind::CacheCleanup,
+            "Удаление временных файлов",
+            false,
+        );
+        task.set_detail(format!(
+            "удалено временных файлов: {}, освобождено {}",
+            sweep.files,
+            crate::disk_space::format_mib(sweep.bytes)
+        ));
+        task.finish(&Ok::<(), String>(()));
+    }
+    Ok(sweep)
+}
+
+/// Removes logs and play history older than the retention windows; a window of 0 keeps
+/// everything. Logs go by modification time, Marsey pipe dumps as whole attempt folders.
+/// Blocking.
+pub fn prune_expired_data(
+    data_dir: &Path,
+    retention: &crate::settings::RetentionSettings,
+) -> Result<RetentionSweep, String> {
+    crate::core::instance_lock::ensure_writable()?;
+    let mut sweep = RetentionSweep::default();
+    if retention.logs_days > 0 {
+        let max_age = days(retention.logs_days);
+        let logs = data_dir.join("logs");
+        prune_old_entries(&logs, max_age, &mut sweep);
+        prune_old_entries(&logs.join("marsey-pipes"), max_age, &mut sweep);
+    }
+    if retention.history_days > 0 {
+        sweep.history_entries = crate::last_server::prune_history(retention.history_days)?;
+    }
+    Ok(sweep)
+}
+
+/// Applies the retention settings; registers a task only when something was removed so
+/// the periodic run does not clutter the tasks panel. Blocking.
+pub fn prune_with_saved_retention(data_dir: &Path) -> Result<(), String> {
+    let retention = crate::settings::load_settings().unwrap_or_default().retention;
+    let sweep = prune_expired_data(data_dir, &retention)?;
+    if sweep.log_files == 0 && sweep.history_entries == 0 {
+        return Ok(());
+    }
+    let task = crate::tasks::register(
+        crate::tasks::TaskKind::CacheCleanup,
+        "Удаление старых логов и истории",
+        false,
+    );
+    task.set_detail(format!(
+        "логов: {} ({}), записей истории: {}",
+        sweep.log_files,
+        crate::disk_space::format_mib(sweep.log_bytes),
+        sweep.history_entries
+    ));
+    task.finish(&Ok::<(), String>(()));
+    Ok(())
+}
+
+/// Runs `prune_with_saved_retention` now and then every `RETENTION_PRUNE_INTERVAL`, for as
+/// long as the launcher is open.
+pub async fn prune_expired_data_periodically() {
+    loop {
+        if let Ok(data_dir) = crate::app_paths::data_dir() {
+            let _ =
+                tokio::task::spawn_blocking(move || prune_with_saved_retention(&data_dir)).await;
+        }
+        tokio::time::sleep(RETENTION_PRUNE_INTERVAL).await;
+    }
+}
+
+fn days(n: u32) -> Duration {
+    Duration::from_secs(u64::from(n) * 24 * 60 * 60)
+}
+
+/// Removes files and folders directly in `dir` not modified for `max_age`; the
+/// `marsey-pipes` folder itself is left for the second pass.
+fn prune_old_entries(dir: &Path, max_age: Duration, sweep: &mut RetentionSweep) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        let expired = meta
+            .modified()
+            .ok()
+            .and_then(|t| t.elapsed().ok())
+            .is_some_and(|age| age >= max_age);
+        if !expired || entry.file_name() == "marsey-pipes" {
+            continue;
+        }
+        let path = entry.path();
+        let (removed, bytes) = if meta.is_dir() {
+            let bytes = dir_size(&path);
+            (fs::remove_dir_all(&path).is_ok(), bytes)
+        } else {
+            (fs::remove_file(&path).is_ok(), meta.len())
+        };
+        if removed {
+            sweep.log_files += 1;
+            sweep.log_bytes += bytes;
+        }
+    }
+}
+
+fn dir_size(dir: &Path) -> u64 {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|e| e.metadata().ok())
+                .map(|m| if m.is_dir() { 0 } else { m.len() })
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+fn is_temp_file_name(name: &str) -> bool {
+    name.contains(".tmp.") || name.ends_with(".tmp")
+}
+
 fn clear_dir_if_exists(path: PathBuf, label: &str) -> Result<(), String> {
     match fs::remove_dir_all(&path) {
         Ok(()) => Ok(()),
@@ -1,8 +1,20 @@
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 
+/// Error returned by work stopped through a `CancelFlag`; callers tell it apart from real
+/// failures with `is_cancellation`.
+pub const CANCELLED: &str = "отменено";
+
+pub fn is_cancellation(err: &str) -> bool {
+    err == CANCELLED
+}
+
 #[derive(Clone, Debug)]
-pub struct CancelFlag(Arc<AtomicBool>);
+pub struct CancelFlag {
+    flag: Arc<AtomicBool>,
+    /// Set for a `child`: cancelling the parent cancels this flag too.
+    parent: Option<Box<CancelFlag>>,
+}
 
 impl Default for CancelFlag {
     fn default() -> Self {
@@ -12,20 +24,32 @@ impl Default for CancelFlag {
 
 impl CancelFlag {
     pub fn new() -> Self {
-        Self(Arc::new(AtomicBool::new(false)))
+        Self {
+            flag: Arc::new(AtomicBool::new(false)),
+            parent: None,
+        }
+    }
+
+    /// A flag for one part of the work: it trips when this one does, and cancelling it
+    /// stops only the work it was handed to.
+    pub fn child(&self) -> Self {
+        Self {
+            flag: Arc::new(AtomicBool::new(false)),
+            parent: Some(Box::new(self.clone())),
+        }
     }
 
     pub fn cancel(&self) {
-        self.0.store(true, Ordering::SeqCst);
+        self.flag.store(true, Ordering::SeqCst);
     }
 
     pub fn is_cancelled(&self) -> bool {
-        self.0.load(Ordering::SeqCst)
+        self.flag.load(Ordering::SeqCst) || self.parent.as_ref().is_some_and(|p| p.is_cancelled())
     }
 
     pub fn check(&self) -> Result<(), String> {
         if self.is_cancelled() {
-            Err("отменено".to_string())
+            Err(CANCELLED.to_string())
         } else {
             Ok(())
         }
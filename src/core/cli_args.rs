@@ -0,0 +1,57 @@
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Launcher command line for shortcuts: `--tab home|news|settings`, `--open-patches` and the
+/// developer toggle `--print-env`. Unknown arguments are ignored so an old shortcut still works.
+#[derive(Debug, Clone, Default)]
+struct StartupArgs {
+    /// Main tab key as given; the UI ignores values it does not know.
+    tab: Option<String>,
+    open_patches: bool,
+    print_env: bool,
+}
+
+fn startup_args() -> &'static StartupArgs {
+    static ARGS: OnceLock<StartupArgs> = OnceLock::new();
+    ARGS.get_or_init(|| parse(std::env::args().skip(1)))
+}
+
+/// Main tab requested on the command line. Only the first call sees it, so the choice does
+/// not stick after the user switches tabs.
+pub fn take_startup_tab() -> Option<&'static str> {
+    static TAKEN: AtomicBool = AtomicBool::new(false);
+    if TAKEN.swap(true, Ordering::Relaxed) {
+        return None;
+    }
+    startup_args().tab.as_deref()
+}
+
+/// Whether the patches section was requested; true for the first call only.
+pub fn take_open_patches() -> bool {
+    static TAKEN: AtomicBool = AtomicBool::new(false);
+    startup_args().open_patches && !TAKEN.swap(true, Ordering::Relaxed)
+}
+
+/// Whether launch logs should list the loader's full PATH and environment.
+pub fn print_env() -> bool {
+    startup_args().print_env
+}
+
+fn parse(mut args: impl Iterator<Item = String>) -> StartupArgs {
+    let mut out = StartupArgs::default();
+    while let Some(arg) = args.next() {
+        if let Some(value) = arg.strip_prefix("--tab=") {
+            out.tab = Some(value.to_ascii_lowercase());
+        } else if arg == "--tab" {
+            out.tab = args.next().map(|v| v.to_ascii_lowercase());
+        } else if arg == "--open-patches" {
+            out.open_patches = true;
+        } else if arg == "--print-env" {
+            out.print_env = true;
+        }
+    }
+    if out.open_patches {
+        out.tab = Some("settings".to_string());
+    }
+    out
+}
@@ -1,5 +1,6 @@
 pub const STYLE: &str = include_str!("../../assets/style.css");
 pub const APP_TITLE: &str = "SGLoader V2";
+pub const APP_VERSION_LABEL: &str = "1.0.0-release";
 pub const WINDOW_SIZE: (f64, f64) = (1280.0, 720.0);
 pub const ASSETS_DIR: &str = "assets";
 pub const TASKBAR_ICON: &str = "taskbar.ico";
@@ -7,3 +8,7 @@ pub const TITLEBAR_ICON: &str = "titlebar.ico";
 
 // News server base URL (can be changed in code if needed).
 pub const NEWS_API_BASE_URL: &str = "https://vzzx.pw";
+
+// Launcher releases; the "Обновления лаунчера" feed and its changelogs come from here.
+pub const LAUNCHER_RELEASES_API_URL: &str =
+    "https://api.github.com/repos/AZERBAIJAN-TECH/SGLoader-V2/releases";
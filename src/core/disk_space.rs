@@ -0,0 +1,76 @@
+use std::path::Path;
+
+/// Headroom kept free on top of the estimate (temp files, logs, settings writes).
+const SAFETY_MARGIN: u64 = 64 * 1024 * 1024;
+
+/// Fails early with a readable message when the volume holding `dir` can't fit `required`
+/// bytes. If free space can't be determined the check is skipped.
+pub fn ensure_free_space(dir: &Path, required: u64, what: &str) -> Result<(), String> {
+    let Some(available) = available_bytes(dir) else {
+        return Ok(());
+    };
+    let needed = required.saturating_add(SAFETY_MARGIN);
+    if available >= needed {
+        return Ok(());
+    }
+    Err(format!(
+        "недостаточно места на диске для {what}: нужно ~{}, свободно {} ({}).\n\
+         Освободите место или очистите кэш (Настройки → Игра → «Очистить движки» / «Очистить контент серверов»), \
+         либо перенесите каталог данных на другой диск (Настройки → Игра → Каталог данных).",
+        format_mib(needed),
+        format_mib(available),
+        dir.display()
+    ))
+}
+
+pub(crate) fn format_mib(bytes: u64) -> String {
+    format!("{:.1} МБ", bytes as f64 / (1024.0 * 1024.0))
+}
+
+/// Walks up to the nearest existing directory; the target may not be created yet.
+fn existing_ancestor(path: &Path) -> Option<&Path> {
+    path.ancestors().find(|p| p.is_dir())
+}
+
+#[cfg(target_os = "windows")]
+pub fn available_bytes(path: &Path) -> Option<u64> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+    use windows::core::PCWSTR;
+
+    let dir = existing_ancestor(path)?;
+    let wide: Vec<u16> = dir
+        .as_os_str()
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+    let mut free: u64 = 0;
+    unsafe {
+        GetDiskFreeSpaceExW(
+            PCWSTR(wide.as_ptr()),
+            Some(&mut free as *mut u64),
+            None,
+            None,
+        )
+    }
+    .ok()?;
+    Some(free)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn available_bytes(path: &Path) -> Option<u64> {
+    // POSIX `df -P` output: header line, then "fs blocks used available capacity mount".
+    let dir = existing_ancestor(path)?;
+    let out = std::process::Command::new("df")
+        .arg("-Pk")
+        .arg(dir)
+        .output()
+        .ok()?;
+    if !out.status.success() {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&out.stdout);
+    let line = text.lines().nth(1)?;
+    let available_kib: u64 = line.split_whitespace().nth(3)?.parse().ok()?;
+    Some(available_kib.saturating_mul(1024))
+}
@@ -0,0 +1,98 @@
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+/// Delays between attempts; the scanner usually lets go within a second.
+const RETRY_DELAYS: [Duration; 4] = [
+    Duration::from_millis(100),
+    Duration::from_millis(300),
+    Duration::from_millis(700),
+    Duration::from_millis(1500),
+];
+
+#[cfg(target_os = "windows")]
+mod win_codes {
+    pub const ERROR_SHARING_VIOLATION: i32 = 32;
+    pub const ERROR_LOCK_VIOLATION: i32 = 33;
+    pub const ERROR_VIRUS_INFECTED: i32 = 225;
+    pub const ERROR_VIRUS_DELETED: i32 = 226;
+}
+
+/// Sharing or lock violation: the file is (probably) held open by an antivirus scanner that
+/// picked it up right after it was written. Plain access denied is a real permission problem.
+pub fn is_lock_error(e: &io::Error) -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        use win_codes::*;
+        matches!(
+            e.raw_os_error(),
+            Some(ERROR_SHARING_VIOLATION | ERROR_LOCK_VIOLATION)
+        )
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = e;
+        false
+    }
+}
+
+/// The antivirus explicitly flagged the file; retrying won't help.
+fn is_quarantine_error(e: &io::Error) -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        use win_codes::*;
+        matches!(
+            e.raw_os_error(),
+            Some(ERROR_VIRUS_INFECTED | ERROR_VIRUS_DELETED)
+        )
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = e;
+        false
+    }
+}
+
+/// Runs `op` (a rename or open right after a write), retrying a few times while a scanner
+/// holds the file.
+pub fn retry_locked<T>(mut op: impl FnMut() -> io::Result<T>) -> io::Result<T> {
+    let mut delays = RETRY_DELAYS.iter();
+    loop {
+        match op() {
+            Err(e) if is_lock_error(&e) => match delays.next() {
+                Some(delay) => std::thread::sleep(*delay),
+                None => return Err(e),
+            },
+            other => return other,
+        }
+    }
+}
+
+/// Formats an io error and, for lock/quarantine errors, explains the likely antivirus cause.
+pub fn describe_error(action: &str, path: &Path, e: &io::Error) -> String {
+    if is_quarantine_error(e) {
+        return format!(
+            "{action} {:?}: {e}\nАнтивирус заблокировал или удалил файл. {}",
+            path,
+            exclusion_hint()
+        );
+    }
+    if is_lock_error(e) {
+        let base = crate::app_paths::describe_io_error(action, path, e);
+        return format!(
+            "{base}\nФайл оставался занят после {} попыток — похоже, его держит антивирус. {}",
+            RETRY_DELAYS.len() + 1,
+            exclusion_hint()
+        );
+    }
+    format!("{action} {:?}: {e}", path)
+}
+
+fn exclusion_hint() -> String {
+    let dir = crate::app_paths::data_dir()
+        .map(|d| d.display().to_string())
+        .unwrap_or_else(|_| "каталог данных лаунчера".to_string());
+    format!("Добавьте {dir} в исключения антивируса и повторите подключение.")
+}
@@ -0,0 +1,88 @@
+use std::fs;
+use std::sync::OnceLock;
+
+const LOCK_FILE_NAME: &str = "launcher.lock";
+/// PID of the lock owner. Separate from the lock file: Windows does not let other
+/// processes read a locked range.
+const PID_FILE_NAME: &str = "launcher.pid";
+
+/// One launcher per data directory: later instances only browse servers, so two processes
+/// never write the same engine, content, blob cache or loader files.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InstanceMode {
+    /// This process owns the data directory.
+    Primary,
+    /// Another launcher owns it; `owner_pid` is what it wrote into the lock file.
+    BrowserOnly { owner_pid: Option<u32> },
+}
+
+struct Instance {
+    mode: InstanceMode,
+    /// Kept open for the lifetime of the process; dropping it would release the lock.
+    _file: Option<fs::File>,
+}
+
+static INSTANCE: OnceLock<Instance> = OnceLock::new();
+
+/// Takes the data directory lock on first call and reports the mode for this process.
+/// If the lock file cannot be created at all the launcher runs as primary, as before the
+/// lock existed; the data directory check reports the underlying problem.
+pub fn instance_mode() -> &'static InstanceMode {
+    &INSTANCE.get_or_init(acquire).mode
+}
+
+/// Errors out in browser-only mode; called before anything writes shared caches.
+pub fn ensure_writable() -> Result<(), String> {
+    match instance_mode() {
+        InstanceMode::Primary => Ok(()),
+        InstanceMode::BrowserOnly { .. } => Err(
+            "запущен другой экземпляр лаунчера с тем же каталогом данных — это окно только для просмотра серверов. Подключайтесь из первого окна или закройте его"
+                .to_string(),
+        ),
+    }
+}
+
+/// Files of this module, which belong to the running process and are not data to carry
+/// over when the data directory moves.
+pub fn is_lock_file(name: &std::ffi::OsStr) -> bool {
+    name == LOCK_FILE_NAME || name == PID_FILE_NAME
+}
+
+fn acquire() -> Instance {
+    let primary = |file| Instance {
+        mode: InstanceMode::Primary,
+        _file: file,
+    };
+    let Ok(data_dir) = crate::app_paths::data_dir() else {
+        return primary(None);
+    };
+    if crate::app_paths::create_dir(&data_dir, "данные").is_err() {
+        return primary(None);
+    }
+    let Ok(file) = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(data_dir.join(LOCK_FILE_NAME))
+    else {
+        return primary(None);
+    };
+
+    let pid_path = data_dir.join(PID_FILE_NAME);
+    match file.try_lock() {
+        Ok(()) => {
+            let _ = fs::write(&pid_path, std::process::id().to_string());
+            primary(Some(file))
+        }
+        Err(fs::TryLockError::WouldBlock) => Instance {
+            mode: InstanceMode::BrowserOnly {
+                owner_pid: fs::read_to_string(&pid_path)
+                    .ok()
+                    .and_then(|raw| raw.trim().parse().ok()),
+            },
+            _file: None,
+        },
+        // Filesystems without locking support: behave as before.
+        Err(fs::TryLockError::Error(_)) => primary(None),
+    }
+}
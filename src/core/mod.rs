@@ -1,6 +1,16 @@
 pub mod app_paths;
 pub mod cache_cleanup;
 pub mod cancel_flag;
+pub mod cli_args;
 pub mod constants;
+pub mod disk_space;
+pub mod fs_retry;
 pub mod hwid_cleanup;
+pub mod instance_lock;
 pub mod open_url;
+pub mod platform;
+pub mod process_priority;
+pub mod shutdown;
+pub mod startup_checks;
+pub mod tasks;
+pub mod wine;
@@ -1,19 +1,50 @@
+#[cfg(not(target_os = "windows"))]
 use std::process::Command;
 
+/// Returns `raw` as an absolute http(s) URL, or `None` for anything else. Links that come
+/// from servers go through this before being shown or opened: the shell would just as
+/// happily run a local file or a custom protocol handler.
+pub fn web_url(raw: &str) -> Option<String> {
+    let url = url::Url::parse(raw.trim()).ok()?;
+    if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+        return None;
+    }
+    Some(url.into())
+}
+
+/// Opens an http(s) link in the default browser; other URLs are ignored.
 pub fn open(url: &str) {
+    let Some(url) = web_url(url) else {
+        return;
+    };
+
     #[cfg(target_os = "windows")]
     {
-        let _ = Command::new("cmd").args(["/C", "start", "", url]).spawn();
+        use windows::Win32::Foundation::HWND;
+        use windows::Win32::UI::Shell::ShellExecuteW;
+        use windows::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL;
+        use windows::core::{HSTRING, PCWSTR, w};
+
+        let target = HSTRING::from(url.as_str());
+        unsafe {
+            ShellExecuteW(
+                HWND::default(),
+                w!("open"),
+                &target,
+                PCWSTR::null(),
+                PCWSTR::null(),
+                SW_SHOWNORMAL,
+            );
+        }
     }
 
     #[cfg(target_os = "macos")]
     {
-        let _ = Command::new("open").arg(url).spawn();
-        return;
+        let _ = Command::new("open").arg(&url).spawn();
     }
 
     #[cfg(all(unix, not(target_os = "macos")))]
     {
-        let _ = Command::new("xdg-open").arg(url).spawn();
+        let _ = Command::new("xdg-open").arg(&url).spawn();
     }
 }
@@ -0,0 +1,95 @@
+use std::sync::OnceLock;
+
+/// .NET runtime identifier of the machine, used for both SS14.Loader and engine builds so
+/// the two always match. Detected once per process.
+///
+/// On Windows this is the real CPU architecture, not the one the launcher was built for: an
+/// x64 launcher running under emulation on Windows on ARM still gets `win-arm64`.
+pub fn host_rid() -> &'static str {
+    static DETECTED: OnceLock<&'static str> = OnceLock::new();
+    DETECTED.get_or_init(detect)
+}
+
+/// Engine RIDs acceptable on this machine, best first.
+pub fn engine_rid_candidates() -> &'static [&'static str] {
+    match host_rid() {
+        "win-x64" => &["win-x64", "win-x86"],
+        "win-x86" => &["win-x86", "win-x64"],
+        "win-arm64" => &["win-arm64", "win-x64"],
+        "linux-x64" => &["linux-x64"],
+        "linux-arm64" => &["linux-arm64"],
+        "osx-x64" => &["osx-x64"],
+        "osx-arm64" => &["osx-arm64"],
+        _ => &[],
+    }
+}
+
+/// Two-letter language of the OS user interface (`ru`, `en`, ...), if it can be told.
+pub fn system_language() -> Option<String> {
+    let locale = system_locale()?;
+    let lang: String = locale
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    (lang.len() == 2).then_some(lang)
+}
+
+/// Locale name such as `ru-RU` (Windows) or `ru_RU.UTF-8` (POSIX).
+fn system_locale() -> Option<String> {
+    #[cfg(windows)]
+    {
+        use winreg::RegKey;
+        use winreg::enums::HKEY_CURRENT_USER;
+
+        if let Ok(name) = RegKey::predef(HKEY_CURRENT_USER)
+            .open_subkey(r"Control Panel\International")
+            .and_then(|key| key.get_value::<String, _>("LocaleName"))
+        {
+            return Some(name);
+        }
+    }
+
+    // POSIX order of precedence; also what Wine sets up from the host.
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|v| !v.is_empty() && v != "C" && v != "POSIX")
+}
+
+fn compiled_rid() -> &'static str {
+    match (std::env::consts::OS, std::env::consts::ARCH) {
+        ("windows", "x86_64") => "win-x64",
+        ("windows", "x86") => "win-x86",
+        ("windows", "aarch64") => "win-arm64",
+        ("linux", "x86_64") => "linux-x64",
+        ("linux", "aarch64") => "linux-arm64",
+        ("macos", "x86_64") => "osx-x64",
+        ("macos", "aarch64") => "osx-arm64",
+        _ => "win-x64",
+    }
+}
+
+#[cfg(windows)]
+fn detect() -> &'static str {
+    use winreg::RegKey;
+    use winreg::enums::HKEY_LOCAL_MACHINE;
+
+    // The system-wide value is not rewritten for emulated processes, unlike the
+    // PROCESSOR_ARCHITECTURE variable in our own environment.
+    let native: Option<String> = RegKey::predef(HKEY_LOCAL_MACHINE)
+        .open_subkey(r"SYSTEM\CurrentControlSet\Control\Session Manager\Environment")
+        .and_then(|key| key.get_value("PROCESSOR_ARCHITECTURE"))
+        .ok();
+    match native.as_deref().map(str::to_ascii_uppercase).as_deref() {
+        Some("ARM64") => "win-arm64",
+        Some("AMD64") => "win-x64",
+        Some("X86") => "win-x86",
+        _ => compiled_rid(),
+    }
+}
+
+#[cfg(not(windows))]
+fn detect() -> &'static str {
+    compiled_rid()
+}
@@ -0,0 +1,123 @@
+use std::process::Child;
+
+use crate::settings::ProcessPriority;
+
+/// Parses a CPU list like `0-3,6` into an affinity bitmask. Empty means "all CPUs" (`None`).
+pub fn parse_cpu_list(spec: &str) -> Result<Option<u64>, String> {
+    let spec = spec.trim();
+    if spec.is_empty() {
+        return Ok(None);
+    }
+
+    let mut mask: u64 = 0;
+    for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (from, to) = match part.split_once('-') {
+            Some((a, b)) => (parse_cpu(a)?, parse_cpu(b)?),
+            None => {
+                let n = parse_cpu(part)?;
+                (n, n)
+            }
+        };
+        if from > to {
+            return Err(format!("неверный диапазон ядер: {part}"));
+        }
+        for cpu in from..=to {
+            mask |= 1 << cpu;
+        }
+    }
+
+    if mask == 0 {
+        return Err("не выбрано ни одного ядра".to_string());
+    }
+    Ok(Some(mask))
+}
+
+fn parse_cpu(s: &str) -> Result<u32, String> {
+    let n: u32 = s
+        .trim()
+        .parse()
+        .map_err(|_| format!("неверный номер ядра: {s}"))?;
+    if n >= 64 {
+        return Err(format!("номер ядра вне диапазона 0-63: {n}"));
+    }
+    Ok(n)
+}
+
+/// Applies the configured priority class and CPU affinity to a freshly spawned game process.
+/// Windows only; elsewhere this is a no-op that reports why.
+pub fn apply(
+    child: &Child,
+    priority: ProcessPriority,
+    affinity: Option<u64>,
+) -> Result<(), String> {
+    if priority == ProcessPriority::Normal && affinity.is_none() {
+        return Ok(());
+    }
+    platform::apply(child, priority, affinity)
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::os::windows::io::AsRawHandle;
+    use std::process::Child;
+
+    use windows::Win32::Foundation::HANDLE;
+    use windows::Win32::System::Threading::{
+        ABOVE_NORMAL_PRIORITY_CLASS, BELOW_NORMAL_PRIORITY_CLASS, GetProcessAffinityMask,
+        HIGH_PRIORITY_CLASS, IDLE_PRIORITY_CLASS, NORMAL_PRIORITY_CLASS, SetPriorityClass,
+        SetProcessAffinityMask,
+    };
+
+    use crate::settings::ProcessPriority;
+
+    pub fn apply(
+        child: &Child,
+        priority: ProcessPriority,
+        affinity: Option<u64>,
+    ) -> Result<(), String> {
+        let handle = HANDLE(child.as_raw_handle() as isize);
+
+        let class = match priority {
+            ProcessPriority::Idle => IDLE_PRIORITY_CLASS,
+            ProcessPriority::BelowNormal => BELOW_NORMAL_PRIORITY_CLASS,
+            ProcessPriority::Normal => NORMAL_PRIORITY_CLASS,
+            ProcessPriority::AboveNormal => ABOVE_NORMAL_PRIORITY_CLASS,
+            ProcessPriority::High => HIGH_PRIORITY_CLASS,
+        };
+        unsafe { SetPriorityClass(handle, class) }
+            .map_err(|e| format!("не удалось задать приоритет процесса: {e}"))?;
+
+        if let Some(mask) = affinity {
+            // Drop CPUs this machine doesn't have instead of failing the whole call.
+            let mut process_mask = 0usize;
+            let mut system_mask = 0usize;
+            let mask = match unsafe {
+                GetProcessAffinityMask(handle, &mut process_mask, &mut system_mask)
+            } {
+                Ok(()) => mask as usize & system_mask,
+                Err(_) => mask as usize,
+            };
+            if mask == 0 {
+                return Err("выбранных ядер нет на этом компьютере".to_string());
+            }
+            unsafe { SetProcessAffinityMask(handle, mask) }
+                .map_err(|e| format!("не удалось задать привязку к ядрам: {e}"))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+mod platform {
+    use std::process::Child;
+
+    use crate::settings::ProcessPriority;
+
+    pub fn apply(
+        _child: &Child,
+        _priority: ProcessPriority,
+        _affinity: Option<u64>,
+    ) -> Result<(), String> {
+        Err("приоритет и привязка к ядрам поддерживаются только в Windows".to_string())
+    }
+}
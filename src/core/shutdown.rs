@@ -0,0 +1,86 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::cancel_flag::CancelFlag;
+
+/// How long closing the window may wait for cancelled work to wind down.
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(2);
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+struct Connects {
+    next_id: u64,
+    active: Vec<(u64, CancelFlag)>,
+}
+
+static CONNECTS: Mutex<Connects> = Mutex::new(Connects {
+    next_id: 0,
+    active: Vec::new(),
+});
+static LOG_WRITERS: Mutex<Vec<std::thread::JoinHandle<()>>> = Mutex::new(Vec::new());
+
+/// Keeps a connect pipeline visible to `shutdown` until dropped.
+pub struct ConnectGuard {
+    id: u64,
+}
+
+impl Drop for ConnectGuard {
+    fn drop(&mut self) {
+        let mut connects = CONNECTS.lock().unwrap_or_else(|e| e.into_inner());
+        connects.active.retain(|(id, _)| *id != self.id);
+    }
+}
+
+/// Registers a running connect (game preparation) so closing the window cancels it.
+pub fn track_connect(cancel: CancelFlag) -> ConnectGuard {
+    let mut connects = CONNECTS.lock().unwrap_or_else(|e| e.into_inner());
+    connects.next_id += 1;
+    let id = connects.next_id;
+    connects.active.push((id, cancel));
+    ConnectGuard { id }
+}
+
+/// Registers a log writer thread; `shutdown` waits for it so the tail of the log is written.
+pub fn track_log_writer(handle: std::thread::JoinHandle<()>) {
+    let mut writers = LOG_WRITERS.lock().unwrap_or_else(|e| e.into_inner());
+    writers.retain(|h| !h.is_finished());
+    writers.push(handle);
+}
+
+/// A game is being prepared (download, install, launch) right now.
+pub fn preparation_in_progress() -> bool {
+    !CONNECTS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .active
+        .is_empty()
+}
+
+/// Cancels running connects and tasks, stops the redial server and waits up to
+/// `SHUTDOWN_GRACE` for them and the connect log writers to finish. Blocking; runs off the
+/// UI thread after a confirmed close, before the app exits.
+pub fn shutdown() {
+    for (_, cancel) in CONNECTS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .active
+        .iter()
+    {
+        cancel.cancel();
+    }
+    crate::net::prefetch_queue::cancel_all();
+    crate::tasks::cancel_all();
+    crate::net::redial_pipe::reset_global_redial_pipe();
+
+    let deadline = Instant::now() + SHUTDOWN_GRACE;
+    while Instant::now() < deadline {
+        let writers_done = LOG_WRITERS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .all(|h| h.is_finished());
+        if !preparation_in_progress() && !crate::tasks::any_running() && writers_done {
+            break;
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
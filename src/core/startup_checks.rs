@@ -0,0 +1,154 @@
+use std::time::Duration;
+
+use crate::core::instance_lock::{self, InstanceMode};
+use crate::storage::hub_urls;
+use crate::{account_store, app_paths, ss14_loader};
+
+/// Hubs get a short look at startup; the server list fetch retries properly later.
+const HUB_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StartupCheck {
+    DataDir,
+    Loader,
+    Hub,
+    Token,
+    Wine,
+    Settings,
+    Instance,
+}
+
+/// One problem found at startup, phrased as what the user should do about it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StartupWarning {
+    pub check: StartupCheck,
+    pub message: String,
+}
+
+/// Runs the lightweight checks that would otherwise only fail mid-connect. Blocking.
+pub fn run_startup_checks() -> Vec<StartupWarning> {
+    let mut out = Vec::new();
+
+    if let InstanceMode::BrowserOnly { owner_pid } = instance_lock::instance_mode() {
+        let owner = owner_pid
+            .map(|pid| format!(" (PID {pid})"))
+            .unwrap_or_default();
+        out.push(StartupWarning {
+            check: StartupCheck::Instance,
+            message: format!(
+                "лаунчер уже запущен{owner} — это окно только для просмотра серверов: подключение, загрузки и очистка кэша недоступны"
+            ),
+        });
+    }
+
+    if let Some(notice) = crate::settings::take_repair_notice() {
+        out.push(StartupWarning {
+            check: StartupCheck::Settings,
+            message: notice,
+        });
+    }
+
+    match app_paths::data_dir() {
+        Ok(dir) => {
+            let health = app_paths::check_data_dir(&dir);
+            if let Some(err) = health.write_error {
+                out.push(StartupWarning {
+                    check: StartupCheck::DataDir,
+                    message: format!(
+                        "каталог данных недоступен для записи: {err}. Перенесите его в Настройки → Игра"
+                    ),
+                });
+            } else if let Some(provider) = health.cloud_sync {
+                out.push(StartupWarning {
+                    check: StartupCheck::DataDir,
+                    message: format!(
+                        "каталог данных лежит в папке {provider} — синхронизация может ломать кэш. Перенесите его в Настройки → Игра"
+                    ),
+                });
+            }
+
+            if let Err(e) = ss14_loader::loader_readiness(&dir) {
+                out.push(StartupWarning {
+                    check: StartupCheck::Loader,
+                    message: e,
+                });
+            }
+        }
+        Err(e) => out.push(StartupWarning {
+            check: StartupCheck::DataDir,
+            message: format!("каталог данных не определён: {e}"),
+        }),
+    }
+
+    if let Some(err) = probe_hubs() {
+        out.push(StartupWarning {
+            check: StartupCheck::Hub,
+            message: format!(
+                "ни один хаб не отвечает ({err}). Проверьте сеть или адреса хабов в настройках"
+            ),
+        });
+    }
+
+    if let Ok(logins) = account_store::load_saved_logins() {
+        let expired: Vec<String> = logins
+            .iter()
+            .filter(|l| l.token.is_time_expired())
+            .map(|l| l.display_name().to_string())
+            .collect();
+        if !expired.is_empty() {
+            out.push(StartupWarning {
+                check: StartupCheck::Token,
+                message: format!(
+                    "срок входа истёк: {} — войдите в аккаунт заново",
+                    expired.join(", ")
+                ),
+            });
+        }
+    }
+
+    if crate::wine::is_wine() {
+        out.push(StartupWarning {
+            check: StartupCheck::Wine,
+            message: "лаунчер запущен под Wine: токены входа защищены DPAPI Wine, который слабее Windows — не копируйте префикс с сохранёнными аккаунтами; очистка HWID отключена".to_string(),
+        });
+    }
+
+    out
+}
+
+/// Returns the last error if no configured hub answers at all.
+fn probe_hubs() -> Option<String> {
+    let client = match crate::launcher_mask::blocking_http_client_api() {
+        Ok(client) => client,
+        Err(e) => return Some(e),
+    };
+    let hubs = hub_urls::load_enabled_hub_urls();
+
+    // All hubs at once, so a dead one costs one timeout rather than one per hub.
+    let results: Vec<Result<(), String>> = std::thread::scope(|scope| {
+        let probes: Vec<_> = hubs
+            .iter()
+            .map(|base| {
+                let client = &client;
+                scope.spawn(move || {
+                    client
+                        .head(base)
+                        .timeout(HUB_PROBE_TIMEOUT)
+                        .send()
+                        // Any HTTP answer means the host is reachable; status is the list fetch's business.
+                        .map(|_| ())
+                        .map_err(|e| format!("{base}: {e}"))
+                })
+            })
+            .collect();
+        probes
+            .into_iter()
+            .map(|probe| probe.join().unwrap_or_else(|_| Err("проверка хаба упала".to_string())))
+            .collect()
+    });
+
+    if results.iter().any(Result::is_ok) {
+        return None;
+    }
+    results.into_iter().filter_map(Result::err).next_back()
+}
@@ -0,0 +1,205 @@
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+use crate::cancel_flag::CancelFlag;
+
+/// How many finished tasks are kept around so the panel can show their outcome.
+const MAX_FINISHED_TASKS: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskKind {
+    Prefetch,
+    CacheCleanup,
+    LoaderBuild,
+    Other,
+}
+
+impl TaskKind {
+    pub fn label_ru(self) -> &'static str {
+        match self {
+            TaskKind::Prefetch => "предзагрузка",
+            TaskKind::CacheCleanup => "очистка кэша",
+            TaskKind::LoaderBuild => "сборка loader",
+            TaskKind::Other => "задача",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TaskStatus {
+    Running,
+    Done,
+    Failed(String),
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskProgress {
+    Bytes { done: u64, total: Option<u64> },
+    Items { done: u64, total: Option<u64> },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskSnapshot {
+    pub id: u64,
+    pub kind: TaskKind,
+    pub label: String,
+    pub detail: Option<String>,
+    pub progress: Option<TaskProgress>,
+    pub status: TaskStatus,
+    pub cancellable: bool,
+    pub started_at: Instant,
+}
+
+struct TaskEntry {
+    snapshot: TaskSnapshot,
+    cancel: CancelFlag,
+}
+
+#[derive(Default)]
+struct Registry {
+    next_id: u64,
+    tasks: Vec<TaskEntry>,
+}
+
+static REGISTRY: OnceLock<Mutex<Registry>> = OnceLock::new();
+
+fn with_registry<T>(f: impl FnOnce(&mut Registry) -> T) -> T {
+    let m = REGISTRY.get_or_init(|| Mutex::new(Registry::default()));
+    let mut guard = m.lock().unwrap_or_else(|e| e.into_inner());
+    f(&mut guard)
+}
+
+/// Handle owned by the code doing the work. Dropping it without `finish` marks the task done.
+pub struct TaskHandle {
+    id: u64,
+    cancel: CancelFlag,
+    finished: bool,
+}
+
+/// Registers a long-running job so it shows up in the tasks panel.
+/// `cancellable` tells the UI whether the job actually checks its cancel flag.
+pub fn register(kind: TaskKind, label: impl Into<String>, cancellable: bool) -> TaskHandle {
+    let cancel = CancelFlag::new();
+    let label = label.into();
+    let id = with_registry(|r| {
+        r.next_id += 1;
+        let id = r.next_id;
+        r.tasks.push(TaskEntry {
+            snapshot: TaskSnapshot {
+                id,
+                kind,
+                label,
+                detail: None,
+                progress: None,
+                status: TaskStatus::Running,
+                cancellable,
+                started_at: Instant::now(),
+            },
+            cancel: cancel.clone(),
+        });
+        id
+    });
+
+    TaskHandle {
+        id,
+        cancel,
+        finished: false,
+    }
+}
+
+impl TaskHandle {
+    pub fn cancel_flag(&self) -> &CancelFlag {
+        &self.cancel
+    }
+
+    pub fn set_progress(&self, progress: TaskProgress) {
+        self.update(|s| s.progress = Some(progress));
+    }
+
+    pub fn set_detail(&self, detail: impl Into<String>) {
+        let detail = detail.into();
+        self.update(|s| s.detail = Some(detail));
+    }
+
+    pub fn finish(mut self, result: &Result<impl Sized, String>) {
+        let status = match result {
+            Ok(_) => TaskStatus::Done,
+            Err(_) if self.cancel.is_cancelled() => TaskStatus::Cancelled,
+            Err(e) => TaskStatus::Failed(e.clone()),
+        };
+        self.set_final_status(status);
+    }
+
+    fn update(&self, f: impl FnOnce(&mut TaskSnapshot)) {
+        with_registry(|r| {
+            if let Some(t) = r.tasks.iter_mut().find(|t| t.snapshot.id == self.id) {
+                f(&mut t.snapshot);
+            }
+        });
+    }
+
+    fn set_final_status(&mut self, status: TaskStatus) {
+        self.finished = true;
+        self.update(|s| s.status = status);
+        with_registry(prune_finished);
+    }
+}
+
+impl Drop for TaskHandle {
+    fn drop(&mut self) {
+        if !self.finished {
+            let status = if self.cancel.is_cancelled() {
+                TaskStatus::Cancelled
+            } else {
+                TaskStatus::Done
+            };
+            self.set_final_status(status);
+        }
+    }
+}
+
+fn prune_finished(r: &mut Registry) {
+    let finished = r
+        .tasks
+        .iter()
+        .filter(|t| t.snapshot.status != TaskStatus::Running)
+        .count();
+    let mut excess = finished.saturating_sub(MAX_FINISHED_TASKS);
+    r.tasks.retain(|t| {
+        if excess > 0 && t.snapshot.status != TaskStatus::Running {
+            excess -= 1;
+            return false;
+        }
+        true
+    });
+}
+
+pub fn snapshot() -> Vec<TaskSnapshot> {
+    with_registry(|r| r.tasks.iter().map(|t| t.snapshot.clone()).collect())
+}
+
+pub fn cancel(id: u64) {
+    with_registry(|r| {
+        if let Some(t) = r.tasks.iter().find(|t| t.snapshot.id == id) {
+            t.cancel.cancel();
+        }
+    });
+}
+
+pub fn clear_finished() {
+    with_registry(|r| r.tasks.retain(|t| t.snapshot.status == TaskStatus::Running));
+}
+
+/// Asks every running task to stop (used when the launcher exits).
+pub fn cancel_all() {
+    with_registry(|r| {
+        for t in r.tasks.iter().filter(|t| t.snapshot.status == TaskStatus::Running) {
+            t.cancel.cancel();
+        }
+    });
+}
+
+pub fn any_running() -> bool {
+    with_registry(|r| r.tasks.iter().any(|t| t.snapshot.status == TaskStatus::Running))
+}
@@ -0,0 +1,36 @@
+use std::sync::OnceLock;
+
+/// Wine starts the .NET client noticeably slower, so pipe handshakes get more time.
+const WINE_PIPE_TIMEOUT_FACTOR: u32 = 3;
+
+/// `true` when the Windows build runs under Wine or Proton. Detected once per process.
+pub fn is_wine() -> bool {
+    static DETECTED: OnceLock<bool> = OnceLock::new();
+    *DETECTED.get_or_init(detect)
+}
+
+/// Timeout for waiting on a named pipe client, stretched under Wine.
+pub fn pipe_timeout_ms(base_ms: u32) -> u32 {
+    if is_wine() {
+        base_ms.saturating_mul(WINE_PIPE_TIMEOUT_FACTOR)
+    } else {
+        base_ms
+    }
+}
+
+#[cfg(windows)]
+fn detect() -> bool {
+    use winreg::RegKey;
+    use winreg::enums::{HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE};
+
+    // Every Wine prefix has these keys; real Windows never does.
+    let has_wine_key = |root| RegKey::predef(root).open_subkey(r"Software\Wine").is_ok();
+    has_wine_key(HKEY_CURRENT_USER)
+        || has_wine_key(HKEY_LOCAL_MACHINE)
+        || std::env::var_os("WINEPREFIX").is_some()
+}
+
+#[cfg(not(windows))]
+fn detect() -> bool {
+    false
+}
@@ -1,23 +1,36 @@
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::fs;
 use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-use std::sync::{Arc, Mutex};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use blake2::Blake2bVar;
 use blake2::digest::{Update, VariableOutput};
 use reqwest::header::{ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_TYPE};
+use serde::{Deserialize, Serialize};
 
+use crate::cache_stats::{self, BlobCacheHits};
 use crate::cancel_flag::CancelFlag;
-use crate::connect_progress::{self, ProgressTx};
+use crate::connect_progress::{self, ErrorTally, ProgressTx};
+use crate::fs_retry;
 use crate::ss14_server_info::ServerBuildInformation;
 
-const MANIFEST_DOWNLOAD_PROTOCOL_VERSION: i32 = 1;
+#[path = "acz_content/codec.rs"]
+mod codec;
+
+use codec::DownloadCodec;
+
 const DEFAULT_ACZ_DOWNLOAD_CONCURRENCY: usize = 8;
 const ZIP_COPY_BUF_SIZE: usize = 256 * 1024;
 const ZIP_DEDUP_READ_MAX: u64 = 4 * 1024 * 1024;
+/// Attempts per blob batch (the first one included) before the whole download fails.
+const MAX_BATCH_ATTEMPTS: u32 = 4;
+/// Pause before retrying a failed batch; grows linearly with the attempt number.
+const BATCH_RETRY_DELAY: Duration = Duration::from_secs(1);
+/// Blob temp files untouched for this long belong to no running download and can be removed.
+const STALE_BLOB_TEMP_AGE: Duration = Duration::from_secs(60);
 
 #[derive(Debug, Clone)]
 struct ManifestEntry {
@@ -25,6 +38,86 @@ struct ManifestEntry {
     hash: [u8; 32],
 }
 
+/// Manifest indices waiting for a download worker. A failed batch comes back holding only the
+/// blobs that did not make it into the cache, and is preferably picked up by another worker.
+struct BlobBatch {
+    indices: Vec<i32>,
+    attempts: u32,
+    failed_by: Option<usize>,
+    not_before: Instant,
+}
+
+impl BlobBatch {
+    fn new(indices: Vec<i32>) -> Self {
+        Self {
+            indices,
+            attempts: 0,
+            failed_by: None,
+            not_before: Instant::now(),
+        }
+    }
+}
+
+/// Takes the next batch for `worker`, skipping batches this worker already failed while
+/// others are queued.
+fn next_batch(queue: &mut VecDeque<BlobBatch>, worker: usize) -> Option<BlobBatch> {
+    let pos = queue
+        .iter()
+        .position(|b| b.failed_by != Some(worker))
+        .unwrap_or(0);
+    queue.remove(pos)
+}
+
+/// Download protocol range (`min`, `max`) each download URL announced this session.
+static PROTOCOL_RANGES: OnceLock<Mutex<HashMap<String, (i32, i32)>>> = OnceLock::new();
+
+fn protocol_ranges() -> std::sync::MutexGuard<'static, HashMap<String, (i32, i32)>> {
+    PROTOCOL_RANGES
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+fn cached_protocol_range(download_url: &str) -> Option<(i32, i32)> {
+    protocol_ranges().get(download_url).copied()
+}
+
+fn remember_protocol_range(download_url: &str, range: (i32, i32)) {
+    protocol_ranges().insert(download_url.to_string(), range);
+}
+
+fn forget_protocol_range(download_url: &str) {
+    protocol_ranges().remove(download_url);
+}
+
+/// Asks the download server which protocol versions it speaks, within the OPTIONS timeout.
+fn probe_protocol_range(
+    client: &reqwest::blocking::Client,
+    download_url: &str,
+) -> Result<(i32, i32), String> {
+    let resp = crate::http_config::blocking_send_idempotent_with_retry(|| {
+        client
+            .request(reqwest::Method::OPTIONS, download_url)
+            .timeout(crate::http_config::options_timeout())
+    })
+    .map_err(|e| format!("OPTIONS {download_url}: {e}"))?;
+    if !resp.status().is_success() {
+        return Err(format!("OPTIONS {download_url}: status {}", resp.status()));
+    }
+
+    let header = |name: &str| {
+        resp.headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|s| s.parse::<i32>().ok())
+            .ok_or_else(|| format!("download server: нет {name}"))
+    };
+    Ok((
+        header("X-Robust-Download-Min-Protocol")?,
+        header("X-Robust-Download-Max-Protocol")?,
+    ))
+}
+
 pub fn build_overlay_zip_from_manifest(
     data_dir: &Path,
     build: &ServerBuildInformation,
@@ -54,6 +147,11 @@ pub fn build_overlay_zip_from_manifest(
 
     let client = crate::launcher_mask::blocking_http_client_download()?;
 
+    // Blob cache: persisted across servers/builds by hash.
+    let cache_root_path = blob_cache_root(data_dir);
+    crate::app_paths::create_dir(&cache_root_path, "blob cache")?;
+    recover_interrupted_build(out_zip, &cache_root_path, progress);
+
     // Fetch manifest.
     let progress_tx = progress.cloned();
     let global_done = Arc::new(AtomicU64::new(0));
@@ -78,7 +176,7 @@ pub fn build_overlay_zip_from_manifest(
         ));
     }
 
-    let manifest_bytes = read_response_bytes_maybe_zstd(resp, "manifest", progress)?;
+    let manifest_bytes = read_response_bytes_maybe_zstd(resp, "контент (manifest)", progress)?;
 
     let (entries, actual_hash) = parse_manifest_and_hash(&manifest_bytes)?;
     if let Some(expected) = expected_manifest_hash
@@ -92,6 +190,12 @@ pub fn build_overlay_zip_from_manifest(
     if let Some(c) = cancel {
         c.check()?;
     }
+    let mut journal = BuildJournal {
+        manifest_hash: actual_hash.clone(),
+        started_at: chrono::Utc::now().timestamp(),
+        temp_zip: None,
+    };
+    journal.save(out_zip);
 
     // Build dedupe map: hash -> paths.
     let mut paths_by_hash: HashMap<[u8; 32], Vec<String>> = HashMap::new();
@@ -111,54 +215,57 @@ pub fn build_overlay_zip_from_manifest(
         }
     }
 
-    // Blob cache: persisted across servers/builds by hash.
-    let cache_root_path = data_dir.join("content_blob_cache").join("blake2b-256");
-    fs::create_dir_all(&cache_root_path)
-        .map_err(|e| format!("создание каталога blob cache: {e}"))?;
-
     let mut indices_to_download: Vec<i32> = Vec::new();
+    let mut hits = BlobCacheHits {
+        blobs_total: unique.len() as u64,
+        ..Default::default()
+    };
     for (idx, hash) in &unique {
         let cache_path = blob_cache_path(&cache_root_path, hash);
-        if !cache_path.exists() {
-            indices_to_download.push(*idx);
+        match fs::metadata(&cache_path) {
+            Ok(meta) => {
+                hits.blobs_cached += 1;
+                hits.bytes_saved += meta.len();
+            }
+            Err(_) => indices_to_download.push(*idx),
         }
     }
 
     if !indices_to_download.is_empty() {
-        // OPTIONS to check protocol.
-        {
-            connect_progress::stage(progress, "проверяем протокол download");
-            let resp = crate::http_config::blocking_send_idempotent_with_retry(|| {
-                client.request(reqwest::Method::OPTIONS, download_url)
-            })
-            .map_err(|e| format!("OPTIONS {download_url}: {e}"))?;
-            if !resp.status().is_success() {
-                return Err(format!("OPTIONS {download_url}: status {}", resp.status()));
-            }
-
-            let min = resp
-                .headers()
-                .get("X-Robust-Download-Min-Protocol")
-                .and_then(|v| v.to_str().ok())
-                .and_then(|s| s.parse::<i32>().ok())
-                .ok_or_else(|| "download server: нет X-Robust-Download-Min-Protocol".to_string())?;
-
-            let max = resp
-                .headers()
-                .get("X-Robust-Download-Max-Protocol")
-                .and_then(|v| v.to_str().ok())
-                .and_then(|s| s.parse::<i32>().ok())
-                .ok_or_else(|| "download server: нет X-Robust-Download-Max-Protocol".to_string())?;
-
-            if min > MANIFEST_DOWNLOAD_PROTOCOL_VERSION || max < MANIFEST_DOWNLOAD_PROTOCOL_VERSION
-            {
-                return Err(format!(
-                    "download server protocol not supported: min={min} max={max}"
-                ));
-            }
-        }
+        // The manifest carries no sizes: estimate from blobs we already have (or a typical size),
+        // counting the missing blobs once for the cache and everything once more for the overlay zip.
+        const TYPICAL_BLOB_SIZE: u64 = 64 * 1024;
+        let avg_blob = hits
+            .bytes_saved
+            .checked_div(hits.blobs_cached)
+            .map_or(TYPICAL_BLOB_SIZE, |avg| avg.max(1));
+        let estimate = (indices_to_download.len() as u64 + hits.blobs_total) * avg_blob;
+        crate::disk_space::ensure_free_space(data_dir, estimate, "контента")?;
+
+        // OPTIONS to negotiate the protocol, once per download URL and session.
+        let codec = {
+            let cached = cached_protocol_range(download_url);
+            let (min, max) = match cached {
+                Some(range) => range,
+                None => {
+                    connect_progress::stage(progress, "проверяем протокол download");
+                    probe_protocol_range(&client, download_url)?
+                }
+            };
+
+            let negotiated = codec::negotiate(min, max);
+            let line = match &negotiated {
+                Ok(n) if cached.is_some() => format!("{} (из кэша сессии)", n.describe()),
+                Ok(n) => n.describe(),
+                Err(e) => e.clone(),
+            };
+            connect_progress::log(progress, line);
+            let codec = negotiated?.codec;
+            remember_protocol_range(download_url, (min, max));
+            codec
+        };
 
-        connect_progress::stage(progress, "скачиваем недостающие blobs");
+        connect_progress::stage(progress, "скачиваем контент: недостающие blobs");
 
         let download_url = download_url.to_string();
         let entries = std::sync::Arc::new(entries);
@@ -176,12 +283,12 @@ pub fn build_overlay_zip_from_manifest(
                     let cur = done.load(Ordering::Relaxed);
                     if cur != last {
                         last = cur;
-                        connect_progress::download(Some(&tx), "blobs", cur, None);
+                        connect_progress::download(Some(&tx), "контент (blobs)", cur, None);
                     }
                     std::thread::sleep(std::time::Duration::from_millis(200));
                 }
                 let cur = done.load(Ordering::Relaxed);
-                connect_progress::download(Some(&tx), "blobs", cur, None);
+                connect_progress::download(Some(&tx), "контент (blobs)", cur, None);
             }));
         }
 
@@ -205,9 +312,9 @@ pub fn build_overlay_zip_from_manifest(
                 computed.clamp(64, 4096)
             });
 
-        let batches: VecDeque<Vec<i32>> = indices_to_download
+        let batches: VecDeque<BlobBatch> = indices_to_download
             .chunks(batch_size)
-            .map(|chunk| chunk.to_vec())
+            .map(|chunk| BlobBatch::new(chunk.to_vec()))
             .collect();
 
         let num_batches = batches.len().max(1);
@@ -215,9 +322,11 @@ pub fn build_overlay_zip_from_manifest(
 
         let queue = Arc::new(Mutex::new(batches));
         let abort = Arc::new(AtomicBool::new(false));
+        // Batch retries by cause: each cause is logged the first time only.
+        let retries = Arc::new(Mutex::new(ErrorTally::default()));
         let mut handles = Vec::new();
 
-        for _ in 0..concurrency {
+        for worker in 0..concurrency {
             let download_url = download_url.clone();
             let entries = entries.clone();
             let cache_root = cache_root.clone();
@@ -226,9 +335,20 @@ pub fn build_overlay_zip_from_manifest(
             let global_done = global_done.clone();
             let queue = queue.clone();
             let abort = abort.clone();
+            let retries = retries.clone();
+            let log_tx = progress_tx.clone();
 
             let handle = std::thread::spawn(move || {
                 let client = crate::launcher_mask::blocking_http_client_download()?;
+                let fetch = BlobFetch {
+                    client: &client,
+                    download_url: &download_url,
+                    entries: &entries,
+                    cache_root: &cache_root,
+                    progress: progress.as_ref(),
+                    global_done: Some(global_done.as_ref()),
+                    cancel: cancel.as_ref(),
+                };
                 loop {
                     if abort.load(Ordering::Relaxed) {
                         return Ok(());
@@ -238,38 +358,106 @@ pub fn build_overlay_zip_from_manifest(
                         let mut q = queue
                             .lock()
                             .map_err(|_| "mutex queue poisoned in blob downloader".to_string())?;
-                        q.pop_front()
+                        next_batch(&mut q, worker)
                     };
 
                     let Some(batch) = batch else {
                         return Ok(());
                     };
 
-                    if let Err(e) = download_blob_chunk_into_cache(
-                        &client,
-                        &download_url,
-                        &entries,
-                        &cache_root,
-                        &batch,
-                        progress.as_ref(),
-                        Some(global_done.as_ref()),
-                        cancel.as_ref(),
-                    ) {
+                    // Retry backoff; sleep in short steps so cancel and abort stay responsive.
+                    while Instant::now() < batch.not_before {
+                        if abort.load(Ordering::Relaxed) {
+                            return Ok(());
+                        }
+                        if let Some(c) = &cancel {
+                            c.check()?;
+                        }
+                        std::thread::sleep(Duration::from_millis(100));
+                    }
+
+                    let mut stored = 0;
+                    let Err(e) =
+                        download_blob_chunk_into_cache(codec, &fetch, &batch.indices, &mut stored)
+                    else {
+                        continue;
+                    };
+
+                    let remaining = batch.indices[stored..].to_vec();
+                    let attempts = batch.attempts + 1;
+                    if cancel.as_ref().is_some_and(|c| c.is_cancelled()) {
                         abort.store(true, Ordering::Relaxed);
                         return Err(e);
                     }
+                    if remaining.is_empty() {
+                        continue;
+                    }
+                    if attempts >= MAX_BATCH_ATTEMPTS {
+                        abort.store(true, Ordering::Relaxed);
+                        connect_progress::log(
+                            log_tx.as_ref(),
+                            format!(
+                                "поток blobs #{worker}: не скачано {} blobs после {attempts} попыток: {e}",
+                                remaining.len()
+                            ),
+                        );
+                        return Err(format!(
+                            "пакет blobs не скачан за {MAX_BATCH_ATTEMPTS} попыток: {e}"
+                        ));
+                    }
+
+                    let first_time = retries
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .add(worker, &e);
+                    if first_time {
+                        connect_progress::log(
+                            log_tx.as_ref(),
+                            format!(
+                                "пакет blobs не скачан ({e}); повтор {attempts}/{}: осталось {} из {}",
+                                MAX_BATCH_ATTEMPTS - 1,
+                                remaining.len(),
+                                batch.indices.len()
+                            ),
+                        );
+                    }
+                    let mut q = queue
+                        .lock()
+                        .map_err(|_| "mutex queue poisoned in blob downloader".to_string())?;
+                    q.push_back(BlobBatch {
+                        indices: remaining,
+                        attempts,
+                        failed_by: Some(worker),
+                        not_before: Instant::now() + BATCH_RETRY_DELAY * attempts,
+                    });
                 }
             });
 
             handles.push(handle);
         }
 
-        for h in handles {
-            match h.join() {
-                Ok(Ok(())) => {}
-                Ok(Err(e)) => return Err(e),
-                Err(_) => return Err("panic в потоке скачивания blobs".to_string()),
-            }
+        // Every worker is joined before reporting, so a shared cause is told once with a count.
+        let mut failures = ErrorTally::default();
+        for (worker, h) in handles.into_iter().enumerate() {
+            let e = match h.join() {
+                Ok(Ok(())) => continue,
+                Ok(Err(e)) => e,
+                Err(_) => "panic в потоке скачивания blobs".to_string(),
+            };
+            connect_progress::log(progress_tx.as_ref(), format!("поток blobs #{worker}: {e}"));
+            failures.add(worker, &e);
+        }
+        let retries = retries.lock().unwrap_or_else(|e| e.into_inner());
+        for line in retries.repeat_lines("повторы пакетов blobs") {
+            connect_progress::log(progress_tx.as_ref(), line);
+        }
+        if let Some(c) = &cancel {
+            c.check()?;
+        }
+        if let Some(msg) = failures.summary("потоков скачивания blobs") {
+            // The server may have changed its protocol since it was probed.
+            forget_protocol_range(&download_url);
+            return Err(msg);
         }
     } else {
         connect_progress::stage(progress, "blobs уже в кэше");
@@ -285,24 +473,192 @@ pub fn build_overlay_zip_from_manifest(
         fs::create_dir_all(parent).map_err(|e| format!("mkdir {:?}: {e}", parent))?;
     }
 
-    let file = fs::File::create(out_zip).map_err(|e| format!("create {:?}: {e}", out_zip))?;
+    connect_progress::stage(progress, "собираем overlay zip");
+
+    // Build into a temp file and rename on success: a crash or cancel mid-build must not leave a
+    // truncated client.zip that later launches would pick up.
+    let tmp_zip = temp_cache_path(out_zip);
+    journal.temp_zip = Some(tmp_zip.clone());
+    journal.save(out_zip);
+    let written = write_overlay_zip(&tmp_zip, &unique, &paths_by_hash, &cache_root_path, cancel);
+    let zip_entries = match written {
+        Ok(n) => n,
+        Err(e) => {
+            let _ = fs::remove_file(&tmp_zip);
+            return Err(e);
+        }
+    };
+    if let Err(e) = fs_retry::retry_locked(|| fs::rename(&tmp_zip, out_zip)) {
+        let _ = fs::remove_file(&tmp_zip);
+        return Err(fs_retry::describe_error("rename", out_zip, &e));
+    }
+    write_overlay_meta(out_zip, &actual_hash, zip_entries);
+    BuildJournal::remove(out_zip);
+
+    hits.bytes_downloaded = global_done.load(Ordering::Relaxed);
+    connect_progress::log(progress, describe_cache_hits(&hits));
+    let _ = cache_stats::record_blob_cache_hits(&hits);
+
+    Ok(())
+}
+
+/// Sidecar next to an overlay zip while it is being built. Removed only after a successful
+/// build, so finding one on the next connect means the previous build was killed, cancelled
+/// or failed and may have left temp files behind.
+#[derive(Debug, Serialize, Deserialize)]
+struct BuildJournal {
+    manifest_hash: String,
+    started_at: i64,
+    temp_zip: Option<PathBuf>,
+}
+
+impl BuildJournal {
+    fn path(zip_path: &Path) -> PathBuf {
+        let mut name = zip_path
+            .file_name()
+            .map(|s| s.to_os_string())
+            .unwrap_or_default();
+        name.push(".journal.json");
+        zip_path.with_file_name(name)
+    }
+
+    fn load(zip_path: &Path) -> Option<Self> {
+        let raw = fs::read_to_string(Self::path(zip_path)).ok()?;
+        serde_json::from_str(&raw).ok()
+    }
+
+    fn save(&self, zip_path: &Path) {
+        if let Ok(json) = serde_json::to_string_pretty(self) {
+            let _ = fs::write(Self::path(zip_path), json);
+        }
+    }
+
+    fn remove(zip_path: &Path) {
+        let _ = fs::remove_file(Self::path(zip_path));
+    }
+}
+
+/// Cleans up after an interrupted build of `out_zip`: its temp zip and stale blob temp files.
+/// Blobs are renamed into the cache only after their hash is verified, so everything already
+/// in the cache is reused and only the missing blobs are downloaded again.
+fn recover_interrupted_build(out_zip: &Path, cache_root: &Path, progress: Option<&ProgressTx>) {
+    let Some(journal) = BuildJournal::load(out_zip) else {
+        return;
+    };
+
+    if let Some(temp_zip) = &journal.temp_zip {
+        let _ = fs::remove_file(temp_zip);
+    }
+    let removed = remove_stale_blob_temps(cache_root).files;
+    let started = chrono::DateTime::from_timestamp(journal.started_at, 0)
+        .map(|t| t.with_timezone(&chrono::Local).format("%d.%m.%Y %H:%M").to_string())
+        .unwrap_or_else(|| "?".to_string());
+    connect_progress::log(
+        progress,
+        format!(
+            "прошлая сборка контента ({started}, manifest {}) не завершилась: удалено временных файлов blobs: {removed}, скачанные blobs берём из кэша",
+            journal.manifest_hash
+        ),
+    );
+    BuildJournal::remove(out_zip);
+}
+
+/// Where verified blobs are kept, one folder per hash prefix.
+pub(crate) fn blob_cache_root(data_dir: &Path) -> PathBuf {
+    data_dir.join("content_blob_cache").join("blake2b-256")
+}
+
+/// Removes `*.blob.tmp.*` files not written to for `STALE_BLOB_TEMP_AGE`. Fresh ones may belong
+/// to a download running for another server.
+pub(crate) fn remove_stale_blob_temps(cache_root: &Path) -> crate::cache_cleanup::TempSweep {
+    let mut removed = crate::cache_cleanup::TempSweep::default();
+    let Ok(dirs) = fs::read_dir(cache_root) else {
+        return removed;
+    };
+    for dir in dirs.flatten() {
+        let Ok(files) = fs::read_dir(dir.path()) else {
+            continue;
+        };
+        for file in files.flatten() {
+            if !file.file_name().to_string_lossy().contains(".blob.tmp.") {
+                continue;
+            }
+            let Ok(meta) = file.metadata() else {
+                continue;
+            };
+            let stale = meta
+                .modified()
+                .ok()
+                .and_then(|t| t.elapsed().ok())
+                .is_some_and(|age| age >= STALE_BLOB_TEMP_AGE);
+            if stale && fs::remove_file(file.path()).is_ok() {
+                removed.files += 1;
+                removed.bytes += meta.len();
+            }
+        }
+    }
+    removed
+}
+
+/// Sidecar next to an overlay zip describing what it was built from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverlayZipMeta {
+    pub manifest_hash: String,
+    pub built_at: i64,
+    pub entries: u64,
+}
+
+pub fn overlay_meta_path(zip_path: &Path) -> PathBuf {
+    let mut name = zip_path
+        .file_name()
+        .map(|s| s.to_os_string())
+        .unwrap_or_default();
+    name.push(".meta.json");
+    zip_path.with_file_name(name)
+}
+
+pub fn read_overlay_meta(zip_path: &Path) -> Option<OverlayZipMeta> {
+    let raw = fs::read_to_string(overlay_meta_path(zip_path)).ok()?;
+    serde_json::from_str(&raw).ok()
+}
+
+fn write_overlay_meta(zip_path: &Path, manifest_hash: &str, entries: u64) {
+    let meta = OverlayZipMeta {
+        manifest_hash: manifest_hash.to_string(),
+        built_at: chrono::Utc::now().timestamp(),
+        entries,
+    };
+    if let Ok(json) = serde_json::to_string_pretty(&meta) {
+        let _ = fs::write(overlay_meta_path(zip_path), json);
+    }
+}
+
+/// Writes the overlay zip to `zip_path` and flushes it to disk. Returns the number of entries.
+fn write_overlay_zip(
+    zip_path: &Path,
+    unique: &[(i32, [u8; 32])],
+    paths_by_hash: &HashMap<[u8; 32], Vec<String>>,
+    cache_root_path: &Path,
+    cancel: Option<&CancelFlag>,
+) -> Result<u64, String> {
+    let file = fs_retry::retry_locked(|| fs::File::create(zip_path))
+        .map_err(|e| fs_retry::describe_error("create", zip_path, &e))?;
     let file = BufWriter::new(file);
     let mut zip = zip::ZipWriter::new(file);
-
-    connect_progress::stage(progress, "собираем overlay zip");
+    let mut entries: u64 = 0;
 
     for (_idx, hash) in unique {
         if let Some(c) = cancel {
             c.check()?;
         }
-        let cache_path = blob_cache_path(&cache_root_path, &hash);
+        let cache_path = blob_cache_path(cache_root_path, hash);
         if !cache_path.exists() {
             return Err(format!("не найден blob в кэше: {}", cache_path.display()));
         }
 
-        let mut f =
-            fs::File::open(&cache_path).map_err(|e| format!("open {:?}: {e}", cache_path))?;
-        let Some(paths) = paths_by_hash.get(&hash) else {
+        let mut f = fs_retry::retry_locked(|| fs::File::open(&cache_path))
+            .map_err(|e| fs_retry::describe_error("open", &cache_path, &e))?;
+        let Some(paths) = paths_by_hash.get(hash) else {
             continue;
         };
 
@@ -322,6 +678,7 @@ pub fn build_overlay_zip_from_manifest(
                     .map_err(|e| format!("zip start_file: {e}"))?;
                 zip.write_all(&data)
                     .map_err(|e| format!("zip write: {e}"))?;
+                entries += 1;
             }
             continue;
         }
@@ -339,13 +696,32 @@ pub fn build_overlay_zip_from_manifest(
                 .map_err(|e| format!("zip start_file: {e}"))?;
             copy_with_buffer(&mut f, &mut zip, copy_buf.as_mut_slice())
                 .map_err(|e| format!("zip write: {e}"))?;
+            entries += 1;
         }
     }
 
-    zip.finish()
-        .map_err(|e| format!("finalize zip {:?}: {e}", out_zip))?;
+    let file = zip
+        .finish()
+        .map_err(|e| format!("finalize zip {:?}: {e}", zip_path))?;
+    let file = file
+        .into_inner()
+        .map_err(|e| format!("flush zip {:?}: {e}", zip_path))?;
+    file.sync_all()
+        .map_err(|e| format!("sync zip {:?}: {e}", zip_path))?;
 
-    Ok(())
+    Ok(entries)
+}
+
+fn describe_cache_hits(hits: &BlobCacheHits) -> String {
+    const MIB: f64 = 1024.0 * 1024.0;
+    format!(
+        "кэш blobs: {} из {} взято из кэша (сэкономлено {:.1} MiB), скачано {} ({:.1} MiB)",
+        hits.blobs_cached,
+        hits.blobs_total,
+        hits.bytes_saved as f64 / MIB,
+        hits.blobs_total.saturating_sub(hits.blobs_cached),
+        hits.bytes_downloaded as f64 / MIB,
+    )
 }
 
 fn read_response_bytes_maybe_zstd(
@@ -384,8 +760,7 @@ fn read_to_end_with_progress(
 ) -> Result<(), String> {
     let mut buf = [0u8; 1024 * 64];
     let mut done: u64 = 0;
-    let mut last_emit: u64 = 0;
-    const EMIT_EVERY: u64 = 2 * 1024 * 1024;
+    let mut throttle = connect_progress::DownloadThrottle::new();
 
     loop {
         let read = reader
@@ -397,8 +772,7 @@ fn read_to_end_with_progress(
 
         out.extend_from_slice(&buf[..read]);
         done += read as u64;
-        if done.saturating_sub(last_emit) >= EMIT_EVERY {
-            last_emit = done;
+        if throttle.should_emit(done) {
             connect_progress::download(progress, label, done, total);
         }
     }
@@ -466,28 +840,40 @@ fn temp_cache_path(final_path: &Path) -> std::path::PathBuf {
     final_path.with_file_name(format!("{name}.tmp.{suffix}"))
 }
 
+/// What every blob request of one download worker shares.
+struct BlobFetch<'a> {
+    client: &'a reqwest::blocking::Client,
+    download_url: &'a str,
+    entries: &'a [ManifestEntry],
+    cache_root: &'a Path,
+    progress: Option<&'a ProgressTx>,
+    /// Bytes of blobs stored in the cache by all workers.
+    global_done: Option<&'a AtomicU64>,
+    cancel: Option<&'a CancelFlag>,
+}
+
+/// Downloads `indices` in one request into the blob cache. `stored` counts how many leading
+/// indices are in the cache, so after a failure only the rest needs to be asked again.
+/// Only bytes of stored blobs go to `global_done`, so a retried remainder is not counted twice.
 fn download_blob_chunk_into_cache(
-    client: &reqwest::blocking::Client,
-    download_url: &str,
-    entries: &std::sync::Arc<Vec<ManifestEntry>>,
-    cache_root: &std::sync::Arc<std::path::PathBuf>,
+    codec: &dyn DownloadCodec,
+    fetch: &BlobFetch<'_>,
     indices: &[i32],
-    progress: Option<&ProgressTx>,
-    global_done: Option<&AtomicU64>,
-    cancel: Option<&CancelFlag>,
+    stored: &mut usize,
 ) -> Result<(), String> {
-    // POST request body: little-endian i32 indices.
-    let mut body = Vec::with_capacity(indices.len() * 4);
-    for idx in indices {
-        body.extend_from_slice(&idx.to_le_bytes());
-    }
-
+    let BlobFetch {
+        client,
+        download_url,
+        entries,
+        cache_root,
+        progress,
+        global_done,
+        cancel,
+    } = *fetch;
+    let body = codec.request_body(indices);
     let req = client
         .post(download_url)
-        .header(
-            "X-Robust-Download-Protocol",
-            MANIFEST_DOWNLOAD_PROTOCOL_VERSION.to_string(),
-        )
+        .header("X-Robust-Download-Protocol", codec.version().to_string())
         .header(ACCEPT_ENCODING, "zstd")
         .header(CONTENT_TYPE, "application/octet-stream")
         .body(body);
@@ -516,9 +902,18 @@ fn download_blob_chunk_into_cache(
         Box::new(resp)
     };
 
-    let mut reader = ProgressRead::new(reader, progress, "blobs", total, global_done);
-    let flags = read_i32_le_reader(&mut reader)?;
-    let precompressed = (flags & 1) != 0;
+    let read = AtomicU64::new(0);
+    let mut counted: u64 = 0;
+    let mut count_stored = || {
+        if let Some(g) = global_done {
+            let now = read.load(Ordering::Relaxed);
+            g.fetch_add(now - counted, Ordering::Relaxed);
+            counted = now;
+        }
+    };
+
+    let mut reader = ProgressRead::new(reader, progress, "контент (blobs)", total, Some(&read));
+    let mut stream = codec.open(&mut reader)?;
 
     for idx in indices {
         if let Some(c) = cancel {
@@ -526,21 +921,14 @@ fn download_blob_chunk_into_cache(
         }
 
         let entry = &entries[*idx as usize];
-        let uncompressed_len = read_i32_le_reader(&mut reader)? as usize;
+        let uncompressed_len = stream.next_blob()?;
 
-        let cache_path = blob_cache_path(cache_root.as_path(), &entry.hash);
+        let cache_path = blob_cache_path(cache_root, &entry.hash);
         if cache_path.exists() {
             // Another concurrent run may have populated it; still must consume bytes from stream.
-            if precompressed {
-                let compressed_len = read_i32_le_reader(&mut reader)? as i32;
-                if compressed_len > 0 {
-                    discard_exact_reader(&mut reader, compressed_len as usize, cancel)?;
-                } else {
-                    discard_exact_reader(&mut reader, uncompressed_len, cancel)?;
-                }
-            } else {
-                discard_exact_reader(&mut reader, uncompressed_len, cancel)?;
-            }
+            stream.skip_blob(cancel)?;
+            *stored += 1;
+            count_stored();
             continue;
         }
 
@@ -548,47 +936,15 @@ fn download_blob_chunk_into_cache(
         if let Some(parent) = temp_path.parent() {
             fs::create_dir_all(parent).map_err(|e| format!("mkdir {:?}: {e}", parent))?;
         }
-        let file =
-            fs::File::create(&temp_path).map_err(|e| format!("create {:?}: {e}", temp_path))?;
+        let file = fs_retry::retry_locked(|| fs::File::create(&temp_path))
+            .map_err(|e| fs_retry::describe_error("create", &temp_path, &e))?;
         let mut file = BufWriter::new(file);
 
         let mut hasher = Blake2bVar::new(32).map_err(|e| format!("blake2 init: {e}"))?;
 
-        let written = if precompressed {
-            let compressed_len = read_i32_le_reader(&mut reader)? as i32;
-            if compressed_len > 0 {
-                let clen = compressed_len as u64;
-                let mut limited = (&mut reader).take(clen);
-                let mut decoder = zstd::stream::read::Decoder::new(&mut limited)
-                    .map_err(|e| format!("zstd decoder: {e}"))?;
-                let written = copy_read_exact_len_with_hash(
-                    &mut decoder,
-                    &mut file,
-                    uncompressed_len,
-                    &mut hasher,
-                    cancel,
-                )?;
-                let _ = std::io::copy(&mut decoder, &mut std::io::sink());
-                let _ = std::io::copy(&mut limited, &mut std::io::sink());
-                written
-            } else {
-                copy_read_exact_len_with_hash(
-                    &mut reader,
-                    &mut file,
-                    uncompressed_len,
-                    &mut hasher,
-                    cancel,
-                )?
-            }
-        } else {
-            copy_read_exact_len_with_hash(
-                &mut reader,
-                &mut file,
-                uncompressed_len,
-                &mut hasher,
-                cancel,
-            )?
-        };
+        let written = stream.read_blob(&mut |blob| {
+            copy_read_exact_len_with_hash(blob, &mut file, uncompressed_len, &mut hasher, cancel)
+        })?;
 
         if written != uncompressed_len {
             let _ = fs::remove_file(&temp_path);
@@ -606,18 +962,20 @@ fn download_blob_chunk_into_cache(
 
         file.flush().map_err(|e| format!("flush cache: {e}"))?;
         drop(file);
-        match fs::rename(&temp_path, &cache_path) {
+        match fs_retry::retry_locked(|| fs::rename(&temp_path, &cache_path)) {
             Ok(()) => {}
             Err(_) => {
                 if cache_path.exists() {
                     let _ = fs::remove_file(&temp_path);
                 } else {
-                    fs::copy(&temp_path, &cache_path)
-                        .map_err(|e| format!("cache copy {:?}: {e}", cache_path))?;
+                    fs_retry::retry_locked(|| fs::copy(&temp_path, &cache_path))
+                        .map_err(|e| fs_retry::describe_error("cache copy", &cache_path, &e))?;
                     let _ = fs::remove_file(&temp_path);
                 }
             }
         }
+        *stored += 1;
+        count_stored();
     }
 
     Ok(())
@@ -654,7 +1012,7 @@ fn copy_read_exact_len_with_hash(
         if let Some(c) = cancel
             && c.is_cancelled()
         {
-            return Err("отменено".to_string());
+            return Err(crate::cancel_flag::CANCELLED.to_string());
         }
 
         let to_read = (len - done).min(buf.len());
@@ -675,39 +1033,6 @@ fn copy_read_exact_len_with_hash(
     Ok(done)
 }
 
-fn discard_exact_reader(
-    reader: &mut dyn Read,
-    len: usize,
-    cancel: Option<&CancelFlag>,
-) -> Result<(), String> {
-    let mut buf = [0u8; 1024 * 64];
-    let mut done: usize = 0;
-    while done < len {
-        if let Some(c) = cancel
-            && c.is_cancelled()
-        {
-            return Err("отменено".to_string());
-        }
-        let to_read = (len - done).min(buf.len());
-        let n = reader
-            .read(&mut buf[..to_read])
-            .map_err(|e| format!("read payload: {e}"))?;
-        if n == 0 {
-            return Err("короткий ответ download stream (payload)".to_string());
-        }
-        done += n;
-    }
-    Ok(())
-}
-
-fn read_i32_le_reader(reader: &mut dyn Read) -> Result<i32, String> {
-    let mut b = [0u8; 4];
-    reader
-        .read_exact(&mut b)
-        .map_err(|_| "короткий ответ download stream".to_string())?;
-    Ok(i32::from_le_bytes(b))
-}
-
 struct ProgressRead<'a> {
     inner: Box<dyn Read>,
     progress: Option<&'a ProgressTx>,
@@ -715,7 +1040,7 @@ struct ProgressRead<'a> {
     label: String,
     total: Option<u64>,
     done: u64,
-    last_emit: u64,
+    throttle: connect_progress::DownloadThrottle,
 }
 
 impl<'a> ProgressRead<'a> {
@@ -733,16 +1058,14 @@ impl<'a> ProgressRead<'a> {
             label: label.to_string(),
             total,
             done: 0,
-            last_emit: 0,
+            throttle: connect_progress::DownloadThrottle::new(),
         }
     }
 
     fn emit(&mut self) {
-        const EMIT_EVERY: u64 = 2 * 1024 * 1024;
-        if self.done.saturating_sub(self.last_emit) < EMIT_EVERY {
+        if !self.throttle.should_emit(self.done) {
             return;
         }
-        self.last_emit = self.done;
         if let Some(tx) = self.progress {
             connect_progress::download(Some(tx), &self.label, self.done, self.total);
         }
@@ -0,0 +1,115 @@
+use std::io::Read;
+
+use crate::cancel_flag::CancelFlag;
+
+#[path = "v1.rs"]
+mod v1;
+
+/// Supported `/download` wire formats, oldest first; a new protocol version is a new module
+/// listed here.
+static CODECS: [&dyn DownloadCodec; 1] = [&v1::V1];
+
+/// One version of the request/response format.
+pub(super) trait DownloadCodec: Sync {
+    fn version(&self) -> i32;
+
+    /// Request body asking for the manifest entries at `indices`.
+    fn request_body(&self, indices: &[i32]) -> Vec<u8>;
+
+    /// Starts reading a response body that answers `request_body`.
+    fn open<'a>(&self, reader: &'a mut dyn Read) -> Result<Box<dyn BlobStream + 'a>, String>;
+}
+
+/// Blobs of one response, in request order.
+pub(super) trait BlobStream {
+    /// Reads the header of the next blob and returns its uncompressed length.
+    fn next_blob(&mut self) -> Result<usize, String>;
+
+    /// Hands `write` the uncompressed content of the blob `next_blob` announced, then
+    /// consumes whatever `write` left unread. Returns what `write` returned.
+    fn read_blob(
+        &mut self,
+        write: &mut dyn FnMut(&mut dyn Read) -> Result<usize, String>,
+    ) -> Result<usize, String>;
+
+    /// Consumes the blob `next_blob` announced without decoding it.
+    fn skip_blob(&mut self, cancel: Option<&CancelFlag>) -> Result<(), String>;
+}
+
+/// Outcome of matching the server's `X-Robust-Download-{Min,Max}-Protocol` against ours.
+pub(super) struct Negotiated {
+    pub codec: &'static dyn DownloadCodec,
+    pub server_min: i32,
+    pub server_max: i32,
+}
+
+impl Negotiated {
+    /// Line for the connect log.
+    pub fn describe(&self) -> String {
+        let (min, max) = supported_range();
+        format!(
+            "download протокол: сервер {}..{}, лаунчер {min}..{max}, выбран {}",
+            self.server_min,
+            self.server_max,
+            self.codec.version()
+        )
+    }
+}
+
+/// Picks the highest version both sides support.
+pub(super) fn negotiate(server_min: i32, server_max: i32) -> Result<Negotiated, String> {
+    let codec = CODECS
+        .iter()
+        .rev()
+        .find(|c| (server_min..=server_max).contains(&c.version()))
+        .ok_or_else(|| {
+            let (min, max) = supported_range();
+            format!(
+                "download server protocol not supported: min={server_min} max={server_max} (лаунчер: {min}..{max})"
+            )
+        })?;
+    Ok(Negotiated {
+        codec: *codec,
+        server_min,
+        server_max,
+    })
+}
+
+fn supported_range() -> (i32, i32) {
+    let first = CODECS.first().map_or(0, |c| c.version());
+    let last = CODECS.last().map_or(0, |c| c.version());
+    (first, last)
+}
+
+pub(super) fn read_i32_le(reader: &mut dyn Read) -> Result<i32, String> {
+    let mut b = [0u8; 4];
+    reader
+        .read_exact(&mut b)
+        .map_err(|_| "короткий ответ download stream".to_string())?;
+    Ok(i32::from_le_bytes(b))
+}
+
+pub(super) fn discard_exact(
+    reader: &mut dyn Read,
+    len: usize,
+    cancel: Option<&CancelFlag>,
+) -> Result<(), String> {
+    let mut buf = [0u8; 1024 * 64];
+    let mut done: usize = 0;
+    while done < len {
+        if let Some(c) = cancel
+            && c.is_cancelled()
+        {
+            return Err(crate::cancel_flag::CANCELLED.to_string());
+        }
+        let to_read = (len - done).min(buf.len());
+        let n = reader
+            .read(&mut buf[..to_read])
+            .map_err(|e| format!("read payload: {e}"))?;
+        if n == 0 {
+            return Err("короткий ответ download stream (payload)".to_string());
+        }
+        done += n;
+    }
+    Ok(())
+}
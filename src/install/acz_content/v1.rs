@@ -0,0 +1,84 @@
+use std::io::Read;
+
+use super::{BlobStream, DownloadCodec, discard_exact, read_i32_le};
+use crate::cancel_flag::CancelFlag;
+
+/// Protocol 1: little-endian `i32` manifest indices in the body. The response starts with an
+/// `i32` flags word (bit 0: zstd-precompressed blobs); each blob is its length, then for
+/// precompressed streams its compressed length (0 = stored as is), then the bytes.
+pub(super) struct V1;
+
+impl DownloadCodec for V1 {
+    fn version(&self) -> i32 {
+        1
+    }
+
+    fn request_body(&self, indices: &[i32]) -> Vec<u8> {
+        let mut body = Vec::with_capacity(indices.len() * 4);
+        for idx in indices {
+            body.extend_from_slice(&idx.to_le_bytes());
+        }
+        body
+    }
+
+    fn open<'a>(&self, reader: &'a mut dyn Read) -> Result<Box<dyn BlobStream + 'a>, String> {
+        let flags = read_i32_le(reader)?;
+        Ok(Box::new(V1Stream {
+            reader,
+            precompressed: (flags & 1) != 0,
+            current: None,
+        }))
+    }
+}
+
+struct V1Stream<'a> {
+    reader: &'a mut dyn Read,
+    precompressed: bool,
+    /// Uncompressed and compressed (0 = none) length of the announced blob.
+    current: Option<(usize, usize)>,
+}
+
+impl V1Stream<'_> {
+    fn take_current(&mut self) -> Result<(usize, usize), String> {
+        self.current
+            .take()
+            .ok_or_else(|| "download stream: blob без заголовка".to_string())
+    }
+}
+
+impl BlobStream for V1Stream<'_> {
+    fn next_blob(&mut self) -> Result<usize, String> {
+        let len = read_i32_le(self.reader)? as usize;
+        let compressed = if self.precompressed {
+            read_i32_le(self.reader)?.max(0) as usize
+        } else {
+            0
+        };
+        self.current = Some((len, compressed));
+        Ok(len)
+    }
+
+    fn read_blob(
+        &mut self,
+        write: &mut dyn FnMut(&mut dyn Read) -> Result<usize, String>,
+    ) -> Result<usize, String> {
+        let (_, compressed) = self.take_current()?;
+        if compressed == 0 {
+            return write(self.reader);
+        }
+        let mut limited = (&mut *self.reader).take(compressed as u64);
+        let mut decoder = zstd::stream::read::Decoder::new(&mut limited)
+            .map_err(|e| format!("zstd decoder: {e}"))?;
+        let written = write(&mut decoder)?;
+        let _ = std::io::copy(&mut decoder, &mut std::io::sink());
+        drop(decoder);
+        let _ = std::io::copy(&mut limited, &mut std::io::sink());
+        Ok(written)
+    }
+
+    fn skip_blob(&mut self, cancel: Option<&CancelFlag>) -> Result<(), String> {
+        let (len, compressed) = self.take_current()?;
+        let on_wire = if compressed > 0 { compressed } else { len };
+        discard_exact(self.reader, on_wire, cancel)
+    }
+}
@@ -1,3 +1,4 @@
+use std::cmp::Reverse;
 use std::fs;
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
@@ -10,59 +11,201 @@ use crate::connect_progress::{self, ProgressTx};
 pub struct ClientInstall {
     pub engine_zip: PathBuf,
     pub engine_signature_hex: String,
+    /// Resolved robust-builds version (the engines/ subdirectory name).
+    pub engine_version: String,
+    /// sha256 of `engine_zip`, already checked against robust-builds.
+    pub engine_sha256: String,
 }
 
+/// Directory holding one installed engine version, for every platform.
+pub fn engine_dir(data_dir: &Path, version: &str) -> PathBuf {
+    data_dir.join("engines").join(sanitize_dir_component(version))
+}
+
+/// Directory holding the `rid` build of one engine version. Builds for different platforms
+/// sit side by side, so a data dir moved between x64 and arm64 machines keeps both.
+pub fn engine_rid_dir(data_dir: &Path, version: &str, rid: &str) -> PathBuf {
+    engine_dir(data_dir, version).join(sanitize_dir_component(rid))
+}
+
+/// Marker in `engines/<version>/` with the unix time a launch or preparation last used it.
+const LAST_USED_FILE_NAME: &str = "last_used";
+
+/// One engine version found in the data dir.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstalledEngine {
+    pub version: String,
+    /// Platforms with an `engine.zip`.
+    pub rids: Vec<String>,
+    /// An `engine.zip` from before per-platform directories; moved on its next use.
+    pub legacy: bool,
+    /// All `engine.zip` files of the version together.
+    pub size_bytes: u64,
+    /// Unix time of the last use; the download time for versions not used since tracking began.
+    pub last_used: Option<i64>,
+}
+
+/// Installed engine versions and their platforms, newest version first.
+pub fn installed_engines(data_dir: &Path) -> Vec<InstalledEngine> {
+    let Ok(versions) = fs::read_dir(data_dir.join("engines")) else {
+        return Vec::new();
+    };
+
+    let mut found: Vec<InstalledEngine> = versions
+        .flatten()
+        .filter(|v| v.path().is_dir())
+        .filter_map(|v| {
+            let mut rids: Vec<String> = fs::read_dir(v.path())
+                .into_iter()
+                .flatten()
+                .flatten()
+                .filter(|r| r.path().join("engine.zip").is_file())
+                .map(|r| r.file_name().to_string_lossy().into_owned())
+                .collect();
+            rids.sort();
+            let legacy = v.path().join("engine.zip").is_file();
+            if rids.is_empty() && !legacy {
+                return None;
+            }
+            let zips: Vec<PathBuf> = rids
+                .iter()
+                .map(|rid| v.path().join(rid).join("engine.zip"))
+                .chain(legacy.then(|| v.path().join("engine.zip")))
+                .collect();
+            let metas: Vec<fs::Metadata> =
+                zips.iter().filter_map(|z| fs::metadata(z).ok()).collect();
+            let last_used = fs::read_to_string(v.path().join(LAST_USED_FILE_NAME))
+                .ok()
+                .and_then(|t| t.trim().parse::<i64>().ok())
+                .or_else(|| {
+                    metas
+                        .iter()
+                        .filter_map(|m| m.modified().ok())
+                        .max()
+                        .map(|t| chrono::DateTime::<chrono::Utc>::from(t).timestamp())
+                });
+            Some(InstalledEngine {
+                version: v.file_name().to_string_lossy().into_owned(),
+                rids,
+                legacy,
+                size_bytes: metas.iter().map(|m| m.len()).sum(),
+                last_used,
+            })
+        })
+        .collect();
+
+    let key = |v: &str| -> Vec<u64> { v.split('.').map(|p| p.parse().unwrap_or(0)).collect() };
+    found.sort_by_key(|e| Reverse(key(&e.version)));
+    found
+}
+
+/// Moves `engines/<version>/engine.zip` from the old flat layout to `zip_path`. The sha256
+/// check that follows redownloads it if it was built for another platform.
+fn migrate_legacy_engine_zip(
+    data_dir: &Path,
+    version: &str,
+    zip_path: &Path,
+    progress: Option<&ProgressTx>,
+) {
+    let legacy = engine_dir(data_dir, version).join("engine.zip");
+    if zip_path.exists() || !legacy.is_file() {
+        return;
+    }
+    let Some(dir) = zip_path.parent() else {
+        return;
+    };
+    if fs::create_dir_all(dir).is_ok() && fs::rename(&legacy, zip_path).is_ok() {
+        connect_progress::log(
+            progress,
+            format!("engine.zip {version} перенесён в {}", dir.display()),
+        );
+    }
+}
+
+/// Downloads and sha256-checks `engines/<version>/<rid>/engine.zip`.
+///
+/// The zip is never extracted: SS14.Loader mounts it directly, so install time is the
+/// download plus one hashing pass (twice on a hash mismatch redownload).
 pub fn ensure_client_installed(
     data_dir: &Path,
     engine_version: &str,
     progress: Option<&ProgressTx>,
     cancel: Option<&CancelFlag>,
 ) -> Result<ClientInstall, String> {
-    let engines_dir = data_dir.join("engines");
-    let build = crate::robust_builds::resolve_engine_build(engine_version)?;
+    let allow_fallback = crate::settings::load_settings()
+        .map(|s| s.game.engine_version_fallback)
+        .unwrap_or(false);
+    let build = crate::robust_builds::resolve_engine_build_with_fallback(
+        engine_version,
+        |requested, candidate| {
+            allow_fallback
+                && connect_progress::ask_engine_fallback(progress, requested, candidate, cancel)
+        },
+    )?;
     connect_progress::log(
         progress,
         format!(
-            "engine_version={} resolved={}",
-            engine_version, build.resolved_version
+            "engine_version={} resolved={} rid={}",
+            engine_version, build.resolved_version, build.rid
         ),
     );
-    let engine_dir = engines_dir.join(sanitize_dir_component(&build.resolved_version));
+    let engine_dir = engine_rid_dir(data_dir, &build.resolved_version, &build.rid);
     let zip_path = engine_dir.join("engine.zip");
+    migrate_legacy_engine_zip(data_dir, &build.resolved_version, &zip_path, progress);
+
+    if let Some(reason) = &build.offline_reason {
+        if !zip_path.exists() {
+            return Err(format!(
+                "robust-builds недоступен, а движок {} ещё не скачан: {reason}",
+                build.resolved_version
+            ));
+        }
+        connect_progress::log(
+            progress,
+            format!("robust-builds недоступен ({reason}), используем сохранённый manifest"),
+        );
+    }
 
-    fs::create_dir_all(&engine_dir).map_err(|e| format!("создание каталога движка: {e}"))?;
+    crate::app_paths::create_dir(&engine_dir, "движок")?;
 
     let needs_download = !zip_path.exists();
     if needs_download {
         if let Some(c) = cancel {
             c.check()?;
         }
-        download_to_file(&build.url, &zip_path, progress, cancel)?;
+        download_to_file(&build.url, &zip_path, &build.resolved_version, progress, cancel)?;
     }
 
     // Verify engine sha256 from robust manifest.
-    let actual = sha256_file_hex(&zip_path)?;
+    let mut actual = sha256_file_hex(&zip_path)?;
     if !eq_hex_case_insensitive(&actual, &build.sha256) {
         // Redownload once.
         let _ = fs::remove_file(&zip_path);
         if let Some(c) = cancel {
             c.check()?;
         }
-        download_to_file(&build.url, &zip_path, progress, cancel)?;
-        let actual2 = sha256_file_hex(&zip_path)?;
-        if !eq_hex_case_insensitive(&actual2, &build.sha256) {
+        download_to_file(&build.url, &zip_path, &build.resolved_version, progress, cancel)?;
+        actual = sha256_file_hex(&zip_path)?;
+        if !eq_hex_case_insensitive(&actual, &build.sha256) {
             return Err("хеш engine.zip не совпадает (sha256)".to_string());
         }
     }
+    let _ = fs::write(
+        self::engine_dir(data_dir, &build.resolved_version).join(LAST_USED_FILE_NAME),
+        chrono::Utc::now().timestamp().to_string(),
+    );
     Ok(ClientInstall {
         engine_zip: zip_path,
         engine_signature_hex: build.signature,
+        engine_version: build.resolved_version,
+        engine_sha256: actual,
     })
 }
 
 fn download_to_file(
     url: &str,
     path: &Path,
+    version: &str,
     progress: Option<&ProgressTx>,
     cancel: Option<&CancelFlag>,
 ) -> Result<(), String> {
@@ -80,21 +223,34 @@ fn download_to_file(
     }
 
     let total = resp.content_length();
-    connect_progress::log(progress, format!("скачивание движка: {url}"));
+    if let (Some(total), Some(dir)) = (total, path.parent()) {
+        crate::disk_space::ensure_free_space(dir, total, "движка")?;
+    }
+    let label = format!("движок {version}");
+    connect_progress::stage(progress, format!("скачиваем движок {version}"));
+    connect_progress::log(
+        progress,
+        format!(
+            "скачивание движка: {url} ({})",
+            total
+                .map(|t| format!("{t} байт"))
+                .unwrap_or_else(|| "размер неизвестен".to_string())
+        ),
+    );
 
-    let mut file = fs::File::create(path).map_err(|e| format!("создание файла {:?}: {e}", path))?;
+    let mut file = crate::fs_retry::retry_locked(|| fs::File::create(path))
+        .map_err(|e| crate::fs_retry::describe_error("создание файла", path, &e))?;
     let mut buf = [0u8; 1024 * 64];
 
     let mut done: u64 = 0;
-    let mut last_emit: u64 = 0;
-    const EMIT_EVERY: u64 = 256 * 1024;
+    let mut throttle = connect_progress::DownloadThrottle::new();
 
     loop {
         if let Some(c) = cancel
             && c.is_cancelled()
         {
             let _ = fs::remove_file(path);
-            return Err("отменено".to_string());
+            return Err(crate::cancel_flag::CANCELLED.to_string());
         }
         let read = resp
             .read(&mut buf)
@@ -104,16 +260,15 @@ fn download_to_file(
         }
 
         done += read as u64;
-        if done.saturating_sub(last_emit) >= EMIT_EVERY {
-            last_emit = done;
-            connect_progress::download(progress, "движок", done, total);
+        if throttle.should_emit(done) {
+            connect_progress::download(progress, label.as_str(), done, total);
         }
 
         file.write_all(&buf[..read])
             .map_err(|e| format!("запись файла {:?}: {e}", path))?;
     }
 
-    connect_progress::download(progress, "движок", done, total);
+    connect_progress::download(progress, label, done, total);
 
     Ok(())
 }
@@ -22,50 +22,24 @@ pub fn ensure_content_overlay_zip(
         .filter(|s| !s.is_empty())
         .ok_or_else(|| "сервер не вернул build.download_url".to_string())?;
 
-    let key = if let Some(h) = build
-        .hash
-        .as_deref()
-        .map(|s| s.trim())
-        .filter(|s| !s.is_empty())
-    {
-        h
-    } else if let Some(h) = build
-        .manifest_hash
-        .as_deref()
-        .map(|s| s.trim())
-        .filter(|s| !s.is_empty())
-    {
-        h
-    } else {
-        build.version.as_str()
-    };
+    let key = content_key(build);
 
     let content_dir = data_dir.join("content").join(sanitize_dir_component(key));
     let zip_path = content_dir.join("client.zip");
     let acz_marker = content_dir.join("client.zip.acz_overlay");
 
-    // Preferred overlay cache: keyed by manifest_hash (content identity), not by build.hash (zip bytes).
-    let overlay_cache_zip: Option<PathBuf> = build
-        .manifest_hash
-        .as_deref()
-        .map(|s| s.trim())
-        .filter(|s| !s.is_empty())
-        .map(|h| {
-            data_dir
-                .join("content_overlay_cache")
-                .join(sanitize_dir_component(h))
-                .join("client.zip")
-        });
+    let overlay_cache_zip = overlay_cache_zip_path(data_dir, build);
     let overlay_cache_marker: Option<PathBuf> = overlay_cache_zip
         .as_ref()
         .and_then(|p| p.parent().map(|d| d.join("client.zip.acz_overlay")));
 
-    fs::create_dir_all(&content_dir).map_err(|e| format!("создание каталога контента: {e}"))?;
+    crate::app_paths::create_dir(&content_dir, "контент")?;
 
     // If we already have a cached overlay zip for this manifest, prefer it.
     if let (Some(overlay_zip), Some(marker)) = (&overlay_cache_zip, &overlay_cache_marker)
         && overlay_zip.exists()
         && marker.exists()
+        && overlay_meta_matches(overlay_zip, build.manifest_hash.as_deref())
     {
         return Ok(overlay_zip.clone());
     }
@@ -197,6 +171,58 @@ pub fn ensure_content_overlay_zip(
     Ok(zip_path)
 }
 
+/// Cheap check (no hashing) whether content for `build` is already on disk, so a launch
+/// would not have to download it.
+pub fn is_content_cached(data_dir: &Path, build: &ServerBuildInformation) -> bool {
+    if let Some(overlay_zip) = overlay_cache_zip_path(data_dir, build)
+        && overlay_zip.exists()
+        && overlay_zip.with_file_name("client.zip.acz_overlay").exists()
+        && overlay_meta_matches(&overlay_zip, build.manifest_hash.as_deref())
+    {
+        return true;
+    }
+    data_dir
+        .join("content")
+        .join(sanitize_dir_component(content_key(build)))
+        .join("client.zip")
+        .exists()
+}
+
+fn content_key(build: &ServerBuildInformation) -> &str {
+    if let Some(h) = build
+        .hash
+        .as_deref()
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+    {
+        h
+    } else if let Some(h) = build
+        .manifest_hash
+        .as_deref()
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+    {
+        h
+    } else {
+        build.version.as_str()
+    }
+}
+
+/// Preferred overlay cache: keyed by manifest_hash (content identity), not by build.hash (zip bytes).
+fn overlay_cache_zip_path(data_dir: &Path, build: &ServerBuildInformation) -> Option<PathBuf> {
+    build
+        .manifest_hash
+        .as_deref()
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(|h| {
+            data_dir
+                .join("content_overlay_cache")
+                .join(sanitize_dir_component(h))
+                .join("client.zip")
+        })
+}
+
 fn download_to_file_with_fallback(
     primary_url: &str,
     fallback_url: Option<&str>,
@@ -296,20 +322,24 @@ fn download_to_file(
 
     let total = resp.content_length();
     connect_progress::log(progress, format!("скачивание {label}: {url}"));
+    if let (Some(total), Some(dir)) = (total, path.parent()) {
+        // The zip itself plus the overlay zip built from it.
+        crate::disk_space::ensure_free_space(dir, total.saturating_mul(2), "контента")?;
+    }
 
-    let mut file = fs::File::create(path).map_err(|e| format!("создание файла {:?}: {e}", path))?;
+    let mut file = crate::fs_retry::retry_locked(|| fs::File::create(path))
+        .map_err(|e| crate::fs_retry::describe_error("создание файла", path, &e))?;
     let mut buf = [0u8; 1024 * 64];
 
     let mut done: u64 = 0;
-    let mut last_emit: u64 = 0;
-    const EMIT_EVERY: u64 = 256 * 1024;
+    let mut throttle = connect_progress::DownloadThrottle::new();
 
     loop {
         if let Some(c) = cancel
             && c.is_cancelled()
         {
             let _ = fs::remove_file(path);
-            return Err("отменено".to_string());
+            return Err(crate::cancel_flag::CANCELLED.to_string());
         }
         let read = resp
             .read(&mut buf)
@@ -319,8 +349,7 @@ fn download_to_file(
         }
 
         done += read as u64;
-        if done.saturating_sub(last_emit) >= EMIT_EVERY {
-            last_emit = done;
+        if throttle.should_emit(done) {
             connect_progress::download(progress, label, done, total);
         }
 
@@ -350,6 +379,15 @@ fn sha256_file_hex(path: &Path) -> Result<String, String> {
     Ok(hex::encode(digest))
 }
 
+/// Overlays built before the metadata sidecar existed have no meta and are trusted as before.
+fn overlay_meta_matches(zip_path: &Path, manifest_hash: Option<&str>) -> bool {
+    let Some(expected) = manifest_hash.map(|s| s.trim()) else {
+        return true;
+    };
+    crate::acz_content::read_overlay_meta(zip_path)
+        .is_none_or(|m| m.manifest_hash.eq_ignore_ascii_case(expected))
+}
+
 fn sanitize_dir_component(s: &str) -> String {
     s.chars()
         .map(|c| {
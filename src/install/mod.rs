@@ -3,3 +3,4 @@ pub mod client_install;
 pub mod content_install;
 pub mod launcher_mask;
 pub mod robust_builds;
+pub mod zip_validate;
@@ -1,4 +1,6 @@
 use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
 
 use serde::Deserialize;
 
@@ -7,13 +9,21 @@ const ROBUST_BUILDS_MANIFEST_URLS: [&str; 2] = [
     "https://robust-builds.fallback.cdn.spacestation14.com/manifest.json",
 ];
 
+/// Last successfully fetched manifest, kept next to the engines it describes.
+const ROBUST_MANIFEST_CACHE_FILE_NAME: &str = "robust_manifest.json";
+
 #[derive(Debug, Clone)]
 pub struct RobustEngineBuild {
     pub requested_version: String,
     pub resolved_version: String,
+    /// Platform of the chosen build, e.g. `win-x64`.
+    pub rid: String,
     pub url: String,
     pub sha256: String,
     pub signature: String,
+    /// Set when robust-builds was unreachable and the build was resolved from the saved
+    /// manifest; holds the network error.
+    pub offline_reason: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -37,9 +47,40 @@ struct BuildInfo {
 }
 
 pub fn resolve_engine_build(engine_version: &str) -> Result<RobustEngineBuild, String> {
-    let manifest = fetch_manifest()?;
+    resolve_engine_build_with_fallback(engine_version, |_, _| false)
+}
+
+/// Like `resolve_engine_build`, but when the exact version is missing from the manifest
+/// (e.g. a yanked build) asks `accept_fallback(requested, candidate)` whether to use the
+/// nearest version with the same major/minor instead.
+pub fn resolve_engine_build_with_fallback(
+    engine_version: &str,
+    accept_fallback: impl FnOnce(&str, &str) -> bool,
+) -> Result<RobustEngineBuild, String> {
+    let (manifest, offline_reason) = match fetch_manifest() {
+        Ok(manifest) => (manifest, None),
+        Err(e) => match load_cached_manifest() {
+            Some(manifest) => (manifest, Some(e)),
+            None => return Err(e),
+        },
+    };
+
+    let (resolved_version, info) = match follow_redirects(engine_version, &manifest) {
+        Ok(found) => found,
+        Err(e) if !manifest.contains_key(engine_version) => {
+            let Some(candidate) = nearest_compatible_version(engine_version, &manifest) else {
+                return Err(e);
+            };
+            if !accept_fallback(engine_version, &candidate) {
+                return Err(format!(
+                    "{e} (ближайшая совместимая версия: {candidate}, замена не подтверждена)"
+                ));
+            }
+            follow_redirects(&candidate, &manifest)?
+        }
+        Err(e) => return Err(e),
+    };
 
-    let (resolved_version, info) = follow_redirects(engine_version, &manifest)?;
     if info.insecure {
         return Err("указанная версия движка помечена как insecure".to_string());
     }
@@ -58,11 +99,43 @@ pub fn resolve_engine_build(engine_version: &str) -> Result<RobustEngineBuild, S
         requested_version: engine_version.to_string(),
         resolved_version,
         url: build.url.clone(),
+        rid,
         sha256: build.sha256.clone(),
         signature: build.signature.clone(),
+        offline_reason,
     })
 }
 
+fn parse_version(v: &str) -> Option<Vec<u64>> {
+    v.trim()
+        .split('.')
+        .map(|p| p.parse::<u64>().ok())
+        .collect()
+}
+
+/// Closest non-insecure version sharing major.minor with `requested`; ties go to the newer one.
+fn nearest_compatible_version(
+    requested: &str,
+    manifest: &HashMap<String, VersionInfo>,
+) -> Option<String> {
+    let want = parse_version(requested)?;
+    if want.len() < 2 {
+        return None;
+    }
+    let want_patch = want.get(2).copied().unwrap_or(0);
+
+    manifest
+        .iter()
+        .filter(|(_, info)| !info.insecure && !info.platforms.is_empty())
+        .filter_map(|(k, _)| {
+            let v = parse_version(k)?;
+            (v.len() >= 2 && v[0] == want[0] && v[1] == want[1])
+                .then(|| (k.clone(), v.get(2).copied().unwrap_or(0)))
+        })
+        .min_by_key(|(_, patch)| (patch.abs_diff(want_patch), std::cmp::Reverse(*patch)))
+        .map(|(k, _)| k)
+}
+
 fn fetch_manifest() -> Result<HashMap<String, VersionInfo>, String> {
     let http = crate::launcher_mask::blocking_http_client_api()?;
 
@@ -70,8 +143,12 @@ fn fetch_manifest() -> Result<HashMap<String, VersionInfo>, String> {
     for url in ROBUST_BUILDS_MANIFEST_URLS {
         match crate::http_config::blocking_send_idempotent_with_retry(|| http.get(url)) {
             Ok(resp) => match resp.error_for_status() {
-                Ok(ok) => match ok.json::<HashMap<String, VersionInfo>>() {
-                    Ok(m) => return Ok(m),
+                Ok(ok) => match read_manifest(ok) {
+                    Ok((m, raw)) => {
+                        // Best effort: a failed write only costs offline launches.
+                        let _ = save_cached_manifest(&raw);
+                        return Ok(m);
+                    }
                     Err(e) => last_err = Some(format!("robust manifest parse: {e}")),
                 },
                 Err(e) => last_err = Some(format!("robust manifest status: {e}")),
@@ -83,6 +160,37 @@ fn fetch_manifest() -> Result<HashMap<String, VersionInfo>, String> {
     Err(last_err.unwrap_or_else(|| "не удалось загрузить robust manifest".to_string()))
 }
 
+/// Parsed manifest together with the raw body, which is what gets cached.
+fn read_manifest(
+    resp: reqwest::blocking::Response,
+) -> Result<(HashMap<String, VersionInfo>, Vec<u8>), String> {
+    let raw = resp.bytes().map_err(|e| e.to_string())?.to_vec();
+    let manifest = serde_json::from_slice(&raw).map_err(|e| e.to_string())?;
+    Ok((manifest, raw))
+}
+
+fn cached_manifest_path() -> Result<PathBuf, String> {
+    Ok(crate::app_paths::data_dir()?
+        .join("engines")
+        .join(ROBUST_MANIFEST_CACHE_FILE_NAME))
+}
+
+fn load_cached_manifest() -> Option<HashMap<String, VersionInfo>> {
+    let raw = fs::read(cached_manifest_path().ok()?).ok()?;
+    serde_json::from_slice(&raw).ok()
+}
+
+fn save_cached_manifest(raw: &[u8]) -> Result<(), String> {
+    let path = cached_manifest_path()?;
+    if let Some(dir) = path.parent() {
+        crate::app_paths::create_dir(dir, "движки")?;
+    }
+    // Written via a temp file so an interrupted write never leaves a truncated manifest.
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, raw).map_err(|e| format!("запись robust manifest: {e}"))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("запись robust manifest: {e}"))
+}
+
 fn follow_redirects(
     requested_version: &str,
     manifest: &HashMap<String, VersionInfo>,
@@ -108,24 +216,8 @@ fn follow_redirects(
 
 fn pick_best_rid(available: Vec<&str>) -> Option<String> {
     // Minimal RID selection mirroring SS14.Launcher behavior.
-    // Prefer exact matches for current OS/arch.
-    let os = std::env::consts::OS;
-    let arch = std::env::consts::ARCH;
-
-    let mut candidates: Vec<&str> = Vec::new();
-
-    match (os, arch) {
-        ("windows", "x86_64") => candidates.extend(["win-x64", "win-x86"]),
-        ("windows", "x86") => candidates.extend(["win-x86", "win-x64"]),
-        ("windows", "aarch64") => candidates.extend(["win-arm64", "win-x64"]),
-        ("linux", "x86_64") => candidates.extend(["linux-x64"]),
-        ("linux", "aarch64") => candidates.extend(["linux-arm64"]),
-        ("macos", "aarch64") => candidates.extend(["osx-arm64"]),
-        ("macos", "x86_64") => candidates.extend(["osx-x64"]),
-        _ => {}
-    }
-
-    for c in candidates {
+    // Prefer exact matches for the machine's OS/arch (native arm64 even for an x64 launcher).
+    for &c in crate::platform::engine_rid_candidates() {
         if available.iter().any(|x| x.eq_ignore_ascii_case(c)) {
             // Use canonical casing from manifest key if possible.
             if let Some(actual) = available.iter().find(|x| x.eq_ignore_ascii_case(c)) {
@@ -0,0 +1,60 @@
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+/// How many entries are fully read (and CRC-checked) per validation.
+const SAMPLE_ENTRIES: usize = 16;
+
+/// Opens `path`, parses the central directory and reads a spread-out sample of entries.
+/// A truncated or corrupted zip fails here instead of as a mount error inside the game.
+/// Returns the number of entries.
+pub fn validate_zip(path: &Path) -> Result<usize, String> {
+    let file = fs::File::open(path).map_err(|e| format!("open {:?}: {e}", path))?;
+    let mut archive = zip::ZipArchive::new(std::io::BufReader::new(file))
+        .map_err(|e| format!("{:?}: повреждён каталог zip ({e})", path))?;
+
+    let count = archive.len();
+    if count == 0 {
+        return Err(format!("{:?}: zip пустой", path));
+    }
+
+    // First and last entries catch truncation; the rest are spread evenly.
+    let step = (count / SAMPLE_ENTRIES).max(1);
+    let mut indices: Vec<usize> = (0..count).step_by(step).take(SAMPLE_ENTRIES).collect();
+    if indices.last() != Some(&(count - 1)) {
+        indices.push(count - 1);
+    }
+
+    let mut sink = [0u8; 64 * 1024];
+    for idx in indices {
+        let mut entry = archive
+            .by_index(idx)
+            .map_err(|e| format!("{:?}: запись #{idx} не читается ({e})", path))?;
+        if entry.is_dir() {
+            continue;
+        }
+        let name = entry.name().to_string();
+        // Reading to the end makes the zip crate verify the entry CRC.
+        loop {
+            match entry.read(&mut sink) {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(e) => return Err(format!("{:?}: файл {name} повреждён ({e})", path)),
+            }
+        }
+    }
+
+    Ok(count)
+}
+
+/// Removes a zip that failed validation together with its `.acz_overlay` marker and meta sidecar, so the next
+/// connect rebuilds it instead of reusing it.
+pub fn discard_invalid_zip(path: &Path) {
+    let _ = fs::remove_file(path);
+    if let (Some(dir), Some(name)) = (path.parent(), path.file_name()) {
+        let mut marker = name.to_os_string();
+        marker.push(".acz_overlay");
+        let _ = fs::remove_file(dir.join(marker));
+    }
+    let _ = fs::remove_file(crate::acz_content::overlay_meta_path(path));
+}
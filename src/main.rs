@@ -10,11 +10,19 @@ mod ui;
 
 pub use core::cache_cleanup;
 pub use core::open_url;
-pub use core::{app_paths, cancel_flag, constants};
+pub use core::{
+    app_paths, cancel_flag, cli_args, constants, disk_space, fs_retry, platform, startup_checks,
+    tasks, wine,
+};
 pub use install::{acz_content, client_install, content_install, launcher_mask, robust_builds};
-pub use net::{auth, connect, connect_progress, http_config, servers};
+pub use net::{
+    auth, connect, connect_progress, content_updates, geoip, http_config, hub_latency, servers,
+};
 pub use ss14::{ss14_loader, ss14_server_info, ss14_uri};
-pub use storage::{account_store, favorites, secure_token, settings};
+pub use storage::{
+    account_store, build_overrides, cache_stats, favorites, favorites_sync, hidden_servers,
+    known_good, last_server, secure_token, settings, ui_state,
+};
 
 pub use marsey::*;
 
@@ -26,5 +34,8 @@ use crate::ui::app;
 use crate::window::app_window;
 
 fn main() {
+    http_config::install_local_proxy_bypass();
+    // Take the data directory before anything else can write to it.
+    let _ = core::instance_lock::instance_mode();
     LaunchBuilder::desktop().with_cfg(app_window()).launch(app);
 }
@@ -31,6 +31,152 @@ pub fn try_read_patch_display_info(path: &Path) -> Option<PatchDisplayInfo> {
     patch_display_info_from_bytes(&bytes).ok().flatten()
 }
 
+/// What identifies an assembly regardless of its file name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssemblyIdentity {
+    /// Module name from metadata (the name it was compiled as, e.g. `MyPatch.dll`).
+    pub module_name: String,
+    /// Module version id: identical for byte-identical builds.
+    pub mvid: [u8; 16],
+}
+
+pub fn try_read_assembly_identity(path: &Path) -> Option<AssemblyIdentity> {
+    let bytes = std::fs::read(path).ok()?;
+    assembly_identity_from_bytes(&bytes).ok().flatten()
+}
+
+/// Strings beyond this are counted, not listed; obfuscated DLLs can carry thousands.
+const MAX_REPORT_STRINGS: usize = 200;
+
+/// Everything the metadata scan can say about a DLL, for vetting a patch before enabling it.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PatchAnalysis {
+    pub file_size: u64,
+    pub is_dotnet: bool,
+    pub identity: Option<AssemblyIdentity>,
+    /// `MarseyPatch` and/or `SubverterPatch`.
+    pub entry_types: Vec<&'static str>,
+    pub namespace: Option<String>,
+    pub preload: bool,
+    pub fields: Vec<String>,
+    pub display: Option<PatchDisplayInfo>,
+    pub strings: Vec<String>,
+    pub strings_omitted: usize,
+}
+
+impl PatchAnalysis {
+    pub fn report(&self) -> String {
+        let mut out = Vec::new();
+        out.push(format!("размер: {} байт", self.file_size));
+        if !self.is_dotnet {
+            out.push("не .NET сборка (нет CLI заголовка) — Marsey её не загрузит".to_string());
+            return out.join("\n");
+        }
+        if let Some(id) = &self.identity {
+            let mvid: String = id.mvid.iter().map(|b| format!("{b:02x}")).collect();
+            out.push(format!("модуль: {} (mvid {mvid})", id.module_name));
+        }
+        if self.entry_types.is_empty() {
+            out.push("точка входа: нет MarseyPatch/SubverterPatch — это не патч".to_string());
+        } else {
+            out.push(format!("точка входа: {}", self.entry_types.join(", ")));
+        }
+        if let Some(ns) = &self.namespace {
+            out.push(format!("namespace: {ns}"));
+        }
+        out.push(format!(
+            "preload: {}",
+            if self.preload { "да (грузится до игры)" } else { "нет" }
+        ));
+        if !self.fields.is_empty() {
+            out.push(format!("поля: {}", self.fields.join(", ")));
+        }
+        if let Some(display) = &self.display {
+            let field = |v: &Option<String>| v.clone().unwrap_or_else(|| "—".to_string());
+            out.push(format!("Name: {}", field(&display.name)));
+            out.push(format!("Description: {}", field(&display.description)));
+            out.push(format!("Harmony id: {}", field(&display.rdnn)));
+        }
+        out.push(format!(
+            "строки ({}{}):",
+            self.strings.len(),
+            if self.strings_omitted > 0 {
+                format!(", ещё {} не показано", self.strings_omitted)
+            } else {
+                String::new()
+            }
+        ));
+        for s in &self.strings {
+            out.push(format!("  {s:?}"));
+        }
+        out.join("\n")
+    }
+}
+
+pub fn analyze_patch(path: &Path) -> Result<PatchAnalysis, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("чтение {:?}: {e}", path))?;
+    let mut analysis = PatchAnalysis {
+        file_size: bytes.len() as u64,
+        ..Default::default()
+    };
+
+    let pe = PeView::parse(&bytes)?;
+    let Some(cli) = pe.cli_header() else {
+        return Ok(analysis);
+    };
+    analysis.is_dotnet = true;
+    let Some(metadata) = pe.metadata_root(cli.metadata_rva)? else {
+        return Ok(analysis);
+    };
+    analysis.identity = assembly_identity_from_bytes(&bytes).ok().flatten();
+    let Some(tables) = metadata.tables_stream()? else {
+        return Ok(analysis);
+    };
+
+    for entry in ["MarseyPatch", "SubverterPatch"] {
+        let Some(fields) = tables.typedef_fields(entry)? else {
+            continue;
+        };
+        analysis.entry_types.push(entry);
+        if analysis.namespace.is_none() {
+            analysis.namespace = tables.find_typedef_namespace(entry)?;
+        }
+        analysis.preload |= fields.iter().any(|f| f.name == "preload" && f.is_bool);
+        analysis
+            .fields
+            .extend(fields.into_iter().map(|f| format!("{entry}.{}", f.name)));
+    }
+
+    analysis.display = patch_display_info_from_bytes(&bytes).ok().flatten();
+    (analysis.strings, analysis.strings_omitted) = tables.user_strings(MAX_REPORT_STRINGS);
+
+    Ok(analysis)
+}
+
+fn assembly_identity_from_bytes(bytes: &[u8]) -> Result<Option<AssemblyIdentity>, String> {
+    let pe = PeView::parse(bytes)?;
+    let Some(cli) = pe.cli_header() else {
+        return Ok(None);
+    };
+    let Some(metadata) = pe.metadata_root(cli.metadata_rva)? else {
+        return Ok(None);
+    };
+    let Some(tables) = metadata.tables_stream()? else {
+        return Ok(None);
+    };
+    let Some((name_idx, mvid_idx)) = tables.module_row()? else {
+        return Ok(None);
+    };
+    let Some(mvid) = metadata.read_guid(mvid_idx) else {
+        return Ok(None);
+    };
+
+    Ok(Some(AssemblyIdentity {
+        module_name: tables.read_string(name_idx)?,
+        mvid,
+    }))
+}
+
 fn classify_bytes(bytes: &[u8]) -> Result<Option<PatchClassification>, String> {
     let pe = PeView::parse(bytes)?;
     let Some(cli) = pe.cli_header() else {
@@ -376,6 +522,7 @@ impl<'a> PeView<'a> {
 
         let mut strings = None;
         let mut blob = None;
+        let mut guid = None;
         let mut us = None;
         let mut tables = None;
 
@@ -408,6 +555,7 @@ impl<'a> PeView<'a> {
             match name.as_str() {
                 "#Strings" => strings = Some((abs_off, size)),
                 "#Blob" => blob = Some((abs_off, size)),
+                "#GUID" => guid = Some((abs_off, size)),
                 "#US" => us = Some((abs_off, size)),
                 "#~" | "#-" => tables = Some((abs_off, size)),
                 _ => {}
@@ -418,12 +566,19 @@ impl<'a> PeView<'a> {
             bytes: self.bytes,
             strings,
             blob,
+            guid,
             us,
             tables,
         }))
     }
 }
 
+#[derive(Debug, Clone)]
+struct FieldInfo {
+    name: String,
+    is_bool: bool,
+}
+
 #[derive(Debug, Clone, Copy)]
 #[allow(dead_code)]
 struct CctorMethod {
@@ -441,11 +596,28 @@ struct MetadataRoot<'a> {
     bytes: &'a [u8],
     strings: Option<(usize, usize)>,
     blob: Option<(usize, usize)>,
+    guid: Option<(usize, usize)>,
     us: Option<(usize, usize)>,
     tables: Option<(usize, usize)>,
 }
 
 impl<'a> MetadataRoot<'a> {
+    /// GUID heap indices are 1-based, 16 bytes per entry.
+    fn read_guid(&self, idx: u32) -> Option<[u8; 16]> {
+        let (guid_off, guid_size) = self.guid?;
+        if idx == 0 {
+            return None;
+        }
+        let start = (idx as usize - 1) * 16;
+        if start + 16 > guid_size {
+            return None;
+        }
+        self.bytes
+            .get(guid_off + start..guid_off + start + 16)?
+            .try_into()
+            .ok()
+    }
+
     fn tables_stream(&self) -> Result<Option<TablesStream<'a>>, String> {
         let Some((tables_off, tables_size)) = self.tables else {
             return Ok(None);
@@ -573,6 +745,30 @@ impl<'a> TablesStream<'a> {
         Ok(Some(s))
     }
 
+    /// Walks the whole #US heap. Returns up to `max` strings and how many were left out.
+    fn user_strings(&self, max: usize) -> (Vec<String>, usize) {
+        let mut out = Vec::new();
+        let mut omitted = 0usize;
+        // Offset 0 is the mandatory empty entry.
+        let mut idx = 1usize;
+        while idx < self.us_size {
+            let Ok((len, hdr)) = read_compressed_u32(self.bytes, self.us_off + idx) else {
+                break;
+            };
+            if let Ok(Some(s)) = self.read_user_string_token(0x7000_0000 | idx as u32)
+                && !s.trim().is_empty()
+            {
+                if out.len() < max {
+                    out.push(s);
+                } else {
+                    omitted += 1;
+                }
+            }
+            idx += hdr + len as usize;
+        }
+        (out, omitted)
+    }
+
     fn read_field_name_from_token(&self, token: u32) -> Result<Option<String>, String> {
         if (token >> 24) != 0x04 {
             return Ok(None);
@@ -718,6 +914,25 @@ impl<'a> TablesStream<'a> {
         Ok(None)
     }
 
+    /// Module table row 0: (Name string index, Mvid guid index).
+    fn module_row(&self) -> Result<Option<(u32, u32)>, String> {
+        if self.rows[0] == 0 {
+            return Ok(None);
+        }
+        let string_index_size = if (self.heap_sizes & 0x01) != 0 { 4 } else { 2 };
+        let guid_index_size = if (self.heap_sizes & 0x02) != 0 { 4 } else { 2 };
+
+        // Generation (u16), Name, Mvid, EncId, EncBaseId.
+        let name_off = self.tables_data_off + 2;
+        let mvid_off = name_off + string_index_size;
+        if mvid_off + guid_index_size > self.bytes.len() {
+            return Ok(None);
+        }
+        let name_idx = read_index(self.bytes, name_off, string_index_size)?;
+        let mvid_idx = read_index(self.bytes, mvid_off, guid_index_size)?;
+        Ok(Some((name_idx, mvid_idx)))
+    }
+
     fn methodptr_table_start(&self) -> Result<usize, String> {
         let string_index_size = if (self.heap_sizes & 0x01) != 0 { 4 } else { 2 };
         let blob_index_size = if (self.heap_sizes & 0x04) != 0 { 4 } else { 2 };
@@ -826,6 +1041,15 @@ impl<'a> TablesStream<'a> {
     }
 
     fn has_typedef_with_preload(&self, type_name: &str) -> Result<(bool, bool), String> {
+        let Some(fields) = self.typedef_fields(type_name)? else {
+            return Ok((false, false));
+        };
+        let preload = fields.iter().any(|f| f.name == "preload" && f.is_bool);
+        Ok((true, preload))
+    }
+
+    /// Fields declared on `type_name`, or `None` if there is no such type.
+    fn typedef_fields(&self, type_name: &str) -> Result<Option<Vec<FieldInfo>>, String> {
         let string_index_size = if (self.heap_sizes & 0x01) != 0 { 4 } else { 2 };
         let guid_index_size = if (self.heap_sizes & 0x02) != 0 { 4 } else { 2 };
         let blob_index_size = if (self.heap_sizes & 0x04) != 0 { 4 } else { 2 };
@@ -867,7 +1091,7 @@ impl<'a> TablesStream<'a> {
 
         let typedef_count = self.rows[2] as usize;
         if typedef_count == 0 {
-            return Ok((false, false));
+            return Ok(None);
         }
 
         let mut wanted_pos: Option<usize> = None;
@@ -898,12 +1122,13 @@ impl<'a> TablesStream<'a> {
         }
 
         let Some(pos) = wanted_pos else {
-            return Ok((false, false));
+            return Ok(None);
         };
 
+        let mut fields = Vec::new();
         let start = fieldlists[pos];
         if start == 0 {
-            return Ok((true, false));
+            return Ok(Some(fields));
         }
 
         let end = if pos + 1 < fieldlists.len() {
@@ -915,10 +1140,9 @@ impl<'a> TablesStream<'a> {
         };
 
         if start >= end {
-            return Ok((true, false));
+            return Ok(Some(fields));
         }
 
-        let mut preload = false;
         for logical_idx in start..end {
             let field_row = if fieldptr_present {
                 let ptr_off = self.tables_data_off
@@ -947,21 +1171,16 @@ impl<'a> TablesStream<'a> {
             p += string_index_size;
             let fsig_idx = read_index(self.bytes, p, blob_index_size)?;
 
-            let fname = self.read_string(fname_idx)?;
-            if fname != "preload" {
-                continue;
-            }
-
-            if let Some(sig) = self.read_blob(fsig_idx)? {
-                // FieldSig ::= 0x06 <type>
-                // bool element type is 0x02
-                if sig.len() >= 2 && sig[0] == 0x06 && sig[1] == 0x02 {
-                    preload = true;
-                }
-            }
+            let name = self.read_string(fname_idx)?;
+            // FieldSig ::= 0x06 <type>
+            // bool element type is 0x02
+            let is_bool = self
+                .read_blob(fsig_idx)?
+                .is_some_and(|sig| sig.len() >= 2 && sig[0] == 0x06 && sig[1] == 0x02);
+            fields.push(FieldInfo { name, is_bool });
         }
 
-        Ok((true, preload))
+        Ok(Some(fields))
     }
 
     fn find_typedef_namespace(&self, type_name: &str) -> Result<Option<String>, String> {
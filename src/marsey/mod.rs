@@ -1,9 +1,17 @@
 use std::collections::HashSet;
 use std::ffi::OsStr;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::time::{Duration, Instant};
+
+use crate::settings::PatchCategories;
 
 mod dotnet_metadata;
-mod pipes;
+pub use dotnet_metadata::PatchAnalysis;
+pub mod patch_repo;
+pub mod patch_setup;
+pub(crate) mod pipes;
 
 const PIPE_MARSEY_CONF: &str = "MarseyConf";
 const PIPE_PRELOAD: &str = "PreloadMarseyPatchesPipe";
@@ -23,6 +31,7 @@ pub struct MarseyLaunchContext {
     pub fork_id: String,
     pub hide_level: String,
     pub disable_redial: bool,
+    pub patch_categories: crate::settings::PatchCategories,
 }
 
 #[derive(Debug, Default)]
@@ -163,8 +172,8 @@ pub fn ensure_marsey_dirs(data_dir: &Path) -> Result<MarseyPaths, String> {
     // Do NOT auto-create it so new installs don't get the old folder.
     let legacy_mods_dir = marsey_root.join(LEGACY_MODS_DIR);
 
-    std::fs::create_dir_all(&patches_dir).map_err(|e| format!("mkdir {:?}: {e}", patches_dir))?;
-    std::fs::create_dir_all(&rpacks_dir).map_err(|e| format!("mkdir {:?}: {e}", rpacks_dir))?;
+    crate::app_paths::create_dir(&patches_dir, "патчи")?;
+    crate::app_paths::create_dir(&rpacks_dir, "ресурспаки")?;
 
     Ok(MarseyPaths {
         marsey_root,
@@ -184,6 +193,7 @@ pub struct MarseyPaths {
 #[derive(Debug, Clone)]
 pub struct PatchEntry {
     pub filename: String,
+    pub path: PathBuf,
     pub enabled: bool,
     pub name: String,
     pub description: String,
@@ -233,6 +243,7 @@ pub fn list_patches(data_dir: &Path) -> Result<(PathBuf, Vec<PatchEntry>), Strin
 
         out.push(PatchEntry {
             filename,
+            path: p,
             enabled,
             name,
             description,
@@ -243,21 +254,88 @@ pub fn list_patches(data_dir: &Path) -> Result<(PathBuf, Vec<PatchEntry>), Strin
     Ok((paths.patches_dir, out))
 }
 
-pub fn set_patch_enabled(data_dir: &Path, filename: &str, enabled: bool) -> Result<(), String> {
+/// A patch DLL that is another copy of an assembly already loaded from `original`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicatePatch {
+    pub assembly: String,
+    /// The copy that stays: the one in `patches/`, otherwise the most recently modified.
+    pub original: PathBuf,
+    pub duplicate: PathBuf,
+}
+
+/// Finds patch DLLs that are the same assembly under different file names (e.g. a renamed
+/// copy in `patches/` and the legacy `Marsey/Mods`): same module name and same file contents.
+/// Different patches that happen to share a module name are left alone.
+pub fn find_duplicate_patches(data_dir: &Path) -> Result<Vec<DuplicatePatch>, String> {
     let paths = ensure_marsey_dirs(data_dir)?;
     let mods_dirs = patch_scan_dirs(&paths);
 
-    // Keep patchlist scoped to actual patches only.
-    let mut all: Vec<String> = Vec::new();
-    let mut dlls = list_patch_dlls(&mods_dirs)?;
-    dlls.retain(|p| dotnet_metadata::try_classify_patch(p).is_some());
-    for p in dlls {
-        let Some(name) = p.file_name() else {
+    let mut groups: Vec<((String, String), String, Vec<PathBuf>)> = Vec::new();
+    for p in list_patch_dlls(&mods_dirs)? {
+        let Some(identity) = dotnet_metadata::try_read_assembly_identity(&p) else {
             continue;
         };
-        all.push(name.to_string_lossy().to_string());
+        let Ok(hash) = patch_setup::sha256_file(&p) else {
+            continue;
+        };
+        let key = (normalize_case(&identity.module_name), hash);
+        match groups.iter_mut().find(|(k, _, _)| *k == key) {
+            Some((_, _, members)) => members.push(p),
+            None => groups.push((key, identity.module_name, vec![p])),
+        }
+    }
+
+    let mut out = Vec::new();
+    for (_, assembly, mut members) in groups {
+        if members.len() < 2 {
+            continue;
+        }
+        members.sort_by_key(|p| {
+            let legacy = p.starts_with(&paths.legacy_mods_dir);
+            let modified = std::fs::metadata(p).and_then(|m| m.modified()).ok();
+            (legacy, std::cmp::Reverse(modified))
+        });
+        for dup in &members[1..] {
+            out.push(DuplicatePatch {
+                assembly: assembly.clone(),
+                original: members[0].clone(),
+                duplicate: dup.clone(),
+            });
+        }
     }
 
+    Ok(out)
+}
+
+/// Deletes a duplicate found by `find_duplicate_patches`. Refuses paths outside the patch dirs.
+pub fn remove_duplicate_patch(data_dir: &Path, path: &Path) -> Result<(), String> {
+    let paths = ensure_marsey_dirs(data_dir)?;
+    let inside = patch_scan_dirs(&paths)
+        .iter()
+        .any(|dir| path.parent() == Some(dir.as_path()));
+    if !inside || !is_dll_path(path) {
+        return Err(format!("{:?} не находится в каталоге патчей", path));
+    }
+    std::fs::remove_file(path).map_err(|e| format!("удаление {:?}: {e}", path))
+}
+
+/// File names of all patch DLLs on disk (non-patch DLLs excluded).
+fn all_patch_filenames(paths: &MarseyPaths) -> Result<Vec<String>, String> {
+    let mut dlls = list_patch_dlls(&patch_scan_dirs(paths))?;
+    dlls.retain(|p| dotnet_metadata::try_classify_patch(p).is_some());
+    Ok(dlls
+        .iter()
+        .filter_map(|p| p.file_name())
+        .map(|name| name.to_string_lossy().to_string())
+        .collect())
+}
+
+pub fn set_patch_enabled(data_dir: &Path, filename: &str, enabled: bool) -> Result<(), String> {
+    let paths = ensure_marsey_dirs(data_dir)?;
+
+    // Keep patchlist scoped to actual patches only.
+    let all = all_patch_filenames(&paths)?;
+
     let target_norm = normalize_case(filename);
 
     let mut enabled_actual: HashSet<String> = if paths.patchlist_file.exists() {
@@ -291,6 +369,27 @@ pub fn set_patch_enabled(data_dir: &Path, filename: &str, enabled: bool) -> Resu
         enabled_actual.retain(|n| normalize_case(n) != target_norm);
     }
 
+    write_patchlist(&paths, &all, enabled_actual)
+}
+
+/// Enables exactly the patches in `enabled` (file names, any case); all others are disabled.
+pub fn set_enabled_patches(data_dir: &Path, enabled: &[String]) -> Result<(), String> {
+    let paths = ensure_marsey_dirs(data_dir)?;
+    let all = all_patch_filenames(&paths)?;
+    let wanted: HashSet<String> = enabled.iter().map(|n| normalize_case(n)).collect();
+    let enabled_actual = all
+        .iter()
+        .filter(|n| wanted.contains(&normalize_case(n)))
+        .cloned()
+        .collect();
+    write_patchlist(&paths, &all, enabled_actual)
+}
+
+fn write_patchlist(
+    paths: &MarseyPaths,
+    all: &[String],
+    enabled_actual: HashSet<String>,
+) -> Result<(), String> {
     // If everything is enabled, keep defaults by removing patchlist file.
     let all_norm: HashSet<String> = all.iter().map(|n| normalize_case(n)).collect();
     let enabled_norm: HashSet<String> = enabled_actual.iter().map(|n| normalize_case(n)).collect();
@@ -316,6 +415,11 @@ pub fn try_get_patch_rdnn(path: &Path) -> Option<String> {
         .or_else(|| dotnet_metadata::try_get_typedef_namespace(path, "SubverterPatch"))
 }
 
+/// Verbose metadata scan of a patch DLL (entry type, preload, fields, strings). Blocking.
+pub fn analyze_patch(path: &Path) -> Result<PatchAnalysis, String> {
+    dotnet_metadata::analyze_patch(path)
+}
+
 pub fn prepare_pipes_for_launch(
     data_dir: &Path,
     ctx: &MarseyLaunchContext,
@@ -326,9 +430,24 @@ pub fn prepare_pipes_for_launch(
     let enabled = load_enabled_patch_filenames(&paths)?;
     let mut scan = scan_mods_dir(&mods_dirs, &enabled)?;
 
+    // A restricted launch sends exactly the classified patches of one kind; the catch-all
+    // below would smuggle the other kind (or unclassified DLLs) back in via the Marsey pipe.
+    match ctx.patch_categories {
+        PatchCategories::All => {}
+        PatchCategories::MarseyOnly => scan.subverter.clear(),
+        PatchCategories::SubverterOnly => {
+            scan.preload.clear();
+            scan.marsey.clear();
+        }
+    }
+
     // Always load all enabled DLLs at least once.
     // Some mods rely on module initializers / self-hooking and don't declare MarseyPatch/SubverterPatch.
-    let all_enabled = collect_enabled_mod_dlls(&mods_dirs, &enabled)?;
+    let all_enabled = if ctx.patch_categories == PatchCategories::All {
+        collect_enabled_mod_dlls(&mods_dirs, &enabled)?
+    } else {
+        Vec::new()
+    };
 
     if !all_enabled.is_empty() {
         let preload_set: HashSet<String> = scan.preload.iter().map(|p| p.to_lowercase()).collect();
@@ -351,6 +470,7 @@ pub fn prepare_pipes_for_launch(
     }
 
     // If the scanner fails to classify anything, fall back to sending all enabled DLLs via Marsey.
+    // (Only in the unrestricted mode: `all_enabled` is empty otherwise.)
     if scan.preload.is_empty() && scan.marsey.is_empty() && scan.subverter.is_empty() {
         scan.marsey = all_enabled;
     }
@@ -426,59 +546,97 @@ fn override_conf_kv(conf: &str, key: &str, value: &str) -> String {
         .join(";")
 }
 
-pub fn send_pipes(batch: MarseyPipeBatch) -> Result<(), String> {
-    // Loader may take a while to reach MarseyConf read (zip mount, ALC resolving, etc.).
-    let timeout_ms = 60_000u32;
-
-    let conf_data = batch.marsey_conf;
-    let preload_data = batch.preload;
-    let marsey_data = batch.marsey;
-    let subverter_data = batch.subverter;
-
-    let t_conf = std::thread::spawn(move || {
-        pipes::send_named_pipe_utf8(PIPE_MARSEY_CONF, &conf_data, timeout_ms)
-            .map_err(|e| format!("{PIPE_MARSEY_CONF}: {e}"))
-    });
-    let t_preload = std::thread::spawn(move || {
-        pipes::send_named_pipe_utf8(PIPE_PRELOAD, &preload_data, timeout_ms)
-            .map_err(|e| format!("{PIPE_PRELOAD}: {e}"))
-    });
-    let t_marsey = std::thread::spawn(move || {
-        pipes::send_named_pipe_utf8(PIPE_MARSEY, &marsey_data, timeout_ms)
-            .map_err(|e| format!("{PIPE_MARSEY}: {e}"))
-    });
-    let t_subverter = std::thread::spawn(move || {
-        pipes::send_named_pipe_utf8(PIPE_SUBVERTER, &subverter_data, timeout_ms)
-            .map_err(|e| format!("{PIPE_SUBVERTER}: {e}"))
-    });
-
-    let mut errors: Vec<String> = Vec::new();
-
-    match t_conf.join() {
-        Ok(Ok(())) => {}
-        Ok(Err(e)) => errors.push(e),
-        Err(_) => errors.push("MarseyConf pipe thread panic".to_string()),
-    }
-    match t_preload.join() {
-        Ok(Ok(())) => {}
-        Ok(Err(e)) => errors.push(e),
-        Err(_) => errors.push("Preload pipe thread panic".to_string()),
+/// Writes each pipe payload to `<logs_dir>/marsey-pipes/<timestamp>-<attempt>/<pipe>.txt`
+/// so patch authors can see exactly what the loader received.
+pub fn dump_pipe_payloads(
+    batch: &MarseyPipeBatch,
+    logs_dir: &Path,
+    attempt: usize,
+) -> Result<PathBuf, String> {
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let dir = logs_dir
+        .join("marsey-pipes")
+        .join(format!("{stamp}-attempt{}", attempt + 1));
+    crate::app_paths::create_dir(&dir, "marsey-pipes")?;
+
+    for (pipe, payload) in [
+        (PIPE_MARSEY_CONF, &batch.marsey_conf),
+        (PIPE_PRELOAD, &batch.preload),
+        (PIPE_MARSEY, &batch.marsey),
+        (PIPE_SUBVERTER, &batch.subverter),
+    ] {
+        let path = dir.join(format!("{pipe}.txt"));
+        std::fs::write(&path, payload).map_err(|e| format!("запись {:?}: {e}", path))?;
     }
-    match t_marsey.join() {
-        Ok(Ok(())) => {}
-        Ok(Err(e)) => errors.push(e),
-        Err(_) => errors.push("Marsey patches pipe thread panic".to_string()),
-    }
-    match t_subverter.join() {
-        Ok(Ok(())) => {}
-        Ok(Err(e)) => errors.push(e),
-        Err(_) => errors.push("Subverter pipe thread panic".to_string()),
+
+    Ok(dir)
+}
+
+/// One pipe the loader never read from (or that broke mid-write).
+#[derive(Debug, Clone)]
+pub struct PipeFailure {
+    pub pipe: &'static str,
+    pub error: String,
+    /// How long this pipe waited before giving up.
+    pub elapsed: Duration,
+}
+
+impl std::fmt::Display for PipeFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} (ждали {:.1} с)",
+            self.pipe,
+            self.error,
+            self.elapsed.as_secs_f32()
+        )
     }
+}
+
+/// Serves the four Marsey pipes until the loader has read them all, the timeout passes or
+/// `stop` is set.
+pub fn send_pipes(batch: MarseyPipeBatch, stop: &Arc<AtomicBool>) -> Result<(), Vec<PipeFailure>> {
+    // Loader may take a while to reach MarseyConf read (zip mount, ALC resolving, etc.).
+    let timeout_ms = crate::wine::pipe_timeout_ms(60_000);
+
+    let threads: Vec<_> = [
+        (PIPE_MARSEY_CONF, batch.marsey_conf),
+        (PIPE_PRELOAD, batch.preload),
+        (PIPE_MARSEY, batch.marsey),
+        (PIPE_SUBVERTER, batch.subverter),
+    ]
+    .into_iter()
+    .map(|(pipe, data)| {
+        let started = Instant::now();
+        let stop = stop.clone();
+        let handle = std::thread::spawn(move || {
+            let res = pipes::send_named_pipe_utf8(pipe, &data, timeout_ms, &stop);
+            (res, started.elapsed())
+        });
+        (pipe, started, handle)
+    })
+    .collect();
+
+    let failures: Vec<PipeFailure> = threads
+        .into_iter()
+        .filter_map(|(pipe, started, handle)| {
+            let (error, elapsed) = match handle.join() {
+                Ok((Ok(()), _)) => return None,
+                Ok((Err(e), elapsed)) => (e, elapsed),
+                Err(_) => ("поток pipe завершился с паникой".to_string(), started.elapsed()),
+            };
+            Some(PipeFailure {
+                pipe,
+                error,
+                elapsed,
+            })
+        })
+        .collect();
 
-    if errors.is_empty() {
+    if failures.is_empty() {
         Ok(())
     } else {
-        Err(errors.join("; "))
+        Err(failures)
     }
 }
 
@@ -507,7 +665,7 @@ fn build_marsey_conf_string(ctx: &MarseyLaunchContext) -> String {
     // Keep it strict: every segment must contain '='.
     let mut parts: Vec<String> = Vec::new();
 
-    // Logging to SS14.Loader stdout (captured by our last-launch.log).
+    // Logging to SS14.Loader stdout (captured by our launch-<session>.log).
     parts.push("MARSEY_LOGGING=true".to_string());
     // Keep defaults quiet; enable when diagnosing patch issues.
     parts.push("MARSEY_LOADER_DEBUG=false".to_string());
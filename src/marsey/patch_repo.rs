@@ -0,0 +1,176 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use ed25519_dalek::{Signature, VerifyingKey};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use super::{ensure_marsey_dirs, is_dll_path};
+
+/// Detached signature of the index lives next to it: `<index_url>.sig` (hex ed25519).
+const INDEX_SIGNATURE_SUFFIX: &str = ".sig";
+const MAX_INDEX_BYTES: u64 = 4 * 1024 * 1024;
+const MAX_SIGNATURE_BYTES: u64 = 4 * 1024;
+const MAX_PATCH_BYTES: u64 = 64 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RemotePatch {
+    pub filename: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub url: String,
+    /// Hex SHA-256 of the DLL.
+    pub sha256: String,
+    /// Hex ed25519 signature over the raw DLL bytes.
+    pub signature: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RemotePatchIndex {
+    #[serde(default)]
+    patches: Vec<RemotePatch>,
+}
+
+/// Parses a trusted key as entered in settings: 32 raw bytes in hex.
+pub fn parse_trusted_key(hex_key: &str) -> Result<VerifyingKey, String> {
+    let bytes = hex::decode(hex_key.trim()).map_err(|e| format!("ключ не hex: {e}"))?;
+    let bytes: [u8; 32] = bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| "ключ должен быть 32 байта (64 hex символа)".to_string())?;
+    VerifyingKey::from_bytes(&bytes).map_err(|e| format!("неверный ed25519 ключ: {e}"))
+}
+
+/// Downloads the index and its detached signature; the index is only parsed
+/// once a trusted key has verified it.
+pub fn fetch_verified_index(
+    index_url: &str,
+    trusted_keys: &[String],
+) -> Result<Vec<RemotePatch>, String> {
+    let index_url = index_url.trim();
+    if index_url.is_empty() {
+        return Err("адрес индекса патчей не задан".to_string());
+    }
+    let keys = load_keys(trusted_keys)?;
+    let sig_url = signature_url(index_url)?;
+
+    let client = crate::launcher_mask::blocking_http_client_api()?;
+    let index_bytes = fetch_bytes(&client, index_url, MAX_INDEX_BYTES)?;
+    let sig_bytes = fetch_bytes(&client, &sig_url, MAX_SIGNATURE_BYTES)?;
+    let sig_hex = String::from_utf8_lossy(&sig_bytes);
+
+    verify_with_any(&keys, &index_bytes, &sig_hex)
+        .map_err(|e| format!("индекс патчей отклонён: {e}"))?;
+
+    let index: RemotePatchIndex = serde_json::from_slice(&index_bytes)
+        .map_err(|e| format!("{index_url}: parse error {e}"))?;
+    Ok(index.patches)
+}
+
+/// Downloads one patch, checks hash and signature, and only then moves it into the patches dir.
+pub fn install_remote_patch(
+    data_dir: &Path,
+    patch: &RemotePatch,
+    trusted_keys: &[String],
+) -> Result<PathBuf, String> {
+    let filename = patch.filename.trim();
+    if filename.is_empty()
+        || filename.contains(['/', '\\', ':'])
+        || filename.starts_with('.')
+        || !is_dll_path(Path::new(filename))
+    {
+        return Err(format!("недопустимое имя файла патча: {filename}"));
+    }
+    let keys = load_keys(trusted_keys)?;
+
+    let client = crate::launcher_mask::blocking_http_client_download()?;
+    let bytes = fetch_bytes(&client, patch.url.trim(), MAX_PATCH_BYTES)?;
+
+    let actual_sha = hex::encode(Sha256::digest(&bytes));
+    if !actual_sha.eq_ignore_ascii_case(patch.sha256.trim()) {
+        return Err(format!(
+            "{filename}: sha256 не совпадает: expected={} actual={actual_sha}",
+            patch.sha256.trim()
+        ));
+    }
+
+    verify_with_any(&keys, &bytes, &patch.signature)
+        .map_err(|e| format!("{filename}: подпись патча отклонена: {e}"))?;
+
+    let paths = ensure_marsey_dirs(data_dir)?;
+    let final_path = paths.patches_dir.join(filename);
+    let temp_path = paths.patches_dir.join(format!(".{filename}.download"));
+    std::fs::write(&temp_path, &bytes).map_err(|e| format!("write {:?}: {e}", temp_path))?;
+    if let Err(e) = std::fs::rename(&temp_path, &final_path) {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(format!("rename {:?}: {e}", final_path));
+    }
+
+    Ok(final_path)
+}
+
+/// `<index_url>.sig`, appended to the path so a query string stays where it was.
+fn signature_url(index_url: &str) -> Result<String, String> {
+    let mut url =
+        url::Url::parse(index_url).map_err(|e| format!("{index_url}: некорректный URL: {e}"))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("{index_url}: нужен адрес http:// или https://"));
+    }
+    let path = format!("{}{INDEX_SIGNATURE_SUFFIX}", url.path());
+    url.set_path(&path);
+    Ok(url.into())
+}
+
+fn load_keys(trusted_keys: &[String]) -> Result<Vec<VerifyingKey>, String> {
+    let keys: Vec<VerifyingKey> = trusted_keys
+        .iter()
+        .filter_map(|k| parse_trusted_key(k).ok())
+        .collect();
+    if keys.is_empty() {
+        return Err("нет доверенных ключей для проверки подписи".to_string());
+    }
+    Ok(keys)
+}
+
+fn verify_with_any(keys: &[VerifyingKey], data: &[u8], signature_hex: &str) -> Result<(), String> {
+    let sig_bytes =
+        hex::decode(signature_hex.trim()).map_err(|e| format!("подпись не hex: {e}"))?;
+    let signature = Signature::try_from(sig_bytes.as_slice())
+        .map_err(|e| format!("подпись имеет неверную длину: {e}"))?;
+
+    if keys
+        .iter()
+        .any(|k| k.verify_strict(data, &signature).is_ok())
+    {
+        Ok(())
+    } else {
+        Err("подпись не совпадает ни с одним доверенным ключом".to_string())
+    }
+}
+
+/// Reads at most `limit` bytes; a larger body is refused without downloading the rest.
+fn fetch_bytes(
+    client: &reqwest::blocking::Client,
+    url: &str,
+    limit: u64,
+) -> Result<Vec<u8>, String> {
+    let resp = crate::http_config::blocking_send_idempotent_with_retry(|| client.get(url))
+        .map_err(|e| format!("{url}: {e}"))?;
+    if !resp.status().is_success() {
+        return Err(format!("{url}: status {}", resp.status()));
+    }
+    let too_large = || format!("{url}: файл больше {limit} байт");
+    if resp.content_length().is_some_and(|len| len > limit) {
+        return Err(too_large());
+    }
+    let mut bytes = Vec::new();
+    resp.take(limit + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("{url}: read body: {e}"))?;
+    if bytes.len() as u64 > limit {
+        return Err(too_large());
+    }
+    Ok(bytes)
+}
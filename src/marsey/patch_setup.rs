@@ -0,0 +1,168 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::settings::PatchCategories;
+
+use super::{PatchEntry, list_patches, normalize_case, set_enabled_patches};
+
+const PATCH_SETUP_FORMAT: &str = "sgloader-patch-setup";
+const PATCH_SETUP_VERSION: u32 = 1;
+
+/// Shareable patch setup: enabled patches and categories, plus the sha256 of every DLL so
+/// another player can tell whether they have the same builds. The DLLs are not included.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PatchSetup {
+    format: String,
+    version: u32,
+    categories: PatchCategories,
+    patches: Vec<PatchSetupEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PatchSetupEntry {
+    filename: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    rdnn: String,
+    sha256: String,
+    enabled: bool,
+}
+
+/// What importing a setup changed and what it could not reproduce.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PatchSetupReport {
+    pub enabled: usize,
+    pub disabled: usize,
+    /// Enabled in the setup but not installed here.
+    pub missing: Vec<String>,
+    /// Installed, but a different build than in the setup (sha256 differs).
+    pub other_version: Vec<String>,
+}
+
+impl PatchSetupReport {
+    pub fn summary(&self) -> String {
+        let mut out = format!(
+            "набор патчей применён: включено {}, выключено {}",
+            self.enabled, self.disabled
+        );
+        if !self.missing.is_empty() {
+            out.push_str(&format!("\nне установлены: {}", self.missing.join(", ")));
+        }
+        if !self.other_version.is_empty() {
+            out.push_str(&format!(
+                "\nдругая версия: {}",
+                self.other_version.join(", ")
+            ));
+        }
+        out
+    }
+}
+
+/// Serializes the current setup to JSON. Blocking: hashes every patch DLL.
+pub fn export_patch_setup(data_dir: &Path, categories: PatchCategories) -> Result<String, String> {
+    let (_, entries) = list_patches(data_dir)?;
+    let mut patches = entries
+        .iter()
+        .map(|p| {
+            Ok(PatchSetupEntry {
+                filename: p.filename.clone(),
+                name: p.name.clone(),
+                rdnn: p.rdnn.clone(),
+                sha256: sha256_file(&p.path)?,
+                enabled: p.enabled,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    patches.sort_by_key(|p| normalize_case(&p.filename));
+
+    let setup = PatchSetup {
+        format: PATCH_SETUP_FORMAT.to_string(),
+        version: PATCH_SETUP_VERSION,
+        categories,
+        patches,
+    };
+    serde_json::to_string_pretty(&setup).map_err(|e| format!("serialize набора патчей: {e}"))
+}
+
+/// Applies an exported setup: installed patches are enabled or disabled to match it.
+/// Returns the categories to save in settings and a report of what differs. Blocking.
+pub fn import_patch_setup(
+    data_dir: &Path,
+    json: &str,
+) -> Result<(PatchCategories, PatchSetupReport), String> {
+    let setup: PatchSetup = serde_json::from_str(json)
+        .map_err(|e| format!("не удалось разобрать набор патчей: {e}"))?;
+    if setup.format != PATCH_SETUP_FORMAT {
+        return Err("это не файл набора патчей SGLoader".to_string());
+    }
+    if setup.version > PATCH_SETUP_VERSION {
+        return Err(format!(
+            "набор патчей из более новой версии лаунчера (формат {})",
+            setup.version
+        ));
+    }
+
+    let (_, installed) = list_patches(data_dir)?;
+    let hashes = installed
+        .iter()
+        .map(|p| sha256_file(&p.path))
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let mut report = PatchSetupReport::default();
+    let mut enable: Vec<String> = Vec::new();
+    for entry in setup.patches.iter().filter(|e| e.enabled) {
+        let label = if entry.name.is_empty() {
+            entry.filename.clone()
+        } else {
+            format!("{} ({})", entry.name, entry.filename)
+        };
+        match find_installed(entry, &installed, &hashes) {
+            Some((local, same_build)) => {
+                if !same_build {
+                    report.other_version.push(label);
+                }
+                if !enable.contains(&local.filename) {
+                    enable.push(local.filename.clone());
+                }
+            }
+            None => report.missing.push(label),
+        }
+    }
+
+    set_enabled_patches(data_dir, &enable)?;
+    report.enabled = enable.len();
+    report.disabled = installed.len().saturating_sub(enable.len());
+    Ok((setup.categories, report))
+}
+
+/// The installed patch an entry refers to: the same build by sha256, else the same file
+/// name, else the same rdnn. The flag tells whether it is the same build.
+fn find_installed<'a>(
+    entry: &PatchSetupEntry,
+    installed: &'a [PatchEntry],
+    hashes: &[String],
+) -> Option<(&'a PatchEntry, bool)> {
+    if let Some(i) = hashes
+        .iter()
+        .position(|h| h.eq_ignore_ascii_case(entry.sha256.trim()))
+    {
+        return Some((&installed[i], true));
+    }
+    installed
+        .iter()
+        .find(|p| normalize_case(&p.filename) == normalize_case(&entry.filename))
+        .or_else(|| {
+            installed
+                .iter()
+                .find(|p| !entry.rdnn.is_empty() && p.rdnn == entry.rdnn)
+        })
+        .map(|p| (p, false))
+}
+
+pub(crate) fn sha256_file(path: &Path) -> Result<String, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("read {:?}: {e}", path))?;
+    Ok(hex::encode(Sha256::digest(&bytes)))
+}
@@ -3,9 +3,28 @@
 mod win;
 
 #[cfg(target_os = "windows")]
-pub use win::send_named_pipe_utf8;
+pub use win::{send_named_pipe_utf8, send_named_pipe_utf8_once};
 
 #[cfg(not(target_os = "windows"))]
-pub fn send_named_pipe_utf8(_pipe_name: &str, _data: &str, _timeout_ms: u32) -> Result<(), String> {
+use std::sync::atomic::AtomicBool;
+
+#[cfg(not(target_os = "windows"))]
+pub fn send_named_pipe_utf8(
+    _pipe_name: &str,
+    _data: &str,
+    _timeout_ms: u32,
+    _stop: &AtomicBool,
+) -> Result<(), String> {
     Err("Marsey IPC поддерживается только на Windows".to_string())
 }
+
+#[cfg(not(target_os = "windows"))]
+pub fn send_named_pipe_utf8_once(
+    _pipe_name: &str,
+    _data: &str,
+    _timeout_ms: u32,
+    _stop: &AtomicBool,
+    _on_created: impl FnOnce(),
+) -> Result<(), String> {
+    Err("named pipes поддерживаются только на Windows".to_string())
+}
@@ -1,12 +1,14 @@
 use std::ffi::OsStr;
 use std::iter;
 use std::os::windows::ffi::OsStrExt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 
 use windows::Win32::Foundation::{
     CloseHandle, ERROR_IO_PENDING, ERROR_PIPE_CONNECTED, GetLastError, HANDLE, WAIT_OBJECT_0,
 };
 use windows::Win32::Storage::FileSystem::{FILE_FLAGS_AND_ATTRIBUTES, FlushFileBuffers, WriteFile};
-use windows::Win32::System::IO::{GetOverlappedResult, OVERLAPPED};
+use windows::Win32::System::IO::{CancelIoEx, GetOverlappedResult, OVERLAPPED};
 use windows::Win32::System::Pipes::{
     ConnectNamedPipe, CreateNamedPipeW, DisconnectNamedPipe, NAMED_PIPE_MODE,
 };
@@ -19,20 +21,57 @@ const PIPE_READMODE_BYTE: u32 = 0x00000000;
 const PIPE_WAIT: u32 = 0x00000000;
 const PIPE_UNLIMITED_INSTANCES: u32 = 255;
 const FILE_FLAG_OVERLAPPED: u32 = 0x40000000;
+const FILE_FLAG_FIRST_PIPE_INSTANCE: u32 = 0x00080000;
+/// How often a pending connect checks the stop flag.
+const STOP_POLL_MS: u32 = 100;
+
+pub fn send_named_pipe_utf8(
+    pipe_name: &str,
+    data: &str,
+    timeout_ms: u32,
+    stop: &AtomicBool,
+) -> Result<(), String> {
+    send_named_pipe(pipe_name, data, timeout_ms, false, stop, || {})
+}
+
+/// Like `send_named_pipe_utf8`, but for secrets: fails if the pipe name is already taken and
+/// allows exactly one instance, so nothing can squat on the name or read it a second time.
+/// `on_created` runs once the pipe exists, before waiting for the reader.
+pub fn send_named_pipe_utf8_once(
+    pipe_name: &str,
+    data: &str,
+    timeout_ms: u32,
+    stop: &AtomicBool,
+    on_created: impl FnOnce(),
+) -> Result<(), String> {
+    send_named_pipe(pipe_name, data, timeout_ms, true, stop, on_created)
+}
 
-pub fn send_named_pipe_utf8(pipe_name: &str, data: &str, timeout_ms: u32) -> Result<(), String> {
+fn send_named_pipe(
+    pipe_name: &str,
+    data: &str,
+    timeout_ms: u32,
+    exclusive: bool,
+    stop: &AtomicBool,
+    on_created: impl FnOnce(),
+) -> Result<(), String> {
     let full_name = format!("\\\\.\\pipe\\{pipe_name}");
     let name_w = to_wide_null(&full_name);
 
     unsafe {
-        let open_mode = FILE_FLAGS_AND_ATTRIBUTES(PIPE_ACCESS_OUTBOUND | FILE_FLAG_OVERLAPPED);
+        let mut open_flags = PIPE_ACCESS_OUTBOUND | FILE_FLAG_OVERLAPPED;
+        if exclusive {
+            open_flags |= FILE_FLAG_FIRST_PIPE_INSTANCE;
+        }
+        let open_mode = FILE_FLAGS_AND_ATTRIBUTES(open_flags);
         let pipe_mode = NAMED_PIPE_MODE(PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT);
+        let max_instances = if exclusive { 1 } else { PIPE_UNLIMITED_INSTANCES };
 
         let handle = CreateNamedPipeW(
             PCWSTR(name_w.as_ptr()),
             open_mode,
             pipe_mode,
-            PIPE_UNLIMITED_INSTANCES,
+            max_instances,
             64 * 1024,
             64 * 1024,
             0,
@@ -44,6 +83,7 @@ pub fn send_named_pipe_utf8(pipe_name: &str, data: &str, timeout_ms: u32) -> Res
         }
 
         let _guard = HandleGuard(handle);
+        on_created();
 
         // Overlapped connect with timeout.
         let event = CreateEventW(None, true, false, None)
@@ -61,10 +101,23 @@ pub fn send_named_pipe_utf8(pipe_name: &str, data: &str, timeout_ms: u32) -> Res
             if err == ERROR_PIPE_CONNECTED {
                 // Connected between CreateNamedPipe and ConnectNamedPipe.
             } else if err == ERROR_IO_PENDING {
-                let wait = WaitForSingleObject(event_guard.0, timeout_ms);
-                if wait != WAIT_OBJECT_0 {
+                let deadline = Instant::now() + Duration::from_millis(u64::from(timeout_ms));
+                while WaitForSingleObject(event_guard.0, STOP_POLL_MS) != WAIT_OBJECT_0 {
+                    let stopped = stop.load(Ordering::Relaxed);
+                    if !stopped && Instant::now() < deadline {
+                        continue;
+                    }
+                    // The pending connect points at `overlapped`: cancel it and wait for the
+                    // cancellation before the pipe is closed and `overlapped` goes away.
+                    let _ = CancelIoEx(handle, Some(&overlapped));
+                    let mut transferred: u32 = 0;
+                    let _ = GetOverlappedResult(handle, &overlapped, &mut transferred, true);
                     let _ = DisconnectNamedPipe(handle);
-                    return Err(format!("ConnectNamedPipe timeout after {timeout_ms}ms"));
+                    return Err(if stopped {
+                        "запуск прерван, pipe закрыт".to_string()
+                    } else {
+                        format!("ConnectNamedPipe timeout after {timeout_ms}ms")
+                    });
                 }
 
                 let mut transferred: u32 = 0;
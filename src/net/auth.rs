@@ -1,6 +1,7 @@
 use std::fmt;
 
 use chrono::{DateTime, Duration, Utc};
+use reqwest::header::AUTHORIZATION;
 use reqwest::{Client, StatusCode};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -24,26 +25,79 @@ impl Default for AuthApi {
 impl AuthApi {
     pub fn new() -> Self {
         Self {
-            client: crate::http_config::build_async_client(crate::http_config::HttpProfile::Api)
+            client: crate::http_config::build_async_client(crate::http_config::HttpProfile::Auth)
                 .unwrap_or_else(|_| Client::new()),
         }
     }
 
+    /// `tfa_code` is sent on the second try, after the server answered `TfaRequired`.
     pub async fn authenticate(
         &self,
         username: String,
         password: String,
+        tfa_code: Option<String>,
     ) -> Result<AuthenticateResult, AuthError> {
         let request = AuthenticateRequest {
             username: Some(username),
             user_id: None,
             password,
-            tfa_code: None,
+            tfa_code,
         };
 
         self.authenticate_inner(request).await
     }
 
+    /// Asks the auth server to mail a password reset link to `email`. The server answers the
+    /// same whether or not an account uses that address.
+    pub async fn reset_password(&self, email: String) -> Result<(), AuthError> {
+        let request = ResetPasswordRequest { email };
+        let mut last_error: Option<AuthError> = None;
+
+        for base in AUTH_BASE_URLS {
+            let url = format!("{}api/auth/resetPassword", base);
+            match self.client.post(url).json(&request).send().await {
+                Ok(resp) if resp.status().is_success() => return Ok(()),
+                Ok(resp) => last_error = Some(AuthError::UnexpectedStatus(resp.status())),
+                Err(err) => last_error = Some(AuthError::Network(err.to_string())),
+            }
+        }
+
+        Err(last_error.unwrap_or(AuthError::Network(
+            "Не удалось связаться с auth сервером".to_string(),
+        )))
+    }
+
+    /// Asks the auth server whether `token` is still accepted (`api/auth/ping`). A token
+    /// past its expiry time is reported revoked without asking.
+    pub async fn check_token(&self, token: &LoginToken) -> Result<TokenCheck, AuthError> {
+        if token.is_time_expired() {
+            return Ok(TokenCheck::Revoked);
+        }
+        let mut last_error: Option<AuthError> = None;
+
+        for base in AUTH_BASE_URLS {
+            let url = format!("{}api/auth/ping", base);
+            let response = self
+                .client
+                .get(url)
+                .header(AUTHORIZATION, format!("SS14Auth {}", token.token))
+                .send()
+                .await;
+            match response {
+                Ok(resp) if resp.status().is_success() => return Ok(TokenCheck::Valid),
+                Ok(resp) if resp.status() == StatusCode::UNAUTHORIZED => {
+                    return Ok(TokenCheck::Revoked);
+                }
+                Ok(resp) => last_error = Some(AuthError::UnexpectedStatus(resp.status())),
+                Err(err) => last_error = Some(AuthError::Network(err.to_string())),
+            }
+        }
+
+        Err(last_error.unwrap_or(AuthError::Network(
+            "Не удалось связаться с auth сервером".to_string(),
+        )))
+    }
+
     async fn authenticate_inner(
         &self,
         request: AuthenticateRequest,
@@ -74,6 +128,7 @@ impl AuthApi {
                     let login_info = LoginInfo {
                         user_id: parsed.user_id,
                         username: parsed.username,
+                        alias: None,
                         token: LoginToken {
                             token: parsed.token,
                             expire_time: parsed.expire_time,
@@ -108,6 +163,12 @@ impl AuthApi {
     }
 }
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ResetPasswordRequest {
+    email: String,
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct AuthenticateRequest {
@@ -157,6 +218,14 @@ pub enum AuthenticateResult {
     },
 }
 
+/// Auth server's verdict on a saved token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenCheck {
+    Valid,
+    /// Expired or revoked: the account has to log in again.
+    Revoked,
+}
+
 #[derive(Debug, Clone)]
 pub struct LoginToken {
     pub token: String,
@@ -177,9 +246,17 @@ impl LoginToken {
 pub struct LoginInfo {
     pub user_id: Uuid,
     pub username: String,
+    /// User-chosen display name ("main", "alt"); stored locally only.
+    pub alias: Option<String>,
     pub token: LoginToken,
 }
 
+impl LoginInfo {
+    pub fn display_name(&self) -> &str {
+        self.alias.as_deref().unwrap_or(&self.username)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum AuthError {
     Network(String),
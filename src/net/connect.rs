@@ -2,7 +2,10 @@ use std::io::{Read, Seek, Write};
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::process::Stdio;
-use std::{fs, io};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::JoinHandle;
+use std::{fmt, fs, io};
 
 #[cfg(all(target_os = "windows", not(debug_assertions)))]
 use std::os::windows::process::CommandExt;
@@ -10,9 +13,10 @@ use std::os::windows::process::CommandExt;
 use url::Url;
 
 use crate::auth::LoginInfo;
-use crate::cancel_flag::CancelFlag;
+use crate::cancel_flag::{CancelFlag, is_cancellation};
 use crate::connect_progress::{self, ProgressTx};
-use crate::ss14_server_info::{AuthMode, ServerInfo};
+use crate::settings::HideLevel;
+use crate::ss14_server_info::{AuthMode, ServerBuildInformation, ServerInfo};
 use crate::ss14_uri;
 
 const AUTH_SERVER_PRIMARY: &str = "https://auth.spacestation14.com/";
@@ -22,21 +26,87 @@ pub struct ConnectResult {
     pub message: String,
 }
 
+/// Why a connect did not go through.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConnectError {
+    /// The user cancelled the attempt.
+    Cancelled,
+    /// The user killed a loader that got stuck starting.
+    ForceStopped,
+    Other(String),
+}
+
+impl ConnectError {
+    /// Only reports a stop the user asked for: not a failure to show in red, analyse or retry.
+    pub fn stopped_by_user(&self) -> bool {
+        matches!(self, Self::Cancelled | Self::ForceStopped)
+    }
+}
+
+impl From<String> for ConnectError {
+    fn from(err: String) -> Self {
+        Self::Other(err)
+    }
+}
+
+impl fmt::Display for ConnectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cancelled => f.write_str("подключение отменено"),
+            Self::ForceStopped => f.write_str("запуск остановлен принудительно"),
+            Self::Other(err) => f.write_str(err),
+        }
+    }
+}
+
 pub fn connect_to_ss14_address(
     address: &str,
     account: Option<LoginInfo>,
     progress: Option<ProgressTx>,
     cancel: Option<CancelFlag>,
-) -> Result<ConnectResult, String> {
+) -> Result<ConnectResult, ConnectError> {
+    // Whatever a cancelled connect fails with afterwards is the cancel, not an error.
+    connect(address, account, progress, cancel.clone()).map_err(|e| match cancel {
+        Some(c) if c.is_cancelled() && !e.stopped_by_user() => ConnectError::Cancelled,
+        _ => e,
+    })
+}
+
+fn connect(
+    address: &str,
+    account: Option<LoginInfo>,
+    progress: Option<ProgressTx>,
+    cancel: Option<CancelFlag>,
+) -> Result<ConnectResult, ConnectError> {
+    crate::core::instance_lock::ensure_writable()?;
     if let Some(c) = &cancel {
         c.check()?;
     }
     connect_progress::stage(progress.as_ref(), "получаем /info");
-    connect_progress::log(progress.as_ref(), format!("address={address}"));
+    connect_progress::log(
+        progress.as_ref(),
+        format!(
+            "session={} address={address}",
+            connect_progress::session_id(progress.as_ref())
+        ),
+    );
+
+    if let Some(warning) = crate::ss14::media_pack::media_pack_warning() {
+        connect_progress::log(progress.as_ref(), format!("media pack: {warning}"));
+        connect_progress::warning(progress.as_ref(), warning);
+    }
 
     let ss14 = ss14_uri::parse_ss14_uri(address)?;
     let info_url = ss14_uri::server_info_url(&ss14)?;
 
+    let query_params = ss14_uri::query_param_names(&ss14);
+    if !query_params.is_empty() {
+        connect_progress::log(
+            progress.as_ref(),
+            format!("ss14 query params (передаются клиенту): {}", query_params.join(", ")),
+        );
+    }
+
     let http = crate::launcher_mask::blocking_http_client_api()?;
 
     let info_resp =
@@ -60,53 +130,13 @@ pub fn connect_to_ss14_address(
         .clone()
         .ok_or_else(|| "сервер не вернул build информацию".to_string())?;
 
-    // Prefer build-provided URLs.
-    // Only infer self-hosted fallbacks if the server didn't provide them.
-    let download_url_missing = build
-        .download_url
-        .as_deref()
-        .map(|s| s.trim().is_empty())
-        .unwrap_or(true);
-    if download_url_missing {
-        build.download_url = Some(ss14_uri::server_selfhosted_client_zip_url(&ss14)?.to_string());
-    }
-
-    // Some servers set ACZ-related URLs even when acz=false, and some CDNs protect the zip download.
-    // Keep parity with SS14.Launcher fallbacks by inferring these URLs when missing.
-    {
-        let api_base = ss14_uri::server_api_base(&ss14)?;
-
-        let manifest_url_missing = build
-            .manifest_url
-            .as_deref()
-            .map(|s| s.trim().is_empty())
-            .unwrap_or(true);
-        if manifest_url_missing {
-            build.manifest_url = Some(
-                api_base
-                    .join("manifest.txt")
-                    .map_err(|e| e.to_string())?
-                    .to_string(),
-            );
-        }
-
-        let manifest_download_url_missing = build
-            .manifest_download_url
-            .as_deref()
-            .map(|s| s.trim().is_empty())
-            .unwrap_or(true);
-        if manifest_download_url_missing {
-            build.manifest_download_url = Some(
-                api_base
-                    .join("download")
-                    .map_err(|e| e.to_string())?
-                    .to_string(),
-            );
-        }
+    for line in crate::build_overrides::load_build_overrides(address).apply(&mut build) {
+        connect_progress::log(progress.as_ref(), format!("адрес сборки переопределён: {line}"));
     }
+    fill_build_fallbacks(&mut build, &ss14)?;
 
     if info.auth_information.mode == AuthMode::Required && account.is_none() {
-        return Err("сервер требует авторизацию — войдите в аккаунт".to_string());
+        return Err("сервер требует авторизацию — войдите в аккаунт".to_string().into());
     }
 
     let data_dir = crate::app_paths::data_dir()?;
@@ -114,44 +144,84 @@ pub fn connect_to_ss14_address(
     // Content is required to start the client (Content.* assemblies/resources).
     // We pass it to SS14.Loader via SS14_LOADER_OVERLAY_ZIP.
     // Some servers return a CDN URL that may be protected; fall back to server-hosted /client.zip.
-    connect_progress::stage(progress.as_ref(), "проверяем/скачиваем контент");
+    //
+    // IMPORTANT: build.download_url / manifest_url are for content only. The engine
+    // (Robust.Client) comes from the robust-builds manifest, as in SS14.Launcher. They live in
+    // different directories and come from different hosts, so both run at once; the first
+    // failure stops the other through their shared flag instead of waiting it out.
+    connect_progress::stage(progress.as_ref(), "проверяем/скачиваем контент и движок");
     let fallback_zip_url = ss14_uri::server_selfhosted_client_zip_url(&ss14)
         .ok()
         .map(|u| u.to_string());
-    let overlay_zip = crate::content_install::ensure_content_overlay_zip(
-        &data_dir,
-        &build,
-        fallback_zip_url.as_deref(),
-        progress.as_ref(),
-        cancel.as_ref(),
-    )?;
+    let downloads_cancel = cancel.as_ref().map_or_else(CancelFlag::new, CancelFlag::child);
+    let (overlay_zip, install) = std::thread::scope(|scope| {
+        let engine = scope.spawn(|| {
+            crate::client_install::ensure_client_installed(
+                &data_dir,
+                &build.engine_version,
+                progress.as_ref(),
+                Some(&downloads_cancel),
+            )
+            .inspect_err(|_| downloads_cancel.cancel())
+        });
+        let content = crate::content_install::ensure_content_overlay_zip(
+            &data_dir,
+            &build,
+            fallback_zip_url.as_deref(),
+            progress.as_ref(),
+            Some(&downloads_cancel),
+        )
+        .inspect_err(|_| downloads_cancel.cancel());
+        let engine = engine.join().unwrap_or_else(|_| {
+            downloads_cancel.cancel();
+            Err("установка движка: поток завершился с паникой".to_string())
+        });
+        match (content, engine) {
+            (Ok(content), Ok(engine)) => Ok((content, engine)),
+            (Err(e), Ok(_)) | (Ok(_), Err(e)) => Err(e),
+            // One side was stopped because the other failed: report the failure only.
+            (Err(e), Err(other)) | (Err(other), Err(e)) if is_cancellation(&other) => Err(e),
+            (Err(content_err), Err(engine_err)) => {
+                Err(format!("{content_err}\nдвижок: {engine_err}"))
+            }
+        }
+    })?;
 
     connect_progress::log(
         progress.as_ref(),
         format!("content_overlay_zip={}", overlay_zip.display()),
     );
 
-    // IMPORTANT: build.download_url / manifest_url относятся к контенту.
-    // Движок (Robust.Client) скачивается через robust-builds manifest, как в SS14.Launcher.
-    connect_progress::stage(progress.as_ref(), "проверяем/скачиваем движок");
-    let install = crate::client_install::ensure_client_installed(
-        &data_dir,
-        &build.engine_version,
-        progress.as_ref(),
-        cancel.as_ref(),
-    )?;
-
+    connect_progress::stage(progress.as_ref(), "проверяем архив контента");
+    match crate::install::zip_validate::validate_zip(&overlay_zip) {
+        Ok(entries) => connect_progress::log(
+            progress.as_ref(),
+            format!("content zip ok: {entries} файлов"),
+        ),
+        Err(e) => {
+            crate::install::zip_validate::discard_invalid_zip(&overlay_zip);
+            return Err(format!(
+                "архив контента повреждён: {e}\nФайл удалён — при следующем подключении он будет скачан заново."
+            )
+            .into());
+        }
+    }
     connect_progress::log(
         progress.as_ref(),
         format!("engine_zip={}", install.engine_zip.display()),
     );
 
+    // Baseline for the "content update available" badge on favorites.
+    if let Err(e) = crate::favorites::record_content_identity(address, &build) {
+        connect_progress::log(progress.as_ref(), format!("content identity: {e}"));
+    }
+
     let mut args: Vec<String> = Vec::new();
 
-    let username = account
-        .as_ref()
-        .map(|a| a.username.clone())
-        .unwrap_or_else(|| "Player".to_string());
+    let username = match account.as_ref() {
+        Some(a) => a.username.clone(),
+        None => guest_username(progress.as_ref(), cancel.as_ref())?,
+    };
 
     args.push("--username".to_string());
     args.push(username);
@@ -168,7 +238,7 @@ pub fn connect_to_ss14_address(
     args.push(connect_addr);
 
     args.push("--ss14-address".to_string());
-    args.push(ss14.to_string());
+    args.push(ss14_uri::client_ss14_address(&ss14));
 
     // build.* CVars (important for modern CDN / content plumbing).
     push_build_cvar(&mut args, "download_url", build.download_url.as_deref());
@@ -188,11 +258,43 @@ pub fn connect_to_ss14_address(
         Some(build.engine_version.as_str()),
     );
 
+    let cfg = crate::settings::load_settings().unwrap_or_default();
+    let security = cfg.security.clone();
+
+    // Saved CVars were validated on save; re-check in case settings.json was edited by hand.
+    match crate::ss14::cvars::validate_cvars(&cfg.game.extra_cvars) {
+        Ok(cvars) => {
+            for cvar in cvars {
+                connect_progress::log(progress.as_ref(), format!("user cvar: {cvar}"));
+                args.push("--cvar".to_string());
+                args.push(cvar);
+            }
+        }
+        Err(e) => {
+            let warning = format!("пользовательские CVar пропущены:\n{e}");
+            connect_progress::log(progress.as_ref(), warning.clone());
+            connect_progress::warning(progress.as_ref(), warning);
+        }
+    }
+
     let mut env: Vec<(String, String)> = Vec::new();
+    let mut token_pipe: Option<TokenPipe> = None;
     if info.auth_information.mode != AuthMode::Disabled
         && let Some(acc) = &account
     {
-        env.push(("ROBUST_AUTH_TOKEN".to_string(), acc.token.token.clone()));
+        // With `token_via_pipe` the token goes only over a one-shot pipe the bundled loader reads
+        // at startup; `launch_client` falls back to ROBUST_AUTH_TOKEN if the pipe can't be
+        // created.
+        if security.token_via_pipe && cfg!(target_os = "windows") {
+            let pipe = TokenPipe {
+                name: format!("SGLOADER_AUTH_{}", uuid::Uuid::new_v4().simple()),
+                token: acc.token.token.clone(),
+            };
+            env.push(("SGLOADER_AUTH_TOKEN_PIPE".to_string(), pipe.name.clone()));
+            token_pipe = Some(pipe);
+        } else {
+            env.push(("ROBUST_AUTH_TOKEN".to_string(), acc.token.token.clone()));
+        }
         env.push(("ROBUST_AUTH_USERID".to_string(), acc.user_id.to_string()));
         env.push((
             "ROBUST_AUTH_PUBKEY".to_string(),
@@ -215,9 +317,6 @@ pub fn connect_to_ss14_address(
         c.check()?;
     }
 
-    let cfg = crate::settings::load_settings().unwrap_or_default();
-    let security = cfg.security.clone();
-
     // Launcher integration (Redial): only advertise launcher if not disabled.
     if !security.disable_redial
         && let Ok(exe) = std::env::current_exe()
@@ -228,7 +327,27 @@ pub fn connect_to_ss14_address(
         ));
     }
 
-    if security.autodelete_hwid {
+    let privacy_flags = info.privacy_flags.unwrap_or_default();
+    if privacy_flags.hwid_required || privacy_flags.telemetry {
+        connect_progress::log(
+            progress.as_ref(),
+            format!(
+                "privacy: hwid_required={} telemetry={}",
+                privacy_flags.hwid_required, privacy_flags.telemetry
+            ),
+        );
+    }
+    if privacy_flags.hwid_required && security.autodelete_hwid {
+        connect_progress::warning(
+            progress.as_ref(),
+            "сервер проверяет HWID: с автоудалением HWID он может посчитать вас новым игроком или обходом бана",
+        );
+    }
+
+    if security.autodelete_hwid && crate::wine::is_wine() {
+        // The prefix registry is not what the game's HWID is derived from under Wine.
+        connect_progress::log(progress.as_ref(), "autodelete hwid: пропущено под Wine");
+    } else if security.autodelete_hwid {
         connect_progress::log(
             progress.as_ref(),
             "autodelete hwid: очищаем HKCU\\Software\\Space Wizards\\Robust",
@@ -238,26 +357,179 @@ pub fn connect_to_ss14_address(
         }
     }
 
+    let hide_level = if security.hide_level == HideLevel::Auto {
+        let mut auto = crate::net::hide_level_rules::resolve_auto_hide_level(
+            &build.fork_id,
+            &security.hide_level_rules_url,
+        );
+        // A server that announces telemetry gets at least a high level whatever the rules say.
+        if privacy_flags.telemetry
+            && matches!(
+                auto.level,
+                HideLevel::Disabled | HideLevel::Low | HideLevel::Medium
+            )
+        {
+            auto.level = HideLevel::High;
+            auto.reason.push_str("; сервер сообщает о телеметрии");
+        }
+        connect_progress::log(
+            progress.as_ref(),
+            format!(
+                "hide level: авто → {} ({})",
+                auto.level.label_ru(),
+                auto.reason
+            ),
+        );
+        auto.level
+    } else {
+        connect_progress::log(
+            progress.as_ref(),
+            format!("hide level: {}", security.hide_level.label_ru()),
+        );
+        security.hide_level
+    };
+
     let marsey_ctx = crate::marsey::MarseyLaunchContext {
         engine_version: build.engine_version.clone(),
         fork_id: build.fork_id.clone(),
-        hide_level: security.hide_level.to_marsey_value().to_string(),
+        hide_level: hide_level.to_marsey_value().to_string(),
         disable_redial: security.disable_redial,
+        patch_categories: cfg.patches.categories,
+    };
+    let record = crate::net::launch_guard::LaunchRecord {
+        address: address.to_string(),
+        engine_version: install.engine_version.clone(),
+        fork_id: build.fork_id.clone(),
+        fork_version: build.version.clone(),
+        content_hash: build.manifest_hash.clone().or_else(|| build.hash.clone()),
+        started_at: std::time::SystemTime::now(),
     };
+    connect_progress::log(
+        progress.as_ref(),
+        format!(
+            "сборка: engine={} fork={} version={} content={}",
+            record.engine_version,
+            record.fork_id,
+            record.fork_version,
+            record.content_hash.as_deref().unwrap_or("-")
+        ),
+    );
+    let mut auto_disabled_backports = false;
     let launched = launch_client(
         &install,
-        &args,
-        &env,
+        &LoaderCommand {
+            args,
+            env,
+            token_pipe,
+        },
         &marsey_ctx,
+        record,
+        &mut auto_disabled_backports,
         progress.as_ref(),
-    )?;
+    )
+    .inspect_err(|e| {
+        if !e.stopped_by_user() {
+            offer_known_good(
+                address,
+                &install.engine_version,
+                !auto_disabled_backports,
+                progress.as_ref(),
+            );
+        }
+    })?;
+
+    match crate::known_good::current_config(&install.engine_version, launched.backports)
+        .and_then(|config| crate::known_good::save_known_good(address, config))
+    {
+        Ok(()) => {}
+        Err(e) => connect_progress::log(
+            progress.as_ref(),
+            format!("рабочая конфигурация не сохранена: {e}"),
+        ),
+    }
 
     Ok(ConnectResult {
         launched: true,
-        message: format!("запущено: {}", launched.display()),
+        message: format!("запущено: {}", launched.entrypoint.display()),
     })
 }
 
+/// After a failed launch: if the last successful one on `address` used other settings that can
+/// be put back, tells the UI what changed since. Only launches that fail inside `launch_client`
+/// get here; a game that crashes later, once the connect modal is gone, is not covered.
+fn offer_known_good(
+    address: &str,
+    engine_version: &str,
+    backports: bool,
+    progress: Option<&ProgressTx>,
+) {
+    let Some(saved) = crate::known_good::load_known_good(address) else {
+        return;
+    };
+    let Ok(current) = crate::known_good::current_config(engine_version, backports) else {
+        return;
+    };
+    if crate::known_good::can_restore(&saved, &current) {
+        connect_progress::known_good_available(
+            progress,
+            crate::known_good::describe_changes(&saved, &current),
+        );
+    }
+}
+
+/// Fills URLs the server left out of `build`, the way SS14.Launcher infers them.
+pub(crate) fn fill_build_fallbacks(
+    build: &mut ServerBuildInformation,
+    ss14: &Url,
+) -> Result<(), String> {
+    // Prefer build-provided URLs.
+    // Only infer self-hosted fallbacks if the server didn't provide them.
+    let download_url_missing = build
+        .download_url
+        .as_deref()
+        .map(|s| s.trim().is_empty())
+        .unwrap_or(true);
+    if download_url_missing {
+        build.download_url = Some(ss14_uri::server_selfhosted_client_zip_url(ss14)?.to_string());
+    }
+
+    // Some servers set ACZ-related URLs even when acz=false, and some CDNs protect the zip download.
+    // Keep parity with SS14.Launcher fallbacks by inferring these URLs when missing.
+    {
+        let api_base = ss14_uri::server_api_base(ss14)?;
+
+        let manifest_url_missing = build
+            .manifest_url
+            .as_deref()
+            .map(|s| s.trim().is_empty())
+            .unwrap_or(true);
+        if manifest_url_missing {
+            build.manifest_url = Some(
+                api_base
+                    .join("manifest.txt")
+                    .map_err(|e| e.to_string())?
+                    .to_string(),
+            );
+        }
+
+        let manifest_download_url_missing = build
+            .manifest_download_url
+            .as_deref()
+            .map(|s| s.trim().is_empty())
+            .unwrap_or(true);
+        if manifest_download_url_missing {
+            build.manifest_download_url = Some(
+                api_base
+                    .join("download")
+                    .map_err(|e| e.to_string())?
+                    .to_string(),
+            );
+        }
+    }
+
+    Ok(())
+}
+
 fn push_build_cvar(args: &mut Vec<String>, name: &str, value: Option<&str>) {
     let Some(v) = value else {
         return;
@@ -269,7 +541,7 @@ fn push_build_cvar(args: &mut Vec<String>, name: &str, value: Option<&str>) {
     args.push(format!("build.{name}={v}"));
 }
 
-fn get_connect_address(info: &ServerInfo, info_url: &Url) -> Result<String, String> {
+pub(crate) fn get_connect_address(info: &ServerInfo, info_url: &Url) -> Result<String, String> {
     if let Some(addr) = &info.connect_address {
         let trimmed = addr.trim();
         if !trimmed.is_empty() {
@@ -296,13 +568,250 @@ fn get_connect_address(info: &ServerInfo, info_url: &Url) -> Result<String, Stri
     Ok(parsed.to_string())
 }
 
+/// A loader that started and stayed up.
+struct Launched {
+    entrypoint: PathBuf,
+    /// False when the automatic backports fix was needed.
+    backports: bool,
+}
+
+/// Pipe senders of one launch attempt. Dropping it stops whatever is still waiting for the
+/// loader and joins the threads, so nothing from a failed attempt outlives it or competes with
+/// the next attempt for the same pipe names.
+struct AttemptPipes {
+    stop: Arc<AtomicBool>,
+    marsey: Option<JoinHandle<Result<(), Vec<crate::marsey::PipeFailure>>>>,
+    token: Option<JoinHandle<Result<(), String>>>,
+    /// Why the token pipe could not be created; the loader then gets the token another way.
+    token_failure: Option<String>,
+}
+
+impl AttemptPipes {
+    fn start(batch: Option<crate::marsey::MarseyPipeBatch>, token: Option<&TokenPipe>) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let marsey = batch.map(|batch| {
+            let stop = stop.clone();
+            std::thread::spawn(move || crate::marsey::send_pipes(batch, &stop))
+        });
+        let mut token_failure = None;
+        let token = token.and_then(|p| {
+            let (name, token, stop) = (p.name.clone(), p.token.clone(), stop.clone());
+            let (created_tx, created_rx) = std::sync::mpsc::channel();
+            let thread = std::thread::spawn(move || {
+                crate::marsey::pipes::send_named_pipe_utf8_once(
+                    &name,
+                    &token,
+                    crate::wine::pipe_timeout_ms(60_000),
+                    &stop,
+                    move || {
+                        let _ = created_tx.send(());
+                    },
+                )
+            });
+            // The sender only gives up before creating the pipe if creating it failed.
+            if created_rx.recv().is_ok() {
+                return Some(thread);
+            }
+            token_failure = Some(match thread.join() {
+                Ok(Err(e)) => e,
+                _ => "поток завершился с паникой".to_string(),
+            });
+            None
+        });
+        Self {
+            stop,
+            marsey,
+            token,
+            token_failure,
+        }
+    }
+
+    fn has_marsey(&self) -> bool {
+        self.marsey.is_some()
+    }
+
+    fn marsey_finished(&self) -> bool {
+        self.marsey.as_ref().is_none_or(|t| t.is_finished())
+    }
+
+    /// Gives up on pipes nobody has connected to yet.
+    fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+
+    /// Waits for the Marsey senders and returns the pipes that were not delivered.
+    fn join_marsey(&mut self, started: std::time::Instant) -> Vec<crate::marsey::PipeFailure> {
+        match self.marsey.take().map(JoinHandle::join) {
+            None | Some(Ok(Ok(()))) => Vec::new(),
+            Some(Ok(Err(failures))) => failures,
+            Some(Err(_)) => vec![crate::marsey::PipeFailure {
+                pipe: "Marsey IPC",
+                error: "поток завершился с паникой".to_string(),
+                elapsed: started.elapsed(),
+            }],
+        }
+    }
+
+    /// Why the token pipe could not be created, if it could not.
+    fn take_token_failure(&mut self) -> Option<String> {
+        self.token_failure.take()
+    }
+
+    /// Error of the token sender if it has already given up.
+    fn finished_token_error(&mut self) -> Option<String> {
+        if !self.token.as_ref().is_some_and(|t| t.is_finished()) {
+            return None;
+        }
+        match self.token.take()?.join() {
+            Ok(Err(e)) => Some(e),
+            _ => None,
+        }
+    }
+
+    /// After a successful launch: the Marsey senders are done by now, the token sender may
+    /// still be waiting for the loader and is handed to the launch to be stopped with it.
+    fn into_token_sender(mut self) -> Option<crate::net::launch_guard::PipeSender> {
+        self.marsey = None;
+        let thread = self.token.take()?;
+        Some(crate::net::launch_guard::PipeSender {
+            stop: self.stop.clone(),
+            thread,
+        })
+    }
+}
+
+impl Drop for AttemptPipes {
+    fn drop(&mut self) {
+        if self.marsey.is_none() && self.token.is_none() {
+            return;
+        }
+        self.stop();
+        if let Some(t) = self.marsey.take() {
+            let _ = t.join();
+        }
+        if let Some(t) = self.token.take() {
+            let _ = t.join();
+        }
+    }
+}
+
+/// A freshly prepared Marsey batch with backports off, for the retry after the backports crash.
+/// Prepared again rather than patched so the second attempt sends a complete, current payload.
+fn batch_without_backports(
+    data_dir: &Path,
+    marsey: &crate::marsey::MarseyLaunchContext,
+) -> Result<crate::marsey::MarseyPipeBatch, String> {
+    let mut batch = crate::marsey::prepare_pipes_for_launch(data_dir, marsey)
+        .map_err(|e| format!("Marsey prepare: {e}"))?;
+    batch.marsey_conf = crate::marsey::with_marsey_backports_enabled(&batch.marsey_conf, false);
+    Ok(batch)
+}
+
+/// Engine arguments and environment `connect` prepared for the loader.
+struct LoaderCommand {
+    args: Vec<String>,
+    env: Vec<(String, String)>,
+    token_pipe: Option<TokenPipe>,
+}
+
+/// Auth token handed to the loader through a named pipe instead of `ROBUST_AUTH_TOKEN`.
+struct TokenPipe {
+    name: String,
+    token: String,
+}
+
+/// Writes where the loader will look for native libraries into its launch log, so
+/// "SDL3.dll not found"-class failures can be traced from the log alone.
+fn log_native_search(
+    mut log: &fs::File,
+    loader_dir: &Path,
+    engine_dir: &Path,
+    system_path: &std::ffi::OsStr,
+) {
+    let _ = writeln!(log, "[SGLOADER] cwd: {}", loader_dir.display());
+    for (label, dir) in [("loader", loader_dir), ("engine", engine_dir)] {
+        let libs = native_libs_in(dir);
+        let listed = if libs.is_empty() {
+            "нет нативных библиотек".to_string()
+        } else if libs.len() > MAX_LOGGED_NATIVE_LIBS {
+            format!(
+                "{} (+{})",
+                libs[..MAX_LOGGED_NATIVE_LIBS].join(", "),
+                libs.len() - MAX_LOGGED_NATIVE_LIBS
+            )
+        } else {
+            libs.join(", ")
+        };
+        let _ = writeln!(
+            log,
+            "[SGLOADER] PATH += {label}: {} — {listed}",
+            dir.display()
+        );
+    }
+    let system_entries = std::env::split_paths(system_path).count();
+    let _ = writeln!(
+        log,
+        "[SGLOADER] PATH += системный PATH ({system_entries} каталогов; полностью: --print-env)"
+    );
+}
+
+const MAX_LOGGED_NATIVE_LIBS: usize = 24;
+
+/// File names of native libraries directly in `dir`, sorted.
+fn native_libs_in(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut libs: Vec<String> = entries
+        .flatten()
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_file()))
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .filter(|name| {
+            let lower = name.to_ascii_lowercase();
+            lower.ends_with(".dll")
+                || lower.ends_with(".dylib")
+                || lower.ends_with(".so")
+                || lower.contains(".so.")
+        })
+        .collect();
+    libs.sort_by_key(|n| n.to_ascii_lowercase());
+    libs
+}
+
+/// `--print-env`: the full PATH and every variable set for the loader, tokens masked.
+fn log_child_env(
+    mut log: &fs::File,
+    env: &[(String, String)],
+    path_key: &str,
+    path: &std::ffi::OsStr,
+) {
+    for (i, dir) in std::env::split_paths(path).enumerate() {
+        let _ = writeln!(log, "[SGLOADER] {path_key}[{i}] = {}", dir.display());
+    }
+    for (key, value) in env {
+        let shown = if key.ends_with("_TOKEN") {
+            "<скрыто>"
+        } else {
+            value.as_str()
+        };
+        let _ = writeln!(log, "[SGLOADER] env {key}={shown}");
+    }
+    let _ = writeln!(log, "[SGLOADER] env SS14_LOADER_SKIP_SIGNATURE_VERIFY=1");
+}
+
 fn launch_client(
     install: &crate::client_install::ClientInstall,
-    args: &[String],
-    env: &[(String, String)],
+    command: &LoaderCommand,
     marsey: &crate::marsey::MarseyLaunchContext,
+    record: crate::net::launch_guard::LaunchRecord,
+    auto_disabled_backports: &mut bool,
     progress: Option<&ProgressTx>,
-) -> Result<PathBuf, String> {
+) -> Result<Launched, ConnectError> {
+    let (args, env, token_pipe) = (
+        command.args.as_slice(),
+        command.env.as_slice(),
+        command.token_pipe.as_ref(),
+    );
     let data_dir = crate::app_paths::data_dir()?;
     let loader = crate::ss14_loader::ensure_loader_installed(&data_dir)?;
 
@@ -323,18 +832,30 @@ fn launch_client(
                     ),
                 );
             } else {
-                return Err(e);
+                connect_progress::engine_signature_failed(progress, install.engine_version.clone());
+                let details = crate::ss14::engine_signature::explain_failure(
+                    &install.engine_zip,
+                    &install.engine_sha256,
+                    &install.engine_signature_hex,
+                    &loader.public_key,
+                );
+                return Err(format!("{e}\n{details}").into());
             }
         }
     }
 
     // Rust-side Redial server: keep it alive globally and pass its pipe name to the loader.
-    let redial_pipe_name = std::env::current_exe()
-        .ok()
+    let redial_pipe_name = match std::env::current_exe()
+        .map_err(|e| format!("путь к лаунчеру: {e}"))
         .and_then(|exe| {
-            crate::net::redial_pipe::ensure_global_redial_pipe(marsey.disable_redial, &exe).ok()
-        })
-        .flatten();
+            crate::net::redial_pipe::ensure_global_redial_pipe(marsey.disable_redial, &exe)
+        }) {
+        Ok(name) => name,
+        Err(e) => {
+            connect_progress::warning(progress, format!("redial не будет работать: {e}"));
+            None
+        }
+    };
 
     let mut marsey_batch = if loader.marsey_enabled {
         Some(
@@ -345,11 +866,17 @@ fn launch_client(
         None
     };
 
-    let log_path = make_launch_log_path(&data_dir)?;
+    let log_path = make_launch_log_path(&data_dir, &connect_progress::session_id(progress))?;
+    let launcher_settings = crate::settings::load_settings().unwrap_or_default();
+    let dump_marsey_pipes = launcher_settings.debug.dump_marsey_pipes;
+    let process_priority = launcher_settings.game.process_priority;
+    // A bad CPU list must not block the launch; it is reported once the process is up.
+    let cpu_affinity = crate::core::process_priority::parse_cpu_list(&launcher_settings.game.cpu_affinity);
     // Auto-mitigation for a known Marsey backports crash (Version.CompareTo called with a string).
     // We keep backports enabled by default, but if SS14.Loader exits immediately with this signature,
-    // retry once with backports disabled via MarseyConf.
-    let mut auto_disabled_backports = false;
+    // retry once with backports disabled via MarseyConf. The caller reads the flag back when the
+    // launch fails, so it knows which backports state the failed attempt ran with.
+    *auto_disabled_backports = false;
     let mut first_attempt_tail: Option<String> = None;
 
     for attempt in 0..2 {
@@ -363,7 +890,7 @@ fn launch_client(
             .try_clone()
             .map_err(|e| format!("не удалось открыть stderr лог: {e}"))?;
 
-        if auto_disabled_backports {
+        if *auto_disabled_backports {
             let _ = writeln!(
                 &log_file_err,
                 "[SGLOADER] Авто-фикс: отключаем Marsey backports из-за крэша сравнения Version; повторный запуск."
@@ -397,6 +924,17 @@ fn launch_client(
             );
         }
 
+        if dump_marsey_pipes && let Some(batch) = &marsey_batch {
+            let logs_dir = data_dir.join("logs");
+            match crate::marsey::dump_pipe_payloads(batch, &logs_dir, attempt) {
+                Ok(dir) => connect_progress::log(
+                    progress,
+                    format!("marsey pipes сохранены: {}", dir.display()),
+                ),
+                Err(e) => connect_progress::log(progress, format!("marsey pipes: {e}")),
+            }
+        }
+
         let mut cmd = if loader
             .entrypoint
             .extension()
@@ -428,9 +966,6 @@ fn launch_client(
             cmd.env("SGLOADER_REDIAL_PIPE", name);
         }
 
-        cmd.stdout(Stdio::from(log_file));
-        cmd.stderr(Stdio::from(log_file_err));
-
         // Windows native DLL resolution depends on cwd and PATH.
         // - SS14.Loader's own native deps should resolve from the loader directory.
         // - Robust engine native deps (e.g. SDL3.dll) are expected next to / extracted alongside the engine zip.
@@ -468,20 +1003,55 @@ fn launch_client(
         new_path.push(engine_dir.as_os_str());
         if !existing_path.is_empty() {
             new_path.push(sep);
-            new_path.push(existing_path);
+            new_path.push(&existing_path);
+        }
+        log_native_search(&log_file_err, loader_dir, engine_dir, &existing_path);
+        if crate::cli_args::print_env() {
+            log_child_env(&log_file_err, env, path_key, &new_path);
         }
         cmd.env(path_key, new_path);
 
-        // Spawn pipe senders shortly before launching the loader.
-        // Only for Marsey-enabled loader builds.
-        let pipe_thread = marsey_batch
-            .clone()
-            .map(|batch| std::thread::spawn(move || crate::marsey::send_pipes(batch)));
+        cmd.stdout(Stdio::from(log_file));
+        cmd.stderr(Stdio::from(log_file_err));
+
+        // Spawn pipe senders shortly before launching the loader: Marsey pipes only for
+        // Marsey-enabled loader builds, the token pipe once per attempt (the loader reads it
+        // once at startup). Whatever this attempt leaves unsent is torn down with `pipes`.
+        let pipes_started = std::time::Instant::now();
+        let mut pipes = AttemptPipes::start(marsey_batch.clone(), token_pipe);
+        match (token_pipe, pipes.take_token_failure()) {
+            (Some(pipe), Some(e)) => {
+                cmd.env_remove("SGLOADER_AUTH_TOKEN_PIPE");
+                cmd.env("ROBUST_AUTH_TOKEN", &pipe.token);
+                connect_progress::warning(
+                    progress,
+                    format!("pipe для токена не создан ({e}); токен передан через ROBUST_AUTH_TOKEN"),
+                );
+            }
+            (Some(_), None) => connect_progress::log(progress, "токен передаётся через pipe"),
+            (None, _) if env.iter().any(|(k, _)| k == "ROBUST_AUTH_TOKEN") => {
+                connect_progress::log(progress, "токен передан через ROBUST_AUTH_TOKEN")
+            }
+            (None, _) => {}
+        }
 
         let mut child = cmd
             .spawn()
             .map_err(|e| format!("не удалось запустить SS14.Loader: {e}"))?;
+        let launch_guard = crate::net::launch_guard::register(child.id(), record.clone());
 
+        let affinity = match &cpu_affinity {
+            Ok(mask) => *mask,
+            Err(e) => {
+                connect_progress::warning(progress, format!("привязка к ядрам пропущена: {e}"));
+                None
+            }
+        };
+        if let Err(e) = crate::core::process_priority::apply(&child, process_priority, affinity) {
+            connect_progress::warning(progress, e);
+        }
+
+        connect_progress::log(progress, format!("лог SS14.Loader: {}", log_path.display()));
         // Countdown for auto-close in UI must start only after the process is actually spawned.
         connect_progress::game_launched(
             progress,
@@ -489,17 +1059,72 @@ fn launch_client(
         );
 
         // If MarseyConf IPC fails, patches will crash the rewrite loader; fail early.
-        if let Some(t) = pipe_thread
-            && let Err(e) = t
-                .join()
-                .unwrap_or_else(|_| Err("Marsey IPC thread panic".to_string()))
-        {
-            let _ = child.kill();
-            return Err(format!("Marsey IPC error: {e}"));
+        // Poll instead of joining so "force stop" in the UI is not stuck behind the pipe timeout,
+        // and so a loader that died before reading every pipe does not leave us waiting on it.
+        if pipes.has_marsey() {
+            while !pipes.marsey_finished() && !launch_guard.stop_requested() {
+                if matches!(child.try_wait(), Ok(Some(_))) {
+                    pipes.stop();
+                    break;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(100));
+            }
+            if launch_guard.stop_requested() {
+                let _ = child.kill();
+                return Err(ConnectError::ForceStopped);
+            }
+            let failures = pipes.join_marsey(pipes_started);
+            if !failures.is_empty() && matches!(child.try_wait(), Ok(Some(_))) {
+                let tail = read_log_tail(&log_path, 16 * 1024).unwrap_or_default();
+                if attempt == 0
+                    && !*auto_disabled_backports
+                    && is_marsey_backports_version_compare_crash(&tail)
+                {
+                    first_attempt_tail = Some(tail);
+                    *auto_disabled_backports = true;
+                    marsey_batch = Some(batch_without_backports(&data_dir, marsey)?);
+                    continue;
+                }
+            }
+            if !failures.is_empty() {
+                // Whether the loader was still there tells "it never reached the pipe read"
+                // apart from "it crashed before reading".
+                let loader_state = match child.try_wait() {
+                    Ok(None) => "SS14.Loader ещё работал".to_string(),
+                    Ok(Some(status)) => format!(
+                        "SS14.Loader уже завершился (code={})",
+                        status.code().unwrap_or(-1)
+                    ),
+                    Err(e) => format!("статус SS14.Loader неизвестен: {e}"),
+                };
+                let _ = child.kill();
+
+                let mut msg = format!(
+                    "Marsey IPC: loader не прочитал данные патчей ({loader_state}, прошло {:.1} с)",
+                    pipes_started.elapsed().as_secs_f32()
+                );
+                for failure in &failures {
+                    msg.push_str(&format!("\n- {failure}"));
+                }
+                connect_progress::log(progress, msg.clone());
+                append_launch_log(&log_path, &format!("[SGLOADER] {msg}"));
+                msg.push_str(&format!("\nЛог: {}", log_path.display()));
+                return Err(msg.into());
+            }
         }
 
         // If the process dies immediately (black screen then close), surface the log.
         std::thread::sleep(std::time::Duration::from_millis(800));
+        if launch_guard.stop_requested() {
+            let _ = child.kill();
+            return Err(ConnectError::ForceStopped);
+        }
+        if let Some(e) = pipes.finished_token_error() {
+            connect_progress::warning(
+                progress,
+                format!("передача токена через pipe не удалась: {e}"),
+            );
+        }
         if let Some(status) = child
             .try_wait()
             .map_err(|e| format!("не удалось проверить статус SS14.Loader: {e}"))?
@@ -508,18 +1133,13 @@ fn launch_client(
 
             if attempt == 0
                 && loader.marsey_enabled
-                && !auto_disabled_backports
+                && !*auto_disabled_backports
                 && marsey_batch.is_some()
                 && is_marsey_backports_version_compare_crash(&tail)
             {
                 first_attempt_tail = Some(tail);
-                auto_disabled_backports = true;
-                marsey_batch = marsey_batch.as_ref().map(|b| {
-                    let mut nb = b.clone();
-                    nb.marsey_conf =
-                        crate::marsey::with_marsey_backports_enabled(&nb.marsey_conf, false);
-                    nb
-                });
+                *auto_disabled_backports = true;
+                marsey_batch = Some(batch_without_backports(&data_dir, marsey)?);
                 continue;
             }
 
@@ -529,7 +1149,7 @@ fn launch_client(
                 log_path.display()
             );
 
-            if auto_disabled_backports {
+            if *auto_disabled_backports {
                 msg.push_str("\n\n[SGLOADER] Пробовали авто-выключение Marsey backports из-за крэша Version.CompareTo.");
             }
 
@@ -545,19 +1165,50 @@ fn launch_client(
                 msg.push_str(tail.trim());
             }
 
-            return Err(msg);
+            return Err(msg.into());
         }
 
-        return Ok(loader.entrypoint);
+        // The loader may still be about to read the token; that sender now lives with the launch.
+        launch_guard.hand_off(child, pipes.into_token_sender());
+        return Ok(Launched {
+            entrypoint: loader.entrypoint,
+            backports: !*auto_disabled_backports,
+        });
     }
 
-    Err("SS14.Loader завершился сразу (неизвестная ошибка)".to_string())
+    Err("SS14.Loader завершился сразу (неизвестная ошибка)".to_string().into())
 }
 
-fn make_launch_log_path(data_dir: &Path) -> Result<PathBuf, String> {
+/// Name for joining without an account: the saved default, or whatever the user types
+/// into the prompt when `ask_guest_username` is on.
+fn guest_username(
+    progress: Option<&ProgressTx>,
+    cancel: Option<&CancelFlag>,
+) -> Result<String, ConnectError> {
+    let game = crate::settings::load_settings().unwrap_or_default().game;
+    let saved = crate::settings::validate_guest_username(&game.guest_username)
+        .unwrap_or_else(|_| crate::settings::DEFAULT_GUEST_USERNAME.to_string());
+    if !game.ask_guest_username {
+        return Ok(saved);
+    }
+
+    connect_progress::stage(progress, "ждём ник для игры без аккаунта");
+    let name = connect_progress::ask_guest_name(progress, &saved, cancel)
+        .ok_or(ConnectError::Cancelled)?;
+    Ok(crate::settings::validate_guest_username(&name)?)
+}
+
+fn make_launch_log_path(data_dir: &Path, session_id: &str) -> Result<PathBuf, String> {
     let logs = data_dir.join("logs");
-    fs::create_dir_all(&logs).map_err(|e| format!("mkdir {:?}: {e}", logs))?;
-    Ok(logs.join("last-launch.log"))
+    crate::app_paths::create_dir(&logs, "логи")?;
+    Ok(connect_progress::session_log_path(&logs, "launch", session_id))
+}
+
+/// Adds a launcher-side note to the loader's log so one file tells the whole story.
+fn append_launch_log(path: &Path, text: &str) {
+    if let Ok(mut file) = fs::OpenOptions::new().append(true).create(true).open(path) {
+        let _ = writeln!(file, "{text}");
+    }
 }
 
 fn read_log_tail(path: &Path, max_bytes: u64) -> io::Result<String> {
@@ -1,18 +1,214 @@
-use tokio::sync::mpsc::UnboundedSender;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use tokio::sync::mpsc::{self, error::TrySendError};
 
 #[derive(Debug, Clone)]
 pub enum ConnectProgress {
     Stage(String),
     Log(String),
+    /// Something likely to break the launch; shown prominently in the connect modal.
+    Warning(String),
     GameLaunched { exe_path: String },
     Download {
         label: String,
         done_bytes: u64,
         total_bytes: Option<u64>,
     },
+    /// Prelaunch engine signature check failed for this (resolved) engine version.
+    EngineSignatureFailed { engine_version: String },
+    /// The launch failed and the last successful one on this server used other settings;
+    /// `changes` lists the differences.
+    KnownGoodAvailable { changes: Vec<String> },
+    /// The exact engine version is gone; the connect thread waits for a yes/no on `reply`.
+    EngineFallbackPrompt {
+        requested: String,
+        candidate: String,
+        reply: std::sync::mpsc::Sender<bool>,
+    },
+    /// Joining without an account; the connect thread waits for a name (`None` cancels).
+    GuestNamePrompt {
+        suggested: String,
+        reply: std::sync::mpsc::Sender<Option<String>>,
+    },
+}
+
+/// Default pause between download progress events; `SGLOADER_PROGRESS_INTERVAL_MS` overrides it.
+const DEFAULT_PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Launch logs kept per kind (`connect-*`, `launch-*`); older sessions are deleted.
+const MAX_SESSION_LOGS: usize = 20;
+
+/// Fan-out sender for connect progress: every event goes to each live subscriber (UI, file
+/// logger, ...). Cheap to clone; the connect thread never blocks on a slow subscriber.
+///
+/// Each sender is one connect session: its id names the log files of that attempt.
+#[derive(Clone)]
+pub struct ProgressTx {
+    subscribers: Arc<Mutex<Vec<Subscriber>>>,
+    session_id: Arc<str>,
+}
+
+enum Subscriber {
+    /// Sees every event; for the UI, which must not miss prompts.
+    Unbounded(mpsc::UnboundedSender<ConnectProgress>),
+    /// Keeps at most `capacity` queued events; anything beyond that is dropped and counted.
+    Bounded {
+        tx: mpsc::Sender<ConnectProgress>,
+        dropped: Arc<AtomicU64>,
+    },
+}
+
+/// All subscribers are gone (the UI was closed); callers treat this like a dropped channel.
+#[derive(Debug)]
+pub struct NoSubscribers;
+
+impl Default for ProgressTx {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProgressTx {
+    pub fn new() -> Self {
+        Self {
+            subscribers: Arc::default(),
+            session_id: new_session_id().into(),
+        }
+    }
+
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    pub fn subscribe(&self) -> mpsc::UnboundedReceiver<ConnectProgress> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.lock().push(Subscriber::Unbounded(tx));
+        rx
+    }
+
+    /// Lossy subscription for consumers that may fall behind. The returned counter holds the
+    /// number of events dropped because the queue was full.
+    pub fn subscribe_bounded(
+        &self,
+        capacity: usize,
+    ) -> (mpsc::Receiver<ConnectProgress>, Arc<AtomicU64>) {
+        let (tx, rx) = mpsc::channel(capacity.max(1));
+        let dropped = Arc::new(AtomicU64::new(0));
+        self.lock().push(Subscriber::Bounded {
+            tx,
+            dropped: dropped.clone(),
+        });
+        (rx, dropped)
+    }
+
+    pub fn send(&self, event: ConnectProgress) -> Result<(), NoSubscribers> {
+        let mut subs = self.lock();
+        subs.retain(|sub| match sub {
+            Subscriber::Unbounded(tx) => tx.send(event.clone()).is_ok(),
+            Subscriber::Bounded { tx, dropped } => match tx.try_send(event.clone()) {
+                Ok(()) => true,
+                Err(TrySendError::Full(_)) => {
+                    dropped.fetch_add(1, Ordering::Relaxed);
+                    true
+                }
+                Err(TrySendError::Closed(_)) => false,
+            },
+        });
+        if subs.is_empty() {
+            Err(NoSubscribers)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Subscriber>> {
+        self.subscribers.lock().unwrap_or_else(|e| e.into_inner())
+    }
 }
 
-pub type ProgressTx = UnboundedSender<ConnectProgress>;
+/// `20261016-153012-3fa2`: sorts by time, the suffix separates attempts within a second.
+fn new_session_id() -> String {
+    let suffix = uuid::Uuid::new_v4().simple().to_string();
+    format!(
+        "{}-{}",
+        chrono::Local::now().format("%Y%m%d-%H%M%S"),
+        &suffix[..4]
+    )
+}
+
+/// Session id for log names when the caller has no progress sender.
+pub fn session_id(tx: Option<&ProgressTx>) -> String {
+    tx.map(|t| t.session_id().to_string())
+        .unwrap_or_else(new_session_id)
+}
+
+/// `<logs_dir>/<kind>-<session>.log`, pruning the oldest logs of that kind beyond
+/// `MAX_SESSION_LOGS`.
+pub fn session_log_path(logs_dir: &Path, kind: &str, session_id: &str) -> PathBuf {
+    let prefix = format!("{kind}-");
+    if let Ok(entries) = std::fs::read_dir(logs_dir) {
+        let mut existing: Vec<PathBuf> = entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| {
+                p.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with(&prefix) && n.ends_with(".log"))
+            })
+            .collect();
+        // Ids start with a timestamp, so name order is age order.
+        existing.sort();
+        let excess = (existing.len() + 1).saturating_sub(MAX_SESSION_LOGS);
+        for old in existing.into_iter().take(excess) {
+            let _ = std::fs::remove_file(old);
+        }
+    }
+    logs_dir.join(format!("{prefix}{session_id}.log"))
+}
+
+/// Appends stages, logs and warnings of one connect attempt to `path` from a background thread.
+pub fn spawn_file_logger(tx: &ProgressTx, path: &Path) -> Result<(), String> {
+    let mut file = std::fs::File::create(path)
+        .map_err(|e| crate::app_paths::describe_io_error("create", path, &e))?;
+    let (mut rx, dropped) = tx.subscribe_bounded(1024);
+    let writer = std::thread::spawn(move || {
+        while let Some(event) = rx.blocking_recv() {
+            let stamp = chrono::Local::now().format("%H:%M:%S%.3f");
+            let line = match event {
+                ConnectProgress::Stage(s) => format!("[{stamp}] == {s}"),
+                ConnectProgress::Log(l) => format!("[{stamp}] {l}"),
+                ConnectProgress::Warning(w) => format!("[{stamp}] ВНИМАНИЕ: {w}"),
+                ConnectProgress::GameLaunched { exe_path } => {
+                    format!("[{stamp}] запущено: {exe_path}")
+                }
+                ConnectProgress::EngineSignatureFailed { engine_version } => {
+                    format!("[{stamp}] подпись движка {engine_version} не прошла проверку")
+                }
+                ConnectProgress::KnownGoodAvailable { changes } => format!(
+                    "[{stamp}] с последнего успешного запуска изменено: {}",
+                    changes.join("; ")
+                ),
+                ConnectProgress::Download { .. }
+                | ConnectProgress::EngineFallbackPrompt { .. }
+                | ConnectProgress::GuestNamePrompt { .. } => {
+                    continue;
+                }
+            };
+            if writeln!(file, "{line}").is_err() {
+                return;
+            }
+        }
+        let lost = dropped.load(Ordering::Relaxed);
+        if lost > 0 {
+            let _ = writeln!(file, "(пропущено событий: {lost})");
+        }
+    });
+    crate::core::shutdown::track_log_writer(writer);
+    Ok(())
+}
 
 pub fn stage(tx: Option<&ProgressTx>, message: impl Into<String>) {
     let Some(tx) = tx else {
@@ -28,6 +224,29 @@ pub fn log(tx: Option<&ProgressTx>, line: impl Into<String>) {
     let _ = tx.send(ConnectProgress::Log(line.into()));
 }
 
+pub fn warning(tx: Option<&ProgressTx>, message: impl Into<String>) {
+    let Some(tx) = tx else {
+        return;
+    };
+    let _ = tx.send(ConnectProgress::Warning(message.into()));
+}
+
+pub fn engine_signature_failed(tx: Option<&ProgressTx>, engine_version: impl Into<String>) {
+    let Some(tx) = tx else {
+        return;
+    };
+    let _ = tx.send(ConnectProgress::EngineSignatureFailed {
+        engine_version: engine_version.into(),
+    });
+}
+
+pub fn known_good_available(tx: Option<&ProgressTx>, changes: Vec<String>) {
+    let Some(tx) = tx else {
+        return;
+    };
+    let _ = tx.send(ConnectProgress::KnownGoodAvailable { changes });
+}
+
 pub fn game_launched(tx: Option<&ProgressTx>, exe_path: impl Into<String>) {
     let Some(tx) = tx else {
         return;
@@ -52,3 +271,179 @@ pub fn download(
         total_bytes,
     });
 }
+
+fn progress_interval() -> Duration {
+    static INTERVAL: OnceLock<Duration> = OnceLock::new();
+    *INTERVAL.get_or_init(|| {
+        std::env::var("SGLOADER_PROGRESS_INTERVAL_MS")
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_PROGRESS_INTERVAL)
+    })
+}
+
+/// Rate limit for download progress events. Time based, so the bar moves at the same pace
+/// on a slow link as on a fast one instead of waiting for a fixed number of bytes.
+pub struct DownloadThrottle {
+    last_emit: Option<Instant>,
+    last_bytes: u64,
+}
+
+impl DownloadThrottle {
+    pub fn new() -> Self {
+        Self {
+            last_emit: None,
+            last_bytes: 0,
+        }
+    }
+
+    /// `true` when an event for `done_bytes` is due: something arrived since the last one
+    /// and the interval has passed (the first event is sent right away).
+    pub fn should_emit(&mut self, done_bytes: u64) -> bool {
+        if done_bytes == self.last_bytes {
+            return false;
+        }
+        let due = self
+            .last_emit
+            .is_none_or(|t| t.elapsed() >= progress_interval());
+        if due {
+            self.last_emit = Some(Instant::now());
+            self.last_bytes = done_bytes;
+        }
+        due
+    }
+}
+
+impl Default for DownloadThrottle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Asks the UI whether to use `candidate` instead of `requested` and blocks until it answers.
+/// Returns `false` without a UI, on cancel, or if the prompt is dropped.
+pub fn ask_engine_fallback(
+    tx: Option<&ProgressTx>,
+    requested: &str,
+    candidate: &str,
+    cancel: Option<&crate::cancel_flag::CancelFlag>,
+) -> bool {
+    let Some(tx) = tx else {
+        return false;
+    };
+    let (reply_tx, reply_rx) = std::sync::mpsc::channel();
+    if tx
+        .send(ConnectProgress::EngineFallbackPrompt {
+            requested: requested.to_string(),
+            candidate: candidate.to_string(),
+            reply: reply_tx,
+        })
+        .is_err()
+    {
+        return false;
+    }
+
+    loop {
+        if cancel.map(|c| c.is_cancelled()).unwrap_or(false) {
+            return false;
+        }
+        match reply_rx.recv_timeout(std::time::Duration::from_millis(250)) {
+            Ok(answer) => return answer,
+            Err(std::sync::mpsc::RecvTimeoutError::Timeout) => continue,
+            Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => return false,
+        }
+    }
+}
+
+/// Asks the UI for the name to join with and blocks until it answers. Without a UI the
+/// suggestion is used as is; `None` means the user cancelled (or the prompt was dropped).
+pub fn ask_guest_name(
+    tx: Option<&ProgressTx>,
+    suggested: &str,
+    cancel: Option<&crate::cancel_flag::CancelFlag>,
+) -> Option<String> {
+    let Some(tx) = tx else {
+        return Some(suggested.to_string());
+    };
+    let (reply_tx, reply_rx) = std::sync::mpsc::channel();
+    if tx
+        .send(ConnectProgress::GuestNamePrompt {
+            suggested: suggested.to_string(),
+            reply: reply_tx,
+        })
+        .is_err()
+    {
+        return Some(suggested.to_string());
+    }
+
+    loop {
+        if cancel.map(|c| c.is_cancelled()).unwrap_or(false) {
+            return None;
+        }
+        match reply_rx.recv_timeout(std::time::Duration::from_millis(250)) {
+            Ok(answer) => return answer,
+            Err(std::sync::mpsc::RecvTimeoutError::Timeout) => continue,
+            Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => return None,
+        }
+    }
+}
+
+/// Failures of parallel workers grouped by cause, so one outage hitting every worker is
+/// reported once with a count instead of once per worker.
+#[derive(Debug, Default)]
+pub struct ErrorTally {
+    /// Cause, how many times it was seen, and by which workers; in order of first appearance.
+    causes: Vec<(String, usize, Vec<usize>)>,
+}
+
+impl ErrorTally {
+    /// Counts `cause` from `worker`; true the first time this cause shows up.
+    pub fn add(&mut self, worker: usize, cause: &str) -> bool {
+        match self.causes.iter_mut().find(|(c, _, _)| c == cause) {
+            Some((_, count, workers)) => {
+                *count += 1;
+                if !workers.contains(&worker) {
+                    workers.push(worker);
+                }
+                false
+            }
+            None => {
+                self.causes.push((cause.to_string(), 1, vec![worker]));
+                true
+            }
+        }
+    }
+
+    /// One line per cause seen more than once, for the log after repeats were left out of it.
+    pub fn repeat_lines(&self, what: &str) -> Vec<String> {
+        self.causes
+            .iter()
+            .filter(|(_, count, _)| *count > 1)
+            .map(|(cause, count, workers)| {
+                format!("{what}: {count} раз у {} потоков: {cause}", workers.len())
+            })
+            .collect()
+    }
+
+    /// Single message for the user: the cause as is when only one worker hit it, otherwise the
+    /// number of `what` that failed with it, one line per distinct cause.
+    pub fn summary(&self, what: &str) -> Option<String> {
+        match self.causes.as_slice() {
+            [] => None,
+            [(cause, _, workers)] if workers.len() == 1 => Some(cause.clone()),
+            [(cause, _, workers)] => Some(format!(
+                "{} {what} завершились с ошибкой: {cause}",
+                workers.len()
+            )),
+            causes => {
+                let total: usize = causes.iter().map(|(_, _, w)| w.len()).sum();
+                let mut out = format!("{total} {what} завершились с ошибками:");
+                for (cause, _, workers) in causes {
+                    out.push_str(&format!("\n- {} × {cause}", workers.len()));
+                }
+                Some(out)
+            }
+        }
+    }
+}
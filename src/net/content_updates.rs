@@ -0,0 +1,138 @@
+use url::Url;
+
+use crate::connect_progress::{ConnectProgress, ProgressTx};
+use crate::favorites::{self, ContentIdentity};
+use crate::ss14_server_info::{ServerBuildInformation, ServerInfo};
+use crate::ss14_uri;
+use crate::tasks::{TaskHandle, TaskProgress};
+
+/// A favorite that serves different content than when it was last launched, not cached yet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContentUpdate {
+    pub version: String,
+    /// Content-Length of the content zip; `None` when the server does not report it
+    /// (manifest-only servers, protected CDNs).
+    pub size_bytes: Option<u64>,
+}
+
+/// Blocking: asks `/info` what `address` serves now and compares it with the identity
+/// recorded at the last launch.
+pub fn check_content_update(address: &str) -> Result<Option<ContentUpdate>, String> {
+    let (build, _) = fetch_build(address)?;
+    let data_dir = crate::app_paths::data_dir()?;
+    let cached = crate::content_install::is_content_cached(&data_dir, &build);
+
+    match favorites::load_content_identity(address) {
+        // Never launched since tracking started: a cached copy is a good enough baseline.
+        None => {
+            if cached {
+                favorites::record_content_identity(address, &build)?;
+            }
+            Ok(None)
+        }
+        Some(seen) if cached || seen == ContentIdentity::from_build(&build) => Ok(None),
+        Some(_) => Ok(Some(ContentUpdate {
+            version: build.version.clone(),
+            size_bytes: content_size(&build),
+        })),
+    }
+}
+
+/// Blocking: downloads the content of `address` ahead of a launch, reporting bytes to `task`.
+pub fn predownload_content(address: &str, task: &TaskHandle) -> Result<(), String> {
+    prefetch(address, false, task)
+}
+
+/// Blocking: everything a launch of `address` needs, engine and content, so connecting later
+/// starts the game right away. Reports bytes to `task`.
+pub fn prepare_server(address: &str, task: &TaskHandle) -> Result<(), String> {
+    prefetch(address, true, task)
+}
+
+fn prefetch(address: &str, with_engine: bool, task: &TaskHandle) -> Result<(), String> {
+    crate::core::instance_lock::ensure_writable()?;
+    let (build, ss14) = fetch_build(address)?;
+    let data_dir = crate::app_paths::data_dir()?;
+    let fallback_zip_url = ss14_uri::server_selfhosted_client_zip_url(&ss14)
+        .ok()
+        .map(|u| u.to_string());
+
+    let progress = ProgressTx::new();
+    let mut rx = progress.subscribe();
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            while let Some(ev) = rx.blocking_recv() {
+                if let ConnectProgress::Download {
+                    done_bytes,
+                    total_bytes,
+                    ..
+                } = ev
+                {
+                    task.set_progress(TaskProgress::Bytes {
+                        done: done_bytes,
+                        total: total_bytes,
+                    });
+                }
+            }
+        });
+
+        let result = (|| {
+            if with_engine {
+                task.set_detail(format!("движок {}", build.engine_version));
+                crate::client_install::ensure_client_installed(
+                    &data_dir,
+                    &build.engine_version,
+                    Some(&progress),
+                    Some(task.cancel_flag()),
+                )?;
+                task.set_detail(format!("контент {}", build.version));
+            }
+            crate::content_install::ensure_content_overlay_zip(
+                &data_dir,
+                &build,
+                fallback_zip_url.as_deref(),
+                Some(&progress),
+                Some(task.cancel_flag()),
+            )
+        })();
+        // Closes the channel so the reader thread ends.
+        drop(progress);
+        result.map(|_| ())
+    })
+}
+
+fn fetch_build(address: &str) -> Result<(ServerBuildInformation, Url), String> {
+    let ss14 = ss14_uri::parse_ss14_uri(address)?;
+    let info_url = ss14_uri::server_info_url(&ss14)?;
+
+    let http = crate::launcher_mask::blocking_http_client_api()?;
+    let info: ServerInfo =
+        crate::http_config::blocking_send_idempotent_with_retry(|| http.get(info_url.as_str()))
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("{}: {e}", info_url.as_str()))?
+            .json()
+            .map_err(|e| format!("{}: parse error {e}", info_url.as_str()))?;
+
+    let mut build = info
+        .build_information
+        .ok_or_else(|| "сервер не вернул build информацию".to_string())?;
+    crate::build_overrides::load_build_overrides(address).apply(&mut build);
+    crate::connect::fill_build_fallbacks(&mut build, &ss14)?;
+    Ok((build, ss14))
+}
+
+fn content_size(build: &ServerBuildInformation) -> Option<u64> {
+    let url = build.download_url.as_deref()?;
+    let http = crate::launcher_mask::blocking_http_client_api().ok()?;
+    let resp = http.head(url).send().ok()?;
+    if !resp.status().is_success() {
+        return None;
+    }
+    resp.headers()
+        .get(reqwest::header::CONTENT_LENGTH)?
+        .to_str()
+        .ok()?
+        .parse::<u64>()
+        .ok()
+        .filter(|&n| n > 0)
+}
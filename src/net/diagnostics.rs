@@ -0,0 +1,283 @@
+use std::net::{ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+
+use tokio::sync::mpsc::UnboundedSender;
+use url::Url;
+
+use crate::ss14_server_info::{AuthMode, ServerInfo};
+use crate::ss14_uri;
+
+const UDP_PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+    Skipped,
+}
+
+impl CheckStatus {
+    pub fn label_ru(self) -> &'static str {
+        match self {
+            CheckStatus::Pass => "ok",
+            CheckStatus::Warn => "внимание",
+            CheckStatus::Fail => "ошибка",
+            CheckStatus::Skipped => "пропущено",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckResult {
+    pub step: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+pub type CheckTx = UnboundedSender<CheckResult>;
+
+fn report(tx: &CheckTx, step: &'static str, status: CheckStatus, detail: impl Into<String>) {
+    let _ = tx.send(CheckResult {
+        step,
+        status,
+        detail: detail.into(),
+    });
+}
+
+/// Runs the join prerequisites one by one and reports each step as it finishes.
+/// Later steps that depend on a failed one are reported as skipped.
+pub fn run_connection_test(address: &str, has_account: bool, tx: CheckTx) {
+    let ss14 = match ss14_uri::parse_ss14_uri(address) {
+        Ok(u) => {
+            report(&tx, "адрес", CheckStatus::Pass, u.to_string());
+            u
+        }
+        Err(e) => {
+            report(&tx, "адрес", CheckStatus::Fail, e);
+            return;
+        }
+    };
+
+    let dns_ok = check_dns(&ss14, &tx);
+
+    let info = if dns_ok {
+        check_info(&ss14, &tx)
+    } else {
+        report(&tx, "/info", CheckStatus::Skipped, "DNS не прошёл");
+        None
+    };
+
+    let Some((info, info_url)) = info else {
+        for step in ["UDP", "авторизация", "движок"] {
+            report(&tx, step, CheckStatus::Skipped, "нет ответа /info");
+        }
+        return;
+    };
+
+    check_udp(&info, &info_url, &tx);
+    check_auth(&info, has_account, &tx);
+    check_engine(&info, &tx);
+}
+
+fn check_dns(ss14: &Url, tx: &CheckTx) -> bool {
+    let Some(host) = ss14.host_str() else {
+        report(tx, "DNS", CheckStatus::Fail, "в адресе нет host");
+        return false;
+    };
+    let port = ss14.port().unwrap_or(1212);
+
+    let started = Instant::now();
+    match (host.trim_matches(['[', ']']), port).to_socket_addrs() {
+        Ok(addrs) => {
+            let ips: Vec<String> = addrs.map(|a| a.ip().to_string()).collect();
+            if ips.is_empty() {
+                report(tx, "DNS", CheckStatus::Fail, format!("{host}: нет адресов"));
+                return false;
+            }
+            report(
+                tx,
+                "DNS",
+                CheckStatus::Pass,
+                format!(
+                    "{host} → {} ({} мс)",
+                    ips.join(", "),
+                    started.elapsed().as_millis()
+                ),
+            );
+            true
+        }
+        Err(e) => {
+            report(tx, "DNS", CheckStatus::Fail, format!("{host}: {e}"));
+            false
+        }
+    }
+}
+
+fn check_info(ss14: &Url, tx: &CheckTx) -> Option<(ServerInfo, Url)> {
+    let info_url = match ss14_uri::server_info_url(ss14) {
+        Ok(u) => u,
+        Err(e) => {
+            report(tx, "/info", CheckStatus::Fail, e);
+            return None;
+        }
+    };
+
+    let result = (|| {
+        let http = crate::launcher_mask::blocking_http_client_api()?;
+        let started = Instant::now();
+        let resp = http
+            .get(info_url.as_str())
+            .send()
+            .map_err(|e| format!("{info_url}: {e}"))?;
+        let status = resp.status();
+        if !status.is_success() {
+            return Err(format!("{info_url}: status {status}"));
+        }
+        let info: ServerInfo = resp.json().map_err(|e| format!("{info_url}: parse: {e}"))?;
+        Ok((info, started.elapsed()))
+    })();
+
+    match result {
+        Ok((info, elapsed)) => {
+            report(
+                tx,
+                "/info",
+                CheckStatus::Pass,
+                format!("{info_url} ({} мс)", elapsed.as_millis()),
+            );
+            Some((info, info_url))
+        }
+        Err(e) => {
+            report(tx, "/info", CheckStatus::Fail, e);
+            None
+        }
+    }
+}
+
+/// UDP has no handshake we can do without speaking the game protocol, so this only
+/// catches the "port closed" case (ICMP unreachable); silence is reported as a warning.
+fn check_udp(info: &ServerInfo, info_url: &Url, tx: &CheckTx) {
+    let connect_addr = match crate::connect::get_connect_address(info, info_url) {
+        Ok(a) => a,
+        Err(e) => {
+            report(tx, "UDP", CheckStatus::Fail, e);
+            return;
+        }
+    };
+
+    let target = Url::parse(&connect_addr).ok().and_then(|u| {
+        let host = u.host_str()?.trim_matches(['[', ']']).to_string();
+        Some((host, u.port().unwrap_or(1212)))
+    });
+    let Some((host, port)) = target else {
+        report(
+            tx,
+            "UDP",
+            CheckStatus::Fail,
+            format!("не разобрать {connect_addr}"),
+        );
+        return;
+    };
+
+    let probe = (|| -> std::io::Result<Option<usize>> {
+        let addr = (host.as_str(), port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| std::io::Error::other("нет адресов"))?;
+        let bind = if addr.is_ipv6() {
+            "[::]:0"
+        } else {
+            "0.0.0.0:0"
+        };
+        let socket = UdpSocket::bind(bind)?;
+        socket.connect(addr)?;
+        socket.set_read_timeout(Some(UDP_PROBE_TIMEOUT))?;
+        socket.send(&[0u8])?;
+        let mut buf = [0u8; 64];
+        match socket.recv(&mut buf) {
+            Ok(n) => Ok(Some(n)),
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) =>
+            {
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    })();
+
+    match probe {
+        Ok(Some(_)) => report(
+            tx,
+            "UDP",
+            CheckStatus::Pass,
+            format!("{host}:{port} ответил"),
+        ),
+        Ok(None) => report(
+            tx,
+            "UDP",
+            CheckStatus::Warn,
+            format!("{host}:{port}: ответа нет, но и отказа нет (для UDP это нормально)"),
+        ),
+        Err(e) if e.kind() == std::io::ErrorKind::ConnectionRefused => report(
+            tx,
+            "UDP",
+            CheckStatus::Fail,
+            format!("{host}:{port}: порт закрыт (ICMP unreachable)"),
+        ),
+        Err(e) => report(tx, "UDP", CheckStatus::Fail, format!("{host}:{port}: {e}")),
+    }
+}
+
+fn check_auth(info: &ServerInfo, has_account: bool, tx: &CheckTx) {
+    let mode = info.auth_information.mode;
+    match (mode, has_account) {
+        (AuthMode::Required, false) => report(
+            tx,
+            "авторизация",
+            CheckStatus::Fail,
+            "сервер требует авторизацию — войдите в аккаунт",
+        ),
+        (AuthMode::Disabled, _) => {
+            report(tx, "авторизация", CheckStatus::Pass, "отключена на сервере")
+        }
+        (AuthMode::Optional, false) => report(
+            tx,
+            "авторизация",
+            CheckStatus::Warn,
+            "необязательна, войдёте гостем",
+        ),
+        _ => report(tx, "авторизация", CheckStatus::Pass, format!("{mode:?}")),
+    }
+}
+
+fn check_engine(info: &ServerInfo, tx: &CheckTx) {
+    let Some(build) = &info.build_information else {
+        report(
+            tx,
+            "движок",
+            CheckStatus::Fail,
+            "сервер не вернул build информацию",
+        );
+        return;
+    };
+
+    match crate::robust_builds::resolve_engine_build(&build.engine_version) {
+        Ok(b) if b.resolved_version != b.requested_version => report(
+            tx,
+            "движок",
+            CheckStatus::Pass,
+            format!("{} → {}", b.requested_version, b.resolved_version),
+        ),
+        Ok(b) => report(tx, "движок", CheckStatus::Pass, b.resolved_version),
+        Err(e) => report(
+            tx,
+            "движок",
+            CheckStatus::Fail,
+            format!("{}: {e}", build.engine_version),
+        ),
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+use std::fs;
+use std::net::{IpAddr, ToSocketAddrs};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Semaphore;
+
+use crate::servers::ServerEntry;
+use crate::ss14_uri;
+
+const GEOIP_CACHE_FILE_NAME: &str = "geoip_cache.json";
+// country.is is open source and does not log lookups; only the server IP is sent.
+const GEOIP_API_BASE_URL: &str = "https://api.country.is/";
+const CACHE_TTL_SECS: i64 = 30 * 24 * 60 * 60;
+/// A failed lookup (network, DNS, rate limit) is retried after this rather than a month later.
+const FAILED_LOOKUP_TTL_SECS: i64 = 10 * 60;
+const MAX_LOOKUPS_PER_REFRESH: usize = 64;
+const MAX_PARALLEL_LOOKUPS: usize = 6;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct GeoIpCacheFile {
+    #[serde(default)]
+    hosts: HashMap<String, GeoIpCacheEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct GeoIpCacheEntry {
+    /// ISO 3166-1 alpha-2 code, `None` when the lookup failed or the host is private.
+    country: Option<String>,
+    checked_at: i64,
+    /// The lookup got an answer, even if it had no country (private host, unknown IP).
+    #[serde(default)]
+    answered: bool,
+}
+
+impl GeoIpCacheEntry {
+    fn expired(&self, now: i64) -> bool {
+        let ttl = if self.country.is_some() || self.answered {
+            CACHE_TTL_SECS
+        } else {
+            FAILED_LOOKUP_TTL_SECS
+        };
+        now - self.checked_at > ttl
+    }
+}
+
+/// Result of asking about one host.
+enum Lookup {
+    Country(String),
+    /// Private address, or the service knows no country for it.
+    NoCountry,
+    Failed,
+}
+
+#[derive(Debug, Deserialize)]
+struct CountryIsResponse {
+    country: Option<String>,
+}
+
+/// Returns `address → region key` for servers that don't advertise `region:` themselves.
+/// Cached hosts are answered locally; at most `MAX_LOOKUPS_PER_REFRESH` new hosts are queried.
+pub async fn infer_regions(addresses: Vec<String>) -> HashMap<String, String> {
+    let mut cache = load_cache();
+    let now = chrono::Utc::now().timestamp();
+
+    let mut host_by_address: HashMap<String, String> = HashMap::new();
+    for address in addresses {
+        if let Some(host) = address_host(&address) {
+            host_by_address.insert(address, host);
+        }
+    }
+
+    let mut pending: Vec<String> = host_by_address
+        .values()
+        .filter(|h| {
+            cache
+                .hosts
+                .get(*h)
+                .map(|e| e.expired(now))
+                .unwrap_or(true)
+        })
+        .cloned()
+        .collect();
+    pending.sort();
+    pending.dedup();
+    pending.truncate(MAX_LOOKUPS_PER_REFRESH);
+
+    if !pending.is_empty()
+        && let Ok(client) = crate::launcher_mask::async_http_client()
+    {
+        let limit = Arc::new(Semaphore::new(MAX_PARALLEL_LOOKUPS));
+        let mut jobs = Vec::new();
+        for host in pending {
+            let client = client.clone();
+            let limit = limit.clone();
+            jobs.push(tokio::spawn(async move {
+                let _permit = limit.acquire_owned().await.ok()?;
+                let lookup = lookup_host_country(&client, &host).await;
+                Some((host, lookup))
+            }));
+        }
+
+        for job in jobs {
+            if let Ok(Some((host, lookup))) = job.await {
+                let (country, answered) = match lookup {
+                    Lookup::Country(c) => (Some(c), true),
+                    Lookup::NoCountry => (None, true),
+                    Lookup::Failed => (None, false),
+                };
+                cache.hosts.insert(
+                    host,
+                    GeoIpCacheEntry {
+                        country,
+                        checked_at: now,
+                        answered,
+                    },
+                );
+            }
+        }
+
+        let _ = save_cache(&cache);
+    }
+
+    host_by_address
+        .into_iter()
+        .filter_map(|(address, host)| {
+            let country = cache.hosts.get(&host)?.country.as_deref()?;
+            country_to_region(country).map(|r| (address, r.to_string()))
+        })
+        .collect()
+}
+
+/// Fills in `region` for entries that have none, marking it as inferred.
+pub fn apply_inferred_regions(servers: &mut [ServerEntry], regions: &HashMap<String, String>) {
+    for srv in servers.iter_mut().filter(|s| s.region.is_none()) {
+        if let Some(region) = regions.get(&srv.address) {
+            srv.region = Some(region.clone());
+            srv.region_inferred = true;
+        }
+    }
+}
+
+async fn lookup_host_country(client: &reqwest::Client, host: &str) -> Lookup {
+    let host_owned = host.to_string();
+    let ip = match tokio::task::spawn_blocking(move || resolve_public_ip(&host_owned)).await {
+        Ok(Resolved::Public(ip)) => ip,
+        Ok(Resolved::Private) => return Lookup::NoCountry,
+        Ok(Resolved::Failed) | Err(_) => return Lookup::Failed,
+    };
+
+    let url = format!("{GEOIP_API_BASE_URL}{ip}");
+    let Ok(response) = client.get(&url).send().await else {
+        return Lookup::Failed;
+    };
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Lookup::NoCountry;
+    }
+    if !response.status().is_success() {
+        return Lookup::Failed;
+    }
+
+    let Ok(parsed) = response.json::<CountryIsResponse>().await else {
+        return Lookup::Failed;
+    };
+    parsed
+        .country
+        .map(|c| c.trim().to_ascii_uppercase())
+        .filter(|c| c.len() == 2)
+        .map_or(Lookup::NoCountry, Lookup::Country)
+}
+
+enum Resolved {
+    Public(IpAddr),
+    Private,
+    Failed,
+}
+
+fn resolve_public_ip(host: &str) -> Resolved {
+    let ip = match host.parse::<IpAddr>() {
+        Ok(ip) => ip,
+        Err(_) => match (host, 0u16).to_socket_addrs().ok().and_then(|mut a| a.next()) {
+            Some(addr) => addr.ip(),
+            None => return Resolved::Failed,
+        },
+    };
+
+    let private = match ip {
+        IpAddr::V4(v4) => {
+            v4.is_private() || v4.is_loopback() || v4.is_link_local() || v4.is_unspecified()
+        }
+        IpAddr::V6(v6) => v6.is_loopback() || v6.is_unspecified(),
+    };
+
+    if private {
+        Resolved::Private
+    } else {
+        Resolved::Public(ip)
+    }
+}
+
+fn address_host(address: &str) -> Option<String> {
+    let url = ss14_uri::parse_ss14_uri(address).ok()?;
+    url.host_str()
+        .map(|h| h.trim_matches(['[', ']']).to_ascii_lowercase())
+}
+
+/// Maps a country code to one of the region keys understood by the server list filters.
+fn country_to_region(country: &str) -> Option<&'static str> {
+    let region = match country {
+        "RU" | "BY" | "KZ" => "ru",
+        "US" | "CA" | "MX" => "na",
+        "BR" | "AR" | "CL" | "CO" | "PE" | "UY" => "sa",
+        "AU" => "au",
+        "NZ" => "oce",
+        "JP" | "KR" | "CN" | "HK" | "TW" | "SG" | "IN" | "VN" | "TH" | "ID" | "MY" | "PH" => "asia",
+        "DE" | "NL" | "FR" | "GB" | "FI" | "SE" | "NO" | "DK" | "PL" | "CZ" | "AT" | "CH"
+        | "BE" | "LU" | "IE" | "ES" | "PT" | "IT" | "LT" | "LV" | "EE" | "UA" | "RO" | "BG"
+        | "HU" | "SK" | "SI" | "HR" | "RS" | "GR" | "MD" | "TR" => "eu",
+        _ => return None,
+    };
+    Some(region)
+}
+
+fn load_cache() -> GeoIpCacheFile {
+    cache_file_path()
+        .ok()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save_cache(cache: &GeoIpCacheFile) -> Result<(), String> {
+    let dir = crate::app_paths::data_dir()?;
+    crate::app_paths::create_dir(&dir, "geoip")?;
+
+    let json = serde_json::to_string(cache).map_err(|e| format!("serialize geoip: {e}"))?;
+    fs::write(cache_file_path()?, json).map_err(|e| format!("запись geoip кэша: {e}"))
+}
+
+fn cache_file_path() -> Result<PathBuf, String> {
+    Ok(crate::app_paths::data_dir()?.join(GEOIP_CACHE_FILE_NAME))
+}
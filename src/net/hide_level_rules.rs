@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::settings::HideLevel;
+
+const HIDE_LEVEL_RULES_FILE_NAME: &str = "hide_level_rules.json";
+const RULES_REFRESH_SECS: i64 = 6 * 60 * 60;
+
+/// Remote ruleset format:
+/// `{ "default": "medium", "rules": { "<fork_id>": "<hide level key>" } }`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HideLevelRuleset {
+    #[serde(default)]
+    pub default: Option<String>,
+    #[serde(default)]
+    pub rules: HashMap<String, String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct HideLevelRulesCache {
+    #[serde(default)]
+    source_url: String,
+    #[serde(default)]
+    fetched_at: i64,
+    #[serde(default)]
+    ruleset: HideLevelRuleset,
+}
+
+/// What `HideLevel::Auto` turned into and why, for the connect log.
+#[derive(Debug, Clone)]
+pub struct AutoHideLevel {
+    pub level: HideLevel,
+    pub reason: String,
+}
+
+/// Picks the recommended level for `fork_id`, refreshing the cached ruleset when it is stale.
+/// Never fails: on any problem it falls back to the default `SecuritySettings` level.
+pub fn resolve_auto_hide_level(fork_id: &str, rules_url: &str) -> AutoHideLevel {
+    let fallback = crate::settings::SecuritySettings::default().hide_level;
+    let rules_url = rules_url.trim();
+
+    if rules_url.is_empty() {
+        return AutoHideLevel {
+            level: fallback,
+            reason: "адрес правил не задан".to_string(),
+        };
+    }
+
+    let (ruleset, note) = match load_ruleset(rules_url) {
+        Ok(r) => (r, None),
+        Err(e) => match load_cache().filter(|c| c.source_url == rules_url) {
+            Some(c) => (c.ruleset, Some(format!("кэш, обновление не удалось: {e}"))),
+            None => {
+                return AutoHideLevel {
+                    level: fallback,
+                    reason: format!("правила недоступны: {e}"),
+                };
+            }
+        },
+    };
+
+    let suffix = note.map(|n| format!(" ({n})")).unwrap_or_default();
+
+    let key = fork_id.trim().to_ascii_lowercase();
+    if let Some(level) = ruleset
+        .rules
+        .iter()
+        .find(|(k, _)| k.trim().to_ascii_lowercase() == key)
+        .and_then(|(_, v)| parse_level(v))
+    {
+        return AutoHideLevel {
+            level,
+            reason: format!("правило для fork_id={fork_id}{suffix}"),
+        };
+    }
+
+    match ruleset.default.as_deref().and_then(parse_level) {
+        Some(level) => AutoHideLevel {
+            level,
+            reason: format!("правило по умолчанию{suffix}"),
+        },
+        None => AutoHideLevel {
+            level: fallback,
+            reason: format!("нет правила для fork_id={fork_id}{suffix}"),
+        },
+    }
+}
+
+fn parse_level(key: &str) -> Option<HideLevel> {
+    HideLevel::from_key(key.trim().to_ascii_lowercase().as_str())
+        .filter(|l| *l != HideLevel::Auto)
+}
+
+fn load_ruleset(rules_url: &str) -> Result<HideLevelRuleset, String> {
+    let now = chrono::Utc::now().timestamp();
+    if let Some(cache) = load_cache()
+        && cache.source_url == rules_url
+        && now - cache.fetched_at < RULES_REFRESH_SECS
+    {
+        return Ok(cache.ruleset);
+    }
+
+    let ruleset = fetch_ruleset(rules_url)?;
+    let _ = save_cache(&HideLevelRulesCache {
+        source_url: rules_url.to_string(),
+        fetched_at: now,
+        ruleset: ruleset.clone(),
+    });
+    Ok(ruleset)
+}
+
+fn fetch_ruleset(rules_url: &str) -> Result<HideLevelRuleset, String> {
+    let client = crate::launcher_mask::blocking_http_client_api()?;
+    let resp = crate::http_config::blocking_send_idempotent_with_retry(|| client.get(rules_url))
+        .map_err(|e| format!("{rules_url}: {e}"))?;
+
+    if !resp.status().is_success() {
+        return Err(format!("{rules_url}: status {}", resp.status()));
+    }
+
+    let bytes = resp
+        .bytes()
+        .map_err(|e| format!("{rules_url}: read body: {e}"))?;
+    serde_json::from_slice(&bytes).map_err(|e| format!("{rules_url}: parse error {e}"))
+}
+
+fn load_cache() -> Option<HideLevelRulesCache> {
+    let path = cache_file_path().ok()?;
+    let contents = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+fn save_cache(cache: &HideLevelRulesCache) -> Result<(), String> {
+    let dir = crate::app_paths::data_dir()?;
+    crate::app_paths::create_dir(&dir, "правила скрытия")?;
+
+    let json = serde_json::to_string_pretty(cache)
+        .map_err(|e| format!("serialize правила скрытия: {e}"))?;
+    fs::write(cache_file_path()?, json).map_err(|e| format!("запись правил скрытия: {e}"))
+}
+
+fn cache_file_path() -> Result<PathBuf, String> {
+    Ok(crate::app_paths::data_dir()?.join(HIDE_LEVEL_RULES_FILE_NAME))
+}
@@ -6,26 +6,97 @@ use reqwest::header::HeaderMap;
 pub enum HttpProfile {
     /// Short-lived JSON/API calls.
     Api,
+    /// Login calls: the user is staring at a spinner, so give up sooner than `Api`.
+    Auth,
     /// Large downloads (ZIPs, manifests, etc.).
     Download,
 }
 
+/// Destinations that never go through a proxy: loopback, private (RFC 1918) and link-local
+/// networks. A corporate proxy cannot reach a dev server on the LAN, so sending those
+/// requests through it only breaks direct connects.
+const LOCAL_NO_PROXY: &str = "localhost,127.0.0.0/8,::1,10.0.0.0/8,172.16.0.0/12,192.168.0.0/16,169.254.0.0/16,fc00::/7,fe80::/10";
+
+/// Adds `LOCAL_NO_PROXY` to the proxy exceptions every HTTP client picks up from the system.
+///
+/// reqwest reads the system proxy (`HTTP(S)_PROXY`, on Windows also Internet Settings) and its
+/// exceptions from `NO_PROXY`; a set `NO_PROXY` replaces the Windows exception list, so that
+/// list is carried over. Must run before any other thread starts.
+pub fn install_local_proxy_bypass() {
+    let existing = std::env::var("NO_PROXY")
+        .or_else(|_| std::env::var("no_proxy"))
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .or_else(windows_proxy_override);
+
+    let no_proxy = match existing {
+        Some(list) => format!("{list},{LOCAL_NO_PROXY}"),
+        None => LOCAL_NO_PROXY.to_string(),
+    };
+    // SAFETY: called first thing in `main`, while the process is still single-threaded.
+    unsafe {
+        std::env::set_var("NO_PROXY", no_proxy);
+    }
+}
+
+/// Proxy exceptions from Windows Internet Settings in `NO_PROXY` form, if a proxy is enabled.
+#[cfg(windows)]
+fn windows_proxy_override() -> Option<String> {
+    use winreg::RegKey;
+    use winreg::enums::HKEY_CURRENT_USER;
+
+    let settings = RegKey::predef(HKEY_CURRENT_USER)
+        .open_subkey(r"Software\Microsoft\Windows\CurrentVersion\Internet Settings")
+        .ok()?;
+    if settings.get_value::<u32, _>("ProxyEnable").unwrap_or(0) == 0 {
+        return None;
+    }
+    let raw: String = settings.get_value("ProxyOverride").ok()?;
+    let list: Vec<String> = raw
+        .split(';')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| s.replace("*.", ""))
+        .collect();
+    (!list.is_empty()).then(|| list.join(","))
+}
+
+#[cfg(not(windows))]
+fn windows_proxy_override() -> Option<String> {
+    None
+}
+
+/// Login timeouts relative to the configured ones: the user is waiting on a spinner, so
+/// give up sooner (6 s connect and 12 s total with the default settings).
+fn auth_share(configured: Duration) -> Duration {
+    configured * 3 / 5
+}
+
 fn connect_timeout(profile: HttpProfile) -> Duration {
+    let configured = Duration::from_secs(crate::settings::network_settings().connect_timeout_secs);
     match profile {
         // Keep connect reasonably small; failures should surface quickly.
-        HttpProfile::Api | HttpProfile::Download => Duration::from_secs(10),
+        HttpProfile::Api | HttpProfile::Download => configured,
+        HttpProfile::Auth => auth_share(configured),
     }
 }
 
 fn request_timeout(profile: HttpProfile) -> Duration {
+    let net = crate::settings::network_settings();
     match profile {
         // For API calls, fail fast.
-        HttpProfile::Api => Duration::from_secs(20),
+        HttpProfile::Api => Duration::from_secs(net.api_timeout_secs),
+        HttpProfile::Auth => auth_share(Duration::from_secs(net.api_timeout_secs)),
         // For large downloads, allow long transfers.
-        HttpProfile::Download => Duration::from_secs(60 * 10),
+        HttpProfile::Download => Duration::from_secs(net.download_timeout_secs),
     }
 }
 
+/// Per-request timeout for the ACZ `OPTIONS` protocol probe (runs on a Download client).
+pub fn options_timeout() -> Duration {
+    Duration::from_secs(crate::settings::network_settings().options_timeout_secs)
+}
+
 pub fn build_async_client(profile: HttpProfile) -> Result<reqwest::Client, String> {
     reqwest::Client::builder()
         .connect_timeout(connect_timeout(profile))
@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Measurements older than this are redone before the next server list fetch.
+const REPROBE_INTERVAL: Duration = Duration::from_secs(10 * 60);
+/// A hub slower than this to answer is treated as unreachable for ordering.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Last measured response time of one hub.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HubLatency {
+    /// `None` when the hub did not answer within `PROBE_TIMEOUT`.
+    pub rtt: Option<Duration>,
+    pub measured_at: Instant,
+}
+
+static LATENCIES: OnceLock<Mutex<HashMap<String, HubLatency>>> = OnceLock::new();
+
+fn latency_map() -> std::sync::MutexGuard<'static, HashMap<String, HubLatency>> {
+    LATENCIES
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+/// Latencies measured so far in this session, keyed by hub URL.
+pub fn latencies() -> HashMap<String, HubLatency> {
+    latency_map().clone()
+}
+
+/// Measures every hub in parallel: the time until `api/servers` answers a HEAD request.
+/// Any HTTP status counts as an answer; only network errors and timeouts do not.
+pub async fn probe_hubs(urls: &[String]) {
+    let Ok(client) = crate::launcher_mask::async_http_client() else {
+        return;
+    };
+
+    let mut jobs = Vec::new();
+    for base in urls {
+        let client = client.clone();
+        let base = base.clone();
+        jobs.push(tokio::spawn(async move {
+            let started = Instant::now();
+            let request = client.head(format!("{base}api/servers")).send();
+            let rtt = match tokio::time::timeout(PROBE_TIMEOUT, request).await {
+                Ok(Ok(_)) => Some(started.elapsed()),
+                _ => None,
+            };
+            (base, rtt)
+        }));
+    }
+
+    for job in jobs {
+        if let Ok((base, rtt)) = job.await {
+            latency_map().insert(
+                base,
+                HubLatency {
+                    rtt,
+                    measured_at: Instant::now(),
+                },
+            );
+        }
+    }
+}
+
+/// Hubs in failover order: answered ones by response time, then unmeasured ones, then
+/// unreachable ones, each group keeping the configured order. Re-probes when any
+/// measurement is missing or older than `REPROBE_INTERVAL`.
+pub async fn ordered_hub_urls(urls: Vec<String>) -> Vec<String> {
+    if urls.len() < 2 {
+        return urls;
+    }
+
+    let stale = {
+        let map = latency_map();
+        urls.iter().any(|u| {
+            map.get(u)
+                .is_none_or(|l| l.measured_at.elapsed() > REPROBE_INTERVAL)
+        })
+    };
+    if stale {
+        probe_hubs(&urls).await;
+    }
+
+    let map = latencies();
+    let mut ordered = urls;
+    ordered.sort_by_key(|u| match map.get(u) {
+        Some(HubLatency { rtt: Some(rtt), .. }) => (0, *rtt),
+        None => (1, Duration::ZERO),
+        Some(_) => (2, Duration::ZERO),
+    });
+    ordered
+}
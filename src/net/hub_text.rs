@@ -0,0 +1,113 @@
+const MAX_NAME_CHARS: usize = 200;
+const MAX_TAG_CHARS: usize = 64;
+const MAX_TAGS: usize = 64;
+const MAX_DESCRIPTION_CHARS: usize = 8000;
+/// Combining marks kept on one base character; real scripts rarely need more.
+const MAX_COMBINING_RUN: usize = 2;
+
+/// Single-line server name; `None` when nothing visible is left.
+pub fn clean_name(name: &str) -> Option<String> {
+    let cleaned = clean(name, false, MAX_NAME_CHARS);
+    (!cleaned.is_empty()).then_some(cleaned)
+}
+
+/// Tags with the same cleanup as names, dropping ones that end up empty.
+pub fn clean_tags(tags: Vec<String>) -> Vec<String> {
+    tags.iter()
+        .map(|t| clean(t, false, MAX_TAG_CHARS))
+        .filter(|t| !t.is_empty())
+        .take(MAX_TAGS)
+        .collect()
+}
+
+/// Description: line breaks are kept, everything else as for names.
+pub fn clean_description(desc: &str) -> Option<String> {
+    let cleaned = clean(desc, true, MAX_DESCRIPTION_CHARS);
+    (!cleaned.is_empty()).then_some(cleaned)
+}
+
+/// Hubs pass server text through as is: drops control, bidi and zero-width characters and
+/// long runs of combining marks, and caps the length.
+fn clean(text: &str, keep_newlines: bool, max_chars: usize) -> String {
+    let mut out = String::with_capacity(text.len().min(max_chars * 4));
+    let mut count = 0;
+    let mut combining_run = 0;
+    let mut pending_space = false;
+
+    for ch in text.chars() {
+        if is_invisible(ch) {
+            continue;
+        }
+        if ch == '\n' && keep_newlines {
+            // Trailing spaces of the line are dropped along with the pending one.
+            pending_space = false;
+            out.push('\n');
+            count += 1;
+            combining_run = 0;
+        } else if ch.is_whitespace() || ch.is_control() {
+            pending_space = !out.is_empty() && !out.ends_with('\n');
+            continue;
+        } else if is_combining_mark(ch) {
+            combining_run += 1;
+            if combining_run > MAX_COMBINING_RUN || out.is_empty() {
+                continue;
+            }
+            out.push(ch);
+            count += 1;
+        } else {
+            if pending_space {
+                out.push(' ');
+                count += 1;
+                pending_space = false;
+            }
+            out.push(ch);
+            count += 1;
+            combining_run = 0;
+        }
+
+        if count >= max_chars {
+            out.push('…');
+            break;
+        }
+    }
+
+    // At most one empty line in a row, none at the ends.
+    if keep_newlines {
+        while out.contains("\n\n\n") {
+            out = out.replace("\n\n\n", "\n\n");
+        }
+    }
+    out.trim_matches('\n').to_string()
+}
+
+/// Bidi controls, zero-width characters and fillers that render as nothing.
+fn is_invisible(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{00AD}'
+            | '\u{034F}'
+            | '\u{061C}'
+            | '\u{115F}'
+            | '\u{1160}'
+            | '\u{180E}'
+            | '\u{200B}'..='\u{200F}'
+            | '\u{202A}'..='\u{202E}'
+            | '\u{2060}'..='\u{2064}'
+            | '\u{2066}'..='\u{2069}'
+            | '\u{3164}'
+            | '\u{FEFF}'
+            | '\u{FFA0}'
+    )
+}
+
+fn is_combining_mark(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{0300}'..='\u{036F}'
+            | '\u{0483}'..='\u{0489}'
+            | '\u{1AB0}'..='\u{1AFF}'
+            | '\u{1DC0}'..='\u{1DFF}'
+            | '\u{20D0}'..='\u{20FF}'
+            | '\u{FE20}'..='\u{FE2F}'
+    )
+}
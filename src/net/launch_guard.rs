@@ -0,0 +1,207 @@
+use std::process::{Child, Command};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::SystemTime;
+
+/// What a game session was launched with, for matching bug reports to builds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LaunchRecord {
+    pub address: String,
+    /// Resolved engine version, which may differ from the one the server asked for.
+    pub engine_version: String,
+    pub fork_id: String,
+    pub fork_version: String,
+    /// Manifest hash, or the zip hash for servers without a manifest.
+    pub content_hash: Option<String>,
+    pub started_at: SystemTime,
+}
+
+/// A launched game whose loader process is still alive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunningSession {
+    pub pid: u32,
+    pub record: LaunchRecord,
+}
+
+/// The loader process of the latest launch, if any.
+struct ActiveLaunch {
+    pid: u32,
+    record: LaunchRecord,
+    stop: Arc<AtomicBool>,
+    /// Kept after a successful launch: the open handle stops the pid from being reused, so a
+    /// later force stop can't hit an unrelated process.
+    child: Option<Child>,
+    token_sender: Option<PipeSender>,
+}
+
+impl Drop for ActiveLaunch {
+    fn drop(&mut self) {
+        if let Some(sender) = self.token_sender.take() {
+            sender.finish();
+        }
+        // Reap the loader once it exits instead of leaving a zombie behind.
+        if let Some(mut child) = self.child.take()
+            && matches!(child.try_wait(), Ok(None))
+        {
+            std::thread::spawn(move || {
+                let _ = child.wait();
+            });
+        }
+    }
+}
+
+/// A pipe sender thread that outlives `launch_client`: the loader may read the pipe any time
+/// during its startup. Stopped and joined when the launch is replaced, force stopped or exits.
+pub struct PipeSender {
+    pub stop: Arc<AtomicBool>,
+    pub thread: JoinHandle<Result<(), String>>,
+}
+
+impl PipeSender {
+    fn finish(self) {
+        self.stop.store(true, Ordering::Relaxed);
+        let _ = self.thread.join();
+    }
+}
+
+static ACTIVE_LAUNCH: Mutex<Option<ActiveLaunch>> = Mutex::new(None);
+
+/// Held by `launch_client` from spawn until the launch is confirmed or abandoned.
+pub struct LaunchGuard {
+    stop: Arc<AtomicBool>,
+    handed_off: bool,
+}
+
+impl LaunchGuard {
+    pub fn stop_requested(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    /// The launch went through: keep the process registered so the game can still be force
+    /// stopped from the connect modal if its window never shows up.
+    pub fn hand_off(mut self, child: Child, token_sender: Option<PipeSender>) {
+        let mut guard = ACTIVE_LAUNCH.lock().unwrap_or_else(|e| e.into_inner());
+        match guard.as_mut() {
+            Some(active) if Arc::ptr_eq(&active.stop, &self.stop) => {
+                active.child = Some(child);
+                active.token_sender = token_sender;
+            }
+            _ => {
+                if let Some(sender) = token_sender {
+                    sender.finish();
+                }
+            }
+        }
+        self.handed_off = true;
+    }
+}
+
+impl Drop for LaunchGuard {
+    fn drop(&mut self) {
+        if self.handed_off {
+            return;
+        }
+        let mut guard = ACTIVE_LAUNCH.lock().unwrap_or_else(|e| e.into_inner());
+        if guard
+            .as_ref()
+            .is_some_and(|a| Arc::ptr_eq(&a.stop, &self.stop))
+        {
+            *guard = None;
+        }
+    }
+}
+
+pub fn register(pid: u32, record: LaunchRecord) -> LaunchGuard {
+    let stop = Arc::new(AtomicBool::new(false));
+    *ACTIVE_LAUNCH.lock().unwrap_or_else(|e| e.into_inner()) = Some(ActiveLaunch {
+        pid,
+        record,
+        stop: stop.clone(),
+        child: None,
+        token_sender: None,
+    });
+    LaunchGuard {
+        stop,
+        handed_off: false,
+    }
+}
+
+/// The launched game, once its launch went through and while the loader is running. Only the
+/// latest launch is tracked; an earlier game started from this launcher is not listed.
+pub fn running_session() -> Option<RunningSession> {
+    let mut guard = ACTIVE_LAUNCH.lock().unwrap_or_else(|e| e.into_inner());
+    let active = guard.as_mut()?;
+    let child = active.child.as_mut()?;
+    if !matches!(child.try_wait(), Ok(None)) {
+        // Exited and reaped; nothing left to stop.
+        *guard = None;
+        return None;
+    }
+    Some(RunningSession {
+        pid: active.pid,
+        record: active.record.clone(),
+    })
+}
+
+/// Kills the stuck loader together with anything it started and drops the redial pipe server,
+/// so the next connect starts from a clean state. Blocking.
+pub fn force_stop() -> Result<String, String> {
+    let active = ACTIVE_LAUNCH
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take();
+    crate::net::redial_pipe::reset_global_redial_pipe();
+
+    let Some(mut active) = active else {
+        return Ok("процесс игры уже завершён".to_string());
+    };
+    active.stop.store(true, Ordering::Relaxed);
+    if let Some(child) = active.child.as_mut()
+        && !matches!(child.try_wait(), Ok(None))
+    {
+        return Ok("процесс игры уже завершён".to_string());
+    }
+    kill_process_tree(active.pid)?;
+    Ok(format!(
+        "процесс SS14.Loader (pid {}) остановлен",
+        active.pid
+    ))
+}
+
+#[cfg(target_os = "windows")]
+fn kill_process_tree(pid: u32) -> Result<(), String> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let out = Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/T", "/F"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| format!("не удалось запустить taskkill: {e}"))?;
+    // 128: the process is already gone.
+    if out.status.success() || out.status.code() == Some(128) {
+        return Ok(());
+    }
+    Err(format!(
+        "taskkill: {}",
+        String::from_utf8_lossy(&out.stderr).trim()
+    ))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn kill_process_tree(pid: u32) -> Result<(), String> {
+    // Children first, then the loader itself.
+    let _ = Command::new("pkill")
+        .args(["-KILL", "-P", &pid.to_string()])
+        .status();
+    let status = Command::new("kill")
+        .args(["-KILL", &pid.to_string()])
+        .status()
+        .map_err(|e| format!("не удалось запустить kill: {e}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("kill {pid} завершился с ошибкой"))
+    }
+}
@@ -1,7 +1,15 @@
 pub mod auth;
 pub mod connect;
 pub mod connect_progress;
+pub mod content_updates;
+pub mod diagnostics;
+pub mod geoip;
+pub mod hide_level_rules;
+pub mod hub_latency;
 pub mod http_config;
+pub mod hub_text;
+pub mod launch_guard;
 pub mod news;
+pub mod prefetch_queue;
 pub mod redial_pipe;
 pub mod servers;
@@ -1,8 +1,11 @@
+use std::cmp::Reverse;
+
 use chrono::{DateTime, Utc};
 use reqwest::Client;
+use reqwest::header::{ACCEPT, HeaderMap, HeaderValue, USER_AGENT};
 use serde::Deserialize;
 
-use crate::constants::NEWS_API_BASE_URL;
+use crate::constants::{APP_TITLE, APP_VERSION_LABEL, LAUNCHER_RELEASES_API_URL, NEWS_API_BASE_URL};
 use crate::http_config::{self, HttpProfile};
 
 #[derive(Debug, Clone, Deserialize)]
@@ -69,3 +72,87 @@ pub async fn fetch_news(limit: usize) -> Result<Vec<NewsPost>, String> {
     parsed.posts.sort_by(|a, b| b.created_at.cmp(&a.created_at));
     Ok(parsed.posts)
 }
+
+/// One GitHub release of the launcher; `body` is its changelog (Markdown).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct LauncherRelease {
+    pub tag_name: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub body: Option<String>,
+    pub html_url: String,
+    #[serde(default)]
+    pub published_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub prerelease: bool,
+    #[serde(default)]
+    pub draft: bool,
+}
+
+impl LauncherRelease {
+    pub fn title(&self) -> &str {
+        self.name
+            .as_deref()
+            .map(str::trim)
+            .filter(|n| !n.is_empty())
+            .unwrap_or(&self.tag_name)
+    }
+
+    /// Whether this release is newer than the running launcher.
+    pub fn is_newer_than_current(&self) -> bool {
+        match (parse_version(&self.tag_name), parse_version(APP_VERSION_LABEL)) {
+            (Some(release), Some(current)) => release > current,
+            _ => false,
+        }
+    }
+}
+
+/// `v1.2.3-release` → `[1, 2, 3]`; anything after the numeric part is ignored.
+fn parse_version(label: &str) -> Option<Vec<u64>> {
+    let numeric: String = label
+        .trim()
+        .trim_start_matches(['v', 'V'])
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    let parts: Option<Vec<u64>> = numeric
+        .split('.')
+        .filter(|p| !p.is_empty())
+        .map(|p| p.parse().ok())
+        .collect();
+    parts.filter(|p| !p.is_empty())
+}
+
+/// Published launcher releases, newest first. Drafts are skipped.
+pub async fn fetch_launcher_releases(limit: usize) -> Result<Vec<LauncherRelease>, String> {
+    let limit = limit.clamp(1, 100);
+
+    // GitHub rejects requests without a User-Agent.
+    let mut headers = HeaderMap::new();
+    let ua = format!("{}/{APP_VERSION_LABEL}", APP_TITLE.replace(' ', "-"));
+    headers.insert(
+        USER_AGENT,
+        HeaderValue::from_str(&ua).map_err(|e| format!("releases user-agent: {e}"))?,
+    );
+    headers.insert(ACCEPT, HeaderValue::from_static("application/vnd.github+json"));
+    let client = http_config::build_async_client_with_headers(headers, HttpProfile::Api)?;
+
+    let url = format!("{LAUNCHER_RELEASES_API_URL}?per_page={limit}");
+    let resp = http_config::async_send_idempotent_with_retry(|| client.get(&url))
+        .await
+        .map_err(|e| format!("{url}: {e}"))?;
+
+    if !resp.status().is_success() {
+        return Err(format!("{url}: status {}", resp.status()));
+    }
+
+    let mut releases: Vec<LauncherRelease> = resp
+        .json()
+        .await
+        .map_err(|e| format!("{url}: parse error {e}"))?;
+
+    releases.retain(|r| !r.draft);
+    releases.sort_by_key(|r| Reverse(r.published_at));
+    Ok(releases)
+}
@@ -0,0 +1,156 @@
+use std::sync::Mutex;
+
+use crate::cancel_flag::CancelFlag;
+use crate::tasks::{self, TaskKind};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueueStatus {
+    Queued,
+    Running,
+    Done,
+    Failed(String),
+    Cancelled,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueueItem {
+    pub id: u64,
+    pub address: String,
+    pub name: String,
+    pub status: QueueStatus,
+}
+
+struct Queue {
+    next_id: u64,
+    items: Vec<QueueItem>,
+    /// Cancel flag of the item being prepared right now.
+    running: Option<(u64, CancelFlag)>,
+    worker_alive: bool,
+}
+
+/// Servers queued for download without launching, worked off one at a time by a background
+/// thread so big downloads do not compete for the connection and the disk.
+static QUEUE: Mutex<Queue> = Mutex::new(Queue {
+    next_id: 0,
+    items: Vec::new(),
+    running: None,
+    worker_alive: false,
+});
+
+fn with_queue<T>(f: impl FnOnce(&mut Queue) -> T) -> T {
+    f(&mut QUEUE.lock().unwrap_or_else(|e| e.into_inner()))
+}
+
+/// Adds servers to the end of the queue; ones already queued or running are skipped.
+/// Returns how many were added.
+pub fn enqueue(servers: impl IntoIterator<Item = (String, String)>) -> usize {
+    let (added, start_worker) = with_queue(|q| {
+        let mut added = 0;
+        for (address, name) in servers {
+            let pending = q.items.iter().any(|i| {
+                i.address == address
+                    && matches!(i.status, QueueStatus::Queued | QueueStatus::Running)
+            });
+            if pending {
+                continue;
+            }
+            q.next_id += 1;
+            q.items.push(QueueItem {
+                id: q.next_id,
+                address,
+                name,
+                status: QueueStatus::Queued,
+            });
+            added += 1;
+        }
+        let start_worker = added > 0 && !q.worker_alive;
+        if start_worker {
+            q.worker_alive = true;
+        }
+        (added, start_worker)
+    });
+    if start_worker {
+        std::thread::spawn(run_worker);
+    }
+    added
+}
+
+/// Cancels one item: a queued one is skipped, a running one is stopped.
+pub fn cancel(id: u64) {
+    with_queue(|q| {
+        if let Some((running_id, flag)) = &q.running
+            && *running_id == id
+        {
+            flag.cancel();
+            return;
+        }
+        if let Some(item) = q
+            .items
+            .iter_mut()
+            .find(|i| i.id == id && i.status == QueueStatus::Queued)
+        {
+            item.status = QueueStatus::Cancelled;
+        }
+    });
+}
+
+/// Cancels everything queued or running; used when the launcher closes.
+pub fn cancel_all() {
+    with_queue(|q| {
+        for item in q
+            .items
+            .iter_mut()
+            .filter(|i| i.status == QueueStatus::Queued)
+        {
+            item.status = QueueStatus::Cancelled;
+        }
+        if let Some((_, flag)) = &q.running {
+            flag.cancel();
+        }
+    });
+}
+
+/// Drops finished, failed and cancelled items from the list.
+pub fn clear_finished() {
+    with_queue(|q| {
+        q.items
+            .retain(|i| matches!(i.status, QueueStatus::Queued | QueueStatus::Running))
+    });
+}
+
+pub fn snapshot() -> Vec<QueueItem> {
+    with_queue(|q| q.items.clone())
+}
+
+fn run_worker() {
+    loop {
+        let next = with_queue(|q| {
+            let Some(item) = q.items.iter_mut().find(|i| i.status == QueueStatus::Queued) else {
+                q.worker_alive = false;
+                return None;
+            };
+            item.status = QueueStatus::Running;
+            Some((item.id, item.address.clone(), item.name.clone()))
+        });
+        let Some((id, address, name)) = next else {
+            return;
+        };
+
+        let task = tasks::register(TaskKind::Prefetch, format!("Подготовка: {name}"), true);
+        with_queue(|q| q.running = Some((id, task.cancel_flag().clone())));
+        let result = crate::content_updates::prepare_server(&address, &task);
+        let cancelled = task.cancel_flag().is_cancelled();
+        task.finish(&result);
+
+        with_queue(|q| {
+            q.running = None;
+            if let Some(item) = q.items.iter_mut().find(|i| i.id == id) {
+                item.status = match result {
+                    Ok(()) => QueueStatus::Done,
+                    Err(_) if cancelled => QueueStatus::Cancelled,
+                    Err(e) => QueueStatus::Failed(e),
+                };
+            }
+        });
+    }
+}
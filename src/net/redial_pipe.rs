@@ -22,13 +22,35 @@ use std::iter;
 use std::os::windows::ffi::OsStrExt;
 
 const REDIAL_PIPE_PREFIX: &str = "SGLOADER_REDIAL_";
+/// Pause after a failed accept so a broken pipe does not spin the thread.
+#[cfg(target_os = "windows")]
+const ACCEPT_RETRY_DELAY: std::time::Duration = std::time::Duration::from_secs(1);
 
 pub struct RedialPipeServer {
     pub pipe_name: String,
     stop: Arc<AtomicBool>,
+    stats: Arc<Mutex<RedialStats>>,
     thread: Option<std::thread::JoinHandle<()>>,
 }
 
+#[derive(Debug, Clone, Default, PartialEq)]
+struct RedialStats {
+    redials: u32,
+    last_error: Option<String>,
+}
+
+/// Snapshot of the global redial server for the settings panel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RedialPipeStatus {
+    /// `None` when no server was started yet (it starts with the first launch).
+    pub pipe_name: Option<String>,
+    /// The accept loop thread is alive.
+    pub running: bool,
+    /// Redial requests handled since the server started.
+    pub redials: u32,
+    pub last_error: Option<String>,
+}
+
 static GLOBAL_SERVER: OnceLock<Mutex<Option<RedialPipeServer>>> = OnceLock::new();
 
 pub fn ensure_global_redial_pipe(
@@ -54,6 +76,45 @@ pub fn ensure_global_redial_pipe(
     Ok(Some(name))
 }
 
+pub fn global_redial_status() -> RedialPipeStatus {
+    let server = GLOBAL_SERVER.get().map(|m| m.lock().unwrap_or_else(|e| e.into_inner()));
+    match server.as_deref() {
+        Some(Some(srv)) => {
+            let stats = srv.stats.lock().unwrap_or_else(|e| e.into_inner()).clone();
+            RedialPipeStatus {
+                pipe_name: Some(srv.pipe_name.clone()),
+                running: srv.thread.as_ref().is_some_and(|t| !t.is_finished()),
+                redials: stats.redials,
+                last_error: stats.last_error,
+            }
+        }
+        _ => RedialPipeStatus {
+            pipe_name: None,
+            running: false,
+            redials: 0,
+            last_error: None,
+        },
+    }
+}
+
+/// Replaces the global redial server with a fresh one (new pipe name). Games that are
+/// already running keep the old name, so only later launches use the new server. With redial
+/// disabled in settings the server is only stopped.
+pub fn restart_global_redial_pipe(disable_redial: bool) -> Result<Option<String>, String> {
+    let exe = std::env::current_exe().map_err(|e| format!("путь к лаунчеру: {e}"))?;
+    reset_global_redial_pipe();
+    ensure_global_redial_pipe(disable_redial, &exe)
+}
+
+/// Stops the global redial server; the next launch starts a fresh one.
+pub fn reset_global_redial_pipe() {
+    let Some(m) = GLOBAL_SERVER.get() else {
+        return;
+    };
+    let server = m.lock().ok().and_then(|mut g| g.take());
+    drop(server);
+}
+
 impl RedialPipeServer {
     pub fn start_if_enabled(disable_redial: bool, launcher_path: &Path) -> Result<Option<Self>, String> {
         if disable_redial {
@@ -64,15 +125,18 @@ impl RedialPipeServer {
         let pipe_name = format!("{REDIAL_PIPE_PREFIX}{}", uuid::Uuid::new_v4());
         let stop = Arc::new(AtomicBool::new(false));
         let stop_thread = stop.clone();
+        let stats = Arc::new(Mutex::new(RedialStats::default()));
+        let stats_thread = stats.clone();
         let pipe_name_thread = pipe_name.clone();
 
         let thread = std::thread::spawn(move || {
-            run_server_loop(&pipe_name_thread, &launcher_path, stop_thread);
+            run_server_loop(&pipe_name_thread, &launcher_path, stop_thread, stats_thread);
         });
 
         Ok(Some(Self {
             pipe_name,
             stop,
+            stats,
             thread: Some(thread),
         }))
     }
@@ -96,12 +160,33 @@ impl Drop for RedialPipeServer {
     }
 }
 
-fn run_server_loop(pipe_name: &str, launcher_path: &PathBuf, stop: Arc<AtomicBool>) {
+fn run_server_loop(
+    pipe_name: &str,
+    launcher_path: &PathBuf,
+    stop: Arc<AtomicBool>,
+    stats: Arc<Mutex<RedialStats>>,
+) {
     while !stop.load(Ordering::Relaxed) {
         #[cfg(target_os = "windows")]
         {
-            if let Ok(Some((reason, connect))) = accept_one(pipe_name) {
-                let _ = spawn_launcher_redial(launcher_path, &reason, &connect);
+            let result = accept_one(pipe_name).and_then(|request| match request {
+                Some((reason, connect)) => {
+                    spawn_launcher_redial(launcher_path, &reason, &connect).map(|()| true)
+                }
+                None => Ok(false),
+            });
+            let mut stats_guard = stats.lock().unwrap_or_else(|e| e.into_inner());
+            match result {
+                Ok(true) => {
+                    stats_guard.redials += 1;
+                    stats_guard.last_error = None;
+                }
+                Ok(false) => {}
+                Err(e) => {
+                    stats_guard.last_error = Some(e);
+                    drop(stats_guard);
+                    std::thread::sleep(ACCEPT_RETRY_DELAY);
+                }
             }
         }
 
@@ -110,6 +195,8 @@ fn run_server_loop(pipe_name: &str, launcher_path: &PathBuf, stop: Arc<AtomicBoo
             let _ = pipe_name;
             let _ = launcher_path;
             let _ = &stop;
+            stats.lock().unwrap_or_else(|e| e.into_inner()).last_error =
+                Some("redial поддерживается только на Windows".to_string());
             return;
         }
     }
@@ -1,8 +1,14 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::{Mutex, OnceLock};
+
 use reqwest::{Client, StatusCode};
 use serde::Deserialize;
 
+use crate::net::hub_text;
 use crate::storage::hub_urls;
-use crate::{ss14_server_info::ServerInfo, ss14_uri};
+use crate::ss14_server_info::{AuthMode, ServerInfo, ServerPrivacyFlags};
+use crate::ss14_uri;
 
 #[derive(Clone, Debug)]
 pub struct ServerEntry {
@@ -12,34 +18,183 @@ pub struct ServerEntry {
     pub max_players: u32,
     pub tags: Vec<String>,
     pub region: Option<String>,
+    /// `region` was guessed from GeoIP rather than a `region:` tag.
+    pub region_inferred: bool,
     pub ping_ms: Option<u32>,
     pub online: bool,
     pub description: Option<String>,
+    /// Game mode from a `mode:`/`gamemode:` tag, as written by the server.
+    pub game_mode: Option<String>,
+    /// Tagged `vanilla`: upstream content without forks' additions.
+    pub vanilla: bool,
+    /// Tagged `18+` (or `nsfw`).
+    pub adult: bool,
+    /// Auth mode from `/info`; unknown until fetched.
+    pub auth_mode: Option<AuthMode>,
+    /// Privacy policy link from `/info`.
+    pub privacy_policy: Option<String>,
+    /// What the server announces about HWID and telemetry in `/info`.
+    pub privacy_flags: ServerPrivacyFlags,
+    /// Base URL of the hub that listed the server.
+    pub hub: String,
+}
+
+impl ServerEntry {
+    /// Tags covered by the structured fields above, shown separately in server cards.
+    pub fn is_structured_tag(tag: &str) -> bool {
+        let lower = tag.to_ascii_lowercase();
+        lower.starts_with("mode:")
+            || lower.starts_with("gamemode:")
+            || matches!(lower.as_str(), "vanilla" | "18+" | "nsfw")
+    }
 }
 
-pub async fn fetch_server_list() -> Result<Vec<ServerEntry>, String> {
-    let hub_urls = hub_urls::load_hub_urls();
+/// Host part of a hub URL, short enough for a label.
+pub fn hub_label(hub: &str) -> String {
+    url::Url::parse(hub)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string))
+        .unwrap_or_else(|| hub.trim_end_matches('/').to_string())
+}
 
-    let client = crate::launcher_mask::async_http_client()?;
-    let mut errors: Vec<String> = Vec::new();
+/// Result of a hub fetch: the parsed servers plus entries that had to be dropped.
+#[derive(Clone, Debug, Default)]
+pub struct ServerListFetch {
+    pub servers: Vec<ServerEntry>,
+    /// Hub entries whose shape we could not parse.
+    pub skipped: usize,
+    /// Parse error of the first skipped entry, for diagnostics.
+    pub first_skip_reason: Option<String>,
+}
+
+impl ServerListFetch {
+    pub fn skipped_summary(&self) -> Option<String> {
+        if self.skipped == 0 {
+            return None;
+        }
+        let mut line = format!("хаб: пропущено записей: {}", self.skipped);
+        if let Some(reason) = self.first_skip_reason.as_deref() {
+            line.push_str(&format!(" (первая ошибка: {reason})"));
+        }
+        Some(line)
+    }
+}
+
+/// Why one hub did not deliver a list.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HubError {
+    /// Hub base URL as configured; what a retry is pointed at.
+    pub hub: String,
+    /// Request that failed; differs from `hub` for later pages.
+    pub url: String,
+    pub status: Option<u16>,
+    /// Start of the response body, when there was one.
+    pub snippet: Option<String>,
+    pub message: String,
+}
+
+impl HubError {
+    fn new(url: &str, message: impl Into<String>) -> Self {
+        Self {
+            hub: String::new(),
+            url: url.to_string(),
+            status: None,
+            snippet: None,
+            message: message.into(),
+        }
+    }
+}
+
+/// `"<url>: <message> (status <code>)"` plus the body on its own line, the shape
+/// `ErrorBox` reads the URL and status from.
+impl fmt::Display for HubError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.url, self.message)?;
+        if let Some(status) = self.status {
+            write!(f, " (status {status})")?;
+        }
+        if let Some(snippet) = self.snippet.as_deref().filter(|s| !s.is_empty()) {
+            write!(f, "\nbody: {snippet}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Every hub failed; one entry per hub in the order they were tried.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HubListError {
+    pub hubs: Vec<HubError>,
+    /// Failure before any hub was asked (no hubs enabled, client setup).
+    pub general: Option<String>,
+}
+
+impl HubListError {
+    fn general(message: impl Into<String>) -> Self {
+        Self {
+            hubs: Vec::new(),
+            general: Some(message.into()),
+        }
+    }
+}
+
+/// Max body characters kept in `HubError::snippet`.
+const HUB_ERROR_SNIPPET_CHARS: usize = 160;
+
+/// Fetches the list from the first hub that answers. `on_page` is called with the
+/// list accumulated so far whenever a paginated hub delivers another page.
+pub async fn fetch_server_list(
+    mut on_page: impl FnMut(&[ServerEntry]),
+) -> Result<ServerListFetch, HubListError> {
+    let hub_urls = crate::hub_latency::ordered_hub_urls(hub_urls::load_enabled_hub_urls()).await;
+    if hub_urls.is_empty() {
+        return Err(HubListError::general(
+            "нет включённых хабов — добавьте или включите хаб в настройках",
+        ));
+    }
+
+    let client = crate::launcher_mask::async_http_client().map_err(HubListError::general)?;
+    let mut errors = HubListError::default();
 
     for base in hub_urls.iter() {
-        match fetch_from_hub(&client, base.as_str()).await {
-            Ok(entries) => {
-                let mapped = entries
-                    .into_iter()
-                    .map(HubServerListEntry::into_server_entry)
-                    .collect();
-                return Ok(mapped);
-            }
-            Err(err) => errors.push(err),
+        let mut fetch = ServerListFetch::default();
+        match fetch_from_hub(&client, base.as_str(), &mut fetch, &mut on_page).await {
+            Ok(()) => return Ok(fetch),
+            Err(err) => errors.hubs.push(HubError {
+                hub: base.clone(),
+                ..err
+            }),
         }
     }
 
-    Err(errors.join("\n"))
+    Err(errors)
+}
+
+/// Fetches the list from one hub only; the per-hub retry of a failed load.
+pub async fn fetch_server_list_from(
+    base: &str,
+    mut on_page: impl FnMut(&[ServerEntry]),
+) -> Result<ServerListFetch, HubError> {
+    let client = crate::launcher_mask::async_http_client().map_err(|e| HubError::new(base, e))?;
+    let mut fetch = ServerListFetch::default();
+    fetch_from_hub(&client, base, &mut fetch, &mut on_page)
+        .await
+        .map_err(|err| HubError {
+            hub: base.to_string(),
+            ..err
+        })?;
+    Ok(fetch)
+}
+
+/// What the server list shows from a server's `/info`.
+#[derive(Clone, Debug)]
+pub struct ServerInfoSummary {
+    pub description: Option<String>,
+    pub auth_mode: AuthMode,
+    pub privacy_policy: Option<String>,
+    pub privacy_flags: ServerPrivacyFlags,
 }
 
-pub async fn fetch_server_description(address: &str) -> Result<Option<String>, String> {
+pub async fn fetch_server_summary(address: &str) -> Result<ServerInfoSummary, String> {
     let ss14 = ss14_uri::parse_ss14_uri(address)?;
     let info_url = ss14_uri::server_info_url(&ss14)?;
 
@@ -71,45 +226,242 @@ pub async fn fetch_server_description(address: &str) -> Result<Option<String>, S
         format!("{}: parse error {e} body: {trimmed}", info_url.as_str())
     })?;
 
-    Ok(info
-        .desc
-        .and_then(|d| {
-            let trimmed = d.trim();
-            (!trimmed.is_empty()).then(|| trimmed.to_string())
-        }))
+    let description = info.desc.as_deref().and_then(hub_text::clean_description);
+    if let Some(build) = &info.build_information
+        && !build.engine_version.trim().is_empty()
+    {
+        remember_engine_version(address, &build.engine_version);
+    }
+    Ok(ServerInfoSummary {
+        description,
+        auth_mode: info.auth_information.mode,
+        privacy_policy: info
+            .privacy_policy
+            .and_then(|p| crate::open_url::web_url(&p.link)),
+        privacy_flags: info.privacy_flags.unwrap_or_default(),
+    })
+}
+
+/// Engine version each server's `/info` announced this session, by canonical address.
+static ENGINE_VERSIONS: OnceLock<Mutex<HashMap<String, String>>> = OnceLock::new();
+
+fn engine_versions() -> std::sync::MutexGuard<'static, HashMap<String, String>> {
+    ENGINE_VERSIONS
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
 }
 
-async fn fetch_from_hub(client: &Client, base: &str) -> Result<Vec<HubServerListEntry>, String> {
-    let url = format!("{base}api/servers");
-    let response = crate::http_config::async_send_idempotent_with_retry(|| client.get(&url))
+fn remember_engine_version(address: &str, version: &str) {
+    engine_versions().insert(
+        crate::favorites::canonicalize_favorite_address(address),
+        version.to_string(),
+    );
+}
+
+/// Engine version `address` announced in the last `/info` fetched this session.
+pub fn announced_engine_version(address: &str) -> Option<String> {
+    engine_versions()
+        .get(&crate::favorites::canonicalize_favorite_address(address))
+        .cloned()
+}
+
+const MAX_HUB_PAGES: usize = 500;
+
+/// Last full list per hub plus the token a delta-capable hub gave us for the next refresh.
+struct HubSnapshot {
+    sync_token: String,
+    entries: Vec<ServerEntry>,
+}
+
+static HUB_SNAPSHOTS: OnceLock<Mutex<HashMap<String, HubSnapshot>>> = OnceLock::new();
+
+fn hub_snapshots() -> std::sync::MutexGuard<'static, HashMap<String, HubSnapshot>> {
+    HUB_SNAPSHOTS
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+async fn fetch_from_hub(
+    client: &Client,
+    base: &str,
+    fetch: &mut ServerListFetch,
+    on_page: &mut impl FnMut(&[ServerEntry]),
+) -> Result<(), HubError> {
+    let previous = hub_snapshots().remove(base);
+
+    // Delta refresh: only hubs that handed out a sync token ever see `?since=`.
+    if let Some(prev) = previous {
+        let url = format!(
+            "{base}api/servers?since={}",
+            url::form_urlencoded::byte_serialize(prev.sync_token.as_bytes()).collect::<String>()
+        );
+        match fetch_hub_page(client, &url).await {
+            Ok(HubServerListResponse::Page(page)) if page.delta => {
+                let mut entries = prev.entries;
+                let removed: HashSet<String> = page.removed.into_iter().collect();
+                entries.retain(|e| !removed.contains(&e.address));
+                for changed in fetch.parse_entries(base, page.servers) {
+                    match entries.iter_mut().find(|e| e.address == changed.address) {
+                        Some(existing) => *existing = changed,
+                        None => entries.push(changed),
+                    }
+                }
+                remember_snapshot(base, page.sync_token, &entries);
+                fetch.servers = entries;
+                return Ok(());
+            }
+            // Anything else (flat list, full page set, error): fall through to a full fetch.
+            _ => {}
+        }
+    }
+
+    let first_url = format!("{base}api/servers");
+    let page = match fetch_hub_page(client, &first_url).await? {
+        HubServerListResponse::Flat(list) => {
+            fetch.servers = fetch.parse_entries(base, list);
+            return Ok(());
+        }
+        HubServerListResponse::Page(page) => page,
+    };
+
+    let mut entries: Vec<ServerEntry> = Vec::new();
+    let mut sync_token = page.sync_token.clone();
+    let mut next = page.next.clone();
+    entries.extend(fetch.parse_entries(base, page.servers));
+    on_page(&entries);
+
+    let mut pages = 1;
+    while let Some(cursor) = next.take().filter(|c| !c.trim().is_empty()) {
+        if pages >= MAX_HUB_PAGES {
+            break;
+        }
+        pages += 1;
+
+        let url = if cursor.starts_with("http://") || cursor.starts_with("https://") {
+            cursor
+        } else {
+            format!(
+                "{base}api/servers?cursor={}",
+                url::form_urlencoded::byte_serialize(cursor.as_bytes()).collect::<String>()
+            )
+        };
+
+        match fetch_hub_page(client, &url).await? {
+            HubServerListResponse::Flat(list) => {
+                entries.extend(fetch.parse_entries(base, list));
+            }
+            HubServerListResponse::Page(page) => {
+                entries.extend(fetch.parse_entries(base, page.servers));
+                next = page.next;
+                if page.sync_token.is_some() {
+                    sync_token = page.sync_token;
+                }
+            }
+        }
+        on_page(&entries);
+    }
+
+    remember_snapshot(base, sync_token, &entries);
+    fetch.servers = entries;
+    Ok(())
+}
+
+impl ServerListFetch {
+    /// Parses entries one by one so a single odd entry doesn't sink the whole hub.
+    fn parse_entries(&mut self, base: &str, raw: Vec<serde_json::Value>) -> Vec<ServerEntry> {
+        let mut out = Vec::with_capacity(raw.len());
+        for value in raw {
+            match serde_json::from_value::<HubServerListEntry>(value) {
+                Ok(entry) => out.push(entry.into_server_entry(base)),
+                Err(e) => {
+                    self.skipped += 1;
+                    if self.first_skip_reason.is_none() {
+                        self.first_skip_reason = Some(e.to_string());
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+fn remember_snapshot(base: &str, sync_token: Option<String>, entries: &[ServerEntry]) {
+    let Some(sync_token) = sync_token.filter(|t| !t.trim().is_empty()) else {
+        return;
+    };
+    hub_snapshots().insert(
+        base.to_string(),
+        HubSnapshot {
+            sync_token,
+            entries: entries.to_vec(),
+        },
+    );
+}
+
+async fn fetch_hub_page(client: &Client, url: &str) -> Result<HubServerListResponse, HubError> {
+    let response = crate::http_config::async_send_idempotent_with_retry(|| client.get(url))
         .await
-        .map_err(|e| format!("{url}: {e}"))?;
+        .map_err(|e| HubError::new(url, e.to_string()))?;
     let status = response.status();
 
     if status == StatusCode::NOT_FOUND {
-        return Err(format!("{url}: 404"));
+        return Err(HubError {
+            status: Some(status.as_u16()),
+            ..HubError::new(url, "список серверов не найден (404)")
+        });
     }
 
     if !status.is_success() {
-        let snippet = response
-            .text()
-            .await
-            .unwrap_or_else(|_| "<no body>".to_string());
-        let trimmed = snippet.chars().take(160).collect::<String>();
-        return Err(format!("{url}: status {} body: {}", status, trimmed));
+        let snippet = response.text().await.ok().map(|body| snippet_of(&body));
+        return Err(HubError {
+            status: Some(status.as_u16()),
+            snippet,
+            ..HubError::new(url, "хаб ответил ошибкой")
+        });
     }
 
     let bytes = response
         .bytes()
         .await
-        .map_err(|e| format!("{url}: read body: {e}"))?;
-    serde_json::from_slice::<Vec<HubServerListEntry>>(&bytes).map_err(|e| {
-        let snippet = String::from_utf8_lossy(&bytes);
-        let trimmed = snippet.chars().take(160).collect::<String>();
-        format!("{url}: parse error {e} body: {trimmed}")
+        .map_err(|e| HubError::new(url, format!("read body: {e}")))?;
+    serde_json::from_slice::<HubServerListResponse>(&bytes).map_err(|e| HubError {
+        status: Some(status.as_u16()),
+        snippet: Some(snippet_of(&String::from_utf8_lossy(&bytes))),
+        ..HubError::new(url, format!("parse error {e}"))
     })
 }
 
+fn snippet_of(body: &str) -> String {
+    body.trim().chars().take(HUB_ERROR_SNIPPET_CHARS).collect()
+}
+
+/// The stock hub returns a bare array; paginated/delta hubs wrap it in an object.
+/// Entries stay raw JSON here and are parsed individually by `parse_entries`.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum HubServerListResponse {
+    Flat(Vec<serde_json::Value>),
+    Page(HubServerListPage),
+}
+
+#[derive(Debug, Deserialize)]
+struct HubServerListPage {
+    #[serde(alias = "items")]
+    servers: Vec<serde_json::Value>,
+    /// Next page URL or opaque cursor.
+    #[serde(default, alias = "nextCursor", alias = "next_cursor")]
+    next: Option<String>,
+    #[serde(default, alias = "syncToken")]
+    sync_token: Option<String>,
+    /// Set when the page only contains changes since the requested token.
+    #[serde(default)]
+    delta: bool,
+    #[serde(default)]
+    removed: Vec<String>,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct HubServerListEntry {
@@ -137,7 +489,7 @@ struct HubServerStatus {
 }
 
 impl HubServerListEntry {
-    fn into_server_entry(self) -> ServerEntry {
+    fn into_server_entry(self, hub: &str) -> ServerEntry {
         let HubServerListEntry {
             address,
             status_data,
@@ -153,10 +505,25 @@ impl HubServerListEntry {
 
         let players = players.max(0) as u32;
         let soft_max_players = soft_max_players.max(0) as u32;
+        let name = name.as_deref().and_then(hub_text::clean_name);
+        let tags = hub_text::clean_tags(tags);
+        let description = description.as_deref().and_then(hub_text::clean_description);
 
         let region = tags
             .iter()
             .find_map(|t| t.strip_prefix("region:").map(|s| s.to_string()));
+        let game_mode = tags.iter().find_map(|t| {
+            let (key, value) = t.split_once(':')?;
+            (key.eq_ignore_ascii_case("mode") || key.eq_ignore_ascii_case("gamemode"))
+                .then(|| value.trim().to_string())
+                .filter(|v| !v.is_empty())
+        });
+        let has_tag = |names: &[&str]| {
+            tags.iter()
+                .any(|t| names.iter().any(|n| t.trim().eq_ignore_ascii_case(n)))
+        };
+        let vanilla = has_tag(&["vanilla"]);
+        let adult = has_tag(&["18+", "nsfw"]);
 
         ServerEntry {
             address: address.clone(),
@@ -169,9 +536,17 @@ impl HubServerListEntry {
             },
             tags,
             region,
+            region_inferred: false,
             ping_ms: None,
             online: true,
             description,
+            game_mode,
+            vanilla,
+            adult,
+            auth_mode: None,
+            privacy_policy: None,
+            privacy_flags: ServerPrivacyFlags::default(),
+            hub: hub.to_string(),
         }
     }
 }
@@ -0,0 +1,97 @@
+/// CVar groups the client is known to have. Anything else is most likely a typo.
+const KNOWN_PREFIXES: &[&str] = &[
+    "audio",
+    "chat",
+    "console",
+    "discord",
+    "display",
+    "game",
+    "hud",
+    "interface",
+    "log",
+    "net",
+    "replay",
+    "res",
+    "thread",
+    "ui",
+    "viewport",
+];
+
+/// Groups the launcher sets itself; overriding them breaks content download or auth.
+const RESERVED_PREFIXES: &[&str] = &["auth", "build", "launch"];
+
+const MAX_VALUE_LEN: usize = 512;
+
+/// Parses one `key=value` line. Keys are dot-separated lowercase segments
+/// (`[a-z0-9_]+`), at least two of them, and the first one must be a known group.
+pub fn parse_cvar(line: &str) -> Result<(String, String), String> {
+    let line = line.trim();
+    let Some((key, value)) = line.split_once('=') else {
+        return Err("нужен формат ключ=значение".to_string());
+    };
+    let key = key.trim();
+    let value = value.trim();
+
+    let segments: Vec<&str> = key.split('.').collect();
+    if segments.len() < 2 {
+        return Err(format!("{key}: ключ должен быть вида группа.имя"));
+    }
+    if segments.iter().any(|s| {
+        s.is_empty()
+            || !s
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+    }) {
+        return Err(format!(
+            "{key}: в ключе допустимы только a-z, 0-9, _ и точки"
+        ));
+    }
+
+    let group = segments[0];
+    if RESERVED_PREFIXES.contains(&group) {
+        return Err(format!("{key}: группу {group}.* задаёт лаунчер"));
+    }
+    if !KNOWN_PREFIXES.contains(&group) {
+        return Err(format!("{key}: неизвестная группа {group}.*"));
+    }
+
+    if value.is_empty() {
+        return Err(format!("{key}: пустое значение"));
+    }
+    if value.len() > MAX_VALUE_LEN {
+        return Err(format!("{key}: значение длиннее {MAX_VALUE_LEN} символов"));
+    }
+    if value.chars().any(char::is_control) {
+        return Err(format!("{key}: управляющие символы в значении"));
+    }
+
+    Ok((key.to_string(), value.to_string()))
+}
+
+/// Validates a list of lines (blank lines and `#` comments skipped) and returns them as
+/// normalized `key=value`. Errors for every bad line are reported together.
+pub fn validate_cvars(lines: &[String]) -> Result<Vec<String>, String> {
+    let mut out = Vec::new();
+    let mut errors = Vec::new();
+
+    for (idx, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        match parse_cvar(trimmed) {
+            Ok((key, value)) => {
+                // Later lines win, like repeated --cvar on the command line.
+                out.retain(|existing: &String| !existing.starts_with(&format!("{key}=")));
+                out.push(format!("{key}={value}"));
+            }
+            Err(e) => errors.push(format!("строка {}: {e}", idx + 1)),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(out)
+    } else {
+        Err(errors.join("\n"))
+    }
+}
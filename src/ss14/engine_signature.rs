@@ -31,6 +31,41 @@ pub fn verify_engine_signature(
         .map_err(|_| "engine signature не прошла проверку".to_string())
 }
 
+/// Details for a failed `verify_engine_signature`: which key was used and what was compared.
+pub fn explain_failure(
+    engine_zip: &Path,
+    engine_sha256: &str,
+    signature_hex: &str,
+    public_key_path: &Path,
+) -> String {
+    use sha2::{Digest, Sha256};
+
+    let key_fingerprint = std::fs::read_to_string(public_key_path)
+        .ok()
+        .and_then(|pem| decode_pem_to_der(&pem).ok())
+        .map(|der| hex::encode(&Sha256::digest(&der)[..8]))
+        .unwrap_or_else(|| "не удалось прочитать".to_string());
+    let zip_size = std::fs::metadata(engine_zip)
+        .map(|m| m.len().to_string())
+        .unwrap_or_else(|_| "?".to_string());
+    let signature = signature_hex.trim();
+    let signature_short = if signature.len() > 32 {
+        format!("{}…{}", &signature[..16], &signature[signature.len() - 16..])
+    } else {
+        signature.to_string()
+    };
+
+    format!(
+        "ключ: {} (sha256 {key_fingerprint})\n\
+         ожидаемая подпись (robust-builds): {signature_short}\n\
+         engine.zip: {} ({zip_size} байт), sha256 {engine_sha256} совпадает с robust-builds\n\
+         Файл скачан целиком, но подпись не подходит к ключу loader'а: либо loader собран с другим ключом, \
+         либо сборка движка подписана не тем ключом. Попробуйте скачать движок заново или удалить эту версию.",
+        public_key_path.display(),
+        engine_zip.display(),
+    )
+}
+
 fn decode_pem_to_der(pem: &str) -> Result<Vec<u8>, String> {
     let b64: String = pem
         .lines()
@@ -0,0 +1,49 @@
+/// DLLs from the Media Feature Pack that the client ends up loading.
+#[cfg(windows)]
+const MEDIA_DLLS: [&str; 2] = ["mfplat.dll", "mf.dll"];
+
+/// Returns a warning for the connect modal when this is a Windows N edition without the Media
+/// Feature Pack: Robust then dies inside the loader with an unhelpful DllNotFound.
+#[cfg(windows)]
+pub fn media_pack_warning() -> Option<String> {
+    use winreg::RegKey;
+    use winreg::enums::HKEY_LOCAL_MACHINE;
+
+    let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
+    let key = hklm
+        .open_subkey(r"SOFTWARE\Microsoft\Windows NT\CurrentVersion")
+        .ok()?;
+    let edition: String = key.get_value("EditionID").ok()?;
+    if !is_n_edition(&edition) {
+        return None;
+    }
+
+    let system_root = std::env::var_os("SystemRoot")?;
+    let system32 = std::path::Path::new(&system_root).join("System32");
+    let missing: Vec<&str> = MEDIA_DLLS
+        .iter()
+        .copied()
+        .filter(|dll| !system32.join(dll).exists())
+        .collect();
+    if missing.is_empty() {
+        return None;
+    }
+
+    Some(format!(
+        "Windows {edition} без Media Feature Pack (нет {}). Клиент может упасть при запуске. \
+         Установите «Пакет компонентов мультимедиа»: Параметры → Приложения → Дополнительные компоненты.",
+        missing.join(", ")
+    ))
+}
+
+#[cfg(not(windows))]
+pub fn media_pack_warning() -> Option<String> {
+    None
+}
+
+/// EditionID values of N SKUs end with "N" (CoreN, ProfessionalN, EnterpriseN, EducationN, ...).
+#[cfg(windows)]
+fn is_n_edition(edition: &str) -> bool {
+    let edition = edition.trim();
+    edition.len() > 1 && edition.ends_with('N') && !edition.ends_with("NN")
+}
@@ -1,3 +1,5 @@
+pub mod cvars;
+pub mod media_pack;
 pub mod ss14_loader;
 pub mod ss14_server_info;
 pub mod ss14_uri;
@@ -1,6 +1,19 @@
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cancel_flag::CancelFlag;
+use crate::tasks::{self, TaskKind};
+
+const PROVENANCE_FILE: &str = "loader_provenance.json";
+const LOADER_BUILD_ID_REWRITE: &str = "rewrite-stable-2";
+/// Hex ed25519 public key that signs prebuilt loader releases, embedded at build time.
+/// Builds without it accept no downloaded loader at all.
+const LOADER_SIGNING_KEY: Option<&str> = option_env!("SGLOADER_LOADER_SIGNING_KEY");
 
 pub struct LoaderInstall {
     pub entrypoint: PathBuf,
@@ -8,15 +21,193 @@ pub struct LoaderInstall {
     pub marsey_enabled: bool,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoaderSource {
+    /// Copied from `dependencies/loader/<rid>` next to the launcher exe.
+    Packaged,
+    /// Fetched as a prebuilt release artifact.
+    Downloaded,
+    /// Built here with `dotnet publish` from the vendored sources.
+    LocalPublish,
+}
+
+impl LoaderSource {
+    pub fn label_ru(self) -> &'static str {
+        match self {
+            LoaderSource::Packaged => "из поставки лаунчера",
+            LoaderSource::Downloaded => "скачанный релиз",
+            LoaderSource::LocalPublish => "собран локально (dotnet publish)",
+        }
+    }
+}
+
+/// Where the installed loader came from; replaces the old loader_source/loader_build_id markers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoaderProvenance {
+    pub source: LoaderSource,
+    pub build_id: String,
+    /// Commit of the loader sources, when known.
+    #[serde(default)]
+    pub commit: Option<String>,
+    pub rid: String,
+    /// Unix seconds.
+    #[serde(default)]
+    pub installed_at: i64,
+    /// Fingerprint of the key whose signature over the artifact was verified; downloaded
+    /// loaders only.
+    #[serde(default)]
+    pub signed_by: Option<String>,
+}
+
+pub fn load_loader_provenance(data_dir: &Path) -> Option<LoaderProvenance> {
+    let path = data_dir
+        .join("loader")
+        .join(platform_rid())
+        .join(PROVENANCE_FILE);
+    let contents = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+fn write_provenance(
+    out_dir: &Path,
+    source: LoaderSource,
+    commit: Option<String>,
+    signed_by: Option<String>,
+) {
+    let provenance = LoaderProvenance {
+        source,
+        build_id: LOADER_BUILD_ID_REWRITE.to_string(),
+        commit,
+        rid: platform_rid().to_string(),
+        installed_at: chrono::Utc::now().timestamp(),
+        signed_by,
+    };
+    if let Ok(json) = serde_json::to_string_pretty(&provenance) {
+        let _ = fs::write(out_dir.join(PROVENANCE_FILE), json);
+    }
+    // Superseded by the JSON file.
+    let _ = fs::remove_file(out_dir.join("loader_source.txt"));
+    let _ = fs::remove_file(out_dir.join("loader_build_id.txt"));
+}
+
+/// Checks the detached signature of a prebuilt loader artifact against the embedded key.
+/// Must pass before anything from the artifact is unpacked or run. Returns the fingerprint
+/// of the key for the provenance file.
+pub fn verify_loader_artifact(artifact: &[u8], signature_hex: &str) -> Result<String, String> {
+    let key_hex = LOADER_SIGNING_KEY
+        .map(str::trim)
+        .filter(|k| !k.is_empty())
+        .ok_or("в эту сборку лаунчера не встроен ключ подписи SS14.Loader")?;
+    let key = crate::marsey::patch_repo::parse_trusted_key(key_hex)?;
+    let sig_bytes =
+        hex::decode(signature_hex.trim()).map_err(|e| format!("подпись не hex: {e}"))?;
+    let signature = ed25519_dalek::Signature::try_from(sig_bytes.as_slice())
+        .map_err(|e| format!("подпись имеет неверную длину: {e}"))?;
+    key.verify_strict(artifact, &signature)
+        .map_err(|_| "подпись SS14.Loader не совпадает с ключом лаунчера".to_string())?;
+    Ok(key_hex.chars().take(16).collect())
+}
+
+/// Dry run of the check a download will do: verifies `artifact` against `<artifact>.sig`
+/// without unpacking or running anything. Blocking.
+pub fn dry_run_verify_loader(artifact: &Path) -> Result<String, String> {
+    let bytes = fs::read(artifact).map_err(|e| format!("read {:?}: {e}", artifact))?;
+    let mut sig_name = artifact.as_os_str().to_os_string();
+    sig_name.push(".sig");
+    let sig_path = PathBuf::from(sig_name);
+    let signature = fs::read_to_string(&sig_path)
+        .map_err(|e| format!("подпись {:?} не найдена: {e}", sig_path))?;
+    let key = verify_loader_artifact(&bytes, &signature)?;
+    Ok(format!("подпись верна (ключ {key}…)"))
+}
+
+/// Reads the provenance file, falling back to (and migrating) the legacy marker files.
+fn read_provenance(out_dir: &Path) -> Option<LoaderProvenance> {
+    if let Ok(contents) = fs::read_to_string(out_dir.join(PROVENANCE_FILE)) {
+        return serde_json::from_str(&contents).ok();
+    }
+
+    let source = fs::read_to_string(out_dir.join("loader_source.txt")).ok()?;
+    let build_id = fs::read_to_string(out_dir.join("loader_build_id.txt")).ok()?;
+    if !source.trim().eq_ignore_ascii_case("rewrite") || build_id.trim() != LOADER_BUILD_ID_REWRITE {
+        return None;
+    }
+    // Legacy markers didn't say how the loader got there; local publish was the common case.
+    write_provenance(out_dir, LoaderSource::LocalPublish, None, None);
+    fs::read_to_string(out_dir.join(PROVENANCE_FILE))
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+}
+
+/// Commit of the vendored loader sources, if they are a git checkout.
+fn source_commit(csproj: &Path) -> Option<String> {
+    let repo = csproj.parent()?.parent()?;
+    let out = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .output()
+        .ok()?;
+    if !out.status.success() {
+        return None;
+    }
+    let commit = String::from_utf8_lossy(&out.stdout).trim().to_string();
+    (!commit.is_empty()).then_some(commit)
+}
+
+/// Runs `dotnet publish`, killing it if the task is cancelled from the tasks panel.
+fn run_publish(mut cmd: Command, cancel: &CancelFlag) -> Result<(), String> {
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("не удалось запустить dotnet для сборки SS14.Loader: {e}"))?;
+
+    loop {
+        if cancel.is_cancelled() {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err("сборка SS14.Loader отменена".to_string());
+        }
+
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => return Ok(()),
+            Ok(Some(_)) => {
+                return Err("dotnet publish SS14.Loader завершился с ошибкой".to_string());
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(200)),
+            Err(e) => return Err(format!("ожидание dotnet publish: {e}")),
+        }
+    }
+}
+
+/// Startup warm-up and a connect may race; only one of them may publish into the loader dir.
+static INSTALL_LOCK: Mutex<()> = Mutex::new(());
+
+/// Prepares the loader on a background thread at startup so the first connect
+/// doesn't pay for a multi-minute `dotnet publish`. Progress shows in the tasks panel.
+pub fn warm_loader_in_background() {
+    // The owning instance builds the loader; a browser-only window never launches.
+    if crate::core::instance_lock::ensure_writable().is_err() {
+        return;
+    }
+    std::thread::spawn(|| {
+        let task = tasks::register(TaskKind::LoaderBuild, "подготовка SS14.Loader", false);
+        let res = crate::app_paths::data_dir()
+            .and_then(|data_dir| ensure_loader_installed(&data_dir).map(|_| ()));
+        task.finish(&res);
+    });
+}
+
 pub fn ensure_loader_installed(data_dir: &Path) -> Result<LoaderInstall, String> {
-    const LOADER_BUILD_ID_REWRITE: &str = "rewrite-stable-2";
+    let _guard = INSTALL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    ensure_loader_installed_locked(data_dir)
+}
 
+fn ensure_loader_installed_locked(data_dir: &Path) -> Result<LoaderInstall, String> {
     let out_dir = data_dir.join("loader").join(platform_rid());
-    fs::create_dir_all(&out_dir).map_err(|e| format!("создание каталога loader: {e}"))?;
+    crate::app_paths::create_dir(&out_dir, "loader")?;
 
     let public_key = out_dir.join("signing_key");
-    let marker = out_dir.join("loader_source.txt");
-    let build_id_file = out_dir.join("loader_build_id.txt");
 
     let exe = out_dir.join("SS14.Loader.exe");
     let dll = out_dir.join("SS14.Loader.dll");
@@ -36,8 +227,12 @@ pub fn ensure_loader_installed(data_dir: &Path) -> Result<LoaderInstall, String>
             fs::copy(&packaged_key, &public_key)
                 .map_err(|e| format!("копирование signing_key: {e}"))?;
 
-            let _ = fs::write(&marker, "rewrite");
-            let _ = fs::write(&build_id_file, LOADER_BUILD_ID_REWRITE);
+            // A packaged build may ship its own provenance with the commit it was built from.
+            let packaged_commit = fs::read_to_string(packaged_dir.join(PROVENANCE_FILE))
+                .ok()
+                .and_then(|c| serde_json::from_str::<LoaderProvenance>(&c).ok())
+                .and_then(|p| p.commit);
+            write_provenance(&out_dir, LoaderSource::Packaged, packaged_commit, None);
 
             let entrypoint = if exe.exists() {
                 exe
@@ -62,16 +257,11 @@ pub fn ensure_loader_installed(data_dir: &Path) -> Result<LoaderInstall, String>
     let desired_build_id = LOADER_BUILD_ID_REWRITE;
 
     if (exe.exists() || dll.exists()) && public_key.exists() {
-        let installed_is_rewrite = fs::read_to_string(&marker)
-            .ok()
-            .map(|s| s.trim().eq_ignore_ascii_case("rewrite"))
-            .unwrap_or(false);
-        let build_ok = fs::read_to_string(&build_id_file)
-            .ok()
-            .map(|s| s.trim() == desired_build_id)
+        let build_ok = read_provenance(&out_dir)
+            .map(|p| p.build_id == desired_build_id)
             .unwrap_or(false);
 
-        if installed_is_rewrite == marsey_enabled && build_ok {
+        if build_ok {
             return Ok(LoaderInstall {
                 entrypoint: if exe.exists() { exe } else { dll },
                 public_key,
@@ -110,21 +300,23 @@ pub fn ensure_loader_installed(data_dir: &Path) -> Result<LoaderInstall, String>
     cmd.arg("-o");
     cmd.arg(&out_dir);
 
-    let status = cmd
-        .status()
-        .map_err(|e| format!("не удалось запустить dotnet для сборки SS14.Loader: {e}"))?;
-
-    if !status.success() {
-        return Err("dotnet publish SS14.Loader завершился с ошибкой".to_string());
-    }
+    let task = tasks::register(TaskKind::LoaderBuild, "dotnet publish SS14.Loader", true);
+    task.set_detail(platform_rid());
+    let res = run_publish(cmd, task.cancel_flag());
+    task.finish(&res);
+    res?;
 
     // Copy signing key (public key) next to loader.
     let key_src = launcher_signing_key_path()?;
     fs::copy(&key_src, &public_key).map_err(|e| format!("копирование signing_key: {e}"))?;
 
     // Record which loader source produced this install.
-    let _ = fs::write(&marker, "rewrite");
-    let _ = fs::write(&build_id_file, desired_build_id);
+    write_provenance(
+        &out_dir,
+        LoaderSource::LocalPublish,
+        source_commit(&csproj),
+        None,
+    );
 
     let entrypoint = if exe.exists() {
         exe
@@ -141,6 +333,46 @@ pub fn ensure_loader_installed(data_dir: &Path) -> Result<LoaderInstall, String>
     })
 }
 
+/// Cheap check that a connect will find a loader: an installed one, a packaged one, or a
+/// `dotnet` SDK plus sources to build it. Does not build or copy anything.
+pub fn loader_readiness(data_dir: &Path) -> Result<(), String> {
+    let out_dir = data_dir.join("loader").join(platform_rid());
+    let installed = (out_dir.join("SS14.Loader.exe").exists()
+        || out_dir.join("SS14.Loader.dll").exists())
+        && out_dir.join("signing_key").exists();
+    if installed {
+        return Ok(());
+    }
+
+    if let Some(dir) = packaged_loader_dir()
+        && (dir.join("SS14.Loader.exe").exists() || dir.join("SS14.Loader.dll").exists())
+        && dir.join("signing_key").exists()
+    {
+        return Ok(());
+    }
+
+    loader_csproj_path().map_err(|_| {
+        "SS14.Loader не найден: нет ни собранного, ни поставляемого в dependencies/loader, ни исходников для сборки"
+            .to_string()
+    })?;
+    let mut cmd = Command::new("dotnet");
+    cmd.arg("--version");
+    // Runs at every startup: don't flash a console window.
+    #[cfg(all(target_os = "windows", not(debug_assertions)))]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+    match cmd.output() {
+        Ok(out) if out.status.success() => Ok(()),
+        _ => Err(
+            "SS14.Loader ещё не собран, а dotnet SDK не найден — установите .NET SDK или используйте сборку с dependencies/loader"
+                .to_string(),
+        ),
+    }
+}
+
 fn packaged_loader_dir() -> Option<PathBuf> {
     let exe_dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
     Some(
@@ -176,19 +408,8 @@ fn copy_dir_files(from: &Path, to: &Path) -> std::io::Result<()> {
 }
 
 fn platform_rid() -> &'static str {
-    // Minimal mapping; we currently only support Windows in this workspace.
-    if cfg!(target_os = "windows") {
-        if cfg!(target_arch = "x86_64") {
-            return "win-x64";
-        }
-        if cfg!(target_arch = "aarch64") {
-            return "win-arm64";
-        }
-        return "win-x86";
-    }
-
-    // Fallback.
-    "win-x64"
+    // Same RID as the engine: the loader runs the engine in its own process.
+    crate::platform::host_rid()
 }
 
 fn loader_csproj_path() -> Result<PathBuf, String> {
@@ -16,6 +16,11 @@ pub struct ServerInfo {
 
     #[serde(rename = "privacy_policy")]
     pub privacy_policy: Option<ServerPrivacyPolicyInfo>,
+
+    /// Not part of upstream `/info`; servers that collect more than the engine does may
+    /// announce it here.
+    #[serde(rename = "privacy", default)]
+    pub privacy_flags: Option<ServerPrivacyFlags>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -77,3 +82,13 @@ pub struct ServerPrivacyPolicyInfo {
     #[serde(rename = "version")]
     pub version: String,
 }
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ServerPrivacyFlags {
+    /// The server bans or checks by hardware ID.
+    #[serde(default, alias = "requires_hwid")]
+    pub hwid_required: bool,
+    /// The server sends gameplay or client telemetry to third parties.
+    #[serde(default)]
+    pub telemetry: bool,
+}
@@ -8,7 +8,7 @@ pub fn parse_ss14_uri(address: &str) -> Result<Url, String> {
         address = format!("ss14://{address}");
     }
 
-    let uri = Url::parse(&address).map_err(|_| "неверный адрес сервера".to_string())?;
+    let mut uri = Url::parse(&address).map_err(|_| "неверный адрес сервера".to_string())?;
 
     match uri.scheme() {
         "ss14" | "ss14s" => {}
@@ -19,9 +19,103 @@ pub fn parse_ss14_uri(address: &str) -> Result<Url, String> {
         return Err("в адресе сервера отсутствует host".to_string());
     }
 
+    // Query parameters (referral/queue tokens used by some forks) are kept as-is and forwarded
+    // to the client. Fragments and a bare trailing '?' carry nothing, so drop them.
+    uri.set_fragment(None);
+    if uri.query().map(|q| q.is_empty()).unwrap_or(false) {
+        uri.set_query(None);
+    }
+
     Ok(uri)
 }
 
+/// Corrected form of a typed server address and what was changed in it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AddressFix {
+    pub address: String,
+    pub notes: Vec<&'static str>,
+}
+
+/// Spots the usual mistakes in a hand-typed address (no scheme, an http(s) link, a stray
+/// trailing slash, spaces in the host name) and returns the `ss14://` form it most likely
+/// means. `None` when the input is fine as is or no fix makes it parse.
+pub fn suggest_address_fix(input: &str) -> Option<AddressFix> {
+    let original = input.trim();
+    let mut notes = Vec::new();
+    let unquoted = original.trim_matches(|c| matches!(c, '"' | '\'' | '<' | '>' | '«' | '»'));
+    if unquoted.is_empty() {
+        return None;
+    }
+    if unquoted != original {
+        notes.push("убраны кавычки");
+    }
+
+    let lower = unquoted.to_ascii_lowercase();
+    let (scheme, rest) = if lower.starts_with("https://") {
+        notes.push("https:// заменён на ss14s://");
+        ("ss14s", &unquoted["https://".len()..])
+    } else if lower.starts_with("http://") {
+        notes.push("http:// заменён на ss14://");
+        ("ss14", &unquoted["http://".len()..])
+    } else if let Some(i) = lower.find("://") {
+        (&unquoted[..i], &unquoted[i + 3..])
+    } else if let Some(scheme) = ["ss14s:", "ss14:"]
+        .into_iter()
+        .find(|p| lower.starts_with(p))
+    {
+        notes.push("исправлено начало адреса");
+        (
+            scheme.trim_end_matches(':'),
+            unquoted[scheme.len()..].trim_start_matches('/'),
+        )
+    } else {
+        notes.push("добавлено ss14://");
+        ("ss14", unquoted)
+    };
+    let scheme = scheme.to_ascii_lowercase();
+
+    let split = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+    let (host, tail) = rest.split_at(split);
+    let compact: String = host.chars().filter(|c| !c.is_whitespace()).collect();
+    if compact != host {
+        notes.push("убраны пробелы в имени сервера");
+    }
+    let query_at = tail.find(['?', '#']).unwrap_or(tail.len());
+    let (path, query) = tail.split_at(query_at);
+    let trimmed_path = path.trim_end_matches('/');
+    if trimmed_path != path {
+        notes.push("убран завершающий /");
+    }
+
+    // A bare host parses as it is: the scheme is added on connect anyway.
+    let only_scheme_added = notes == ["добавлено ss14://"] && parse_ss14_uri(original).is_ok();
+    let address = format!("{scheme}://{compact}{trimmed_path}{query}");
+    if notes.is_empty()
+        || only_scheme_added
+        || address == original
+        || parse_ss14_uri(&address).is_err()
+    {
+        return None;
+    }
+    Some(AddressFix { address, notes })
+}
+
+/// Address passed to the client as `--ss14-address`, including any query parameters.
+pub fn client_ss14_address(ss14_uri: &Url) -> String {
+    ss14_uri.to_string()
+}
+
+/// Names of the query parameters carried by the address (values are not exposed: they may be tokens).
+pub fn query_param_names(ss14_uri: &Url) -> Vec<String> {
+    ss14_uri
+        .query_pairs()
+        .map(|(k, _)| k.into_owned())
+        .filter(|k| !k.is_empty())
+        .collect()
+}
+
+/// HTTP base for the server API. Query parameters of the ss14 address are intentionally not
+/// carried over: they are meant for the client, not for `/info` and friends.
 pub fn server_api_base(ss14_uri: &Url) -> Result<Url, String> {
     let host = ss14_uri
         .host_str()
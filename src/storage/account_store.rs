@@ -9,6 +9,7 @@ use crate::auth::LoginInfo;
 use crate::secure_token::{decrypt_token, encrypt_token};
 
 const LOGIN_FILE_NAME: &str = "logins.json";
+const MAX_ALIAS_CHARS: usize = 32;
 
 pub fn load_saved_logins() -> Result<Vec<LoginInfo>, String> {
     let stored = read_logins_file()?;
@@ -44,11 +45,12 @@ pub fn save_login(login: &LoginInfo) -> Result<(), String> {
         .map_err(|e| format!("ошибка шифрования токена: {e}"))?;
     let token_enc = general_purpose::STANDARD.encode(encrypted);
 
-    let stored_login = StoredLogin {
+    let mut stored_login = StoredLogin {
         user_id: login.user_id,
         username: login.username.clone(),
         token_enc,
         expire_time: login.token.expire_time,
+        alias: login.alias.clone(),
     };
 
     let stored_user_id = stored_login.user_id;
@@ -58,6 +60,10 @@ pub fn save_login(login: &LoginInfo) -> Result<(), String> {
         .iter_mut()
         .find(|i| i.user_id == stored_user_id)
     {
+        // A fresh login from the auth server carries no alias; keep the one the user set.
+        if stored_login.alias.is_none() {
+            stored_login.alias = existing.alias.take();
+        }
         *existing = stored_login;
     } else {
         stored_file.items.push(stored_login);
@@ -77,19 +83,131 @@ pub fn set_active_login(user_id: uuid::Uuid) -> Result<(), String> {
     write_logins_file(&stored)
 }
 
-pub fn remove_login(user_id: uuid::Uuid) -> Result<(), String> {
+/// Moves an account up (`delta < 0`) or down (`delta > 0`) in the saved order.
+pub fn move_login(user_id: uuid::Uuid, delta: isize) -> Result<(), String> {
     let mut stored = read_logins_file()?;
-    let before = stored.items.len();
-    stored.items.retain(|i| i.user_id != user_id);
-    if stored.items.len() == before {
-        return Err("указанный аккаунт не найден".to_string());
+    let from = stored
+        .items
+        .iter()
+        .position(|i| i.user_id == user_id)
+        .ok_or_else(|| "указанный аккаунт не найден".to_string())?;
+
+    let last = stored.items.len().saturating_sub(1) as isize;
+    let to = (from as isize + delta).clamp(0, last) as usize;
+    if to == from {
+        return Ok(());
     }
-    if stored.active_user_id == Some(user_id) {
+
+    let item = stored.items.remove(from);
+    stored.items.insert(to, item);
+    write_logins_file(&stored)
+}
+
+pub fn set_login_alias(user_id: uuid::Uuid, alias: &str) -> Result<(), String> {
+    let mut stored = read_logins_file()?;
+    let item = stored
+        .items
+        .iter_mut()
+        .find(|i| i.user_id == user_id)
+        .ok_or_else(|| "указанный аккаунт не найден".to_string())?;
+
+    let trimmed = alias.trim();
+    if trimmed.chars().count() > MAX_ALIAS_CHARS {
+        return Err(format!(
+            "имя аккаунта слишком длинное (максимум {MAX_ALIAS_CHARS} символов)"
+        ));
+    }
+    item.alias = (!trimmed.is_empty()).then(|| trimmed.to_string());
+    write_logins_file(&stored)
+}
+
+/// A saved login as it was before `remove_login` or `sign_out_login`, for undo.
+#[derive(Debug, Clone)]
+pub struct RemovedLogin {
+    index: usize,
+    item: StoredLogin,
+    was_active: bool,
+}
+
+impl RemovedLogin {
+    pub fn user_id(&self) -> uuid::Uuid {
+        self.item.user_id
+    }
+}
+
+pub fn remove_login(user_id: uuid::Uuid) -> Result<RemovedLogin, String> {
+    let mut stored = read_logins_file()?;
+    let index = stored
+        .items
+        .iter()
+        .position(|i| i.user_id == user_id)
+        .ok_or_else(|| "указанный аккаунт не найден".to_string())?;
+    let item = stored.items.remove(index);
+    let was_active = stored.active_user_id == Some(user_id);
+    if was_active {
         stored.active_user_id = None;
     }
+    write_logins_file(&stored)?;
+    Ok(RemovedLogin {
+        index,
+        item,
+        was_active,
+    })
+}
+
+/// Drops the token but keeps the entry (name, alias, place in the list), so the next login
+/// form can offer the username. Signed-out entries are not returned by `load_saved_logins`.
+pub fn sign_out_login(user_id: uuid::Uuid) -> Result<RemovedLogin, String> {
+    let mut stored = read_logins_file()?;
+    let index = stored
+        .items
+        .iter()
+        .position(|i| i.user_id == user_id)
+        .ok_or_else(|| "указанный аккаунт не найден".to_string())?;
+    let before = stored.items[index].clone();
+    stored.items[index].token_enc.clear();
+    let was_active = stored.active_user_id == Some(user_id);
+    if was_active {
+        stored.active_user_id = None;
+    }
+    write_logins_file(&stored)?;
+    Ok(RemovedLogin {
+        index,
+        item: before,
+        was_active,
+    })
+}
+
+/// Puts back what `remove_login` or `sign_out_login` took. Does nothing if the account was
+/// logged into again in the meantime, so a fresh token is never replaced by the old one.
+pub fn restore_login(removed: &RemovedLogin) -> Result<(), String> {
+    let mut stored = read_logins_file()?;
+    let user_id = removed.item.user_id;
+    if let Some(pos) = stored.items.iter().position(|i| i.user_id == user_id) {
+        if !stored.items[pos].token_enc.is_empty() {
+            return Ok(());
+        }
+        stored.items.remove(pos);
+    }
+    let index = removed.index.min(stored.items.len());
+    stored.items.insert(index, removed.item.clone());
+    if removed.was_active {
+        stored.active_user_id = Some(user_id);
+    }
     write_logins_file(&stored)
 }
 
+/// Usernames of signed-out entries, in saved order.
+pub fn signed_out_usernames() -> Result<Vec<String>, String> {
+    let stored = read_logins_file()?;
+    Ok(stored
+        .items
+        .into_iter()
+        .filter(|i| i.token_enc.is_empty())
+        .map(|i| i.username)
+        .collect())
+}
+
 pub fn clear_saved_logins() -> Result<(), String> {
     let path = login_file_path()?;
     match fs::remove_file(&path) {
@@ -126,8 +244,7 @@ fn read_logins_file() -> Result<StoredLoginsFileV2, String> {
 
 fn write_logins_file(stored: &StoredLoginsFileV2) -> Result<(), String> {
     let dir = crate::app_paths::data_dir()?;
-    fs::create_dir_all(&dir)
-        .map_err(|err| format!("не удалось создать каталог для логинов: {err}"))?;
+    crate::app_paths::create_dir(&dir, "логины")?;
 
     let path = login_file_path()?;
     let serialized = serde_json::to_string_pretty(stored)
@@ -137,11 +254,15 @@ fn write_logins_file(stored: &StoredLoginsFileV2) -> Result<(), String> {
 }
 
 fn decode_login(item: StoredLogin) -> Option<LoginInfo> {
+    if item.token_enc.is_empty() {
+        return None;
+    }
     let encrypted = general_purpose::STANDARD.decode(item.token_enc).ok()?;
     let token = decrypt_token(&encrypted).ok()?;
     Some(LoginInfo {
         user_id: item.user_id,
         username: item.username,
+        alias: item.alias,
         token: crate::auth::LoginToken {
             token,
             expire_time: item.expire_time,
@@ -171,4 +292,6 @@ struct StoredLogin {
     username: String,
     token_enc: String,
     expire_time: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    alias: Option<String>,
 }
@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::favorites::canonicalize_favorite_address;
+use crate::ss14_server_info::ServerBuildInformation;
+
+const BUILD_OVERRIDES_FILE_NAME: &str = "build_overrides.json";
+
+/// Per-server replacements for the content URLs from `/info`, for servers behind a CDN or
+/// proxy whose announced URLs do not work from here. Applied before the self-hosted fallbacks.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BuildOverrides {
+    /// Replaces `build.download_url` (content zip).
+    #[serde(default)]
+    pub download_url: Option<String>,
+    /// Replaces `build.manifest_url`.
+    #[serde(default)]
+    pub manifest_url: Option<String>,
+}
+
+impl BuildOverrides {
+    pub fn is_empty(&self) -> bool {
+        self.download_url.is_none() && self.manifest_url.is_none()
+    }
+
+    /// Puts the overridden URLs into `build`. Returns a log line per replaced URL.
+    pub fn apply(&self, build: &mut ServerBuildInformation) -> Vec<String> {
+        let mut applied = Vec::new();
+        if let Some(url) = &self.download_url {
+            applied.push(format!("download_url → {url}"));
+            build.download_url = Some(url.clone());
+        }
+        if let Some(url) = &self.manifest_url {
+            applied.push(format!("manifest_url → {url}"));
+            build.manifest_url = Some(url.clone());
+        }
+        applied
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BuildOverridesFile {
+    servers: HashMap<String, BuildOverrides>,
+}
+
+pub fn load_build_overrides(address: &str) -> BuildOverrides {
+    read_file()
+        .ok()
+        .and_then(|mut file| file.servers.remove(&canonicalize_favorite_address(address)))
+        .unwrap_or_default()
+}
+
+/// Saves the overrides for `address`; empty fields are dropped, and so is the entry once
+/// nothing is left. URLs must be absolute http(s).
+pub fn save_build_overrides(address: &str, overrides: BuildOverrides) -> Result<(), String> {
+    let overrides = BuildOverrides {
+        download_url: normalize_url(overrides.download_url, "download_url")?,
+        manifest_url: normalize_url(overrides.manifest_url, "manifest_url")?,
+    };
+
+    let mut file = read_file()?;
+    let key = canonicalize_favorite_address(address);
+    if overrides.is_empty() {
+        if file.servers.remove(&key).is_none() {
+            return Ok(());
+        }
+    } else {
+        file.servers.insert(key, overrides);
+    }
+
+    let dir = crate::app_paths::data_dir()?;
+    crate::app_paths::create_dir(&dir, "адреса сборки")?;
+    let json =
+        serde_json::to_string_pretty(&file).map_err(|e| format!("serialize адреса сборки: {e}"))?;
+    fs::write(build_overrides_file_path()?, json).map_err(|e| format!("запись адресов сборки: {e}"))
+}
+
+fn normalize_url(value: Option<String>, field: &str) -> Result<Option<String>, String> {
+    let Some(value) = value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+    else {
+        return Ok(None);
+    };
+    let url = url::Url::parse(&value).map_err(|e| format!("{field}: некорректный URL: {e}"))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("{field}: нужен адрес http:// или https://"));
+    }
+    Ok(Some(value))
+}
+
+fn read_file() -> Result<BuildOverridesFile, String> {
+    let path = build_overrides_file_path()?;
+    let contents = match fs::read_to_string(&path) {
+        Ok(data) => data,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(BuildOverridesFile::default());
+        }
+        Err(err) => return Err(format!("не удалось прочитать адреса сборки: {err}")),
+    };
+    serde_json::from_str(&contents).map_err(|e| format!("не удалось разобрать адреса сборки: {e}"))
+}
+
+fn build_overrides_file_path() -> Result<PathBuf, String> {
+    Ok(crate::app_paths::data_dir()?.join(BUILD_OVERRIDES_FILE_NAME))
+}
@@ -0,0 +1,65 @@
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+const CACHE_STATS_FILE_NAME: &str = "cache_stats.json";
+
+/// Result of one content overlay build: how much came from the blob cache.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BlobCacheHits {
+    pub blobs_total: u64,
+    pub blobs_cached: u64,
+    pub bytes_saved: u64,
+    pub bytes_downloaded: u64,
+}
+
+/// Lifetime totals shown next to the cache controls in settings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CacheStatsTotals {
+    #[serde(default)]
+    pub builds: u64,
+    #[serde(default)]
+    pub blobs_from_cache: u64,
+    #[serde(default)]
+    pub blobs_downloaded: u64,
+    #[serde(default)]
+    pub bytes_saved: u64,
+    #[serde(default)]
+    pub bytes_downloaded: u64,
+}
+
+pub fn load_cache_stats() -> CacheStatsTotals {
+    cache_stats_file_path()
+        .ok()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+pub fn record_blob_cache_hits(hits: &BlobCacheHits) -> Result<(), String> {
+    let mut totals = load_cache_stats();
+    totals.builds += 1;
+    totals.blobs_from_cache += hits.blobs_cached;
+    totals.blobs_downloaded += hits.blobs_total.saturating_sub(hits.blobs_cached);
+    totals.bytes_saved += hits.bytes_saved;
+    totals.bytes_downloaded += hits.bytes_downloaded;
+    save_cache_stats(&totals)
+}
+
+pub fn reset_cache_stats() -> Result<(), String> {
+    save_cache_stats(&CacheStatsTotals::default())
+}
+
+fn save_cache_stats(totals: &CacheStatsTotals) -> Result<(), String> {
+    let dir = crate::app_paths::data_dir()?;
+    crate::app_paths::create_dir(&dir, "статистика кэша")?;
+
+    let json = serde_json::to_string_pretty(totals)
+        .map_err(|e| format!("serialize статистика кэша: {e}"))?;
+    fs::write(cache_stats_file_path()?, json).map_err(|e| format!("запись статистики кэша: {e}"))
+}
+
+fn cache_stats_file_path() -> Result<PathBuf, String> {
+    Ok(crate::app_paths::data_dir()?.join(CACHE_STATS_FILE_NAME))
+}
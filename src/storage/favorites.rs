@@ -1,38 +1,179 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
 use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
 
+use crate::ss14_server_info::ServerBuildInformation;
+
 const FAVORITES_FILE_NAME: &str = "favorites.json";
 
+/// Favorites beyond membership: manual order and pinned quick-connect tiles.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FavoritesLayout {
+    #[serde(default)]
+    pub order: Vec<String>,
+    #[serde(default)]
+    pub pinned: Vec<String>,
+}
+
+pub const MAX_PINNED: usize = 3;
+
+impl FavoritesLayout {
+    /// Sort key for a favorite; ones never dragged keep their list position after the rest.
+    pub fn position(&self, address: &str) -> usize {
+        self.order
+            .iter()
+            .position(|a| a == address)
+            .unwrap_or(usize::MAX)
+    }
+
+    /// Moves `dragged` right before `target`. `visible` is the current on-screen order, used to
+    /// place favorites that have no stored position yet.
+    pub fn move_before(&mut self, dragged: &str, target: &str, visible: &[String]) {
+        for addr in visible {
+            if !self.order.contains(addr) {
+                self.order.push(addr.clone());
+            }
+        }
+        self.order.retain(|a| a != dragged);
+        let idx = self
+            .order
+            .iter()
+            .position(|a| a == target)
+            .unwrap_or(self.order.len());
+        self.order.insert(idx, dragged.to_string());
+    }
+
+    pub fn is_pinned(&self, address: &str) -> bool {
+        self.pinned.iter().any(|a| a == address)
+    }
+
+    pub fn toggle_pin(&mut self, address: &str) -> Result<(), String> {
+        if self.is_pinned(address) {
+            self.pinned.retain(|a| a != address);
+            return Ok(());
+        }
+        if self.pinned.len() >= MAX_PINNED {
+            return Err(format!("закрепить можно не больше {MAX_PINNED} серверов"));
+        }
+        self.pinned.push(address.to_string());
+        Ok(())
+    }
+
+    /// Drops entries for servers that are no longer favorites.
+    pub fn retain_favorites(&mut self, set: &HashSet<String>) {
+        self.order.retain(|a| set.contains(a));
+        self.pinned.retain(|a| set.contains(a));
+    }
+}
+
 pub fn load_favorites() -> Result<HashSet<String>, String> {
+    Ok(read_favorites_file()?.addresses.into_iter().collect())
+}
+
+pub fn load_favorites_layout() -> FavoritesLayout {
+    let stored = read_favorites_file().unwrap_or_default();
+    FavoritesLayout {
+        order: stored.order,
+        pinned: stored.pinned,
+    }
+}
+
+pub fn save_favorites(set: &HashSet<String>) -> Result<(), String> {
+    let mut addresses: Vec<String> = set.iter().cloned().collect();
+    addresses.sort();
+
+    // Keep the layout and content identities, minus servers that were unfavorited.
+    let mut stored = read_favorites_file().unwrap_or_default();
+    let mut layout = FavoritesLayout {
+        order: stored.order,
+        pinned: stored.pinned,
+    };
+    layout.retain_favorites(set);
+    stored.content.retain(|addr, _| set.contains(addr));
+    write_favorites_file(&FavoritesFile {
+        addresses,
+        order: layout.order,
+        pinned: layout.pinned,
+        content: stored.content,
+    })
+}
+
+pub fn save_favorites_layout(layout: &FavoritesLayout) -> Result<(), String> {
+    let mut stored = read_favorites_file()?;
+    let set: HashSet<String> = stored.addresses.iter().cloned().collect();
+    let mut layout = layout.clone();
+    layout.retain_favorites(&set);
+    stored.order = layout.order;
+    stored.pinned = layout.pinned;
+    write_favorites_file(&stored)
+}
+
+/// Content a favorite served the last time we launched it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContentIdentity {
+    pub version: String,
+    #[serde(default)]
+    pub manifest_hash: Option<String>,
+}
+
+impl ContentIdentity {
+    pub fn from_build(build: &ServerBuildInformation) -> Self {
+        Self {
+            version: build.version.clone(),
+            manifest_hash: build
+                .manifest_hash
+                .as_deref()
+                .map(str::trim)
+                .filter(|h| !h.is_empty())
+                .map(str::to_string),
+        }
+    }
+}
+
+pub fn load_content_identity(address: &str) -> Option<ContentIdentity> {
+    read_favorites_file()
+        .ok()?
+        .content
+        .remove(&canonicalize_favorite_address(address))
+}
+
+/// Remembers what `address` serves now. Does nothing for servers that are not favorites.
+pub fn record_content_identity(address: &str, build: &ServerBuildInformation) -> Result<(), String> {
+    let key = canonicalize_favorite_address(address);
+    let mut stored = read_favorites_file()?;
+    if !stored.addresses.contains(&key) {
+        return Ok(());
+    }
+    let identity = ContentIdentity::from_build(build);
+    if stored.content.get(&key) == Some(&identity) {
+        return Ok(());
+    }
+    stored.content.insert(key, identity);
+    write_favorites_file(&stored)
+}
+
+fn read_favorites_file() -> Result<FavoritesFile, String> {
     let path = favorites_file_path()?;
     let contents = match fs::read_to_string(&path) {
         Ok(data) => data,
-        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(HashSet::new()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(FavoritesFile::default());
+        }
         Err(err) => return Err(format!("не удалось прочитать избранное: {err}")),
     };
 
-    let stored: FavoritesFile = serde_json::from_str(&contents)
-        .map_err(|e| format!("не удалось разобрать избранное: {e}"))?;
-
-    Ok(stored.addresses.into_iter().collect())
+    serde_json::from_str(&contents).map_err(|e| format!("не удалось разобрать избранное: {e}"))
 }
 
-pub fn save_favorites(set: &HashSet<String>) -> Result<(), String> {
+fn write_favorites_file(stored: &FavoritesFile) -> Result<(), String> {
     let dir = crate::app_paths::data_dir()?;
-    fs::create_dir_all(&dir).map_err(|e| format!("mkdir избранное: {e}"))?;
+    crate::app_paths::create_dir(&dir, "избранное")?;
 
-    let path = favorites_file_path()?;
-    let mut addresses: Vec<String> = set.iter().cloned().collect();
-    addresses.sort();
-
-    let stored = FavoritesFile { addresses };
     let json =
-        serde_json::to_string_pretty(&stored).map_err(|e| format!("serialize избранное: {e}"))?;
-
-    fs::write(&path, json).map_err(|e| format!("запись избранного: {e}"))?;
+        serde_json::to_string_pretty(stored).map_err(|e| format!("serialize избранное: {e}"))?;
+    fs::write(favorites_file_path()?, json).map_err(|e| format!("запись избранного: {e}"))?;
     Ok(())
 }
 
@@ -43,6 +184,15 @@ fn favorites_file_path() -> Result<PathBuf, String> {
 #[derive(Debug, Serialize, Deserialize, Default)]
 struct FavoritesFile {
     addresses: Vec<String>,
+    /// Manual drag-and-drop order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    order: Vec<String>,
+    /// Favorites shown as quick-connect tiles, at most `MAX_PINNED`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pinned: Vec<String>,
+    /// Last launched content per favorite, for update detection.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    content: BTreeMap<String, ContentIdentity>,
 }
 
 pub fn canonicalize_favorite_address(address: &str) -> String {
@@ -0,0 +1,509 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use base64::{Engine as _, engine::general_purpose};
+use reqwest::StatusCode;
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, ETAG, HeaderMap, IF_MATCH, IF_NONE_MATCH};
+use serde::{Deserialize, Serialize};
+
+use crate::favorites::{self, FavoritesLayout};
+use crate::secure_token::{decrypt_token, encrypt_token};
+use crate::settings::FavoritesSyncSettings;
+
+const SYNC_STATE_FILE_NAME: &str = "favorites_sync.json";
+const REMOTE_FORMAT_VERSION: u32 = 1;
+/// Another machine may upload between our GET and PUT; re-merge this many times.
+const MAX_SYNC_ATTEMPTS: usize = 3;
+
+/// Held for every read-modify-write of the local favorites done here, so changes made in quick
+/// succession and a sync running in the background do not overwrite each other.
+static FAVORITES_LOCK: Mutex<()> = Mutex::new(());
+
+/// The document stored at the user's endpoint (a WebDAV file or anything with GET/PUT),
+/// and the body of a hub's `api/favorites`.
+#[derive(Debug, Serialize, Deserialize)]
+struct RemoteFavorites {
+    #[serde(default)]
+    version: u32,
+    addresses: Vec<String>,
+    #[serde(default)]
+    updated_at: i64,
+    /// Manual order and pins; absent from copies kept by hubs that store addresses only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    order: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pinned: Option<Vec<String>>,
+}
+
+impl RemoteFavorites {
+    fn new(addresses: Vec<String>, layout: &FavoritesLayout) -> Self {
+        Self {
+            version: REMOTE_FORMAT_VERSION,
+            addresses,
+            updated_at: chrono::Utc::now().timestamp(),
+            order: Some(layout.order.clone()),
+            pinned: Some(layout.pinned.clone()),
+        }
+    }
+
+    fn layout(&self) -> Option<FavoritesLayout> {
+        if self.order.is_none() && self.pinned.is_none() {
+            return None;
+        }
+        Some(FavoritesLayout {
+            order: self.order.clone().unwrap_or_default(),
+            pinned: self.pinned.clone().unwrap_or_default(),
+        })
+    }
+}
+
+/// What both sides looked like after the last successful sync; the merge base.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct FavoritesSyncState {
+    #[serde(default)]
+    base: Vec<String>,
+    #[serde(default)]
+    etag: Option<String>,
+    #[serde(default)]
+    pub synced_at: Option<i64>,
+    #[serde(default)]
+    pub last_error: Option<String>,
+    /// Merge base of the hub account copy, like `base` for the endpoint.
+    #[serde(default)]
+    hub_base: Vec<String>,
+    /// Hub that keeps the account copy; `None` while no hub offers it.
+    #[serde(default)]
+    pub hub_url: Option<String>,
+    /// When the hubs were last asked, whether or not one offered it.
+    #[serde(default)]
+    pub hub_checked_at: Option<i64>,
+    /// Order and pins after the last sync with the endpoint; merge base like `base`.
+    #[serde(default)]
+    base_layout: FavoritesLayout,
+    /// Same for the hub account copy.
+    #[serde(default)]
+    hub_base_layout: FavoritesLayout,
+}
+
+#[derive(Debug, Clone)]
+pub struct SyncOutcome {
+    pub favorites: HashSet<String>,
+    pub added: usize,
+    pub removed: usize,
+}
+
+impl SyncOutcome {
+    pub fn summary(&self) -> String {
+        if self.added == 0 && self.removed == 0 {
+            return "избранное синхронизировано, изменений нет".to_string();
+        }
+        format!(
+            "избранное синхронизировано: +{} / −{}",
+            self.added, self.removed
+        )
+    }
+}
+
+impl FavoritesSyncSettings {
+    /// Any kind of sync is configured.
+    pub fn is_enabled(&self) -> bool {
+        self.has_endpoint() || self.hub_account
+    }
+
+    pub fn has_endpoint(&self) -> bool {
+        !self.url.trim().is_empty()
+    }
+
+    pub fn secret(&self) -> Option<String> {
+        if self.secret_encrypted.is_empty() {
+            return None;
+        }
+        let bytes = general_purpose::STANDARD
+            .decode(&self.secret_encrypted)
+            .ok()?;
+        decrypt_token(&bytes).ok()
+    }
+
+    pub fn set_secret(&mut self, secret: &str) -> Result<(), String> {
+        if secret.is_empty() {
+            self.secret_encrypted.clear();
+            return Ok(());
+        }
+        let encrypted = encrypt_token(secret.as_bytes())?;
+        self.secret_encrypted = general_purpose::STANDARD.encode(encrypted);
+        Ok(())
+    }
+}
+
+pub fn load_sync_state() -> FavoritesSyncState {
+    sync_state_path()
+        .ok()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+fn save_sync_state(state: &FavoritesSyncState) -> Result<(), String> {
+    let path = sync_state_path()?;
+    let json = serde_json::to_string_pretty(state)
+        .map_err(|e| format!("serialize состояние синхронизации: {e}"))?;
+    fs::write(&path, json).map_err(|e| format!("запись состояния синхронизации: {e}"))
+}
+
+fn sync_state_path() -> Result<PathBuf, String> {
+    Ok(crate::app_paths::data_dir()?.join(SYNC_STATE_FILE_NAME))
+}
+
+/// Local favorites after `update_and_sync`.
+#[derive(Debug, Clone)]
+pub struct SavedFavorites {
+    pub favorites: HashSet<String>,
+    pub layout: FavoritesLayout,
+    /// Set when the change was saved but syncing it failed.
+    pub sync_error: Option<String>,
+}
+
+/// Applies `change` to the saved favorites and their layout and, when sync is configured,
+/// merges the result with the remote copies. Changes are applied one at a time to what is on
+/// disk, so quick successive edits all land. Blocking.
+pub fn update_and_sync(
+    change: impl FnOnce(&mut HashSet<String>, &mut FavoritesLayout),
+) -> Result<SavedFavorites, String> {
+    let _lock = FAVORITES_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut set = favorites::load_favorites()?;
+    let mut layout = favorites::load_favorites_layout();
+    let before = (set.clone(), layout.clone());
+    change(&mut set, &mut layout);
+    if before != (set.clone(), layout.clone()) {
+        favorites::save_favorites(&set)?;
+        favorites::save_favorites_layout(&layout)?;
+    }
+
+    let settings = crate::settings::load_settings()?.favorites_sync;
+    let sync_error = if settings.is_enabled() {
+        sync_locked(&settings).err()
+    } else {
+        None
+    };
+    Ok(SavedFavorites {
+        favorites: favorites::load_favorites()?,
+        layout: favorites::load_favorites_layout(),
+        sync_error,
+    })
+}
+
+/// Three-way merge against the last synced state: additions from either side are kept,
+/// and a removal on either side wins over an untouched copy on the other. The endpoint goes
+/// first, then the hub account copy, so both end up with the same list. Order and pins go
+/// along as a whole: the side that changed them since the last sync wins, this machine when
+/// both did.
+pub fn sync_favorites(settings: &FavoritesSyncSettings) -> Result<SyncOutcome, String> {
+    let _lock = FAVORITES_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    sync_locked(settings)
+}
+
+fn sync_locked(settings: &FavoritesSyncSettings) -> Result<SyncOutcome, String> {
+    let mut state = load_sync_state();
+    let before = favorites::load_favorites()?;
+    let mut result = Ok(());
+    if settings.has_endpoint() {
+        result = sync_inner(settings, &mut state).map(|_| ());
+    }
+    if result.is_ok() && settings.hub_account {
+        result = sync_hub(&mut state);
+    }
+    state.last_error = result.as_ref().err().cloned();
+    let _ = save_sync_state(&state);
+    result?;
+
+    let after = favorites::load_favorites()?;
+    Ok(SyncOutcome {
+        added: after.difference(&before).count(),
+        removed: before.difference(&after).count(),
+        favorites: after,
+    })
+}
+
+/// Favorites that exist only on this machine: not in the last synced copy of the endpoint
+/// nor of the hub account. Empty when sync is off, as then every favorite is local.
+pub fn local_only(
+    favorites: &HashSet<String>,
+    settings: &FavoritesSyncSettings,
+) -> HashSet<String> {
+    if !settings.is_enabled() {
+        return HashSet::new();
+    }
+    let state = load_sync_state();
+    favorites
+        .iter()
+        .filter(|a| !state.base.contains(a) && !state.hub_base.contains(a))
+        .cloned()
+        .collect()
+}
+
+/// Syncs with the first enabled hub that keeps favorites for the active account. Hubs
+/// without the feature answer 404 and are skipped; none having it is not an error.
+/// Third-party hubs get the token only after [`hub_offers_favorites`].
+fn sync_hub(state: &mut FavoritesSyncState) -> Result<(), String> {
+    let Some(login) = crate::account_store::load_saved_login()? else {
+        return Err("избранное в аккаунте хаба: войдите в аккаунт".to_string());
+    };
+    if login.token.is_time_expired() {
+        return Err("избранное в аккаунте хаба: срок входа истёк, войдите заново".to_string());
+    }
+    let client = crate::http_config::build_blocking_client_with_headers(
+        HeaderMap::new(),
+        crate::http_config::HttpProfile::Api,
+    )?;
+    let auth = format!("SS14Auth {}", login.token.token);
+    state.hub_checked_at = Some(chrono::Utc::now().timestamp());
+
+    for hub in crate::storage::hub_urls::load_enabled_hub_urls() {
+        let url = format!("{}/api/favorites", hub.trim_end_matches('/'));
+        if !is_official_hub(&hub) && !hub_offers_favorites(&client, &url) {
+            continue;
+        }
+        let resp = match client.get(&url).header(AUTHORIZATION, &auth).send() {
+            Ok(r) => r,
+            Err(_) => continue,
+        };
+        match resp.status() {
+            StatusCode::NOT_FOUND
+            | StatusCode::METHOD_NOT_ALLOWED
+            | StatusCode::NOT_IMPLEMENTED => {
+                continue;
+            }
+            s if !s.is_success() => {
+                return Err(format!("избранное в аккаунте хаба: {hub}: GET status {s}"));
+            }
+            _ => {}
+        }
+        let doc: RemoteFavorites = resp
+            .json()
+            .map_err(|e| format!("избранное в аккаунте хаба: неверный формат ({e})"))?;
+
+        // A different hub than last time has its own copy: start from a fresh base.
+        if state.hub_url.as_deref() != Some(hub.as_str()) {
+            state.hub_base.clear();
+            state.hub_base_layout = FavoritesLayout::default();
+        }
+        let local = favorites::load_favorites()?;
+        let local_layout = favorites::load_favorites_layout();
+        let base: HashSet<String> = state.hub_base.iter().cloned().collect();
+        let remote: HashSet<String> = doc
+            .addresses
+            .iter()
+            .map(|a| favorites::canonicalize_favorite_address(a))
+            .collect();
+        let merged = merge(&base, &local, &remote);
+        let remote_layout = doc.layout();
+        let mut layout = merge_layout(&state.hub_base_layout, &local_layout, remote_layout.clone());
+        layout.retain_favorites(&merged);
+
+        let mut addresses: Vec<String> = merged.iter().cloned().collect();
+        addresses.sort();
+        if merged != remote || remote_layout.as_ref() != Some(&layout) {
+            let body = RemoteFavorites::new(addresses.clone(), &layout);
+            let put = client
+                .put(&url)
+                .header(AUTHORIZATION, &auth)
+                .json(&body)
+                .send()
+                .map_err(|e| format!("избранное в аккаунте хаба: {e}"))?;
+            if !put.status().is_success() {
+                return Err(format!(
+                    "избранное в аккаунте хаба: {hub}: PUT status {}",
+                    put.status()
+                ));
+            }
+        }
+        save_merged(&merged, &local, &layout, &local_layout)?;
+        state.hub_base = addresses;
+        state.hub_base_layout = layout;
+        state.hub_url = Some(hub);
+        state.synced_at = Some(chrono::Utc::now().timestamp());
+        return Ok(());
+    }
+
+    state.hub_url = None;
+    Ok(())
+}
+
+/// The account token is sent as is to the official hubs only; a third-party hub first has
+/// to show, without credentials, that it keeps favorites at all.
+fn is_official_hub(hub: &str) -> bool {
+    crate::storage::hub_urls::default_hub_urls()
+        .iter()
+        .any(|official| official.trim_end_matches('/') == hub.trim_end_matches('/'))
+}
+
+/// An anonymous GET: a hub with the feature asks for a login, one without it answers 404.
+fn hub_offers_favorites(client: &reqwest::blocking::Client, url: &str) -> bool {
+    client.get(url).send().is_ok_and(|resp| {
+        matches!(
+            resp.status(),
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN
+        )
+    })
+}
+
+fn sync_inner(
+    settings: &FavoritesSyncSettings,
+    state: &mut FavoritesSyncState,
+) -> Result<SyncOutcome, String> {
+    let url = settings.url.trim();
+    let client = crate::http_config::build_blocking_client_with_headers(
+        HeaderMap::new(),
+        crate::http_config::HttpProfile::Api,
+    )?;
+    let secret = settings.secret();
+    let with_auth = |req: reqwest::blocking::RequestBuilder| {
+        let user = settings.username.trim();
+        match (&secret, user.is_empty()) {
+            (Some(s), false) => req.basic_auth(user, Some(s)),
+            (None, false) => req.basic_auth(user, None::<&str>),
+            (Some(s), true) => req.bearer_auth(s),
+            (None, true) => req,
+        }
+    };
+
+    let local = favorites::load_favorites()?;
+    let local_layout = favorites::load_favorites_layout();
+
+    for _ in 0..MAX_SYNC_ATTEMPTS {
+        let resp = with_auth(client.get(url))
+            .send()
+            .map_err(|e| format!("синхронизация избранного: {e}"))?;
+        let (remote, etag) = match resp.status() {
+            StatusCode::NOT_FOUND => (None, None),
+            s if s.is_success() => {
+                let etag = header_string(resp.headers(), ETAG);
+                let doc: RemoteFavorites = resp
+                    .json()
+                    .map_err(|e| format!("синхронизация избранного: неверный формат ({e})"))?;
+                (Some(doc), etag)
+            }
+            s => return Err(format!("синхронизация избранного: GET status {s}")),
+        };
+
+        let base: HashSet<String> = state.base.iter().cloned().collect();
+        let merged = match &remote {
+            Some(doc) => {
+                let remote_set: HashSet<String> = doc
+                    .addresses
+                    .iter()
+                    .map(|a| favorites::canonicalize_favorite_address(a))
+                    .collect();
+                merge(&base, &local, &remote_set)
+            }
+            None => local.clone(),
+        };
+
+        let remote_layout = remote.as_ref().and_then(RemoteFavorites::layout);
+        let mut layout = merge_layout(&state.base_layout, &local_layout, remote_layout.clone());
+        layout.retain_favorites(&merged);
+
+        let remote_matches = remote.as_ref().is_some_and(|doc| {
+            doc.addresses.len() == merged.len() && doc.addresses.iter().all(|a| merged.contains(a))
+        }) && remote_layout.as_ref() == Some(&layout);
+        let mut new_etag = etag.clone();
+        if !remote_matches {
+            let mut addresses: Vec<String> = merged.iter().cloned().collect();
+            addresses.sort();
+            let doc = RemoteFavorites::new(addresses, &layout);
+            let body =
+                serde_json::to_vec_pretty(&doc).map_err(|e| format!("serialize избранное: {e}"))?;
+            let mut req = with_auth(client.put(url))
+                .header(CONTENT_TYPE, "application/json")
+                .body(body);
+            // Conditional PUT: if someone uploaded in between, fetch and merge again.
+            req = match &etag {
+                Some(tag) => req.header(IF_MATCH, tag.as_str()),
+                None if remote.is_none() => req.header(IF_NONE_MATCH, "*"),
+                None => req,
+            };
+            let put = req
+                .send()
+                .map_err(|e| format!("синхронизация избранного: {e}"))?;
+            if put.status() == StatusCode::PRECONDITION_FAILED {
+                continue;
+            }
+            if !put.status().is_success() {
+                return Err(format!(
+                    "синхронизация избранного: PUT status {}",
+                    put.status()
+                ));
+            }
+            new_etag = header_string(put.headers(), ETAG);
+        }
+
+        save_merged(&merged, &local, &layout, &local_layout)?;
+
+        let outcome = SyncOutcome {
+            added: merged.difference(&local).count(),
+            removed: local.difference(&merged).count(),
+            favorites: merged.clone(),
+        };
+        let mut base: Vec<String> = merged.into_iter().collect();
+        base.sort();
+        state.base = base;
+        state.base_layout = layout;
+        state.etag = new_etag;
+        state.synced_at = Some(chrono::Utc::now().timestamp());
+        return Ok(outcome);
+    }
+
+    Err(
+        "синхронизация избранного: список постоянно меняется на другой машине, попробуйте позже"
+            .to_string(),
+    )
+}
+
+fn merge(
+    base: &HashSet<String>,
+    local: &HashSet<String>,
+    remote: &HashSet<String>,
+) -> HashSet<String> {
+    local
+        .union(remote)
+        .filter(|a| !base.contains(*a) || (local.contains(*a) && remote.contains(*a)))
+        .cloned()
+        .collect()
+}
+
+/// Order and pins are merged as a whole: the remote copy is taken only if this machine left
+/// them as they were after the last sync. A copy without layout leaves the local one alone.
+fn merge_layout(
+    base: &FavoritesLayout,
+    local: &FavoritesLayout,
+    remote: Option<FavoritesLayout>,
+) -> FavoritesLayout {
+    match remote {
+        Some(remote) if local == base => remote,
+        _ => local.clone(),
+    }
+}
+
+fn save_merged(
+    merged: &HashSet<String>,
+    local: &HashSet<String>,
+    layout: &FavoritesLayout,
+    local_layout: &FavoritesLayout,
+) -> Result<(), String> {
+    if merged != local {
+        favorites::save_favorites(merged)?;
+    }
+    if layout != local_layout {
+        favorites::save_favorites_layout(layout)?;
+    }
+    Ok(())
+}
+
+fn header_string(headers: &HeaderMap, name: reqwest::header::HeaderName) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string())
+}
@@ -0,0 +1,49 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::favorites::canonicalize_favorite_address;
+
+const HIDDEN_SERVERS_FILE_NAME: &str = "hidden_servers.json";
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct HiddenServersFile {
+    addresses: Vec<String>,
+}
+
+pub fn load_hidden_servers() -> Result<HashSet<String>, String> {
+    let path = hidden_servers_file_path()?;
+    let contents = match fs::read_to_string(&path) {
+        Ok(data) => data,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(HashSet::new()),
+        Err(err) => return Err(format!("не удалось прочитать скрытые серверы: {err}")),
+    };
+
+    let stored: HiddenServersFile = serde_json::from_str(&contents)
+        .map_err(|e| format!("не удалось разобрать скрытые серверы: {e}"))?;
+
+    Ok(stored
+        .addresses
+        .iter()
+        .map(|a| canonicalize_favorite_address(a))
+        .collect())
+}
+
+pub fn save_hidden_servers(set: &HashSet<String>) -> Result<(), String> {
+    let dir = crate::app_paths::data_dir()?;
+    crate::app_paths::create_dir(&dir, "скрытые серверы")?;
+
+    let mut addresses: Vec<String> = set.iter().cloned().collect();
+    addresses.sort();
+
+    let json = serde_json::to_string_pretty(&HiddenServersFile { addresses })
+        .map_err(|e| format!("serialize скрытые серверы: {e}"))?;
+    fs::write(hidden_servers_file_path()?, json)
+        .map_err(|e| format!("запись скрытых серверов: {e}"))
+}
+
+fn hidden_servers_file_path() -> Result<PathBuf, String> {
+    Ok(crate::app_paths::data_dir()?.join(HIDDEN_SERVERS_FILE_NAME))
+}
@@ -1,4 +1,3 @@
-use std::collections::HashSet;
 use std::fs;
 use std::path::PathBuf;
 
@@ -9,6 +8,9 @@ const HUB_URLS_FILE_NAME: &str = "hub_urls.json";
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 struct HubUrlsFile {
     urls: Vec<String>,
+    /// Hubs muted by the user: kept in `urls` but skipped when fetching the server list.
+    #[serde(default)]
+    disabled: Vec<String>,
 }
 
 pub fn default_hub_urls() -> Vec<String> {
@@ -26,29 +28,72 @@ pub fn load_hub_urls() -> Vec<String> {
 }
 
 pub fn try_load_hub_urls() -> Result<Vec<String>, String> {
+    let Some(stored) = read_hub_urls_file()? else {
+        return Ok(default_hub_urls());
+    };
+    normalize_and_validate_urls(&stored.urls)
+}
+
+/// Hubs switched off in hub settings (normalized URLs).
+pub fn load_disabled_hub_urls() -> Vec<String> {
+    read_hub_urls_file()
+        .ok()
+        .flatten()
+        .map(|stored| {
+            stored
+                .disabled
+                .iter()
+                .filter_map(|u| normalize_hub_url(u).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Hubs to ask for the server list: the configured ones minus disabled ones. If every hub
+/// is disabled (hand-edited file), the override is ignored rather than leaving no hub.
+pub fn load_enabled_hub_urls() -> Vec<String> {
+    let urls = load_hub_urls();
+    let disabled = load_disabled_hub_urls();
+    let enabled: Vec<String> = urls
+        .iter()
+        .filter(|u| !disabled.contains(u))
+        .cloned()
+        .collect();
+    if enabled.is_empty() { urls } else { enabled }
+}
+
+fn read_hub_urls_file() -> Result<Option<HubUrlsFile>, String> {
     let path = hub_urls_file_path()?;
     let contents = match fs::read_to_string(&path) {
         Ok(data) => data,
-        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(default_hub_urls()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
         Err(err) => return Err(format!("не удалось прочитать ссылки хаба: {err}")),
     };
 
-    let stored: HubUrlsFile = serde_json::from_str(&contents)
-        .map_err(|err| format!("не удалось разобрать ссылки хаба: {err}"))?;
-
-    normalize_and_validate_urls(&stored.urls)
+    serde_json::from_str(&contents)
+        .map(Some)
+        .map_err(|err| format!("не удалось разобрать ссылки хаба: {err}"))
 }
 
-pub fn save_hub_urls(urls: &[String]) -> Result<Vec<String>, String> {
+/// Saves the hub list and which of its hubs are disabled. Returns the normalized list.
+pub fn save_hub_urls(urls: &[String], disabled: &[String]) -> Result<Vec<String>, String> {
     let dir = crate::app_paths::data_dir()?;
-    fs::create_dir_all(&dir)
-        .map_err(|err| format!("не удалось создать каталог для настроек хаба: {err}"))?;
+    crate::app_paths::create_dir(&dir, "настройки хаба")?;
 
     let normalized = normalize_and_validate_urls(urls)?;
+    let disabled: Vec<String> = normalized
+        .iter()
+        .filter(|u| disabled.iter().any(|d| normalize_hub_url(d).is_ok_and(|d| d == **u)))
+        .cloned()
+        .collect();
+    if disabled.len() == normalized.len() {
+        return Err("все хабы отключены — оставьте включённым хотя бы один".to_string());
+    }
     let path = hub_urls_file_path()?;
 
     let stored = HubUrlsFile {
         urls: normalized.clone(),
+        disabled,
     };
     let json = serde_json::to_string_pretty(&stored)
         .map_err(|err| format!("не удалось сериализовать ссылки хаба: {err}"))?;
@@ -62,28 +107,71 @@ fn hub_urls_file_path() -> Result<PathBuf, String> {
     Ok(crate::app_paths::data_dir()?.join(HUB_URLS_FILE_NAME))
 }
 
-fn normalize_and_validate_urls(raw: &[String]) -> Result<Vec<String>, String> {
-    let mut seen: HashSet<String> = HashSet::new();
-    let mut out: Vec<String> = Vec::new();
+/// Per-row result of checking the hub list, for inline validation in the editor.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HubUrlCheck {
+    Valid(String),
+    Empty,
+    /// Same hub as an earlier row (by index); dropped on save.
+    Duplicate(usize),
+    Invalid(String),
+}
 
-    for s in raw {
-        let mut url = s.trim().to_string();
-        if url.is_empty() {
-            continue;
-        }
+/// Canonical form of a hub URL: lowercase scheme and host, punycode for IDN hosts, no
+/// fragment, trailing slash on the path.
+pub fn normalize_hub_url(raw: &str) -> Result<String, String> {
+    let raw = raw.trim();
+    let mut url = url::Url::parse(raw).map_err(|e| format!("не разобрать ссылку: {e}"))?;
 
-        if !(url.starts_with("https://") || url.starts_with("http://")) {
-            return Err(format!(
-                "некорректная ссылка хаба: {url} (нужен http/https)"
-            ));
-        }
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("схема {}: нужен http или https", url.scheme()));
+    }
+    if url.host_str().is_none_or(|h| h.is_empty()) {
+        return Err("в ссылке нет хоста".to_string());
+    }
 
-        if !url.ends_with('/') {
-            url.push('/');
-        }
+    url.set_fragment(None);
+    if !url.path().ends_with('/') {
+        let path = format!("{}/", url.path());
+        url.set_path(&path);
+    }
+
+    Ok(url.to_string())
+}
+
+pub fn check_hub_urls(raw: &[String]) -> Vec<HubUrlCheck> {
+    let mut first_seen: Vec<(String, usize)> = Vec::new();
+
+    raw.iter()
+        .enumerate()
+        .map(|(idx, s)| {
+            if s.trim().is_empty() {
+                return HubUrlCheck::Empty;
+            }
+            match normalize_hub_url(s) {
+                Err(e) => HubUrlCheck::Invalid(e),
+                Ok(url) => match first_seen.iter().find(|(u, _)| *u == url) {
+                    Some((_, first)) => HubUrlCheck::Duplicate(*first),
+                    None => {
+                        first_seen.push((url.clone(), idx));
+                        HubUrlCheck::Valid(url)
+                    }
+                },
+            }
+        })
+        .collect()
+}
+
+fn normalize_and_validate_urls(raw: &[String]) -> Result<Vec<String>, String> {
+    let mut out: Vec<String> = Vec::new();
 
-        if seen.insert(url.clone()) {
-            out.push(url);
+    for (check, s) in check_hub_urls(raw).into_iter().zip(raw) {
+        match check {
+            HubUrlCheck::Valid(url) => out.push(url),
+            HubUrlCheck::Empty | HubUrlCheck::Duplicate(_) => {}
+            HubUrlCheck::Invalid(e) => {
+                return Err(format!("некорректная ссылка хаба: {} ({e})", s.trim()));
+            }
         }
     }
 
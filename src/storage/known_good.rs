@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::favorites::canonicalize_favorite_address;
+use crate::settings::{HideLevel, PatchCategories};
+
+const KNOWN_GOOD_FILE_NAME: &str = "known_good.json";
+
+/// Settings the last successful launch on a server used, offered back when a later launch
+/// fails to start after they changed. A game that crashes once it is up is not covered.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KnownGoodConfig {
+    /// Enabled patch file names, sorted case-insensitively.
+    pub enabled_patches: Vec<String>,
+    pub patch_categories: PatchCategories,
+    /// The setting as chosen, not what `Auto` resolved to.
+    pub hide_level: HideLevel,
+    /// Off when the launch needed the automatic backports fix.
+    pub backports: bool,
+    pub engine_version: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct KnownGoodFile {
+    servers: HashMap<String, KnownGoodConfig>,
+}
+
+/// Settings a launch on `engine_version` uses right now.
+pub fn current_config(engine_version: &str, backports: bool) -> Result<KnownGoodConfig, String> {
+    let data_dir = crate::app_paths::data_dir()?;
+    let settings = crate::settings::load_settings()?;
+    let (_, patches) = crate::marsey::list_patches(&data_dir)?;
+    let mut enabled_patches: Vec<String> = patches
+        .into_iter()
+        .filter(|p| p.enabled)
+        .map(|p| p.filename)
+        .collect();
+    enabled_patches.sort_by_key(|n| n.to_lowercase());
+    Ok(KnownGoodConfig {
+        enabled_patches,
+        patch_categories: settings.patches.categories,
+        hide_level: settings.security.hide_level,
+        backports,
+        engine_version: engine_version.to_string(),
+    })
+}
+
+pub fn load_known_good(address: &str) -> Option<KnownGoodConfig> {
+    let mut file = read_file().ok()?;
+    file.servers.remove(&canonicalize_favorite_address(address))
+}
+
+pub fn save_known_good(address: &str, config: KnownGoodConfig) -> Result<(), String> {
+    let mut file = read_file()?;
+    let key = canonicalize_favorite_address(address);
+    if file.servers.get(&key) == Some(&config) {
+        return Ok(());
+    }
+    file.servers.insert(key, config);
+
+    let dir = crate::app_paths::data_dir()?;
+    crate::app_paths::create_dir(&dir, "рабочие конфигурации")?;
+    let json = serde_json::to_string_pretty(&file)
+        .map_err(|e| format!("serialize рабочие конфигурации: {e}"))?;
+    fs::write(known_good_file_path()?, json)
+        .map_err(|e| format!("запись рабочих конфигураций: {e}"))
+}
+
+/// Engine version each of `addresses` runs, with those addresses. `announced` is what the
+/// server's `/info` says now; the last successful launch is only asked about servers it has
+/// no answer for. Servers known to neither are left out.
+pub fn engine_users<'a>(
+    addresses: impl IntoIterator<Item = &'a String>,
+    announced: impl Fn(&str) -> Option<String>,
+) -> HashMap<String, Vec<String>> {
+    let mut file = read_file().unwrap_or_default();
+    let mut users: HashMap<String, Vec<String>> = HashMap::new();
+    for address in addresses {
+        let version = announced(address).or_else(|| {
+            file.servers
+                .remove(&canonicalize_favorite_address(address))
+                .map(|config| config.engine_version)
+        });
+        if let Some(version) = version {
+            users.entry(version).or_default().push(address.clone());
+        }
+    }
+    users
+}
+
+/// What changed between the known-good `saved` and `current`, one line per difference.
+/// Backports and the engine are listed too, though only the server or the automatic fix
+/// decides them.
+pub fn describe_changes(saved: &KnownGoodConfig, current: &KnownGoodConfig) -> Vec<String> {
+    let mut out = Vec::new();
+    let was = lowercase_names(&saved.enabled_patches);
+    let now = lowercase_names(&current.enabled_patches);
+    let added: Vec<&str> = current
+        .enabled_patches
+        .iter()
+        .filter(|n| !was.contains(&n.to_lowercase()))
+        .map(String::as_str)
+        .collect();
+    let removed: Vec<&str> = saved
+        .enabled_patches
+        .iter()
+        .filter(|n| !now.contains(&n.to_lowercase()))
+        .map(String::as_str)
+        .collect();
+    if !added.is_empty() {
+        out.push(format!("включены патчи: {}", added.join(", ")));
+    }
+    if !removed.is_empty() {
+        out.push(format!("выключены патчи: {}", removed.join(", ")));
+    }
+    if saved.patch_categories != current.patch_categories {
+        out.push(format!(
+            "набор патчей: {} → {}",
+            saved.patch_categories.label_ru(),
+            current.patch_categories.label_ru()
+        ));
+    }
+    if saved.hide_level != current.hide_level {
+        out.push(format!(
+            "hide level: {} → {}",
+            saved.hide_level.label_ru(),
+            current.hide_level.label_ru()
+        ));
+    }
+    if saved.backports != current.backports {
+        let state = |on: bool| {
+            if on {
+                "включены"
+            } else {
+                "выключены"
+            }
+        };
+        out.push(format!(
+            "backports: {} → {}",
+            state(saved.backports),
+            state(current.backports)
+        ));
+    }
+    if saved.engine_version != current.engine_version {
+        out.push(format!(
+            "движок: {} → {} (выбирает сервер)",
+            saved.engine_version, current.engine_version
+        ));
+    }
+    out
+}
+
+/// Whether `current` differs from `saved` in something the launcher can put back.
+pub fn can_restore(saved: &KnownGoodConfig, current: &KnownGoodConfig) -> bool {
+    lowercase_names(&saved.enabled_patches) != lowercase_names(&current.enabled_patches)
+        || saved.patch_categories != current.patch_categories
+        || saved.hide_level != current.hide_level
+}
+
+/// Puts back the patch set, patch categories and hide level of the last successful launch on
+/// `address`. Patches deleted since then stay missing.
+pub fn restore_known_good(address: &str) -> Result<(), String> {
+    let saved = load_known_good(address)
+        .ok_or_else(|| "для этого сервера нет сохранённой рабочей конфигурации".to_string())?;
+    let data_dir = crate::app_paths::data_dir()?;
+    crate::marsey::set_enabled_patches(&data_dir, &saved.enabled_patches)?;
+
+    let mut settings = crate::settings::load_settings()?;
+    settings.patches.categories = saved.patch_categories;
+    settings.security.hide_level = saved.hide_level;
+    crate::settings::save_settings(&settings)
+}
+
+fn lowercase_names(list: &[String]) -> Vec<String> {
+    list.iter().map(|n| n.to_lowercase()).collect()
+}
+
+fn read_file() -> Result<KnownGoodFile, String> {
+    let path = known_good_file_path()?;
+    let contents = match fs::read_to_string(&path) {
+        Ok(data) => data,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(KnownGoodFile::default());
+        }
+        Err(err) => return Err(format!("не удалось прочитать рабочие конфигурации: {err}")),
+    };
+    serde_json::from_str(&contents)
+        .map_err(|e| format!("не удалось разобрать рабочие конфигурации: {e}"))
+}
+
+fn known_good_file_path() -> Result<PathBuf, String> {
+    Ok(crate::app_paths::data_dir()?.join(KNOWN_GOOD_FILE_NAME))
+}
@@ -0,0 +1,138 @@
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+const LAST_SERVER_FILE_NAME: &str = "last_server.json";
+const RECENT_SERVERS_FILE_NAME: &str = "recent_servers.json";
+const MAX_RECENT_SERVERS: usize = 10;
+
+/// The last server the game was successfully launched against.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LastServer {
+    pub address: String,
+    #[serde(default)]
+    pub user_id: Option<uuid::Uuid>,
+    #[serde(default)]
+    pub joined_at: i64,
+}
+
+pub fn load_last_server() -> Option<LastServer> {
+    let path = last_server_file_path().ok()?;
+    let contents = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+pub fn save_last_server(address: &str, user_id: Option<uuid::Uuid>) -> Result<(), String> {
+    let dir = crate::app_paths::data_dir()?;
+    crate::app_paths::create_dir(&dir, "последний сервер")?;
+
+    let stored = LastServer {
+        address: address.to_string(),
+        user_id,
+        joined_at: chrono::Utc::now().timestamp(),
+    };
+    let json = serde_json::to_string_pretty(&stored)
+        .map_err(|e| format!("serialize последний сервер: {e}"))?;
+    fs::write(last_server_file_path()?, json)
+        .map_err(|e| format!("запись последнего сервера: {e}"))?;
+    push_recent_server(address)
+}
+
+/// One entry of the recent servers list. Older files hold bare addresses.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+enum RecentServer {
+    Timed { address: String, joined_at: i64 },
+    Legacy(String),
+}
+
+impl RecentServer {
+    fn address(&self) -> &str {
+        match self {
+            RecentServer::Timed { address, .. } | RecentServer::Legacy(address) => address,
+        }
+    }
+}
+
+/// Addresses launched recently, newest first.
+pub fn load_recent_servers() -> Vec<String> {
+    load_recent_entries()
+        .iter()
+        .map(|r| r.address().to_string())
+        .collect()
+}
+
+/// Drops the last server and recent entries joined longer than `max_age_days` ago. Entries
+/// from before join times were recorded get the current time, so they expire one window
+/// later. Returns how many entries were dropped.
+pub fn prune_history(max_age_days: u32) -> Result<usize, String> {
+    let now = chrono::Utc::now().timestamp();
+    let cutoff = now - i64::from(max_age_days) * 24 * 60 * 60;
+    let mut dropped = 0;
+
+    if load_last_server().is_some_and(|last| last.joined_at < cutoff) {
+        match fs::remove_file(last_server_file_path()?) {
+            Ok(()) => dropped += 1,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(format!("удаление последнего сервера: {e}")),
+        }
+    }
+
+    let recent = load_recent_entries();
+    let mut changed = false;
+    let mut kept = Vec::with_capacity(recent.len());
+    for entry in recent {
+        match entry {
+            RecentServer::Legacy(address) => {
+                changed = true;
+                kept.push(RecentServer::Timed {
+                    address,
+                    joined_at: now,
+                });
+            }
+            RecentServer::Timed { joined_at, .. } if joined_at < cutoff => {
+                changed = true;
+                dropped += 1;
+            }
+            timed => kept.push(timed),
+        }
+    }
+    if changed {
+        write_recent_entries(&kept)?;
+    }
+    Ok(dropped)
+}
+
+fn load_recent_entries() -> Vec<RecentServer> {
+    crate::app_paths::data_dir()
+        .ok()
+        .and_then(|d| fs::read_to_string(d.join(RECENT_SERVERS_FILE_NAME)).ok())
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+fn push_recent_server(address: &str) -> Result<(), String> {
+    let mut recent = load_recent_entries();
+    recent.retain(|r| r.address() != address);
+    recent.insert(
+        0,
+        RecentServer::Timed {
+            address: address.to_string(),
+            joined_at: chrono::Utc::now().timestamp(),
+        },
+    );
+    recent.truncate(MAX_RECENT_SERVERS);
+    write_recent_entries(&recent)
+}
+
+fn write_recent_entries(recent: &[RecentServer]) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(recent)
+        .map_err(|e| format!("serialize недавние серверы: {e}"))?;
+    fs::write(crate::app_paths::data_dir()?.join(RECENT_SERVERS_FILE_NAME), json)
+        .map_err(|e| format!("запись недавних серверов: {e}"))
+}
+
+fn last_server_file_path() -> Result<PathBuf, String> {
+    Ok(crate::app_paths::data_dir()?.join(LAST_SERVER_FILE_NAME))
+}
@@ -1,5 +1,12 @@
 pub mod account_store;
+pub mod build_overrides;
+pub mod cache_stats;
 pub mod favorites;
+pub mod favorites_sync;
+pub mod hidden_servers;
 pub mod hub_urls;
+pub mod known_good;
+pub mod last_server;
 pub mod secure_token;
 pub mod settings;
+pub mod ui_state;
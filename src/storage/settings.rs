@@ -1,13 +1,397 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 const SETTINGS_FILE_NAME: &str = "settings.json";
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct LauncherSettings {
     pub security: SecuritySettings,
+    #[serde(default)]
+    pub server_list: ServerListSettings,
+    #[serde(default)]
+    pub patch_repo: PatchRepoSettings,
+    #[serde(default)]
+    pub game: GameSettings,
+    #[serde(default)]
+    pub network: NetworkSettings,
+    #[serde(default)]
+    pub debug: DebugSettings,
+    #[serde(default)]
+    pub favorites_sync: FavoritesSyncSettings,
+    #[serde(default)]
+    pub patches: PatchSettings,
+    #[serde(default)]
+    pub retention: RetentionSettings,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct PatchSettings {
+    /// Which enabled patches go to the loader at launch.
+    #[serde(default)]
+    pub categories: PatchCategories,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PatchCategories {
+    #[default]
+    All,
+    /// Marsey patches (including preload) only; Subverter pipe stays empty.
+    MarseyOnly,
+    /// Subverter patches only; Marsey and preload pipes stay empty.
+    SubverterOnly,
+}
+
+impl PatchCategories {
+    pub const ALL: [PatchCategories; 3] = [
+        PatchCategories::All,
+        PatchCategories::MarseyOnly,
+        PatchCategories::SubverterOnly,
+    ];
+
+    pub fn label_ru(self) -> &'static str {
+        match self {
+            PatchCategories::All => "Все включённые патчи",
+            PatchCategories::MarseyOnly => "Только Marsey",
+            PatchCategories::SubverterOnly => "Только Subverter",
+        }
+    }
+
+    pub fn as_key(self) -> &'static str {
+        match self {
+            PatchCategories::All => "all",
+            PatchCategories::MarseyOnly => "marsey_only",
+            PatchCategories::SubverterOnly => "subverter_only",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "all" => Some(PatchCategories::All),
+            "marsey_only" => Some(PatchCategories::MarseyOnly),
+            "subverter_only" => Some(PatchCategories::SubverterOnly),
+            _ => None,
+        }
+    }
+}
+
+/// Endpoint for sharing favorites between machines; empty `url` disables sync.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct FavoritesSyncSettings {
+    #[serde(default)]
+    pub url: String,
+    /// Basic auth user; without it the secret is sent as a bearer token.
+    #[serde(default)]
+    pub username: String,
+    /// Password or token, protected like saved login tokens.
+    #[serde(default)]
+    pub secret_encrypted: String,
+    /// Also keep favorites in the SS14 account on hubs that offer `api/favorites`.
+    #[serde(default)]
+    pub hub_account: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct DebugSettings {
+    /// Write the Marsey pipe payloads of every launch to `logs/marsey-pipes`.
+    #[serde(default)]
+    pub dump_marsey_pipes: bool,
+}
+
+/// How long local records are kept; 0 keeps them forever. Applied by
+/// `cache_cleanup::prune_expired_data`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RetentionSettings {
+    /// Connect and launch logs, Marsey pipe dumps.
+    #[serde(default = "default_logs_days")]
+    pub logs_days: u32,
+    /// Last and recent servers.
+    #[serde(default = "default_history_days")]
+    pub history_days: u32,
+}
+
+pub const MAX_RETENTION_DAYS: u32 = 3650;
+
+fn default_logs_days() -> u32 {
+    30
+}
+
+fn default_history_days() -> u32 {
+    180
+}
+
+impl Default for RetentionSettings {
+    fn default() -> Self {
+        Self {
+            logs_days: default_logs_days(),
+            history_days: default_history_days(),
+        }
+    }
+}
+
+/// HTTP timeouts in seconds; see `http_config` for where each one applies.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct NetworkSettings {
+    #[serde(default = "default_connect_timeout_secs")]
+    pub connect_timeout_secs: u64,
+    #[serde(default = "default_api_timeout_secs")]
+    pub api_timeout_secs: u64,
+    #[serde(default = "default_download_timeout_secs")]
+    pub download_timeout_secs: u64,
+    #[serde(default = "default_options_timeout_secs")]
+    pub options_timeout_secs: u64,
+}
+
+fn default_connect_timeout_secs() -> u64 {
+    10
+}
+
+fn default_api_timeout_secs() -> u64 {
+    20
+}
+
+fn default_download_timeout_secs() -> u64 {
+    60 * 10
+}
+
+fn default_options_timeout_secs() -> u64 {
+    10
+}
+
+impl Default for NetworkSettings {
+    fn default() -> Self {
+        Self {
+            connect_timeout_secs: default_connect_timeout_secs(),
+            api_timeout_secs: default_api_timeout_secs(),
+            download_timeout_secs: default_download_timeout_secs(),
+            options_timeout_secs: default_options_timeout_secs(),
+        }
+    }
+}
+
+impl NetworkSettings {
+    /// Keeps hand-edited values within something that can still work.
+    pub fn clamped(self) -> Self {
+        Self {
+            connect_timeout_secs: self.connect_timeout_secs.clamp(2, 120),
+            api_timeout_secs: self.api_timeout_secs.clamp(5, 600),
+            download_timeout_secs: self.download_timeout_secs.clamp(60, 6 * 60 * 60),
+            options_timeout_secs: self.options_timeout_secs.clamp(2, 120),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameSettings {
+    /// Offer the nearest same-major/minor engine when the requested one is not published.
+    #[serde(default)]
+    pub engine_version_fallback: bool,
+    /// What the connect modal does once the game has started.
+    #[serde(default)]
+    pub auto_close: AutoClose,
+    #[serde(default = "default_auto_close_delay_secs")]
+    pub auto_close_delay_secs: u64,
+    /// Priority class for the spawned game process (Windows).
+    #[serde(default)]
+    pub process_priority: ProcessPriority,
+    /// CPU list like `0-3,6`; empty keeps the default affinity.
+    #[serde(default)]
+    pub cpu_affinity: String,
+    /// Extra `key=value` CVars for every launch, validated by `cvars::validate_cvars`.
+    #[serde(default)]
+    pub extra_cvars: Vec<String>,
+    /// `--username` for servers joined without an account (auth optional or disabled).
+    #[serde(default = "default_guest_username")]
+    pub guest_username: String,
+    /// Ask for the name on every such connect, prefilled with `guest_username`.
+    #[serde(default)]
+    pub ask_guest_username: bool,
+    /// Chose "play without account": no login window at startup, only servers that do not
+    /// require auth can be joined. Cleared by the next login.
+    #[serde(default)]
+    pub guest_mode: bool,
+    /// Tell the user that closing the launcher interrupts a game preparation.
+    #[serde(default = "default_warn_on_close_while_preparing")]
+    pub warn_on_close_while_preparing: bool,
+}
+
+fn default_warn_on_close_while_preparing() -> bool {
+    true
+}
+
+fn default_auto_close_delay_secs() -> u64 {
+    10
+}
+
+pub const DEFAULT_GUEST_USERNAME: &str = "Player";
+
+fn default_guest_username() -> String {
+    DEFAULT_GUEST_USERNAME.to_string()
+}
+
+/// Same rules the game server applies to usernames: 3–32 characters, latin letters,
+/// digits and `_`.
+pub fn validate_guest_username(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    let len = name.chars().count();
+    if !(3..=32).contains(&len) {
+        return Err("ник должен быть длиной от 3 до 32 символов".to_string());
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err("в нике допустимы только латинские буквы, цифры и _".to_string());
+    }
+    Ok(name.to_string())
+}
+
+/// Switches guest mode on or off. Entering it the first time replaces the stock guest name
+/// with a generated one, kept for later sessions. Returns the guest name.
+pub fn set_guest_mode(enabled: bool) -> Result<String, String> {
+    let mut settings = load_settings()?;
+    settings.game.guest_mode = enabled;
+    if enabled && settings.game.guest_username == DEFAULT_GUEST_USERNAME {
+        let suffix = uuid::Uuid::new_v4().as_u128() % 10_000;
+        settings.game.guest_username = format!("{DEFAULT_GUEST_USERNAME}_{suffix:04}");
+    }
+    save_settings(&settings)?;
+    Ok(settings.game.guest_username)
+}
+
+impl Default for GameSettings {
+    fn default() -> Self {
+        Self {
+            engine_version_fallback: false,
+            auto_close: AutoClose::default(),
+            auto_close_delay_secs: default_auto_close_delay_secs(),
+            process_priority: ProcessPriority::default(),
+            cpu_affinity: String::new(),
+            extra_cvars: Vec::new(),
+            guest_username: default_guest_username(),
+            ask_guest_username: false,
+            guest_mode: false,
+            warn_on_close_while_preparing: default_warn_on_close_while_preparing(),
+        }
+    }
+}
+
+impl GameSettings {
+    /// Seconds until the connect modal closes after launch; `None` keeps it open.
+    pub fn auto_close_after_secs(&self) -> Option<u64> {
+        match self.auto_close {
+            AutoClose::Off => None,
+            AutoClose::Immediate => Some(0),
+            AutoClose::Delay => Some(self.auto_close_delay_secs.clamp(1, 600)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessPriority {
+    Idle,
+    BelowNormal,
+    #[default]
+    Normal,
+    AboveNormal,
+    High,
+}
+
+impl ProcessPriority {
+    pub const ALL: [ProcessPriority; 5] = [
+        ProcessPriority::Idle,
+        ProcessPriority::BelowNormal,
+        ProcessPriority::Normal,
+        ProcessPriority::AboveNormal,
+        ProcessPriority::High,
+    ];
+
+    pub fn label_ru(self) -> &'static str {
+        match self {
+            ProcessPriority::Idle => "Низкий",
+            ProcessPriority::BelowNormal => "Ниже среднего",
+            ProcessPriority::Normal => "Обычный",
+            ProcessPriority::AboveNormal => "Выше среднего",
+            ProcessPriority::High => "Высокий",
+        }
+    }
+
+    pub fn as_key(self) -> &'static str {
+        match self {
+            ProcessPriority::Idle => "idle",
+            ProcessPriority::BelowNormal => "below_normal",
+            ProcessPriority::Normal => "normal",
+            ProcessPriority::AboveNormal => "above_normal",
+            ProcessPriority::High => "high",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "idle" => Some(ProcessPriority::Idle),
+            "below_normal" => Some(ProcessPriority::BelowNormal),
+            "normal" => Some(ProcessPriority::Normal),
+            "above_normal" => Some(ProcessPriority::AboveNormal),
+            "high" => Some(ProcessPriority::High),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoClose {
+    Off,
+    Immediate,
+    #[default]
+    Delay,
+}
+
+impl AutoClose {
+    pub const ALL: [AutoClose; 3] = [AutoClose::Off, AutoClose::Immediate, AutoClose::Delay];
+
+    pub fn label_ru(self) -> &'static str {
+        match self {
+            AutoClose::Off => "Не закрывать",
+            AutoClose::Immediate => "Сразу после запуска",
+            AutoClose::Delay => "Через N секунд",
+        }
+    }
+
+    pub fn as_key(self) -> &'static str {
+        match self {
+            AutoClose::Off => "off",
+            AutoClose::Immediate => "immediate",
+            AutoClose::Delay => "delay",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "off" => Some(AutoClose::Off),
+            "immediate" => Some(AutoClose::Immediate),
+            "delay" => Some(AutoClose::Delay),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct PatchRepoSettings {
+    #[serde(default)]
+    pub index_url: String,
+    /// Hex-encoded ed25519 public keys allowed to sign the index and patches.
+    #[serde(default)]
+    pub trusted_keys: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ServerListSettings {
+    /// Look up the host country for servers without a `region:` tag.
+    #[serde(default)]
+    pub geoip_regions: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,6 +400,14 @@ pub struct SecuritySettings {
     pub auto_login: bool,
     pub disable_redial: bool,
     pub autodelete_hwid: bool,
+    /// Where the fork_id → hide level ruleset for `HideLevel::Auto` is fetched from.
+    #[serde(default)]
+    pub hide_level_rules_url: String,
+    /// Hand the auth token to the bundled loader on a one-shot named pipe instead of
+    /// `ROBUST_AUTH_TOKEN` (Windows only). The variable is still used if the pipe can't be
+    /// created.
+    #[serde(default)]
+    pub token_via_pipe: bool,
 }
 
 impl Default for SecuritySettings {
@@ -25,6 +417,8 @@ impl Default for SecuritySettings {
             auto_login: true,
             disable_redial: false,
             autodelete_hwid: false,
+            hide_level_rules_url: String::new(),
+            token_via_pipe: false,
         }
     }
 }
@@ -37,6 +431,8 @@ pub enum HideLevel {
     Medium,
     High,
     Maximum,
+    /// Resolved per server at connect time from the hide level ruleset.
+    Auto,
 }
 
 impl HideLevel {
@@ -47,6 +443,7 @@ impl HideLevel {
             HideLevel::Medium => "Средний",
             HideLevel::High => "Высокий",
             HideLevel::Maximum => "Максимальный",
+            HideLevel::Auto => "Авто (по серверу)",
         }
     }
 
@@ -59,6 +456,8 @@ impl HideLevel {
             HideLevel::Medium => "Normal",
             HideLevel::High => "Explicit",
             HideLevel::Maximum => "Unconditional",
+            // Should be resolved before launch; fall back to the default level.
+            HideLevel::Auto => "Normal",
         }
     }
 
@@ -69,6 +468,7 @@ impl HideLevel {
             "medium" => Some(HideLevel::Medium),
             "high" => Some(HideLevel::High),
             "maximum" => Some(HideLevel::Maximum),
+            "auto" => Some(HideLevel::Auto),
             _ => None,
         }
     }
@@ -80,10 +480,26 @@ impl HideLevel {
             HideLevel::Medium => "medium",
             HideLevel::High => "high",
             HideLevel::Maximum => "maximum",
+            HideLevel::Auto => "auto",
         }
     }
 }
 
+/// Set when `load_settings` had to correct the file; shown once by the startup checks.
+static REPAIR_NOTICE: Mutex<Option<String>> = Mutex::new(None);
+
+/// Network settings read by every HTTP client; loaded once and replaced by `save_settings`.
+static NETWORK_SETTINGS: Mutex<Option<NetworkSettings>> = Mutex::new(None);
+
+/// Clamped network settings, without reading the settings file on every client build.
+pub fn network_settings() -> NetworkSettings {
+    let mut cached = NETWORK_SETTINGS.lock().unwrap_or_else(|e| e.into_inner());
+    *cached.get_or_insert_with(|| load_settings().map(|s| s.network).unwrap_or_default().clamped())
+}
+
+/// Loads the settings file. Values that do not parse or are out of range are replaced by
+/// defaults one by one instead of dropping the whole file; the original is then kept next
+/// to it as `settings.json.invalid-<time>` and the corrected settings are written back.
 pub fn load_settings() -> Result<LauncherSettings, String> {
     let path = settings_file_path()?;
     let contents = match fs::read_to_string(&path) {
@@ -94,17 +510,161 @@ pub fn load_settings() -> Result<LauncherSettings, String> {
         Err(err) => return Err(format!("не удалось прочитать настройки: {err}")),
     };
 
-    serde_json::from_str(&contents).map_err(|e| format!("не удалось разобрать настройки: {e}"))
+    let (mut settings, mut fixes, parsed_whole) =
+        match serde_json::from_str::<LauncherSettings>(&contents) {
+            Ok(s) => (s, Vec::new(), true),
+            Err(_) => {
+                let (s, rejected) = recover_settings(&contents);
+                (s, rejected, false)
+            }
+        };
+    fixes.extend(settings.validate());
+    if !fixes.is_empty() {
+        quarantine_and_save(&path, &contents, &settings, &fixes);
+    } else if !parsed_whole {
+        // Only required fields were missing: fill them in quietly.
+        let _ = save_settings(&settings);
+    }
+    Ok(settings)
+}
+
+/// What the last `load_settings` corrected, if anything; returned once.
+pub fn take_repair_notice() -> Option<String> {
+    let _ = load_settings();
+    REPAIR_NOTICE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take()
+}
+
+impl LauncherSettings {
+    /// Pulls hand-edited values back into range. Returns a line per corrected field.
+    fn validate(&mut self) -> Vec<String> {
+        let mut fixes = Vec::new();
+
+        let network = self.network.clamped();
+        if network != self.network {
+            self.network = network;
+            fixes.push("network: таймауты вне допустимых пределов".to_string());
+        }
+        let game = &mut self.game;
+        if !(1..=600).contains(&game.auto_close_delay_secs) {
+            game.auto_close_delay_secs = game.auto_close_delay_secs.clamp(1, 600);
+            fixes.push("game.auto_close_delay_secs: вне 1–600 с".to_string());
+        }
+        if validate_guest_username(&game.guest_username).is_err() {
+            game.guest_username = default_guest_username();
+            fixes.push("game.guest_username: недопустимый ник".to_string());
+        }
+        if crate::core::process_priority::parse_cpu_list(&game.cpu_affinity).is_err() {
+            game.cpu_affinity.clear();
+            fixes.push("game.cpu_affinity: неверный список CPU".to_string());
+        }
+        let before = game.extra_cvars.len();
+        game.extra_cvars.retain(|line| {
+            let trimmed = line.trim();
+            trimmed.is_empty()
+                || trimmed.starts_with('#')
+                || crate::ss14::cvars::parse_cvar(trimmed).is_ok()
+        });
+        if game.extra_cvars.len() != before {
+            fixes.push("game.extra_cvars: убраны неверные строки".to_string());
+        }
+        let retention = &mut self.retention;
+        if retention.logs_days > MAX_RETENTION_DAYS || retention.history_days > MAX_RETENTION_DAYS {
+            retention.logs_days = retention.logs_days.min(MAX_RETENTION_DAYS);
+            retention.history_days = retention.history_days.min(MAX_RETENTION_DAYS);
+            fixes.push(format!(
+                "retention: срок хранения больше {MAX_RETENTION_DAYS} дней"
+            ));
+        }
+        fixes
+    }
+}
+
+/// Builds settings from a file that does not parse as a whole: defaults, with every value
+/// from the file that parses on its own put on top. Returns the rejected values too.
+fn recover_settings(contents: &str) -> (LauncherSettings, Vec<String>) {
+    let defaults = LauncherSettings::default();
+    let (Ok(user @ Value::Object(_)), Ok(mut merged)) = (
+        serde_json::from_str::<Value>(contents),
+        serde_json::to_value(&defaults),
+    ) else {
+        return (
+            defaults,
+            vec!["файл повреждён, все настройки сброшены".to_string()],
+        );
+    };
+
+    let mut rejected = Vec::new();
+    merge_valid(&mut merged, &mut Vec::new(), &user, &mut rejected);
+    let rejected = rejected
+        .into_iter()
+        .map(|path| format!("{path}: недопустимое значение"))
+        .collect();
+    (serde_json::from_value(merged).unwrap_or(defaults), rejected)
+}
+
+fn merge_valid(root: &mut Value, path: &mut Vec<String>, user: &Value, rejected: &mut Vec<String>) {
+    let Some(fields) = user.as_object() else {
+        return;
+    };
+    for (key, value) in fields {
+        path.push(key.clone());
+        let target_is_object = path
+            .iter()
+            .try_fold(&*root, |v, key| v.get(key))
+            .is_some_and(Value::is_object);
+        if value.is_object() && target_is_object {
+            merge_valid(root, path, value, rejected);
+        } else {
+            let mut candidate = root.clone();
+            if let Some(parent) =
+                pointer_mut(&mut candidate, &path[..path.len() - 1]).and_then(Value::as_object_mut)
+            {
+                parent.insert(key.clone(), value.clone());
+                if LauncherSettings::deserialize(&candidate).is_ok() {
+                    *root = candidate;
+                } else {
+                    rejected.push(path.join("."));
+                }
+            }
+        }
+        path.pop();
+    }
+}
+
+fn pointer_mut<'a>(root: &'a mut Value, path: &[String]) -> Option<&'a mut Value> {
+    path.iter().try_fold(root, |v, key| v.get_mut(key))
+}
+
+fn quarantine_and_save(path: &Path, contents: &str, settings: &LauncherSettings, fixes: &[String]) {
+    let backup = path.with_file_name(format!(
+        "{SETTINGS_FILE_NAME}.invalid-{}",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    let kept = fs::write(&backup, contents).is_ok();
+    let _ = save_settings(settings);
+
+    let mut notice = format!("настройки исправлены: {}", fixes.join("; "));
+    if kept {
+        notice.push_str(&format!(
+            ". Исходный файл сохранён как {}",
+            backup.display()
+        ));
+    }
+    *REPAIR_NOTICE.lock().unwrap_or_else(|e| e.into_inner()) = Some(notice);
 }
 
 pub fn save_settings(settings: &LauncherSettings) -> Result<(), String> {
     let dir = crate::app_paths::data_dir()?;
-    fs::create_dir_all(&dir).map_err(|e| format!("mkdir настройки: {e}"))?;
+    crate::app_paths::create_dir(&dir, "настройки")?;
 
     let path = settings_file_path()?;
     let json =
         serde_json::to_string_pretty(settings).map_err(|e| format!("serialize настройки: {e}"))?;
     fs::write(&path, json).map_err(|e| format!("запись настроек: {e}"))?;
+    *NETWORK_SETTINGS.lock().unwrap_or_else(|e| e.into_inner()) = Some(settings.network.clamped());
 
     Ok(())
 }
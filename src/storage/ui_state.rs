@@ -0,0 +1,50 @@
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+const UI_STATE_FILE_NAME: &str = "ui_state.json";
+
+/// Where the user left the launcher; restored on the next start.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UiState {
+    #[serde(default)]
+    pub main_tab: String,
+    #[serde(default)]
+    pub settings_tab: String,
+    #[serde(default)]
+    pub home_scroll_top: f64,
+    /// The user changed the language filter at least once, so it no longer starts
+    /// preset to the system language.
+    #[serde(default)]
+    pub lang_filter_touched: bool,
+}
+
+pub fn load_ui_state() -> UiState {
+    ui_state_file_path()
+        .ok()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Read-modify-write so callers only touch the field they own.
+pub fn update_ui_state(f: impl FnOnce(&mut UiState)) -> Result<(), String> {
+    let mut state = load_ui_state();
+    let before = state.clone();
+    f(&mut state);
+    if state == before {
+        return Ok(());
+    }
+
+    let dir = crate::app_paths::data_dir()?;
+    crate::app_paths::create_dir(&dir, "состояние окна")?;
+
+    let json = serde_json::to_string_pretty(&state)
+        .map_err(|e| format!("serialize состояние окна: {e}"))?;
+    fs::write(ui_state_file_path()?, json).map_err(|e| format!("запись состояния окна: {e}"))
+}
+
+fn ui_state_file_path() -> Result<PathBuf, String> {
+    Ok(crate::app_paths::data_dir()?.join(UI_STATE_FILE_NAME))
+}
@@ -0,0 +1,43 @@
+use dioxus::prelude::*;
+use uuid::Uuid;
+
+/// Initials badge shown next to account names. The SS14 auth API exposes no profile picture,
+/// so the badge is derived locally: up to two initials on a colour stable per account.
+#[component]
+pub fn AccountBadge(name: String, user_id: Uuid) -> Element {
+    let initials = initials(&name);
+    let hue = badge_hue(user_id);
+    rsx! {
+        span {
+            class: "account-badge",
+            style: "background: hsl({hue}, 45%, 38%);",
+            title: "{name}",
+            {initials}
+        }
+    }
+}
+
+fn initials(name: &str) -> String {
+    let mut words = name
+        .split(|c: char| c.is_whitespace() || c == '_' || c == '-' || c == '.')
+        .filter(|w| !w.is_empty());
+    let first = words.next().and_then(|w| w.chars().next());
+    // Single-word names take the next capital instead ("SpaceCat" → "SC").
+    let second = words
+        .next()
+        .and_then(|w| w.chars().next())
+        .or_else(|| name.chars().skip(1).find(|c| c.is_uppercase()));
+
+    let out: String = first
+        .into_iter()
+        .chain(second)
+        .flat_map(char::to_uppercase)
+        .take(2)
+        .collect();
+    if out.is_empty() { "?".to_string() } else { out }
+}
+
+fn badge_hue(user_id: Uuid) -> u16 {
+    let bytes = user_id.as_bytes();
+    u16::from_le_bytes([bytes[0], bytes[1]]) % 360
+}
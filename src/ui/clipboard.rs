@@ -0,0 +1,127 @@
+use dioxus::prelude::*;
+
+/// Copies text to the system clipboard through the webview.
+pub fn copy_text(text: String) {
+    let js = eval(
+        r#"
+        let text = await dioxus.recv();
+        try { await navigator.clipboard.writeText(text); } catch (_) {}
+        "#,
+    );
+    let _ = js.send(serde_json::Value::String(text));
+}
+
+/// Asks the user for a destination and writes the text there.
+/// Returns `Ok(None)` if the dialog was cancelled.
+pub async fn save_text_as(default_name: &str, text: String) -> Result<Option<String>, String> {
+    save_as(default_name, ("text", &["txt", "log"]), text).await
+}
+
+/// Like `save_text_as`, offering `.json`.
+pub async fn save_json_as(default_name: &str, text: String) -> Result<Option<String>, String> {
+    save_as(default_name, ("json", &["json"]), text).await
+}
+
+/// Asks the user for a `.json` file and reads it. Returns `Ok(None)` if the dialog was cancelled.
+pub async fn open_json_file(title: &str) -> Result<Option<String>, String> {
+    let Some(handle) = rfd::AsyncFileDialog::new()
+        .set_title(title)
+        .add_filter("json", &["json"])
+        .pick_file()
+        .await
+    else {
+        return Ok(None);
+    };
+
+    let path = handle.path().to_path_buf();
+    tokio::task::spawn_blocking({
+        let path = path.clone();
+        move || std::fs::read_to_string(&path)
+    })
+    .await
+    .map_err(|e| format!("ошибка задачи: {e}"))?
+    .map(Some)
+    .map_err(|e| format!("чтение {:?}: {e}", path))
+}
+
+async fn save_as(
+    default_name: &str,
+    (filter_name, extensions): (&str, &[&str]),
+    text: String,
+) -> Result<Option<String>, String> {
+    let Some(handle) = rfd::AsyncFileDialog::new()
+        .set_file_name(default_name)
+        .add_filter(filter_name, extensions)
+        .save_file()
+        .await
+    else {
+        return Ok(None);
+    };
+
+    let path = handle.path().to_path_buf();
+    tokio::task::spawn_blocking({
+        let path = path.clone();
+        move || std::fs::write(&path, text)
+    })
+    .await
+    .map_err(|e| format!("ошибка задачи: {e}"))?
+    .map_err(|e| format!("запись {:?}: {e}", path))?;
+
+    Ok(Some(path.display().to_string()))
+}
+
+/// Asks the user to pick a file. Returns `None` if the dialog was cancelled.
+pub async fn pick_file(title: &str) -> Option<std::path::PathBuf> {
+    rfd::AsyncFileDialog::new()
+        .set_title(title)
+        .pick_file()
+        .await
+        .map(|handle| handle.path().to_path_buf())
+}
+
+/// Asks the user to pick a folder. Returns `None` if the dialog was cancelled.
+pub async fn pick_folder(title: &str) -> Option<std::path::PathBuf> {
+    rfd::AsyncFileDialog::new()
+        .set_title(title)
+        .pick_folder()
+        .await
+        .map(|handle| handle.path().to_path_buf())
+}
+
+/// Question asked before the content of every server is deleted.
+pub const CLEAR_CONTENT_QUESTION: &str =
+    "Удалить скачанный контент всех серверов? При следующем подключении он скачается заново.";
+
+/// OK/Cancel question in a native dialog; `true` when the user pressed OK.
+pub async fn confirm(description: &str) -> bool {
+    rfd::AsyncMessageDialog::new()
+        .set_level(rfd::MessageLevel::Warning)
+        .set_title(crate::constants::APP_TITLE)
+        .set_description(description)
+        .set_buttons(rfd::MessageButtons::OkCancel)
+        .show()
+        .await
+        == rfd::MessageDialogResult::Ok
+}
+
+/// Scrolls an element to its bottom edge (used for auto-scrolling logs).
+pub fn scroll_to_bottom(element_id: &str) {
+    let _ = eval(&format!(
+        "let el = document.getElementById({element_id:?}); if (el) {{ el.scrollTop = el.scrollHeight; }}"
+    ));
+}
+
+/// Reads an element's vertical scroll offset.
+pub async fn scroll_top(element_id: &str) -> Option<f64> {
+    let mut js = eval(&format!(
+        "let el = document.getElementById({element_id:?}); dioxus.send(el ? el.scrollTop : null);"
+    ));
+    js.recv().await.ok()?.as_f64()
+}
+
+/// Restores an element's vertical scroll offset.
+pub fn set_scroll_top(element_id: &str, top: f64) {
+    let _ = eval(&format!(
+        "let el = document.getElementById({element_id:?}); if (el) {{ el.scrollTop = {top}; }}"
+    ));
+}
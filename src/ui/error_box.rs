@@ -0,0 +1,96 @@
+use dioxus::prelude::*;
+
+use crate::constants::APP_VERSION_LABEL;
+use crate::ui::clipboard;
+
+/// What failed and where. Built from the `String` errors the backend returns: those are
+/// formatted as `"<url>: status <code> ..."`, so the URL and HTTP status are recovered here.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorContext {
+    pub operation: String,
+    pub message: String,
+    pub url: Option<String>,
+    pub status: Option<u16>,
+}
+
+impl ErrorContext {
+    pub fn from_message(operation: &str, message: &str) -> Self {
+        Self {
+            operation: operation.to_string(),
+            message: message.to_string(),
+            url: find_url(message),
+            status: find_status(message),
+        }
+    }
+
+    /// Plain-text block for bug reports.
+    pub fn report(&self) -> String {
+        let mut out = format!("операция: {}\n", self.operation);
+        if let Some(url) = &self.url {
+            out.push_str(&format!("url: {url}\n"));
+        }
+        if let Some(status) = self.status {
+            out.push_str(&format!("http status: {status}\n"));
+        }
+        out.push_str(&format!(
+            "лаунчер: {APP_VERSION_LABEL} ({} {})\n",
+            std::env::consts::OS,
+            std::env::consts::ARCH
+        ));
+        out.push_str(&format!(
+            "время: {}\n\n",
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+        ));
+        out.push_str(&self.message);
+        out
+    }
+}
+
+fn find_url(message: &str) -> Option<String> {
+    let start = message
+        .find("https://")
+        .or_else(|| message.find("http://"))?;
+    let url: String = message[start..]
+        .chars()
+        .take_while(|c| !c.is_whitespace() && !matches!(c, '"' | '\'' | ')' | ','))
+        .collect();
+    // "<url>: status ..." leaves the separator glued to the URL.
+    Some(url.trim_end_matches([':', ';', '.']).to_string())
+}
+
+fn find_status(message: &str) -> Option<u16> {
+    let lower = message.to_lowercase();
+    lower.match_indices("status").find_map(|(idx, key)| {
+        let rest = lower[idx + key.len()..].trim_start_matches([':', ' ']);
+        let code: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+        code.parse::<u16>().ok().filter(|c| (100..600).contains(c))
+    })
+}
+
+/// Error block with a button that copies the message together with its context.
+#[component]
+pub fn ErrorBox(operation: String, message: String) -> Element {
+    let mut copied = use_signal(|| false);
+    let context = ErrorContext::from_message(&operation, &message);
+    let report = context.report();
+
+    rsx! {
+        div { class: "status status-error status-block error-box",
+            div { class: "error-box-text selectable", {message} }
+            div { class: "error-box-meta",
+                span { class: "muted", {context.operation.clone()} }
+                if let Some(status) = context.status {
+                    span { class: "muted", {format!("HTTP {status}")} }
+                }
+                button {
+                    class: "ghost small",
+                    onclick: move |_| {
+                        clipboard::copy_text(report.clone());
+                        copied.set(true);
+                    },
+                    { if copied() { "Скопировано" } else { "Копировать" } }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use dioxus::prelude::*;
+
+use crate::build_overrides::{self, BuildOverrides};
+use crate::ui::error_box::ErrorBox;
+
+/// Advanced settings of one server: content URLs that replace what its `/info` announces.
+#[component]
+pub fn BuildOverridesModal(address: String, on_close: EventHandler<()>) -> Element {
+    let saved = use_hook({
+        let address = address.clone();
+        move || build_overrides::load_build_overrides(&address)
+    });
+    let mut download_url = use_signal(|| saved.download_url.clone().unwrap_or_default());
+    let mut manifest_url = use_signal(|| saved.manifest_url.clone().unwrap_or_default());
+    let mut error: Signal<Option<String>> = use_signal(|| None);
+
+    let save = {
+        let address = address.clone();
+        move |overrides: BuildOverrides| match build_overrides::save_build_overrides(
+            &address, overrides,
+        ) {
+            Ok(()) => on_close.call(()),
+            Err(e) => error.set(Some(e)),
+        }
+    };
+    let mut save_fields = save.clone();
+    let mut save_reset = save;
+
+    rsx! {
+        div { class: "modal-backdrop", onclick: move |_| on_close.call(()),
+            div { class: "modal filter-modal", onclick: move |evt| evt.stop_propagation(),
+                div { class: "modal-header",
+                    div {
+                        h3 { "Адреса сборки" }
+                        p { class: "muted selectable", {address.clone()} }
+                    }
+                }
+                div { class: "modal-body",
+                    p { class: "muted",
+                        "Для серверов за CDN или обратным прокси: заменяют адреса из /info сервера. Пустое поле — как объявил сервер."
+                    }
+                    label { "build.download_url" }
+                    input {
+                        class: "input text-input",
+                        r#type: "text",
+                        placeholder: "https://mirror.example/client.zip",
+                        value: download_url(),
+                        oninput: move |evt| {
+                            download_url.set(evt.value());
+                            error.set(None);
+                        },
+                    }
+                    label { "build.manifest_url" }
+                    input {
+                        class: "input text-input",
+                        r#type: "text",
+                        placeholder: "https://mirror.example/manifest.txt",
+                        value: manifest_url(),
+                        oninput: move |evt| {
+                            manifest_url.set(evt.value());
+                            error.set(None);
+                        },
+                    }
+                    if let Some(err) = error() {
+                        ErrorBox { operation: "адреса сборки", message: err }
+                    }
+                }
+                div { class: "modal-actions",
+                    button {
+                        class: "ghost modal-actions-left",
+                        disabled: saved.is_empty(),
+                        onclick: move |_| save_reset(BuildOverrides::default()),
+                        "Сбросить"
+                    }
+                    button { class: "ghost", onclick: move |_| on_close.call(()), "Отмена" }
+                    button {
+                        class: "primary",
+                        onclick: move |_| {
+                            save_fields(BuildOverrides {
+                                download_url: Some(download_url()),
+                                manifest_url: Some(manifest_url()),
+                            })
+                        },
+                        "Сохранить"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use dioxus::prelude::*;
+
+use crate::net::diagnostics::{self, CheckResult, CheckStatus};
+
+/// Runs the staged connection checks for `address` and lists results as they arrive.
+#[component]
+pub fn ConnectionTestModal(
+    address: String,
+    has_account: bool,
+    on_close: EventHandler<()>,
+) -> Element {
+    let mut results: Signal<Vec<CheckResult>> = use_signal(Vec::new);
+    let mut running = use_signal(|| false);
+    let mut run_id = use_signal(|| 0u32);
+
+    let address_for_run = address.clone();
+    use_effect(move || {
+        let _ = run_id();
+        let address = address_for_run.clone();
+        results.set(Vec::new());
+        running.set(true);
+
+        spawn(async move {
+            let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<CheckResult>();
+            let worker = tokio::task::spawn_blocking(move || {
+                diagnostics::run_connection_test(&address, has_account, tx)
+            });
+
+            while let Some(step) = rx.recv().await {
+                results.write().push(step);
+            }
+            let _ = worker.await;
+            running.set(false);
+        });
+    });
+
+    rsx! {
+        div { class: "modal-backdrop",
+            div { class: "modal connect-modal",
+                div { class: "modal-header",
+                    div {
+                        h3 { "проверка соединения" }
+                        p { class: "muted selectable", {address.clone()} }
+                    }
+                }
+
+                div { class: "modal-body",
+                    div { class: "check-list",
+                        for (idx, r) in results().into_iter().enumerate() {
+                            div { key: "{idx}", class: "check-row",
+                                span {
+                                    class: match r.status {
+                                        CheckStatus::Pass => "check-status pass",
+                                        CheckStatus::Warn => "check-status warn",
+                                        CheckStatus::Fail => "check-status fail",
+                                        CheckStatus::Skipped => "check-status skipped",
+                                    },
+                                    {r.status.label_ru()}
+                                }
+                                span { class: "check-step", {r.step} }
+                                span { class: "check-detail selectable", {r.detail} }
+                            }
+                        }
+                    }
+
+                    if running() {
+                        div { class: "progress-indeterminate",
+                            div { class: "progress-indeterminate-bar" }
+                        }
+                    }
+                }
+
+                div { class: "modal-actions",
+                    button {
+                        class: "ghost",
+                        disabled: running(),
+                        onclick: move |_| run_id += 1,
+                        "повторить"
+                    }
+                    button {
+                        class: "ghost",
+                        onclick: move |_| on_close.call(()),
+                        "закрыть"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use dioxus::prelude::*;
+
+use crate::servers::{self, HubError, HubListError, ServerListFetch};
+use crate::ui::error_box::ErrorBox;
+
+/// Why the server list could not be loaded, one block per hub, each with its own retry.
+/// A successful retry hands the list to `on_loaded`; a failed one replaces that hub's
+/// entry through `on_failed`.
+#[component]
+pub(super) fn HubErrorList(
+    error: HubListError,
+    on_loaded: EventHandler<ServerListFetch>,
+    on_failed: EventHandler<HubError>,
+) -> Element {
+    // Hub being retried; one at a time so two lists never race into the signal.
+    let mut retrying: Signal<Option<String>> = use_signal(|| None);
+
+    rsx! {
+        div { class: "hub-errors",
+            if let Some(general) = error.general.clone() {
+                ErrorBox { operation: "загрузка списка серверов", message: general }
+            }
+            if !error.hubs.is_empty() {
+                p { class: "muted", {format!("ни один хаб не ответил ({}):", error.hubs.len())} }
+            }
+            for hub_error in error.hubs {
+                {
+                    let hub = hub_error.hub.clone();
+                    let busy = retrying().as_deref() == Some(hub.as_str());
+                    rsx! {
+                        div { key: "{hub}", class: "hub-error",
+                            ErrorBox {
+                                operation: format!("загрузка списка серверов: {hub}"),
+                                message: hub_error.to_string(),
+                            }
+                            button {
+                                class: "ghost small",
+                                disabled: retrying().is_some(),
+                                onclick: move |_| {
+                                    let hub = hub.clone();
+                                    retrying.set(Some(hub.clone()));
+                                    spawn(async move {
+                                        match servers::fetch_server_list_from(&hub, |_| {}).await {
+                                            Ok(fetch) => on_loaded.call(fetch),
+                                            Err(e) => on_failed.call(e),
+                                        }
+                                        retrying.set(None);
+                                    });
+                                },
+                                {if busy { "повтор..." } else { "Повторить" }}
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
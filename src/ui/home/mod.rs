@@ -1,4 +1,16 @@
-mod helpers;
+mod build_overrides;
+mod connection_test;
+pub(crate) mod helpers;
+mod hub_errors;
+mod log_ring;
+mod palette;
+mod prefetch_queue;
+mod running_game;
+mod search;
+mod server_diff;
+mod server_list;
+mod store;
 mod tab;
 
+pub use store::{use_home_store, HomeStore};
 pub use tab::tab_home;
@@ -0,0 +1,139 @@
+use std::cmp::Reverse;
+
+use dioxus::prelude::*;
+
+const PALETTE_INPUT_ID: &str = "command-palette-input";
+const MAX_RESULTS: usize = 12;
+
+/// What picking a palette entry does; executed by the home tab.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum PaletteAction {
+    Connect(String),
+    DirectConnect,
+    OpenSettings,
+    ClearContentCache,
+    OpenDataDir,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct PaletteItem {
+    pub title: String,
+    pub subtitle: String,
+    pub action: PaletteAction,
+}
+
+/// Subsequence match: every query char must appear in order. Consecutive runs and matches at
+/// word starts score higher; `None` means no match.
+pub(crate) fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let query: Vec<char> = query.trim().to_lowercase().chars().collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    let mut score = 0;
+    let mut qi = 0;
+    let mut prev_match: Option<usize> = None;
+    for (ti, c) in text.iter().enumerate() {
+        if qi == query.len() {
+            break;
+        }
+        if *c != query[qi] {
+            continue;
+        }
+        score += 1;
+        if prev_match == Some(ti.wrapping_sub(1)) {
+            score += 4;
+        }
+        if ti == 0 || !text[ti - 1].is_alphanumeric() {
+            score += 3;
+        }
+        prev_match = Some(ti);
+        qi += 1;
+    }
+    (qi == query.len()).then_some(score - text.len() as i32 / 16)
+}
+
+/// Ctrl+K quick-connect palette over servers, recent addresses and launcher actions.
+#[component]
+pub(crate) fn CommandPalette(
+    items: Vec<PaletteItem>,
+    on_pick: EventHandler<PaletteAction>,
+    on_close: EventHandler<()>,
+) -> Element {
+    let mut query = use_signal(String::new);
+    let mut selected = use_signal(|| 0usize);
+
+    use_effect(|| {
+        let _ = eval(&format!(
+            "setTimeout(() => document.getElementById({PALETTE_INPUT_ID:?})?.focus(), 0);"
+        ));
+    });
+
+    let q = query();
+    let mut matches: Vec<(i32, &PaletteItem)> = items
+        .iter()
+        .filter_map(|item| {
+            let title = fuzzy_score(&q, &item.title);
+            let sub = fuzzy_score(&q, &item.subtitle).map(|s| s - 2);
+            title.max(sub).map(|s| (s, item))
+        })
+        .collect();
+    // Stable sort keeps the caller's order (actions, recent, servers) among equal scores.
+    matches.sort_by_key(|(score, _)| Reverse(*score));
+    let results: Vec<PaletteItem> = matches
+        .into_iter()
+        .take(MAX_RESULTS)
+        .map(|(_, item)| item.clone())
+        .collect();
+    let current = selected().min(results.len().saturating_sub(1));
+    let picked = results.get(current).map(|i| i.action.clone());
+
+    rsx! {
+        div { class: "modal-backdrop", onclick: move |_| on_close.call(()),
+            div { class: "modal command-palette", onclick: move |evt| evt.stop_propagation(),
+                input {
+                    id: PALETTE_INPUT_ID,
+                    class: "input",
+                    placeholder: "сервер, адрес или действие…",
+                    value: "{q}",
+                    oninput: move |evt| {
+                        query.set(evt.value());
+                        selected.set(0);
+                    },
+                    onkeydown: move |evt| match evt.key() {
+                        Key::ArrowDown => selected.set(current + 1),
+                        Key::ArrowUp => selected.set(current.saturating_sub(1)),
+                        Key::Escape => on_close.call(()),
+                        Key::Enter => {
+                            if let Some(action) = picked.clone() {
+                                on_pick.call(action);
+                            }
+                        }
+                        _ => {}
+                    },
+                }
+                if results.is_empty() {
+                    p { class: "muted", "ничего не найдено" }
+                }
+                div { class: "palette-results",
+                    for (idx, item) in results.into_iter().enumerate() {
+                        {
+                            let action = item.action.clone();
+                            rsx! {
+                                button {
+                                    class: if idx == current { "dropdown-item selected" } else { "dropdown-item" },
+                                    onmouseenter: move |_| selected.set(idx),
+                                    onclick: move |_| on_pick.call(action.clone()),
+                                    span { {item.title} }
+                                    span { class: "dropdown-item-sub", {item.subtitle} }
+                                }
+                            }
+                        }
+                    }
+                }
+                p { class: "muted palette-hint", "↑↓ — выбор · Enter — выполнить · Esc — закрыть" }
+            }
+        }
+    }
+}
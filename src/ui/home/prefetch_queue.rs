@@ -0,0 +1,80 @@
+use std::time::Duration;
+
+use dioxus::prelude::*;
+
+use crate::net::prefetch_queue::{self, QueueItem, QueueStatus};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Servers queued for preparation with their state; hidden while the queue is empty.
+/// Download progress of the running item is in the tasks panel.
+#[component]
+pub(super) fn PrefetchQueuePanel() -> Element {
+    let mut items: Signal<Vec<QueueItem>> = use_signal(Vec::new);
+
+    use_future(move || async move {
+        loop {
+            let next = prefetch_queue::snapshot();
+            if *items.peek() != next {
+                items.set(next);
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    });
+
+    let list = items();
+    if list.is_empty() {
+        return rsx! {};
+    }
+    let pending = list
+        .iter()
+        .filter(|i| matches!(i.status, QueueStatus::Queued | QueueStatus::Running))
+        .count();
+    let has_finished = pending < list.len();
+
+    rsx! {
+        div { class: "prefetch-queue",
+            div { class: "prefetch-queue-head",
+                span { class: "muted", {format!("Очередь подготовки: осталось {pending}")} }
+                button {
+                    class: "ghost small",
+                    disabled: !has_finished,
+                    onclick: move |_| {
+                        prefetch_queue::clear_finished();
+                        items.set(prefetch_queue::snapshot());
+                    },
+                    "Убрать завершённые"
+                }
+            }
+            for item in list {
+                {
+                    let id = item.id;
+                    let (status_text, status_class) = match &item.status {
+                        QueueStatus::Queued => ("в очереди".to_string(), "muted"),
+                        QueueStatus::Running => ("скачивается…".to_string(), "muted"),
+                        QueueStatus::Done => ("готово".to_string(), "muted"),
+                        QueueStatus::Cancelled => ("отменено".to_string(), "muted"),
+                        QueueStatus::Failed(e) => (format!("ошибка: {e}"), "prefetch-failed"),
+                    };
+                    let cancellable = matches!(item.status, QueueStatus::Queued | QueueStatus::Running);
+                    rsx! {
+                        div { key: "{id}", class: "hub-row prefetch-queue-row",
+                            span { title: item.address.clone(), {item.name.clone()} }
+                            span { class: "{status_class} selectable", {status_text} }
+                            if cancellable {
+                                button {
+                                    class: "ghost small",
+                                    onclick: move |_| {
+                                        prefetch_queue::cancel(id);
+                                        items.set(prefetch_queue::snapshot());
+                                    },
+                                    "Отменить"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use std::time::Duration;
+
+use dioxus::prelude::*;
+
+use crate::net::launch_guard::{self, LaunchRecord, RunningSession};
+use crate::ui::clipboard;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Build the running game was launched with; hidden while no game is running.
+#[component]
+pub(super) fn RunningGamePanel() -> Element {
+    let mut session: Signal<Option<RunningSession>> = use_signal(|| None);
+
+    use_future(move || async move {
+        loop {
+            let next = tokio::task::spawn_blocking(launch_guard::running_session)
+                .await
+                .ok()
+                .flatten();
+            if *session.peek() != next {
+                session.set(next);
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    });
+
+    let RunningSession { pid, record } = session()?;
+    let started = chrono::DateTime::<chrono::Local>::from(record.started_at)
+        .format("%H:%M")
+        .to_string();
+    let summary = build_summary(&record);
+
+    rsx! {
+        div { class: "running-game",
+            div { class: "running-game-head",
+                span { title: record.address.clone(),
+                    {format!("Игра запущена в {started} (pid {pid})")}
+                }
+                button {
+                    class: "ghost small",
+                    title: "скопировать для отчёта об ошибке",
+                    onclick: {
+                        let text = format!("{}\n{summary}", record.address);
+                        move |_| clipboard::copy_text(text.clone())
+                    },
+                    "Копировать"
+                }
+            }
+            span { class: "muted selectable", {summary.clone()} }
+        }
+    }
+}
+
+fn build_summary(record: &LaunchRecord) -> String {
+    format!(
+        "движок {} · {} {} · контент {}",
+        record.engine_version,
+        record.fork_id,
+        record.fork_version,
+        record.content_hash.as_deref().unwrap_or("без хэша"),
+    )
+}
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::servers::{ServerEntry, ServerInfoSummary, fetch_server_summary};
+
+/// `/info` requests in flight at once while filling descriptions and auth modes.
+const DESCRIPTION_FETCH_BATCH: usize = 8;
+
+/// Placeholder stored when a server has no description, so it is not asked again.
+pub(super) const NO_DESCRIPTION: &str = "Описание не указано";
+
+/// Case-insensitive substring match of an already lowercased `needle`.
+pub(super) fn description_matches(srv: &ServerEntry, needle: &str) -> bool {
+    srv.description
+        .as_deref()
+        .is_some_and(|d| d != NO_DESCRIPTION && d.to_lowercase().contains(needle))
+}
+
+/// Splits `text` into `(segment, is_match)` parts around case-insensitive occurrences of
+/// `needle` (lowercase). Works on chars so the split never lands inside a UTF-8 sequence.
+pub(super) fn highlight_segments(text: &str, needle: &str) -> Vec<(String, bool)> {
+    let chars: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = chars
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+    let needle: Vec<char> = needle.chars().collect();
+    if needle.is_empty() || needle.len() > chars.len() {
+        return vec![(text.to_string(), false)];
+    }
+
+    let mut out = Vec::new();
+    let mut plain_start = 0;
+    let mut i = 0;
+    while i + needle.len() <= chars.len() {
+        if lower[i..i + needle.len()] == needle[..] {
+            if plain_start < i {
+                out.push((chars[plain_start..i].iter().collect(), false));
+            }
+            out.push((chars[i..i + needle.len()].iter().collect(), true));
+            i += needle.len();
+            plain_start = i;
+        } else {
+            i += 1;
+        }
+    }
+    if plain_start < chars.len() {
+        out.push((chars[plain_start..].iter().collect(), false));
+    }
+    out
+}
+
+/// Fetches `/info` for `addresses` a batch at a time and hands each finished batch to
+/// `on_batch` together with the number of servers asked. Servers that fail to answer are
+/// left out.
+pub(super) async fn fetch_summaries(
+    addresses: Vec<String>,
+    mut on_batch: impl FnMut(HashMap<String, ServerInfoSummary>, usize),
+) {
+    for chunk in addresses.chunks(DESCRIPTION_FETCH_BATCH) {
+        let jobs: Vec<_> = chunk
+            .iter()
+            .cloned()
+            .map(|address| {
+                tokio::spawn(async move {
+                    let summary = fetch_server_summary(&address).await.ok()?;
+                    Some((address, summary))
+                })
+            })
+            .collect();
+
+        let mut found = HashMap::new();
+        for job in jobs {
+            if let Ok(Some((address, summary))) = job.await {
+                found.insert(address, summary);
+            }
+        }
+        on_batch(found, chunk.len());
+    }
+}
+
+/// Stores fetched summaries in the list: description (`NO_DESCRIPTION` when the server has
+/// none), auth mode and privacy details.
+pub(super) fn apply_summaries(
+    list: &mut [Arc<ServerEntry>],
+    found: &HashMap<String, ServerInfoSummary>,
+) {
+    for srv in list.iter_mut() {
+        if let Some(summary) = found.get(&srv.address) {
+            let srv = Arc::make_mut(srv);
+            srv.description = Some(
+                summary
+                    .description
+                    .clone()
+                    .unwrap_or_else(|| NO_DESCRIPTION.to_string()),
+            );
+            srv.auth_mode = Some(summary.auth_mode);
+            srv.privacy_policy = summary.privacy_policy.clone();
+            srv.privacy_flags = summary.privacy_flags;
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::servers::ServerEntry;
+
+/// Player count jumps smaller than this are noise and not highlighted.
+const MIN_PLAYER_DELTA: u32 = 5;
+
+/// What changed for a server between two refreshes of the list.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) enum ServerChange {
+    /// Not in the previous list at all.
+    Appeared,
+    CameOnline,
+    WentOffline,
+    PlayersUp(u32),
+    PlayersDown(u32),
+}
+
+impl ServerChange {
+    pub(super) fn css_class(self) -> &'static str {
+        match self {
+            ServerChange::Appeared | ServerChange::CameOnline | ServerChange::PlayersUp(_) => {
+                "change-up"
+            }
+            ServerChange::WentOffline | ServerChange::PlayersDown(_) => "change-down",
+        }
+    }
+
+    pub(super) fn label(self) -> String {
+        match self {
+            ServerChange::Appeared => "новый".to_string(),
+            ServerChange::CameOnline => "снова в сети".to_string(),
+            ServerChange::WentOffline => "офлайн".to_string(),
+            ServerChange::PlayersUp(n) => format!("+{n}"),
+            ServerChange::PlayersDown(n) => format!("−{n}"),
+        }
+    }
+}
+
+/// Servers worth highlighting after a refresh, keyed by address. A player count change is
+/// significant when it is at least `MIN_PLAYER_DELTA` and a fifth of the previous count.
+/// Nothing is highlighted when there was no previous list to compare with.
+pub(super) fn diff_servers(
+    old: &[Arc<ServerEntry>],
+    new: &[Arc<ServerEntry>],
+) -> HashMap<String, ServerChange> {
+    if old.is_empty() {
+        return HashMap::new();
+    }
+    let previous: HashMap<&str, &ServerEntry> =
+        old.iter().map(|s| (s.address.as_str(), &**s)).collect();
+
+    let mut changes = HashMap::new();
+    for srv in new {
+        let change = match previous.get(srv.address.as_str()) {
+            None if srv.online => Some(ServerChange::Appeared),
+            None => None,
+            Some(prev) if !prev.online && srv.online => Some(ServerChange::CameOnline),
+            Some(prev) if prev.online && !srv.online => Some(ServerChange::WentOffline),
+            Some(prev) => {
+                let threshold = MIN_PLAYER_DELTA.max(prev.players / 5);
+                if srv.players >= prev.players + threshold {
+                    Some(ServerChange::PlayersUp(srv.players - prev.players))
+                } else if prev.players >= srv.players + threshold {
+                    Some(ServerChange::PlayersDown(prev.players - srv.players))
+                } else {
+                    None
+                }
+            }
+        };
+        if let Some(change) = change {
+            changes.insert(srv.address.clone(), change);
+        }
+    }
+    changes
+}
+
+/// Keeps what was fetched lazily for the old entries (description, auth mode, GeoIP
+/// region) so a refresh does not throw it away.
+pub(super) fn carry_over_details(old: &[Arc<ServerEntry>], new: &mut [Arc<ServerEntry>]) {
+    let previous: HashMap<&str, &ServerEntry> =
+        old.iter().map(|s| (s.address.as_str(), &**s)).collect();
+
+    for srv in new.iter_mut() {
+        let Some(prev) = previous.get(srv.address.as_str()) else {
+            continue;
+        };
+        // Entries are fresh from the fetch, so make_mut does not clone them.
+        let srv = Arc::make_mut(srv);
+        if srv.description.is_none() {
+            srv.description = prev.description.clone();
+        }
+        // Auth mode and privacy details come from the same `/info` request.
+        if srv.auth_mode.is_none() {
+            srv.auth_mode = prev.auth_mode;
+            srv.privacy_policy = prev.privacy_policy.clone();
+            srv.privacy_flags = prev.privacy_flags;
+        }
+        if srv.region.is_none() && prev.region_inferred {
+            srv.region = prev.region.clone();
+            srv.region_inferred = true;
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::favorites;
+use crate::servers::ServerEntry;
+
+use super::search;
+
+/// Language filter choices: hub `lang:` tag code and label.
+pub(super) const LANGUAGE_FILTERS: [(&str, &str); 7] = [
+    ("en", "English"),
+    ("ru", "Русский"),
+    ("fr", "French"),
+    ("de", "German"),
+    ("pl", "Polish"),
+    ("pt", "Portuguese"),
+    ("uk", "Ukrainian"),
+];
+
+/// Language filter the home tab starts with: the system language while the user has never
+/// touched the filter and it is one of `LANGUAGE_FILTERS`, otherwise none.
+pub(super) fn initial_language_filter() -> Vec<String> {
+    if crate::ui_state::load_ui_state().lang_filter_touched {
+        return Vec::new();
+    }
+    crate::platform::system_language()
+        .filter(|lang| LANGUAGE_FILTERS.iter().any(|(code, _)| code == lang))
+        .into_iter()
+        .collect()
+}
+
+/// Remembers that the language filter was set by hand, so the system default stops applying.
+pub(super) fn mark_language_filter_touched() {
+    let _ = crate::ui_state::update_ui_state(|s| s.lang_filter_touched = true);
+}
+
+/// Server list as held by the home tab. Entries are shared, so reading the signal, filtering
+/// and rendering copy pointers instead of names, tags and descriptions.
+pub(super) type SharedServers = Vec<Arc<ServerEntry>>;
+
+pub(super) fn share(list: Vec<ServerEntry>) -> SharedServers {
+    list.into_iter().map(Arc::new).collect()
+}
+
+/// Filter settings of the home tab, captured once per render.
+pub(super) struct ServerFilter<'a> {
+    /// Lowercase search string; empty matches everything.
+    pub needle: String,
+    pub search_in_desc: bool,
+    /// Region key or `"all"`.
+    pub region: String,
+    /// `lang:<code>` tags, any of which must be present.
+    pub lang_tags: Vec<String>,
+    /// `rp:<level>` tags, any of which must be present.
+    pub rp_tags: Vec<String>,
+    /// Game mode (compared case-insensitively) or `"all"`.
+    pub game_mode: String,
+    pub only_vanilla: bool,
+    pub hide_adult: bool,
+    pub only_online: bool,
+    pub hide_full: bool,
+    pub hide_empty: bool,
+    pub show_hidden: bool,
+    pub hidden: &'a HashSet<String>,
+    pub min_players: u32,
+    pub max_players: Option<u32>,
+}
+
+impl ServerFilter<'_> {
+    pub(super) fn matches(&self, srv: &ServerEntry) -> bool {
+        let has_any_tag = |wanted: &[String]| {
+            wanted.is_empty()
+                || wanted
+                    .iter()
+                    .any(|w| srv.tags.iter().any(|t| t.eq_ignore_ascii_case(w)))
+        };
+
+        (self.region == "all" || srv.region.as_deref() == Some(self.region.as_str()))
+            && (self.game_mode == "all"
+                || srv
+                    .game_mode
+                    .as_deref()
+                    .is_some_and(|m| m.eq_ignore_ascii_case(&self.game_mode)))
+            && (!self.only_vanilla || srv.vanilla)
+            && (!self.hide_adult || !srv.adult)
+            && (!self.only_online || srv.online)
+            && (!self.hide_full || srv.players < srv.max_players)
+            && (!self.hide_empty || srv.players > 0)
+            && srv.players >= self.min_players
+            && self.max_players.is_none_or(|m| srv.players <= m)
+            && has_any_tag(&self.lang_tags)
+            && has_any_tag(&self.rp_tags)
+            && (self.show_hidden
+                || !self
+                    .hidden
+                    .contains(&favorites::canonicalize_favorite_address(&srv.address)))
+            // Text search last: it is the only check that allocates.
+            && self.matches_search(srv)
+    }
+
+    fn matches_search(&self, srv: &ServerEntry) -> bool {
+        let needle = self.needle.as_str();
+        needle.is_empty()
+            || srv.name.to_lowercase().contains(needle)
+            || srv.address.to_lowercase().contains(needle)
+            || srv
+                .tags
+                .iter()
+                .any(|tag| tag.to_lowercase().contains(needle))
+            || (self.search_in_desc && search::description_matches(srv, needle))
+    }
+}
+
+/// Game modes present in `list` for the mode filter, deduplicated case-insensitively.
+pub(super) fn game_modes(list: &[Arc<ServerEntry>]) -> Vec<String> {
+    let mut modes: Vec<String> = Vec::new();
+    for mode in list.iter().filter_map(|s| s.game_mode.as_deref()) {
+        if !modes.iter().any(|m| m.eq_ignore_ascii_case(mode)) {
+            modes.push(mode.to_string());
+        }
+    }
+    modes.sort_by_key(|m| m.to_lowercase());
+    modes
+}
+
+/// Entries per source hub, in the order the hubs first appear in `list`.
+pub(super) fn hub_counts(list: &[Arc<ServerEntry>]) -> Vec<(String, usize)> {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    for srv in list {
+        match counts.iter_mut().find(|(hub, _)| *hub == srv.hub) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((srv.hub.clone(), 1)),
+        }
+    }
+    counts
+}
+
+/// Indices of `list` entries passing `filter`, ordered by `sort_mode`.
+pub(super) fn filter_and_sort(
+    list: &[Arc<ServerEntry>],
+    filter: &ServerFilter,
+    sort_mode: &str,
+) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..list.len())
+        .filter(|&i| filter.matches(&list[i]))
+        .collect();
+
+    match sort_mode {
+        "online_desc" => indices.sort_by(|&a, &b| list[b].players.cmp(&list[a].players)),
+        "online_asc" => indices.sort_by_key(|&i| list[i].players),
+        // Cached keys: one lowercase copy per server instead of two per comparison.
+        "name_asc" => indices.sort_by_cached_key(|&i| list[i].name.to_lowercase()),
+        "name_desc" => {
+            indices.sort_by_cached_key(|&i| std::cmp::Reverse(list[i].name.to_lowercase()))
+        }
+        _ => {}
+    }
+    indices
+}
@@ -0,0 +1,270 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use dioxus::prelude::*;
+
+use crate::content_updates::{self, ContentUpdate};
+use crate::favorites::{self, FavoritesLayout};
+use crate::favorites_sync;
+use crate::servers::{HubListError, fetch_server_list};
+use crate::{geoip, hidden_servers, settings};
+
+use super::search;
+use super::server_diff::{self, ServerChange};
+use super::server_list::{self, SharedServers};
+
+const SERVER_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+const CHANGE_HIGHLIGHT_DURATION: Duration = Duration::from_secs(6);
+/// Favorites' `/info` is polled on every Nth list refresh, not every minute.
+const CONTENT_CHECK_EVERY: u32 = 10;
+
+/// Home tab state that outlives the tab: created once by `use_home_store` in `app` and
+/// handed to `tab_home`, so switching tabs neither refetches the server list nor resets
+/// filters, favorites and expanded descriptions.
+#[derive(Clone, Copy, PartialEq)]
+pub struct HomeStore {
+    pub(super) servers: Signal<SharedServers>,
+    pub(super) loading: Signal<bool>,
+    pub(super) hub_errors: Signal<Option<HubListError>>,
+    pub(super) hub_skip_note: Signal<Option<String>>,
+    pub(super) server_changes: Signal<HashMap<String, ServerChange>>,
+    pub(super) content_updates: Signal<HashMap<String, ContentUpdate>>,
+    pub(super) favorites: Signal<HashSet<String>>,
+    pub(super) favorites_layout: Signal<FavoritesLayout>,
+    pub(super) hidden: Signal<HashSet<String>>,
+    /// Bumped after every favorites sync so the "local only" badge picks up the new merge base.
+    pub(super) fav_sync_done: Signal<u32>,
+    pub(super) expanded_desc: Signal<HashSet<String>>,
+    pub(super) search: Signal<String>,
+    pub(super) search_in_desc: Signal<bool>,
+    pub(super) region: Signal<String>,
+    pub(super) only_online: Signal<bool>,
+    pub(super) game_mode: Signal<String>,
+    pub(super) only_vanilla: Signal<bool>,
+    pub(super) hide_adult: Signal<bool>,
+    pub(super) hide_full: Signal<bool>,
+    pub(super) hide_empty: Signal<bool>,
+    pub(super) min_players: Signal<u32>,
+    pub(super) max_players: Signal<Option<u32>>,
+    pub(super) selected_langs: Signal<Vec<String>>,
+    pub(super) selected_rp: Signal<Vec<String>>,
+    pub(super) sort_mode: Signal<String>,
+    pub(super) show_filters: Signal<bool>,
+    pub(super) show_hidden: Signal<bool>,
+    /// Bumped to load the server list again from scratch, e.g. after every hub failed.
+    pub(super) reload: Signal<u32>,
+}
+
+/// Creates the store and starts loading the server list, its periodic refresh and the
+/// favorites in the calling component's scope.
+pub fn use_home_store() -> HomeStore {
+    let store = HomeStore {
+        servers: use_signal(SharedServers::new),
+        loading: use_signal(|| true),
+        hub_errors: use_signal(|| None),
+        hub_skip_note: use_signal(|| None),
+        server_changes: use_signal(HashMap::new),
+        content_updates: use_signal(HashMap::new),
+        favorites: use_signal(HashSet::new),
+        favorites_layout: use_signal(FavoritesLayout::default),
+        hidden: use_signal(HashSet::new),
+        fav_sync_done: use_signal(|| 0),
+        expanded_desc: use_signal(HashSet::new),
+        search: use_signal(String::new),
+        search_in_desc: use_signal(|| false),
+        region: use_signal(|| "all".to_string()),
+        only_online: use_signal(|| false),
+        game_mode: use_signal(|| "all".to_string()),
+        only_vanilla: use_signal(|| false),
+        hide_adult: use_signal(|| false),
+        hide_full: use_signal(|| false),
+        hide_empty: use_signal(|| false),
+        min_players: use_signal(|| 0),
+        max_players: use_signal(|| None),
+        selected_langs: use_signal(server_list::initial_language_filter),
+        selected_rp: use_signal(Vec::new),
+        sort_mode: use_signal(|| "online_desc".to_string()),
+        show_filters: use_signal(|| false),
+        show_hidden: use_signal(|| false),
+        reload: use_signal(|| 0),
+    };
+    let HomeStore {
+        servers,
+        loading,
+        hub_errors,
+        hub_skip_note,
+        server_changes,
+        content_updates: content_updates_sig,
+        favorites: favorites_set,
+        favorites_layout,
+        hidden: hidden_set,
+        mut fav_sync_done,
+        reload,
+        ..
+    } = store;
+
+    {
+        let mut servers = servers;
+        let mut loading = loading;
+        let mut hub_errors = hub_errors;
+        let mut hub_skip_note = hub_skip_note;
+        let mut server_changes = server_changes;
+        let mut content_updates_sig = content_updates_sig;
+        let mut list_loader = use_future(move || async move {
+            loading.set(true);
+            match fetch_server_list(|partial| {
+                servers.set(server_list::share(partial.to_vec()));
+                loading.set(false);
+            })
+            .await
+            {
+                Ok(fetch) => {
+                    hub_skip_note.set(fetch.skipped_summary());
+                    servers.set(server_list::share(fetch.servers));
+                    hub_errors.set(None);
+                }
+                Err(err) => hub_errors.set(Some(err)),
+            }
+            loading.set(false);
+
+            let geoip_enabled = settings::load_settings()
+                .map(|s| s.server_list.geoip_regions)
+                .unwrap_or(false);
+            if geoip_enabled {
+                let missing: Vec<String> = servers
+                    .peek()
+                    .iter()
+                    .filter(|s| s.region.is_none())
+                    .map(|s| s.address.clone())
+                    .collect();
+                if !missing.is_empty() {
+                    let inferred = geoip::infer_regions(missing).await;
+                    let mut list = servers();
+                    for srv in list
+                        .iter_mut()
+                        .filter(|s| s.region.is_none() && inferred.contains_key(&s.address))
+                    {
+                        geoip::apply_inferred_regions(
+                            std::slice::from_mut(Arc::make_mut(srv)),
+                            &inferred,
+                        );
+                    }
+                    servers.set(list);
+                }
+            }
+
+            fill_server_summaries(servers, &favorites_set.peek()).await;
+
+            // Background refresh: briefly mark servers that changed instead of silently
+            // reshuffling the list. Failed refreshes keep the previous list.
+            let mut refreshes: u32 = 0;
+            loop {
+                if refreshes.is_multiple_of(CONTENT_CHECK_EVERY) {
+                    let favs: Vec<String> = favorites_set.peek().iter().cloned().collect();
+                    content_updates_sig.set(find_content_updates(favs).await);
+                }
+                refreshes = refreshes.wrapping_add(1);
+
+                tokio::time::sleep(SERVER_REFRESH_INTERVAL).await;
+                let Ok(fetch) = fetch_server_list(|_| {}).await else {
+                    continue;
+                };
+                hub_skip_note.set(fetch.skipped_summary());
+                let previous = servers.peek().clone();
+                let mut list = server_list::share(fetch.servers);
+                server_diff::carry_over_details(&previous, &mut list);
+                server_changes.set(server_diff::diff_servers(&previous, &list));
+                servers.set(list);
+                hub_errors.set(None);
+                // Only servers new since the last refresh are still unknown.
+                fill_server_summaries(servers, &favorites_set.peek()).await;
+
+                tokio::time::sleep(CHANGE_HIGHLIGHT_DURATION).await;
+                server_changes.set(HashMap::new());
+            }
+        });
+        use_effect(move || {
+            if reload() > 0 {
+                list_loader.restart();
+            }
+        });
+    }
+
+    {
+        let mut fav_sig = favorites_set;
+        let mut layout_sig = favorites_layout;
+        let mut hidden_sig = hidden_set;
+        use_future(move || async move {
+            if let Ok(set) = favorites::load_favorites() {
+                fav_sig.set(set);
+            }
+            layout_sig.set(favorites::load_favorites_layout());
+            if let Ok(set) = hidden_servers::load_hidden_servers() {
+                hidden_sig.set(set);
+            }
+
+            // Pick up changes made on other machines since the last run.
+            let sync = settings::load_settings().unwrap_or_default().favorites_sync;
+            if sync.is_enabled()
+                && let Ok(Ok(outcome)) =
+                    tokio::task::spawn_blocking(move || favorites_sync::sync_favorites(&sync)).await
+            {
+                fav_sig.set(outcome.favorites);
+                layout_sig.set(favorites::load_favorites_layout());
+                *fav_sync_done.write() += 1;
+            }
+        });
+    }
+
+    store
+}
+
+/// Favorites whose content changed since their last launch and is not downloaded yet.
+async fn find_content_updates(favorites: Vec<String>) -> HashMap<String, ContentUpdate> {
+    tokio::task::spawn_blocking(move || {
+        favorites
+            .into_iter()
+            .filter_map(
+                |address| match content_updates::check_content_update(&address) {
+                    Ok(Some(update)) => Some((address, update)),
+                    _ => None,
+                },
+            )
+            .collect()
+    })
+    .await
+    .unwrap_or_default()
+}
+
+/// Fetches `/info` for online favorites whose auth mode is still unknown, filling in auth
+/// modes and descriptions as batches finish. Other servers are asked when their card is
+/// expanded or a description search needs them, not for the whole hub list up front.
+pub(super) async fn fill_server_summaries(
+    mut servers: Signal<SharedServers>,
+    favorites: &HashSet<String>,
+) {
+    let addresses: Vec<String> = servers
+        .peek()
+        .iter()
+        .filter(|s| {
+            s.online
+                && s.auth_mode.is_none()
+                && favorites.contains(&favorites::canonicalize_favorite_address(&s.address))
+        })
+        .map(|s| s.address.clone())
+        .collect();
+    if addresses.is_empty() {
+        return;
+    }
+
+    search::fetch_summaries(addresses, |found, _| {
+        if found.is_empty() {
+            return;
+        }
+        let mut list = servers();
+        search::apply_summaries(&mut list, &found);
+        servers.set(list);
+    })
+    .await;
+}
@@ -1,77 +1,255 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::time::{Duration, Instant};
 
 use dioxus::prelude::*;
 
 use crate::auth::LoginInfo;
 use crate::cancel_flag::CancelFlag;
-use crate::connect_progress::ConnectProgress;
-use crate::favorites;
-use crate::servers::{fetch_server_description, fetch_server_list, ServerEntry};
+use crate::connect_progress::{self, ConnectProgress, ProgressTx};
+use crate::content_updates;
+use crate::favorites::{self, FavoritesLayout};
+use crate::favorites_sync;
+use crate::tasks::{self, TaskKind};
+use crate::{app_paths, hidden_servers, last_server, settings, ui_state};
+use crate::servers::{fetch_server_summary, hub_label, HubError, ServerEntry, ServerListFetch};
+use crate::storage::hub_urls;
+use crate::ss14_server_info::AuthMode;
+
+use crate::ui::clipboard;
+use crate::ui::error_box::ErrorBox;
+
+use super::build_overrides::BuildOverridesModal;
+use super::connection_test::ConnectionTestModal;
+use super::hub_errors::HubErrorList;
+use super::helpers::{display_region, display_tag, format_bytes, truncate_name};
+use super::log_ring::LogRing;
+use super::palette::{CommandPalette, PaletteAction, PaletteItem};
+use super::prefetch_queue::PrefetchQueuePanel;
+use super::running_game::RunningGamePanel;
+use super::search;
+use super::store::{fill_server_summaries, HomeStore};
+use super::server_list::{self, ServerFilter, SharedServers};
+use super::server_diff::ServerChange;
+
+const CONNECT_LOG_ID: &str = "connect-log";
+/// Lines kept for copy/save; only the tail window is rendered.
+const CONNECT_LOG_CAPACITY: usize = 5000;
+const CONNECT_LOG_WINDOW: usize = 200;
+
+/// Latest byte counters for one download (engine and content run side by side).
+#[derive(Clone, Debug, PartialEq)]
+struct DownloadLine {
+    label: String,
+    done_bytes: u64,
+    total_bytes: Option<u64>,
+}
+
+/// Pending question from the connect thread about a substitute engine version.
+#[derive(Clone, Debug)]
+struct EnginePrompt {
+    requested: String,
+    candidate: String,
+    reply: std::sync::mpsc::Sender<bool>,
+}
+
+/// Pending question from the connect thread: the name to join with when there is no account.
+#[derive(Clone, Debug)]
+struct GuestPrompt {
+    suggested: String,
+    reply: std::sync::mpsc::Sender<Option<String>>,
+}
+
+/// Recovery actions offered after the prelaunch engine signature check failed.
+#[derive(Clone, Debug)]
+struct EngineRecovery {
+    engine_version: String,
+    address: String,
+    account: Option<LoginInfo>,
+}
+
+/// Offered after a failed launch when the last successful one on the server used other settings.
+#[derive(Clone, Debug)]
+struct ConfigRecovery {
+    changes: Vec<String>,
+    address: String,
+    account: Option<LoginInfo>,
+}
+
+/// The connect modal state that one connect attempt drives.
+#[derive(Clone, Copy)]
+struct ConnectSignals {
+    connecting: Signal<bool>,
+    show_modal: Signal<bool>,
+    message: Signal<Option<String>>,
+    error: Signal<Option<String>>,
+    stage: Signal<String>,
+    downloads: Signal<Vec<DownloadLine>>,
+    warnings: Signal<Vec<String>>,
+    logs: Signal<LogRing>,
+    cancel: Signal<Option<CancelFlag>>,
+    success: Signal<bool>,
+    game_launched_at: Signal<Option<Instant>>,
+    auto_close_left: Signal<Option<u64>>,
+    engine_prompt: Signal<Option<EnginePrompt>>,
+    guest_prompt: Signal<Option<GuestPrompt>>,
+    engine_recovery: Signal<Option<EngineRecovery>>,
+    config_recovery: Signal<Option<ConfigRecovery>>,
+}
+
+/// Favorites state that a saved change (and the sync after it) updates.
+#[derive(Clone, Copy)]
+struct FavoritesSignals {
+    favorites: Signal<HashSet<String>>,
+    layout: Signal<FavoritesLayout>,
+    error: Signal<Option<String>>,
+    sync_done: Signal<u32>,
+}
 
-use super::helpers::{display_region, display_tag, truncate_name};
+const SERVER_LIST_ID: &str = "server-list";
+const SCROLL_SAVE_INTERVAL: Duration = Duration::from_secs(1);
 
 #[component]
-pub fn tab_home(active_account: Signal<Option<LoginInfo>>) -> Element {
-    let servers = use_signal(Vec::<ServerEntry>::new);
-    let loading = use_signal(|| true);
-    let error_message: Signal<Option<String>> = use_signal(|| None);
+pub fn tab_home(
+    store: HomeStore,
+    active_account: Signal<Option<LoginInfo>>,
+    palette_open: Signal<bool>,
+    on_open_settings: EventHandler<()>,
+    on_add_hub: EventHandler<()>,
+) -> Element {
+    let HomeStore {
+        servers,
+        loading,
+        hub_errors,
+        hub_skip_note,
+        server_changes,
+        content_updates: content_updates_sig,
+        favorites: favorites_set,
+        favorites_layout,
+        hidden: hidden_set,
+        fav_sync_done,
+        expanded_desc,
+        mut search,
+        mut search_in_desc,
+        mut region,
+        mut only_online,
+        mut game_mode,
+        mut only_vanilla,
+        mut hide_adult,
+        mut hide_full,
+        mut hide_empty,
+        mut min_players,
+        mut max_players,
+        mut selected_langs,
+        mut selected_rp,
+        mut sort_mode,
+        mut show_filters,
+        mut show_hidden,
+        mut reload,
+    } = store;
     let mut connect_message: Signal<Option<String>> = use_signal(|| None);
+    let connect_error: Signal<Option<String>> = use_signal(|| None);
     let connect_stage: Signal<String> = use_signal(|| "".to_string());
-    let connect_download_label: Signal<Option<String>> = use_signal(|| None);
-    let connect_done_bytes: Signal<u64> = use_signal(|| 0);
-    let connect_total_bytes: Signal<Option<u64>> = use_signal(|| None);
-    let connect_logs: Signal<Vec<String>> = use_signal(Vec::<String>::new);
+    let connect_downloads: Signal<Vec<DownloadLine>> = use_signal(Vec::new);
+    let connect_warnings: Signal<Vec<String>> = use_signal(Vec::new);
+    let connect_logs: Signal<LogRing> = use_signal(|| LogRing::with_capacity(CONNECT_LOG_CAPACITY));
     let connect_cancel: Signal<Option<CancelFlag>> = use_signal(|| None);
     let connecting = use_signal(|| false);
     let mut show_connect_modal = use_signal(|| false);
+    let mut log_auto_scroll = use_signal(|| true);
+    let mut log_window = use_signal(|| CONNECT_LOG_WINDOW);
+    let mut log_action_info: Signal<Option<String>> = use_signal(|| None);
 
     let connect_success = use_signal(|| false);
     let game_launched_at: Signal<Option<Instant>> = use_signal(|| None);
-    let mut last_launcher_activity_at: Signal<Instant> = use_signal(Instant::now);
-
-    let mut search = use_signal(String::new);
-    let mut region = use_signal(|| "all".to_string());
-    let mut only_online = use_signal(|| false);
-    let mut hide_full = use_signal(|| false);
-    let mut hide_empty = use_signal(|| false);
-    let mut min_players = use_signal(|| 0u32);
-    let mut max_players = use_signal(|| None::<u32>);
-    let mut selected_langs = use_signal(Vec::<String>::new);
-    let mut selected_rp = use_signal(Vec::<String>::new);
-    let mut sort_mode = use_signal(|| "online_desc".to_string());
-    let mut show_filters = use_signal(|| false);
+    // Seconds left before the connect modal closes itself after launch; `None` = stays open.
+    let mut auto_close_left: Signal<Option<u64>> = use_signal(|| None);
+    let mut engine_prompt: Signal<Option<EnginePrompt>> = use_signal(|| None);
+    let mut guest_prompt: Signal<Option<GuestPrompt>> = use_signal(|| None);
+    // Edited name in the guest prompt; `None` shows the suggestion.
+    let mut guest_name: Signal<Option<String>> = use_signal(|| None);
+    let mut engine_recovery: Signal<Option<EngineRecovery>> = use_signal(|| None);
+    let mut config_recovery: Signal<Option<ConfigRecovery>> = use_signal(|| None);
+    let connect_signals = ConnectSignals {
+        connecting,
+        show_modal: show_connect_modal,
+        message: connect_message,
+        error: connect_error,
+        stage: connect_stage,
+        downloads: connect_downloads,
+        warnings: connect_warnings,
+        logs: connect_logs,
+        cancel: connect_cancel,
+        success: connect_success,
+        game_launched_at,
+        auto_close_left,
+        engine_prompt,
+        guest_prompt,
+        engine_recovery,
+        config_recovery,
+    };
+
+    // Set while descriptions are being filled in for search; (done, total).
+    let mut desc_fetch_progress: Signal<Option<(usize, usize)>> = use_signal(|| None);
     let mut show_direct_connect = use_signal(|| false);
     let mut direct_connect_address = use_signal(String::new);
     let mut direct_connect_error: Signal<Option<String>> = use_signal(|| None);
-    let expanded_desc = use_signal(HashSet::<String>::new);
-    let favorites_set = use_signal(HashSet::<String>::new);
-
-    {
-        let mut servers = servers;
-        let mut loading = loading;
-        let mut error_message = error_message;
-        use_future(move || async move {
-            loading.set(true);
-            match fetch_server_list().await {
-                Ok(list) => {
-                    servers.set(list);
-                    error_message.set(None);
-                }
-                Err(err) => error_message.set(Some(err)),
-            }
-            loading.set(false);
-        });
-    }
+    let local_only_favs = use_memo(move || {
+        let _ = fav_sync_done();
+        let sync = settings::load_settings().unwrap_or_default().favorites_sync;
+        favorites_sync::local_only(&favorites_set.read(), &sync)
+    });
+    let mut dragging_fav: Signal<Option<String>> = use_signal(|| None);
+    let predownloading = use_signal(HashSet::<String>::new);
+    let mut select_mode = use_signal(|| false);
+    let mut selected = use_signal(HashSet::<String>::new);
+    let mut bulk_info: Signal<Option<String>> = use_signal(|| None);
+    let mut connection_test_target: Signal<Option<String>> = use_signal(|| None);
+    let mut build_overrides_target: Signal<Option<String>> = use_signal(|| None);
+    let mut scroll_dirty = use_signal(|| false);
+    let mut scroll_restored = use_signal(|| false);
+
+    // Restore the saved offset once the list has something to scroll through.
+    use_effect(move || {
+        if servers.read().is_empty() || *scroll_restored.peek() {
+            return;
+        }
+        scroll_restored.set(true);
+        let top = ui_state::load_ui_state().home_scroll_top;
+        if top > 0.0 {
+            clipboard::set_scroll_top(SERVER_LIST_ID, top);
+        }
+    });
 
-    {
-        let mut fav_sig = favorites_set;
-        use_future(move || async move {
-            if let Ok(set) = favorites::load_favorites() {
-                fav_sig.set(set);
+    // Persist the offset at most once per interval instead of on every scroll event.
+    use_future(move || async move {
+        loop {
+            tokio::time::sleep(SCROLL_SAVE_INTERVAL).await;
+            if !*scroll_dirty.peek() {
+                continue;
             }
-        });
-    }
+            scroll_dirty.set(false);
+            if let Some(top) = clipboard::scroll_top(SERVER_LIST_ID).await {
+                let _ = ui_state::update_ui_state(|s| s.home_scroll_top = top);
+            }
+        }
+    });
+
+    let mut last_joined: Signal<Option<last_server::LastServer>> =
+        use_signal(last_server::load_last_server);
+
+    // Pick up the address saved by a finished connect attempt.
+    use_effect(move || {
+        if !connecting() {
+            last_joined.set(last_server::load_last_server());
+        }
+    });
+
+    use_effect(move || {
+        let _ = connect_logs.read().len();
+        if log_auto_scroll() && show_connect_modal() {
+            clipboard::scroll_to_bottom(CONNECT_LOG_ID);
+        }
+    });
 
     let regions: Vec<String> = {
         let mut list: Vec<String> = servers().iter().filter_map(|s| s.region.clone()).collect();
@@ -79,119 +257,252 @@ pub fn tab_home(active_account: Signal<Option<LoginInfo>>) -> Element {
         list.dedup();
         list
     };
-
-    let (filtered_servers, favorite_count): (Vec<(ServerEntry, String, String)>, usize) = {
-        let needle = search().to_lowercase();
-        let selected_region = region();
-        let langs = selected_langs();
-        let rp_levels = selected_rp();
-        let min_players = min_players();
-        let max_players = max_players();
-        let mut list: Vec<ServerEntry> = servers()
+    let game_modes = server_list::game_modes(&servers.read());
+    let hub_counts = server_list::hub_counts(&servers.read());
+    // Source labels only say something once a hub other than the stock ones is in the list.
+    let default_hubs = hub_urls::default_hub_urls();
+    let show_hub_labels =
+        hub_counts.len() > 1 || hub_counts.iter().any(|(hub, _)| !default_hubs.contains(hub));
+
+    let (filtered_servers, favorite_count): (SharedServers, usize) = {
+        let all = servers.read();
+        let hidden = hidden_set.read();
+        let filter = ServerFilter {
+            needle: search().to_lowercase(),
+            search_in_desc: search_in_desc(),
+            region: region(),
+            game_mode: game_mode(),
+            only_vanilla: only_vanilla(),
+            hide_adult: hide_adult(),
+            lang_tags: selected_langs().iter().map(|c| format!("lang:{c}")).collect(),
+            rp_tags: selected_rp().iter().map(|l| format!("rp:{l}")).collect(),
+            only_online: only_online(),
+            hide_full: hide_full(),
+            hide_empty: hide_empty(),
+            show_hidden: show_hidden(),
+            hidden: &hidden,
+            min_players: min_players(),
+            max_players: max_players(),
+        };
+        let indices = server_list::filter_and_sort(&all, &filter, &sort_mode());
+
+        let favs = favorites_set.read();
+        let (mut fav_list, other_list): (Vec<usize>, Vec<usize>) = indices
             .into_iter()
-            .filter(|srv| {
-                let matches_search = needle.is_empty()
-                    || srv.name.to_lowercase().contains(&needle)
-                    || srv.address.to_lowercase().contains(&needle)
-                    || srv
-                        .tags
-                        .iter()
-                        .any(|tag| tag.to_lowercase().contains(&needle));
-
-                let matches_region = selected_region == "all"
-                    || srv.region.as_deref() == Some(selected_region.as_str());
-                let matches_online = !only_online() || srv.online;
-                let matches_full = !hide_full() || srv.players < srv.max_players;
-                let matches_empty = !hide_empty() || srv.players > 0;
+            .partition(|&i| favs.contains(&favorites::canonicalize_favorite_address(&all[i].address)));
 
-                let matches_lang = if langs.is_empty() {
-                    true
-                } else {
-                    langs.iter().any(|code| {
-                        srv.tags
-                            .iter()
-                            .any(|t| t.to_lowercase() == format!("lang:{}", code))
-                    })
-                };
+        // Stable sort: favorites never dragged keep the sort_mode order after the arranged ones.
+        let layout = favorites_layout.read();
+        fav_list.sort_by_key(|&i| {
+            layout.position(&favorites::canonicalize_favorite_address(&all[i].address))
+        });
 
-                let matches_rp = if rp_levels.is_empty() {
-                    true
-                } else {
-                    rp_levels.iter().any(|lvl| {
-                        srv.tags
-                            .iter()
-                            .any(|t| t.to_lowercase() == format!("rp:{}", lvl))
-                    })
-                };
-
-                let matches_min = srv.players >= min_players;
-                let matches_max = max_players.map(|m| srv.players <= m).unwrap_or(true);
-
-                matches_search
-                    && matches_region
-                    && matches_online
-                    && matches_full
-                    && matches_empty
-                    && matches_lang
-                    && matches_rp
-                    && matches_min
-                    && matches_max
-            })
+        let favorite_count = fav_list.len();
+        let ordered = fav_list
+            .into_iter()
+            .chain(other_list)
+            .map(|i| all[i].clone())
             .collect();
+        (ordered, favorite_count)
+    };
 
-        match sort_mode().as_str() {
-            "online_desc" => list.sort_by(|a, b| b.players.cmp(&a.players)),
-            "online_asc" => list.sort_by(|a, b| a.players.cmp(&b.players)),
-            "name_asc" => list.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase())),
-            "name_desc" => list.sort_by(|a, b| b.name.to_lowercase().cmp(&a.name.to_lowercase())),
-            _ => {}
-        }
+    let filtered_servers_len = filtered_servers.len();
+    let visible_fav_order: Vec<String> = filtered_servers[..favorite_count]
+        .iter()
+        .map(|srv| favorites::canonicalize_favorite_address(&srv.address))
+        .collect();
 
+    let pinned_servers: SharedServers = {
         let favs = favorites_set();
-        let mut fav_list: Vec<(ServerEntry, String, String)> = Vec::new();
-        let mut other_list: Vec<(ServerEntry, String, String)> = Vec::new();
-
-        for srv in list.into_iter() {
-            let addr = srv.address.clone();
-            let addr_fav = addr.clone();
-            let fav_key = favorites::canonicalize_favorite_address(&addr_fav);
-            if favs.contains(&fav_key) {
-                fav_list.push((srv, addr, addr_fav));
-            } else {
-                other_list.push((srv, addr, addr_fav));
-            }
-        }
-
-        let favorite_count = fav_list.len();
-        fav_list.extend(other_list);
-        (fav_list, favorite_count)
+        let all = servers.read();
+        favorites_layout()
+            .pinned
+            .iter()
+            .filter(|key| favs.contains(*key))
+            .filter_map(|key| {
+                all.iter()
+                    .find(|s| &favorites::canonicalize_favorite_address(&s.address) == key)
+                    .cloned()
+            })
+            .collect()
+    };
+    let mut favorites_error: Signal<Option<String>> = use_signal(|| None);
+    let favorites_signals = FavoritesSignals {
+        favorites: favorites_set,
+        layout: favorites_layout,
+        error: favorites_error,
+        sync_done: fav_sync_done,
+    };
+    let save_layout = move |layout: FavoritesLayout| {
+        let mut layout_sig = favorites_layout;
+        layout_sig.set(layout.clone());
+        save_favorites_change(favorites_signals, move |_, saved| *saved = layout);
     };
-
-    let filtered_servers_len = filtered_servers.len();
 
     let mut reset_filters = move || {
         search.set(String::new());
         region.set("all".to_string());
+        game_mode.set("all".to_string());
+        only_vanilla.set(false);
+        hide_adult.set(false);
         only_online.set(false);
         hide_full.set(false);
         hide_empty.set(false);
+        show_hidden.set(false);
         min_players.set(0);
         max_players.set(None);
+        if !selected_langs.peek().is_empty() {
+            server_list::mark_language_filter_touched();
+        }
         selected_langs.set(Vec::new());
         selected_rp.set(Vec::new());
     };
 
     let regions_list = regions.clone();
 
+    let mut palette_info: Signal<Option<String>> = use_signal(|| None);
+    // Read once per opening of the palette rather than on every render.
+    let recent_servers = use_resource(move || async move {
+        if palette_open() {
+            last_server::load_recent_servers()
+        } else {
+            Vec::new()
+        }
+    });
+    let palette_items = move || {
+        let mut items = vec![
+            PaletteItem {
+                title: "Прямое подключение".to_string(),
+                subtitle: "ввести адрес ss14://".to_string(),
+                action: PaletteAction::DirectConnect,
+            },
+            PaletteItem {
+                title: "Открыть настройки".to_string(),
+                subtitle: "действие".to_string(),
+                action: PaletteAction::OpenSettings,
+            },
+            PaletteItem {
+                title: "Очистить контент серверов".to_string(),
+                subtitle: "действие · кэш".to_string(),
+                action: PaletteAction::ClearContentCache,
+            },
+            PaletteItem {
+                title: "Открыть каталог данных".to_string(),
+                subtitle: "действие".to_string(),
+                action: PaletteAction::OpenDataDir,
+            },
+        ];
+        let list = servers();
+        for address in recent_servers().unwrap_or_default() {
+            let name = list.iter().find(|s| s.address == address).map(|s| s.name.clone());
+            items.push(PaletteItem {
+                title: name.unwrap_or_else(|| address.clone()),
+                subtitle: format!("недавний · {address}"),
+                action: PaletteAction::Connect(address),
+            });
+        }
+        for srv in list.iter().filter(|s| s.online) {
+            items.push(PaletteItem {
+                title: srv.name.clone(),
+                subtitle: format!("{}/{} · {}", srv.players, srv.max_players, srv.address),
+                action: PaletteAction::Connect(srv.address.clone()),
+            });
+        }
+        items
+    };
+
+    let run_palette_action = move |action: PaletteAction| {
+        palette_open.set(false);
+        match action {
+            PaletteAction::Connect(address) => {
+                if connecting() {
+                    return;
+                }
+                start_connect_task(address, active_account(), connect_signals);
+            }
+            PaletteAction::DirectConnect => {
+                direct_connect_error.set(None);
+                show_direct_connect.set(true);
+            }
+            PaletteAction::OpenSettings => on_open_settings.call(()),
+            PaletteAction::OpenDataDir => {
+                if let Ok(dir) = app_paths::data_dir() {
+                    let _ = app_paths::open_in_file_manager(&dir);
+                }
+            }
+            PaletteAction::ClearContentCache => {
+                spawn(async move {
+                    if !clipboard::confirm(clipboard::CLEAR_CONTENT_QUESTION).await {
+                        return;
+                    }
+                    palette_info.set(Some("очищаем контент серверов...".to_string()));
+                    let res = tokio::task::spawn_blocking(|| {
+                        let data_dir = app_paths::data_dir()?;
+                        let task = tasks::register(TaskKind::CacheCleanup, "Очистка контента серверов", false);
+                        let r = crate::core::cache_cleanup::clear_server_content_cache(&data_dir);
+                        task.finish(&r);
+                        r
+                    })
+                    .await;
+                    palette_info.set(Some(match res {
+                        Ok(Ok(())) => "контент серверов очищен".to_string(),
+                        Ok(Err(e)) => format!("не удалось очистить контент: {e}"),
+                        Err(e) => format!("ошибка задачи: {e}"),
+                    }));
+                });
+            }
+        }
+    };
+
     rsx! {
         div {
             class: "section",
-            onmousedown: move |_| last_launcher_activity_at.set(Instant::now()),
-            onmousemove: move |_| last_launcher_activity_at.set(Instant::now()),
-            onkeydown: move |_| last_launcher_activity_at.set(Instant::now()),
             p { class: "muted", {format!("Серверов: {}", servers().len())} }
 
+            if let Some(info) = palette_info() {
+                p { class: "status status-info selectable", "{info}" }
+            }
+
+            if palette_open() {
+                CommandPalette {
+                    items: palette_items(),
+                    on_pick: run_palette_action,
+                    on_close: move |_| palette_open.set(false),
+                }
+            }
+
             div { class: "filter-bar",
+                if let Some(last) = last_joined() {
+                    button {
+                        class: "pill primary reconnect-pill",
+                        title: format!("переподключиться к {}", last.address),
+                        disabled: connecting(),
+                        onclick: move |_| {
+                            // Prefer the account that joined last time, if it is still saved.
+                            let remembered = last.user_id.and_then(|id| {
+                                crate::account_store::load_saved_logins()
+                                    .ok()?
+                                    .into_iter()
+                                    .find(|a| a.user_id == id)
+                            });
+                            let fell_back = last.user_id.is_some() && remembered.is_none();
+                            let account = remembered.or_else(&*active_account);
+                            start_connect_task(last.address.clone(), account.clone(), connect_signals);
+                            if fell_back && connecting() {
+                                let mut warnings = connect_signals.warnings;
+                                warnings.write().push(match &account {
+                                    Some(acc) => format!(
+                                        "аккаунт, с которым вы заходили сюда в прошлый раз, больше не сохранён; подключаемся как {}",
+                                        acc.username
+                                    ),
+                                    None => "аккаунт, с которым вы заходили сюда в прошлый раз, больше не сохранён; подключаемся без аккаунта".to_string(),
+                                });
+                            }
+                        },
+                        "Переподключиться"
+                    }
+                }
+
                 button {
                     class: "pill ghost",
                     onclick: move |_| {
@@ -207,14 +518,63 @@ pub fn tab_home(active_account: Signal<Option<LoginInfo>>) -> Element {
                     "Фильтры"
                 }
 
+                button {
+                    class: format_args!("pill ghost {}", if select_mode() { "active" } else { "" }),
+                    onclick: move |_| {
+                        select_mode.set(!select_mode());
+                        selected.set(HashSet::new());
+                        bulk_info.set(None);
+                    },
+                    "Выбор"
+                }
+
                 input {
                     class: "input text-input",
                     r#type: "search",
-                    placeholder: "Поиск по названию/Адресу",
+                    placeholder: if search_in_desc() { "Поиск по названию/адресу/описанию" } else { "Поиск по названию/Адресу" },
                     value: search(),
                     oninput: move |evt| search.set(evt.value()),
                 }
 
+                button {
+                    class: format_args!("pill ghost {}", if search_in_desc() { "active" } else { "" }),
+                    title: "искать также в описаниях серверов (описания загружаются при включении)",
+                    onclick: move |_| {
+                        let enable = !search_in_desc();
+                        search_in_desc.set(enable);
+                        if !enable || desc_fetch_progress().is_some() {
+                            return;
+                        }
+                        let missing: Vec<String> = servers()
+                            .iter()
+                            .filter(|s| s.online && s.description.is_none())
+                            .map(|s| s.address.clone())
+                            .collect();
+                        if missing.is_empty() {
+                            return;
+                        }
+                        let total = missing.len();
+                        desc_fetch_progress.set(Some((0, total)));
+                        let mut servers = servers;
+                        spawn(async move {
+                            let mut done = 0;
+                            search::fetch_summaries(missing, |found, asked| {
+                                done += asked;
+                                desc_fetch_progress.set(Some((done, total)));
+                                if found.is_empty() {
+                                    return;
+                                }
+                                let mut list = servers();
+                                search::apply_summaries(&mut list, &found);
+                                servers.set(list);
+                            })
+                            .await;
+                            desc_fetch_progress.set(None);
+                        });
+                    },
+                    "В описаниях"
+                }
+
                 select {
                     class: "select sort-select",
                     value: sort_mode(),
@@ -224,23 +584,70 @@ pub fn tab_home(active_account: Signal<Option<LoginInfo>>) -> Element {
                     option { value: "name_asc", "Сортировать: А→Я" }
                     option { value: "name_desc", "Сортировать: Я→А" }
                 }
+
+                if !hub_counts.is_empty() {
+                    span {
+                        class: "muted hub-counts",
+                        title: hub_counts
+                            .iter()
+                            .map(|(hub, n)| format!("{hub}: {n}"))
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                        {hub_counts
+                            .iter()
+                            .map(|(hub, n)| format!("{}: {n}", hub_label(hub)))
+                            .collect::<Vec<_>>()
+                            .join(" · ")}
+                    }
+                }
             }
 
             if loading() {
                 p { class: "status status-info", "загружаем список серверов..." }
             }
+            if active_account().is_none() {
+                p { class: "status status-info",
+                    "Игра без аккаунта: доступны серверы, где авторизация отключена или не обязательна."
+                }
+            }
+
+            if let Some(err) = hub_errors() {
+                HubErrorList {
+                    error: err,
+                    on_loaded: move |fetch: ServerListFetch| {
+                        let mut servers = servers;
+                        let mut hub_errors = hub_errors;
+                        let mut hub_skip_note = hub_skip_note;
+                        hub_skip_note.set(fetch.skipped_summary());
+                        servers.set(server_list::share(fetch.servers));
+                        hub_errors.set(None);
+                        spawn(async move {
+                            fill_server_summaries(servers, &favorites_set.peek()).await;
+                        });
+                    },
+                    on_failed: move |failed: HubError| {
+                        let mut hub_errors = hub_errors;
+                        if let Some(list) = hub_errors.write().as_mut()
+                            && let Some(entry) = list.hubs.iter_mut().find(|h| h.hub == failed.hub)
+                        {
+                            *entry = failed;
+                        }
+                    },
+                }
+            }
+
+            if let Some(note) = hub_skip_note() {
+                p { class: "status status-info selectable", "{note}" }
+            }
 
-            if let Some(err) = error_message() {
-                 div { class: "status status-error status-block selectable error-log", {format!("ошибка: {}", err)} }
+            if let Some((done, total)) = desc_fetch_progress() {
+                p { class: "status status-info", {format!("загружаем описания серверов для поиска: {done}/{total}")} }
             }
 
             if show_connect_modal() {
                 div { class: "modal-backdrop locked",
                     div {
                         class: "modal login-modal connect-modal",
-                        onmousedown: move |_| last_launcher_activity_at.set(Instant::now()),
-                        onmousemove: move |_| last_launcher_activity_at.set(Instant::now()),
-                        onkeydown: move |_| last_launcher_activity_at.set(Instant::now()),
                         div { class: "modal-header",
                             div {
                                 h3 { "подключение" }
@@ -255,37 +662,334 @@ pub fn tab_home(active_account: Signal<Option<LoginInfo>>) -> Element {
                                 p { class: "muted", {connect_stage()} }
                             }
 
-                            if let Some(label) = connect_download_label() {
+                            if let Some(recovery) = engine_recovery().filter(|_| !connecting()) {
                                 {
-                                    let done = connect_done_bytes();
-                                    let total = connect_total_bytes();
+                                    let version = recovery.engine_version.clone();
+                                    let version_clear = recovery.engine_version.clone();
                                     rsx! {
-                                        div { class: "connect-progress",
-                                            p { class: "muted", {format!("{}: {}{}", label, format_bytes(done), total.map(|t| format!(" / {}", format_bytes(t))).unwrap_or_default())} }
+                                        div { class: "engine-prompt",
+                                            p {
+                                                {format!("Подпись движка {version} не прошла проверку. Подробности — в сообщении ниже.")}
+                                            }
+                                            div { class: "connect-log-toolbar",
+                                                button {
+                                                    class: "primary small",
+                                                    onclick: move |_| {
+                                                        let res = app_paths::data_dir().and_then(|dir| {
+                                                            crate::core::cache_cleanup::clear_engine_version(&dir, &recovery.engine_version)
+                                                        });
+                                                        if let Err(e) = res {
+                                                            connect_message.set(Some(e));
+                                                            return;
+                                                        }
+                                                        start_connect_task(
+                                                            recovery.address.clone(),
+                                                            recovery.account.clone(),
+                                                            connect_signals,
+                                                        );
+                                                    },
+                                                    "Скачать движок заново и подключиться"
+                                                }
+                                                button {
+                                                    class: "ghost small",
+                                                    onclick: move |_| {
+                                                        let res = app_paths::data_dir().and_then(|dir| {
+                                                            crate::core::cache_cleanup::clear_engine_version(&dir, &version_clear)
+                                                        });
+                                                        match res {
+                                                            Ok(()) => {
+                                                                engine_recovery.set(None);
+                                                                connect_message.set(Some(format!("движок {version_clear} удалён")));
+                                                            }
+                                                            Err(e) => connect_message.set(Some(e)),
+                                                        }
+                                                    },
+                                                    {format!("Удалить версию {}", recovery.engine_version)}
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+
+                            if let Some(recovery) = config_recovery().filter(|_| !connecting()) {
+                                {
+                                    let address_only = recovery.address.clone();
+                                    rsx! {
+                                        div { class: "engine-prompt",
+                                            p { "Последний успешный запуск на этом сервере был с другими настройками:" }
+                                            ul { class: "known-good-changes",
+                                                for change in recovery.changes.iter() {
+                                                    li { class: "selectable", {change.clone()} }
+                                                }
+                                            }
+                                            div { class: "connect-log-toolbar",
+                                                button {
+                                                    class: "primary small",
+                                                    onclick: move |_| {
+                                                        if let Err(e) = crate::known_good::restore_known_good(&recovery.address) {
+                                                            connect_message.set(Some(e));
+                                                            return;
+                                                        }
+                                                        start_connect_task(
+                                                            recovery.address.clone(),
+                                                            recovery.account.clone(),
+                                                            connect_signals,
+                                                        );
+                                                    },
+                                                    "Вернуть рабочую конфигурацию и подключиться"
+                                                }
+                                                button {
+                                                    class: "ghost small",
+                                                    onclick: move |_| {
+                                                        match crate::known_good::restore_known_good(&address_only) {
+                                                            Ok(()) => {
+                                                                config_recovery.set(None);
+                                                                connect_message.set(Some("рабочая конфигурация восстановлена".to_string()));
+                                                            }
+                                                            Err(e) => connect_message.set(Some(e)),
+                                                        }
+                                                    },
+                                                    "Только вернуть"
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+
+                            if let Some(prompt) = engine_prompt() {
+                                {
+                                    let reply_yes = prompt.reply.clone();
+                                    let reply_no = prompt.reply.clone();
+                                    rsx! {
+                                        div { class: "engine-prompt",
+                                            p {
+                                                {format!(
+                                                    "Движок {} больше не опубликован. Использовать ближайшую совместимую версию {}? Сервер может отказать в подключении.",
+                                                    prompt.requested, prompt.candidate
+                                                )}
+                                            }
+                                            div { class: "connect-log-toolbar",
+                                                button {
+                                                    class: "primary small",
+                                                    onclick: move |_| {
+                                                        let _ = reply_yes.send(true);
+                                                        engine_prompt.set(None);
+                                                    },
+                                                    {format!("Использовать {}", prompt.candidate)}
+                                                }
+                                                button {
+                                                    class: "ghost small",
+                                                    onclick: move |_| {
+                                                        let _ = reply_no.send(false);
+                                                        engine_prompt.set(None);
+                                                    },
+                                                    "Отмена"
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+
+                            if let Some(prompt) = guest_prompt() {
+                                {
+                                    let reply_ok = prompt.reply.clone();
+                                    let reply_cancel = prompt.reply.clone();
+                                    let typed = guest_name().unwrap_or(prompt.suggested.clone());
+                                    let checked = settings::validate_guest_username(&typed);
+                                    rsx! {
+                                        div { class: "engine-prompt",
+                                            p { "Сервер пускает без аккаунта. Под каким ником играть?" }
+                                            input {
+                                                r#type: "text",
+                                                value: typed,
+                                                class: if checked.is_err() { "input invalid" } else { "input" },
+                                                oninput: move |evt| guest_name.set(Some(evt.value())),
+                                            }
+                                            if let Err(e) = &checked {
+                                                p { class: "field-error", {e.clone()} }
+                                            }
+                                            div { class: "connect-log-toolbar",
+                                                button {
+                                                    class: "primary small",
+                                                    disabled: checked.is_err(),
+                                                    onclick: move |_| {
+                                                        let Ok(name) = checked.clone() else {
+                                                            return;
+                                                        };
+                                                        // The last name typed becomes the new default.
+                                                        if let Ok(mut next) = settings::load_settings()
+                                                            && next.game.guest_username != name
+                                                        {
+                                                            next.game.guest_username = name.clone();
+                                                            let _ = settings::save_settings(&next);
+                                                        }
+                                                        let _ = reply_ok.send(Some(name));
+                                                        guest_prompt.set(None);
+                                                        guest_name.set(None);
+                                                    },
+                                                    "Играть"
+                                                }
+                                                button {
+                                                    class: "ghost small",
+                                                    onclick: move |_| {
+                                                        let _ = reply_cancel.send(None);
+                                                        guest_prompt.set(None);
+                                                        guest_name.set(None);
+                                                    },
+                                                    "Отмена"
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
 
-                                            // Always show an indeterminate (cyclic) progress bar.
-                                            div { class: "progress-indeterminate",
-                                                div { class: "progress-indeterminate-bar" }
+                            for warning in connect_warnings() {
+                                div { class: "status status-error status-block selectable", "{warning}" }
+                            }
+
+                            for line in connect_downloads() {
+                                {
+                                    let DownloadLine { label, done_bytes: done, total_bytes: total } = line;
+                                    rsx! {
+                                        div { class: "connect-progress",
+                                            p { class: "muted", {format!("{}: {}{}", label, format_bytes(done), total.map(|t| format!(" / {} ({}%)", format_bytes(t), (done.min(t) * 100) / t.max(1))).unwrap_or_default())} }
+
+                                            // Determinate bar when the server sent Content-Length, cyclic otherwise.
+                                            if let Some(total) = total.filter(|t| *t > 0) {
+                                                div { class: "progress-determinate",
+                                                    div {
+                                                        class: "progress-determinate-bar",
+                                                        style: "width: {(done.min(total) * 100) / total}%",
+                                                    }
+                                                }
+                                            } else {
+                                                div { class: "progress-indeterminate",
+                                                    div { class: "progress-indeterminate-bar" }
+                                                }
                                             }
                                         }
                                     }
                                 }
                             }
 
-                            if !connect_logs().is_empty() {
-                                div { class: "status status-info status-block selectable connect-log",
-                                    {connect_logs().join("\n")}
+                            if !connect_logs.read().is_empty() {
+                                div { class: "connect-log-toolbar",
+                                    button {
+                                        class: "ghost small",
+                                        onclick: move |_| {
+                                            clipboard::copy_text(connect_logs.read().joined());
+                                            log_action_info.set(Some("лог скопирован".to_string()));
+                                        },
+                                        "Копировать лог"
+                                    }
+                                    button {
+                                        class: "ghost small",
+                                        onclick: move |_| {
+                                            let text = connect_logs.read().joined();
+                                            let mut info = log_action_info;
+                                            spawn(async move {
+                                                match clipboard::save_text_as("connect-log.txt", text).await {
+                                                    Ok(Some(path)) => info.set(Some(format!("лог сохранён: {path}"))),
+                                                    Ok(None) => {}
+                                                    Err(e) => info.set(Some(format!("не удалось сохранить лог: {e}"))),
+                                                }
+                                            });
+                                        },
+                                        "Сохранить как…"
+                                    }
+                                    label { class: "connect-log-toggle",
+                                        input {
+                                            r#type: "checkbox",
+                                            checked: log_auto_scroll(),
+                                            onchange: move |_| log_auto_scroll.set(!log_auto_scroll()),
+                                        }
+                                        span { "автопрокрутка" }
+                                    }
+                                    if let Some(info) = log_action_info() {
+                                        span { class: "muted connect-log-info", {info} }
+                                    }
+                                }
+                                div {
+                                    id: CONNECT_LOG_ID,
+                                    class: "status status-info status-block selectable connect-log",
+                                    {
+                                        let logs = connect_logs.read();
+                                        let hidden = logs.len().saturating_sub(log_window()) as u64 + logs.dropped();
+                                        rsx! {
+                                            if hidden > 0 {
+                                                div { class: "connect-log-more",
+                                                    span { class: "muted", "… скрыто строк: {hidden}" }
+                                                    if logs.len() > log_window() {
+                                                        button {
+                                                            class: "ghost small",
+                                                            onclick: move |_| log_window.set(log_window() + CONNECT_LOG_WINDOW),
+                                                            "Показать ещё"
+                                                        }
+                                                    }
+                                                }
+                                            }
+                                            for line in logs.tail(log_window()) {
+                                                div { class: "connect-log-line", "{line}" }
+                                            }
+                                        }
+                                    }
                                 }
                             }
 
-                            if let Some(msg) = connect_message() {
+                            if let Some(err) = connect_error() {
+                                ErrorBox { operation: "подключение к серверу", message: err }
+                            } else if let Some(msg) = connect_message() {
                                 div { class: "status status-info status-block selectable", {msg} }
                             } else {
                                 p { class: "muted", "ожидание..." }
                             }
                         }
 
+                        if let Some(left) = auto_close_left() {
+                            div { class: "hub-row auto-close-row",
+                                span { class: "muted",
+                                    if connecting() {
+                                        "окно закроется после завершения подключения"
+                                    } else {
+                                        "окно закроется через {left} с"
+                                    }
+                                }
+                                button {
+                                    class: "ghost small",
+                                    onclick: move |_| auto_close_left.set(None),
+                                    "Не закрывать"
+                                }
+                            }
+                        }
+
                         div { class: "modal-actions",
+                            if game_launched_at().is_some() {
+                                button {
+                                    class: "ghost",
+                                    title: "убить процесс SS14.Loader и всё, что он запустил",
+                                    onclick: move |_| {
+                                        if let Some(flag) = connect_cancel() {
+                                            flag.cancel();
+                                        }
+                                        spawn(async move {
+                                            let res = tokio::task::spawn_blocking(crate::net::launch_guard::force_stop)
+                                                .await
+                                                .map_err(|e| format!("ошибка задачи: {e}"))
+                                                .and_then(|r| r);
+                                            auto_close_left.set(None);
+                                            connect_message.set(Some(match res {
+                                                Ok(msg) => msg,
+                                                Err(e) => format!("не удалось остановить игру: {e}"),
+                                            }));
+                                        });
+                                    },
+                                    "Принудительно остановить"
+                                }
+                            }
                             button {
                                 class: "ghost",
                                 onclick: move |_| {
@@ -297,10 +1001,13 @@ pub fn tab_home(active_account: Signal<Option<LoginInfo>>) -> Element {
                                         // Allow the user to dismiss the modal even if the
                                         // background connect task is still unwinding.
                                         show_connect_modal.set(false);
+                                        log_action_info.set(None);
                                         return;
                                     }
 
                                     show_connect_modal.set(false);
+                                    log_action_info.set(None);
+                                    auto_close_left.set(None);
                                 },
                                 { if connecting() { "остановить" } else { "закрыть" } }
                             }
@@ -329,11 +1036,40 @@ pub fn tab_home(active_account: Signal<Option<LoginInfo>>) -> Element {
                                     direct_connect_error.set(None);
                                 },
                             }
+                            if let Some(fix) = crate::ss14_uri::suggest_address_fix(&direct_connect_address()) {
+                                div { class: "hub-row address-fix",
+                                    span { class: "muted",
+                                        "Возможно, имелось в виду "
+                                        code { class: "selectable", {fix.address.clone()} }
+                                        {format!(" ({})", fix.notes.join(", "))}
+                                    }
+                                    button {
+                                        class: "ghost small",
+                                        onclick: move |_| {
+                                            direct_connect_address.set(fix.address.clone());
+                                            direct_connect_error.set(None);
+                                        },
+                                        "Исправить"
+                                    }
+                                }
+                            }
                             if let Some(err) = direct_connect_error() {
-                                div { class: "status status-error status-block selectable", {err} }
+                                ErrorBox { operation: "прямое подключение", message: err }
                             }
                         }
                         div { class: "modal-actions",
+                            button {
+                                class: "ghost modal-actions-left",
+                                disabled: direct_connect_address().trim().is_empty(),
+                                onclick: move |_| {
+                                    let input = direct_connect_address().trim().to_string();
+                                    if !input.is_empty() {
+                                        show_direct_connect.set(false);
+                                        connection_test_target.set(Some(input));
+                                    }
+                                },
+                                "Проверить"
+                            }
                             button {
                                 class: "ghost",
                                 onclick: move |_| show_direct_connect.set(false),
@@ -356,18 +1092,7 @@ pub fn tab_home(active_account: Signal<Option<LoginInfo>>) -> Element {
                                             start_connect_task(
                                                 uri.to_string(),
                                                 active_account(),
-                                                connecting,
-                                                show_connect_modal,
-                                                connect_message,
-                                                connect_stage,
-                                                connect_download_label,
-                                                connect_done_bytes,
-                                                connect_total_bytes,
-                                                connect_logs,
-                                                connect_cancel,
-                                                connect_success,
-                                                game_launched_at,
-                                                last_launcher_activity_at,
+                                                connect_signals,
                                             );
                                         }
                                         Err(e) => direct_connect_error.set(Some(e)),
@@ -401,6 +1126,7 @@ pub fn tab_home(active_account: Signal<Option<LoginInfo>>) -> Element {
                                             value: current_lang,
                                             oninput: move |evt| {
                                                 let val = evt.value();
+                                                server_list::mark_language_filter_touched();
                                                 if val == "all" {
                                                     langs_sig.set(Vec::new());
                                                 } else {
@@ -408,13 +1134,9 @@ pub fn tab_home(active_account: Signal<Option<LoginInfo>>) -> Element {
                                                 }
                                             },
                                             option { value: "all", "Все языки" }
-                                            option { value: "en", "English" }
-                                            option { value: "ru", "Русский" }
-                                            option { value: "fr", "French" }
-                                            option { value: "de", "German" }
-                                            option { value: "pl", "Polish" }
-                                            option { value: "pt", "Portuguese" }
-                                            option { value: "uk", "Ukrainian" }
+                                            for (code, label) in server_list::LANGUAGE_FILTERS {
+                                                option { value: code, {label} }
+                                            }
                                         }
                                     }
                                 }
@@ -453,6 +1175,11 @@ pub fn tab_home(active_account: Signal<Option<LoginInfo>>) -> Element {
                                             }
                                         }
                                     }
+                                    button {
+                                        class: format_args!("pill chip {}", if show_hidden() { "active" } else { "" }),
+                                        onclick: move |_| show_hidden.set(!show_hidden()),
+                                        "показывать скрытые"
+                                    }
                                 }
                             }
 
@@ -482,6 +1209,36 @@ pub fn tab_home(active_account: Signal<Option<LoginInfo>>) -> Element {
                                 }
                             }
 
+                            div { class: "filters-group",
+                                h4 { "Режим" }
+                                div { class: "chips",
+                                    for mode in std::iter::once("all".to_string()).chain(game_modes.clone()) {
+                                        {
+                                            let active = game_mode().eq_ignore_ascii_case(&mode);
+                                            let label = if mode == "all" { "все".to_string() } else { mode.clone() };
+                                            let mut game_mode_sig = game_mode;
+                                            rsx! {
+                                                button {
+                                                    class: format_args!("pill chip {}", if active { "active" } else { "" }),
+                                                    onclick: move |_| game_mode_sig.set(mode.clone()),
+                                                    {label}
+                                                }
+                                            }
+                                        }
+                                    }
+                                    button {
+                                        class: format_args!("pill chip {}", if only_vanilla() { "active" } else { "" }),
+                                        onclick: move |_| only_vanilla.set(!only_vanilla()),
+                                        "только ванилла"
+                                    }
+                                    button {
+                                        class: format_args!("pill chip {}", if hide_adult() { "active" } else { "" }),
+                                        onclick: move |_| hide_adult.set(!hide_adult()),
+                                        "без 18+"
+                                    }
+                                }
+                            }
+
                             div { class: "filters-group",
                                 h4 { "RP-уровень" }
                                 div { class: "chips",
@@ -553,42 +1310,373 @@ pub fn tab_home(active_account: Signal<Option<LoginInfo>>) -> Element {
                 }
             }
 
-            div { class: "server-list compact",
+            if let Some(address) = connection_test_target() {
+                ConnectionTestModal {
+                    address,
+                    has_account: active_account().is_some(),
+                    on_close: move |_| connection_test_target.set(None),
+                }
+            }
+
+            if let Some(address) = build_overrides_target() {
+                BuildOverridesModal {
+                    address,
+                    on_close: move |_| build_overrides_target.set(None),
+                }
+            }
+
+            if select_mode() {
+                {
+                    let visible: Vec<String> = filtered_servers
+                        .iter()
+                        .map(|srv| favorites::canonicalize_favorite_address(&srv.address))
+                        .collect();
+                    let selected_now = selected();
+                    let mut fav_sig = favorites_set;
+                    let mut hidden_sig = hidden_set;
+                    let mut apply_favorites = move |add: bool| {
+                        let chosen = selected();
+                        let mut set = fav_sig();
+                        apply_selection(&mut set, &chosen, add);
+                        fav_sig.set(set);
+                        save_favorites_change(favorites_signals, move |set, _| {
+                            apply_selection(set, &chosen, add);
+                        });
+                    };
+                    let mut apply_favorites2 = apply_favorites;
+                    let mut apply_hidden = move |hide: bool| {
+                        let mut set = hidden_sig();
+                        for addr in selected().iter() {
+                            if hide {
+                                set.insert(addr.clone());
+                            } else {
+                                set.remove(addr);
+                            }
+                        }
+                        hidden_sig.set(set.clone());
+                        match hidden_servers::save_hidden_servers(&set) {
+                            Ok(()) => bulk_info.set(None),
+                            Err(e) => bulk_info.set(Some(e)),
+                        }
+                    };
+                    let mut apply_hidden2 = apply_hidden;
+                    rsx! {
+                        div { class: "bulk-bar",
+                            span { class: "muted", {format!("Выбрано: {}", selected_now.len())} }
+                            button {
+                                class: "ghost small",
+                                onclick: move |_| {
+                                    let mut set = selected();
+                                    set.extend(visible.iter().cloned());
+                                    selected.set(set);
+                                },
+                                "Выбрать все"
+                            }
+                            button {
+                                class: "ghost small",
+                                onclick: move |_| selected.set(HashSet::new()),
+                                "Снять выбор"
+                            }
+                            button {
+                                class: "ghost small",
+                                disabled: selected_now.is_empty(),
+                                onclick: move |_| apply_favorites(true),
+                                "В избранное"
+                            }
+                            button {
+                                class: "ghost small",
+                                disabled: selected_now.is_empty(),
+                                onclick: move |_| apply_favorites2(false),
+                                "Из избранного"
+                            }
+                            button {
+                                class: "ghost small",
+                                disabled: selected_now.is_empty(),
+                                onclick: move |_| apply_hidden(true),
+                                "Скрыть"
+                            }
+                            button {
+                                class: "ghost small",
+                                disabled: selected_now.is_empty(),
+                                onclick: move |_| apply_hidden2(false),
+                                "Показать"
+                            }
+                            button {
+                                class: "ghost small",
+                                disabled: selected_now.is_empty(),
+                                onclick: move |_| {
+                                    let chosen = selected();
+                                    let text = servers()
+                                        .iter()
+                                        .filter(|s| chosen.contains(&favorites::canonicalize_favorite_address(&s.address)))
+                                        .map(|s| format!("{}\t{}", s.address, s.name))
+                                        .collect::<Vec<_>>()
+                                        .join("\n");
+                                    spawn(async move {
+                                        match clipboard::save_text_as("servers.txt", text).await {
+                                            Ok(Some(path)) => bulk_info.set(Some(format!("сохранено: {path}"))),
+                                            Ok(None) => {}
+                                            Err(e) => bulk_info.set(Some(e)),
+                                        }
+                                    });
+                                },
+                                "Экспорт…"
+                            }
+                            button {
+                                class: "ghost small",
+                                disabled: selected_now.is_empty(),
+                                title: "скачать движок и контент выбранных серверов по очереди, без запуска",
+                                onclick: move |_| {
+                                    let chosen = selected();
+                                    let added = crate::net::prefetch_queue::enqueue(
+                                        servers()
+                                            .iter()
+                                            .filter(|s| s.online && chosen.contains(&favorites::canonicalize_favorite_address(&s.address)))
+                                            .map(|s| (s.address.clone(), s.name.clone())),
+                                    );
+                                    bulk_info.set(Some(format!("в очередь подготовки добавлено: {added}")));
+                                },
+                                "Подготовить"
+                            }
+                            if let Some(msg) = bulk_info() {
+                                span { class: "muted selectable", {msg} }
+                            }
+                        }
+                    }
+                }
+            }
+
+            RunningGamePanel {}
+            PrefetchQueuePanel {}
+
+            if !pinned_servers.is_empty() {
+                div { class: "pinned-tiles",
+                    for server in pinned_servers.into_iter() {
+                        {
+                            let addr_connect = server.address.clone();
+                            let unpin_key = favorites::canonicalize_favorite_address(&server.address);
+                            let needs_account = requires_account(&server, active_account().is_some());
+                            rsx! {
+                                div { key: "{addr_connect}", class: "pinned-tile",
+                                    div { class: "pinned-tile-head",
+                                        h3 { title: server.name.clone(), {truncate_name(&server.name, 48)} }
+                                        button {
+                                            class: "ghost small",
+                                            title: "Открепить",
+                                            onclick: move |_| {
+                                                let mut layout = favorites_layout();
+                                                layout.pinned.retain(|a| a != &unpin_key);
+                                                save_layout(layout);
+                                            },
+                                            "×"
+                                        }
+                                    }
+                                    div { class: "stat-line",
+                                        span { class: "stat players", {format!("{}/{}", server.players, server.max_players)} }
+                                        span { class: "stat ping", {server.ping_ms.map(|p| format!("{} мс", p)).unwrap_or_else(|| "—".to_string())} }
+                                    }
+                                    button {
+                                        class: "primary",
+                                        disabled: !server.online || connecting() || needs_account,
+                                        title: if needs_account { NEEDS_ACCOUNT_HINT } else { "" },
+                                        onclick: move |_| {
+                                            start_connect_task(
+                                                addr_connect.clone(),
+                                                active_account(),
+                                                connect_signals,
+                                            );
+                                        },
+                                        "Подключиться"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            if let Some(err) = favorites_error() {
+                ErrorBox { operation: "избранное", message: err }
+            }
+
+            div {
+                id: SERVER_LIST_ID,
+                class: "server-list compact",
+                onscroll: move |_| scroll_dirty.set(true),
                 if !loading() && filtered_servers.is_empty() {
                     div { class: "empty-state",
-                        h3 { "Ничего не нашли" }
-                        p { class: "muted", "Попробуй изменить фильтры или строку поиска." }
+                        if servers.read().is_empty() {
+                            if hub_errors.read().is_some() {
+                                h3 { "Хаб недоступен" }
+                                p { class: "muted", "Проверь соединение или добавь другой хаб." }
+                            } else {
+                                h3 { "Список серверов пуст" }
+                                p { class: "muted", "Хабы не вернули ни одного сервера." }
+                            }
+                            div { class: "empty-state-actions",
+                                button { class: "primary", onclick: move |_| reload += 1, "Повторить" }
+                                button { class: "ghost", onclick: move |_| on_add_hub.call(()), "Добавить хаб" }
+                                button {
+                                    class: "ghost",
+                                    onclick: move |_| {
+                                        direct_connect_error.set(None);
+                                        show_direct_connect.set(true);
+                                    },
+                                    "Прямое подключение"
+                                }
+                            }
+                        } else {
+                            h3 { "Ничего не нашли" }
+                            p { class: "muted", "Попробуй изменить фильтры или строку поиска." }
+                            div { class: "empty-state-actions",
+                                button { class: "primary", onclick: move |_| reset_filters(), "Сбросить фильтры" }
+                                button {
+                                    class: "ghost",
+                                    onclick: move |_| {
+                                        direct_connect_error.set(None);
+                                        show_direct_connect.set(true);
+                                    },
+                                    "Прямое подключение"
+                                }
+                            }
+                        }
                     }
                 } else {
-                    for (i, (server, addr_connect, addr_fav)) in filtered_servers.into_iter().enumerate() {
+                    for (i, server) in filtered_servers.into_iter().enumerate() {
                         if i == favorite_count && favorite_count > 0 && favorite_count < filtered_servers_len {
                             div { class: "settings-divider" }
                         }
                         {
+                            let addr_connect = server.address.clone();
+                            let addr_fav = server.address.clone();
                             let key = addr_connect.clone();
                             let expanded = expanded_desc().contains(&key);
                             let mut expanded_sig = expanded_desc;
                             let servers_sig = servers;
-                            let needs_desc_fetch = server.description.is_none();
+                            let needs_desc_fetch = server.description.is_none() || server.auth_mode.is_none();
+                            let desc_hit = {
+                                let needle = search().to_lowercase();
+                                search_in_desc()
+                                    && !needle.is_empty()
+                                    && !server.name.to_lowercase().contains(&needle)
+                                    && search::description_matches(&server, &needle)
+                            };
                             let addr_connect_for_desc = addr_connect.clone();
                             let fav_key = favorites::canonicalize_favorite_address(&addr_fav);
                             let is_fav = favorites_set().contains(&fav_key);
+                            let is_hidden = hidden_set().contains(&fav_key);
+                            let is_selected = selected().contains(&fav_key);
+                            let select_key = fav_key.clone();
                             let mut fav_sig = favorites_set;
+                            let draggable = is_fav && !select_mode();
+                            let drag_key = fav_key.clone();
+                            let drop_key = fav_key.clone();
+                            let pin_key = fav_key.clone();
+                            let is_pinned = is_fav && favorites_layout().is_pinned(&fav_key);
+                            let is_drop_target = draggable
+                                && dragging_fav().is_some_and(|d| d != fav_key);
+                            let drop_order = visible_fav_order.clone();
+                            let change = server_changes().get(&addr_connect).copied();
+                            let content_update = is_fav.then(|| content_updates_sig().get(&fav_key).cloned()).flatten();
+                            let predownload_key = fav_key.clone();
+                            let predownload_name = server.name.clone();
+                            let is_predownloading = predownloading().contains(&fav_key);
+                            let needs_account = requires_account(&server, active_account().is_some());
                             rsx! {
-                                div { key: "{addr_connect}", class: "server-card row",
+                                div {
+                                    key: "{addr_connect}",
+                                    class: format_args!(
+                                        "server-card row {} {} {} {}",
+                                        if is_selected { "selected" } else { "" },
+                                        if is_hidden { "hidden-server" } else { "" },
+                                        if is_drop_target { "drop-target" } else { "" },
+                                        change.map(ServerChange::css_class).unwrap_or(""),
+                                    ),
+                                    draggable: if draggable { "true" } else { "false" },
+                                    prevent_default: if draggable { "ondragover ondrop" } else { "" },
+                                    ondragstart: move |_| {
+                                        if draggable {
+                                            dragging_fav.set(Some(drag_key.clone()));
+                                        }
+                                    },
+                                    ondragover: move |_| {},
+                                    ondragend: move |_| dragging_fav.set(None),
+                                    ondrop: move |_| {
+                                        let Some(dragged) = dragging_fav() else { return };
+                                        dragging_fav.set(None);
+                                        if !draggable || dragged == drop_key {
+                                            return;
+                                        }
+                                        let mut layout = favorites_layout();
+                                        layout.move_before(&dragged, &drop_key, &drop_order);
+                                        save_layout(layout);
+                                    },
                                     div { class: "server-row",
                                         div { class: "server-main",
+                                            if select_mode() {
+                                                input {
+                                                    r#type: "checkbox",
+                                                    checked: is_selected,
+                                                    onchange: move |_| {
+                                                        let mut set = selected();
+                                                        if !set.remove(&select_key) {
+                                                            set.insert(select_key.clone());
+                                                        }
+                                                        selected.set(set);
+                                                    }
+                                                }
+                                            }
                                             div { class: "server-name-block",
                                                 div { class: "name-line",
                                                     h3 { title: server.name.clone(), {truncate_name(&server.name, 100)} }
+                                                    if local_only_favs.read().contains(&fav_key) {
+                                                        span {
+                                                            class: "update-badge local-only-badge",
+                                                            title: "это избранное ещё не синхронизировано и есть только на этом компьютере",
+                                                            "только локально"
+                                                        }
+                                                    }
+                                                    if let Some(update) = content_update.clone() {
+                                                        span {
+                                                            class: "update-badge",
+                                                            title: format!("новая версия контента: {}", update.version),
+                                                            {match update.size_bytes {
+                                                                Some(size) => format!("обновление (~{})", format_bytes(size)),
+                                                                None => "обновление".to_string(),
+                                                            }}
+                                                        }
+                                                    }
+                                                    if show_hub_labels {
+                                                        span {
+                                                            class: "hub-source",
+                                                            title: format!("из хаба {}", server.hub),
+                                                            {hub_label(&server.hub)}
+                                                        }
+                                                    }
                                                     if let Some(region) = server.region.clone() {
+                                                        if server.region_inferred {
+                                                            span {
+                                                                class: "region-pill inferred",
+                                                                title: "регион определён по GeoIP",
+                                                                {format!("~{}", display_region(&region))}
+                                                            }
+                                                        } else {
                                                             span { class: "region-pill", {display_region(&region)} }
+                                                        }
                                                     }
                                                 }
 
                                                 if !server.tags.is_empty() {
                                                     div { class: "tag-row dense",
-                                                            for tag in server.tags.iter() {
+                                                            if let Some(mode) = server.game_mode.as_deref() {
+                                                                span { class: "tag tag-mode", title: "режим", {mode.to_string()} }
+                                                            }
+                                                            if server.vanilla {
+                                                                span { class: "tag tag-vanilla", "ванилла" }
+                                                            }
+                                                            if server.adult {
+                                                                span { class: "tag tag-adult", "18+" }
+                                                            }
+                                                            for tag in server.tags.iter().filter(|t| !ServerEntry::is_structured_tag(t)) {
                                                                 if let Some(label) = display_tag(tag) {
                                                                     span { class: "tag", {label} }
                                                                 }
@@ -600,6 +1688,15 @@ pub fn tab_home(active_account: Signal<Option<LoginInfo>>) -> Element {
 
                                         div { class: "server-right",
                                             div { class: "stat-line",
+                                                if let Some(change) = change {
+                                                    span { class: "change-badge {change.css_class()}", {change.label()} }
+                                                }
+                                                if desc_hit {
+                                                    span { class: "change-badge search-badge", "в описании" }
+                                                }
+                                                if let Some(mode) = server.auth_mode {
+                                                    span { class: "stat auth {auth_css_class(mode)}", title: auth_title(mode), {auth_icon(mode)} }
+                                                }
                                                 span { class: "stat players", {format!("{}/{}", server.players, server.max_players)} }
                                                 span { class: "stat ping", {server.ping_ms.map(|p| format!("{} мс", p)).unwrap_or_else(|| "—".to_string())} }
                                             }
@@ -607,23 +1704,13 @@ pub fn tab_home(active_account: Signal<Option<LoginInfo>>) -> Element {
                                             div { class: "server-actions",
                                                 button {
                                                     class: "primary small",
-                                                    disabled: !server.online || connecting(),
+                                                    disabled: !server.online || connecting() || needs_account,
+                                                    title: if needs_account { NEEDS_ACCOUNT_HINT } else { "" },
                                                     onclick: move |_| {
                                                         start_connect_task(
                                                             addr_connect.clone(),
                                                             active_account(),
-                                                            connecting,
-                                                            show_connect_modal,
-                                                            connect_message,
-                                                            connect_stage,
-                                                            connect_download_label,
-                                                            connect_done_bytes,
-                                                            connect_total_bytes,
-                                                            connect_logs,
-                                                            connect_cancel,
-                                                            connect_success,
-                                                            game_launched_at,
-                                                            last_launcher_activity_at,
+                                                            connect_signals,
                                                         );
                                                     },
                                                     "Подключиться"
@@ -645,17 +1732,10 @@ pub fn tab_home(active_account: Signal<Option<LoginInfo>>) -> Element {
                                                             let mut servers_sig2 = servers_sig;
                                                             let address = addr_connect_for_desc.clone();
                                                             spawn(async move {
-                                                                match fetch_server_description(&address).await {
-                                                                    Ok(desc) => {
-                                                                        let mut list = servers_sig2();
-                                                                        if let Some(srv) = list.iter_mut().find(|s| s.address == address) {
-                                                                            srv.description = Some(
-                                                                                desc.unwrap_or_else(|| "Описание не указано".to_string()),
-                                                                            );
-                                                                            servers_sig2.set(list);
-                                                                        }
-                                                                    }
-                                                                    Err(_) => {}
+                                                                if let Ok(summary) = fetch_server_summary(&address).await {
+                                                                    let mut list = servers_sig2();
+                                                                    search::apply_summaries(&mut list, &HashMap::from([(address, summary)]));
+                                                                    servers_sig2.set(list);
                                                                 }
                                                             });
                                                         }
@@ -668,20 +1748,121 @@ pub fn tab_home(active_account: Signal<Option<LoginInfo>>) -> Element {
                                                     onclick: move |_| {
                                                         let mut set = fav_sig();
                                                         favorites::toggle_favorite(&mut set, &addr_fav);
-                                                        fav_sig.set(set.clone());
-
-                                                        spawn(async move {
-                                                            let _ = tokio::task::spawn_blocking(move || favorites::save_favorites(&set)).await;
+                                                        fav_sig.set(set);
+
+                                                        let address = addr_fav.clone();
+                                                        save_favorites_change(favorites_signals, move |set, _| {
+                                                            if is_fav {
+                                                                set.remove(&address);
+                                                            } else {
+                                                                set.insert(address);
+                                                            }
                                                         });
                                                     },
                                                     { if is_fav { "В избранном" } else { "В избранное" } }
                                                 }
+
+                                                if content_update.is_some() {
+                                                    button {
+                                                        class: "ghost small",
+                                                        disabled: is_predownloading,
+                                                        title: "Скачать новый контент сейчас, чтобы подключение потом было быстрым",
+                                                        onclick: move |_| {
+                                                            let address = predownload_key.clone();
+                                                            let name = predownload_name.clone();
+                                                            let mut predownloading = predownloading;
+                                                            let mut updates = content_updates_sig;
+                                                            predownloading.write().insert(address.clone());
+                                                            spawn(async move {
+                                                                let task = tasks::register(TaskKind::Prefetch, format!("Контент: {name}"), true);
+                                                                let addr = address.clone();
+                                                                let res = tokio::task::spawn_blocking(move || {
+                                                                    let res = content_updates::predownload_content(&addr, &task);
+                                                                    task.finish(&res);
+                                                                    res
+                                                                })
+                                                                .await;
+                                                                predownloading.write().remove(&address);
+                                                                if matches!(res, Ok(Ok(()))) {
+                                                                    updates.write().remove(&address);
+                                                                }
+                                                            });
+                                                        },
+                                                        { if is_predownloading { "Скачивается…" } else { "Скачать заранее" } }
+                                                    }
+                                                }
+
+                                                if is_fav {
+                                                    button {
+                                                        class: format_args!("ghost small {}", if is_pinned { "active" } else { "" }),
+                                                        title: "Закреплённые серверы показываются плитками над списком",
+                                                        onclick: move |_| {
+                                                            let mut layout = favorites_layout();
+                                                            match layout.toggle_pin(&pin_key) {
+                                                                Ok(()) => save_layout(layout),
+                                                                Err(e) => favorites_error.set(Some(e)),
+                                                            }
+                                                        },
+                                                        { if is_pinned { "Открепить" } else { "Закрепить" } }
+                                                    }
+                                                }
                                             }
                                         }
                                     }
 
                                     if expanded {
-                                        div { class: "server-description", { server.description.clone().unwrap_or_else(|| "Описание недоступно".to_string()) } }
+                                        div { class: "server-description",
+                                            {
+                                                let text = server.description.clone().unwrap_or_else(|| "Описание недоступно".to_string());
+                                                let needle = if search_in_desc() { search().to_lowercase() } else { String::new() };
+                                                rsx! {
+                                                    for (segment, hit) in search::highlight_segments(&text, &needle) {
+                                                        if hit {
+                                                            mark { class: "search-hit", {segment} }
+                                                        } else {
+                                                            {segment}
+                                                        }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                        if server.privacy_policy.is_some() || server.privacy_flags.hwid_required || server.privacy_flags.telemetry {
+                                            div { class: "server-privacy",
+                                                if server.privacy_flags.hwid_required {
+                                                    span { class: "tag tag-adult", title: "сервер сообщает, что проверяет HWID", "HWID" }
+                                                }
+                                                if server.privacy_flags.telemetry {
+                                                    span { class: "tag tag-adult", title: "сервер сообщает, что собирает телеметрию", "телеметрия" }
+                                                }
+                                                if let Some(link) = server.privacy_policy.clone() {
+                                                    button {
+                                                        class: "ghost small",
+                                                        title: link.clone(),
+                                                        onclick: move |_| crate::open_url::open(&link),
+                                                        "Политика конфиденциальности"
+                                                    }
+                                                }
+                                            }
+                                        }
+                                        {
+                                            let test_address = server.address.clone();
+                                            let overrides_address = server.address.clone();
+                                            rsx! {
+                                                div { class: "server-actions",
+                                                    button {
+                                                        class: "ghost small",
+                                                        onclick: move |_| connection_test_target.set(Some(test_address.clone())),
+                                                        "Проверить соединение"
+                                                    }
+                                                    button {
+                                                        class: "ghost small",
+                                                        title: "заменить адреса контента из /info (зеркало, обратный прокси)",
+                                                        onclick: move |_| build_overrides_target.set(Some(overrides_address.clone())),
+                                                        "Адреса сборки"
+                                                    }
+                                                }
+                                            }
+                                        }
                                     }
                                 }
                             }
@@ -693,22 +1874,93 @@ pub fn tab_home(active_account: Signal<Option<LoginInfo>>) -> Element {
     }
 }
 
-fn start_connect_task(
-    address: String,
-    account: Option<LoginInfo>,
-    mut connecting: Signal<bool>,
-    mut show_connect_modal: Signal<bool>,
-    mut connect_message: Signal<Option<String>>,
-    mut connect_stage: Signal<String>,
-    mut connect_download_label: Signal<Option<String>>,
-    mut connect_done_bytes: Signal<u64>,
-    mut connect_total_bytes: Signal<Option<u64>>,
-    mut connect_logs: Signal<Vec<String>>,
-    mut connect_cancel: Signal<Option<CancelFlag>>,
-    mut connect_success: Signal<bool>,
-    mut game_launched_at: Signal<Option<Instant>>,
-    last_launcher_activity_at: Signal<Instant>,
+const NEEDS_ACCOUNT_HINT: &str = "сервер требует авторизацию — войдите в аккаунт";
+
+/// Applies `change` to the saved favorites in the background, then shows what was saved
+/// and synced. The caller updates the signals right away for a responsive UI.
+fn save_favorites_change(
+    signals: FavoritesSignals,
+    change: impl FnOnce(&mut HashSet<String>, &mut FavoritesLayout) + Send + 'static,
 ) {
+    let FavoritesSignals {
+        mut favorites,
+        mut layout,
+        mut error,
+        mut sync_done,
+    } = signals;
+    spawn(async move {
+        match tokio::task::spawn_blocking(move || favorites_sync::update_and_sync(change)).await {
+            Ok(Ok(saved)) => {
+                favorites.set(saved.favorites);
+                layout.set(saved.layout);
+                error.set(saved.sync_error.map(|e| format!("синхронизация: {e}")));
+            }
+            Ok(Err(e)) => error.set(Some(e)),
+            Err(e) => error.set(Some(format!("сохранение избранного: {e}"))),
+        }
+        *sync_done.write() += 1;
+    });
+}
+
+fn apply_selection(set: &mut HashSet<String>, selected: &HashSet<String>, add: bool) {
+    for addr in selected {
+        if add {
+            set.insert(addr.clone());
+        } else {
+            set.remove(addr);
+        }
+    }
+}
+
+/// Whether joining `server` is pointless without an account. Unknown auth modes are let
+/// through; the connect itself reports them.
+fn requires_account(server: &ServerEntry, has_account: bool) -> bool {
+    !has_account && server.auth_mode == Some(AuthMode::Required)
+}
+
+fn auth_icon(mode: AuthMode) -> &'static str {
+    match mode {
+        AuthMode::Required => "🔒",
+        AuthMode::Optional => "🔓",
+        AuthMode::Disabled => "👤",
+    }
+}
+
+fn auth_title(mode: AuthMode) -> &'static str {
+    match mode {
+        AuthMode::Required => "нужен вход в аккаунт",
+        AuthMode::Optional => "вход необязателен, можно зайти гостем",
+        AuthMode::Disabled => "авторизация отключена, все заходят гостями",
+    }
+}
+
+fn auth_css_class(mode: AuthMode) -> &'static str {
+    match mode {
+        AuthMode::Required => "auth-required",
+        AuthMode::Optional => "auth-optional",
+        AuthMode::Disabled => "auth-disabled",
+    }
+}
+
+fn start_connect_task(address: String, account: Option<LoginInfo>, signals: ConnectSignals) {
+    let ConnectSignals {
+        mut connecting,
+        show_modal: mut show_connect_modal,
+        message: mut connect_message,
+        error: mut connect_error,
+        stage: mut connect_stage,
+        downloads: mut connect_downloads,
+        warnings: mut connect_warnings,
+        logs: mut connect_logs,
+        cancel: mut connect_cancel,
+        success: mut connect_success,
+        mut game_launched_at,
+        mut auto_close_left,
+        mut engine_prompt,
+        mut guest_prompt,
+        mut engine_recovery,
+        mut config_recovery,
+    } = signals;
     if connecting() {
         return;
     }
@@ -717,14 +1969,19 @@ fn start_connect_task(
     show_connect_modal.set(true);
 
     connect_message.set(Some(format!("подключаемся к {}...", address)));
+    connect_error.set(None);
     connect_stage.set("подготовка...".to_string());
-    connect_download_label.set(None);
-    connect_done_bytes.set(0);
-    connect_total_bytes.set(None);
-    connect_logs.set(Vec::new());
+    connect_downloads.set(Vec::new());
+    connect_warnings.set(Vec::new());
+    connect_logs.write().clear();
 
     connect_success.set(false);
     game_launched_at.set(None);
+    auto_close_left.set(None);
+    engine_prompt.set(None);
+    guest_prompt.set(None);
+    engine_recovery.set(None);
+    config_recovery.set(None);
 
     let cancel_flag = CancelFlag::new();
     connect_cancel.set(Some(cancel_flag.clone()));
@@ -735,66 +1992,130 @@ fn start_connect_task(
         let mut connecting_sig = connecting;
         let mut connect_success_sig = connect_success;
 
-        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<ConnectProgress>();
+        let tx = ProgressTx::new();
+        let session_id = tx.session_id().to_string();
+        let mut rx = tx.subscribe();
+        // Keep a copy of the attempt on disk for bug reports, next to the loader's launch log.
+        if let Ok(logs) = app_paths::data_dir().map(|d| d.join("logs"))
+            && app_paths::create_dir(&logs, "логи").is_ok()
+        {
+            let path = connect_progress::session_log_path(&logs, "connect", &session_id);
+            let _ = connect_progress::spawn_file_logger(&tx, &path);
+        }
+        let recovery_address = address.clone();
+        let recovery_account = account.clone();
 
         let mut stage_sig2 = connect_stage;
-        let mut label_sig2 = connect_download_label;
-        let mut done_sig2 = connect_done_bytes;
-        let mut total_sig2 = connect_total_bytes;
+        let mut downloads_sig2 = connect_downloads;
+        let mut warnings_sig2 = connect_warnings;
         let mut logs_sig2 = connect_logs;
 
         let mut game_launched_at_sig2 = game_launched_at;
         let show_connect_modal_sig2 = show_connect_modal;
         let connect_success_sig2 = connect_success_sig;
         let connecting_sig2 = connecting_sig;
-        let last_activity_sig2 = last_launcher_activity_at;
         spawn(async move {
             while let Some(ev) = rx.recv().await {
                 match ev {
                     ConnectProgress::Stage(s) => stage_sig2.set(s),
+                    ConnectProgress::Warning(w) => {
+                        let mut list = warnings_sig2();
+                        if !list.contains(&w) {
+                            list.push(w);
+                            warnings_sig2.set(list);
+                        }
+                    }
                     ConnectProgress::Download {
                         label,
                         done_bytes,
                         total_bytes,
                     } => {
-                        label_sig2.set(Some(label));
-                        done_sig2.set(done_bytes);
-                        total_sig2.set(total_bytes);
-                    }
-                    ConnectProgress::Log(line) => {
-                        let mut lines = logs_sig2();
-                        lines.push(line);
-                        if lines.len() > 200 {
-                            let drop = lines.len() - 200;
-                            lines.drain(0..drop);
+                        // Engine and content download in parallel; keep one line per label.
+                        let mut lines = downloads_sig2();
+                        match lines.iter_mut().find(|l| l.label == label) {
+                            Some(line) => {
+                                line.done_bytes = done_bytes;
+                                line.total_bytes = total_bytes;
+                            }
+                            None => lines.push(DownloadLine {
+                                label,
+                                done_bytes,
+                                total_bytes,
+                            }),
                         }
-                        logs_sig2.set(lines);
+                        downloads_sig2.set(lines);
+                    }
+                    ConnectProgress::Log(line) => logs_sig2.write().push(line),
+                    ConnectProgress::EngineFallbackPrompt {
+                        requested,
+                        candidate,
+                        reply,
+                    } => {
+                        engine_prompt.set(Some(EnginePrompt {
+                            requested,
+                            candidate,
+                            reply,
+                        }));
+                    }
+                    ConnectProgress::GuestNamePrompt { suggested, reply } => {
+                        guest_prompt.set(Some(GuestPrompt { suggested, reply }));
+                    }
+                    ConnectProgress::EngineSignatureFailed { engine_version } => {
+                        engine_recovery.set(Some(EngineRecovery {
+                            engine_version,
+                            address: recovery_address.clone(),
+                            account: recovery_account.clone(),
+                        }));
+                    }
+                    ConnectProgress::KnownGoodAvailable { changes } => {
+                        config_recovery.set(Some(ConfigRecovery {
+                            changes,
+                            address: recovery_address.clone(),
+                            account: recovery_account.clone(),
+                        }));
                     }
                     ConnectProgress::GameLaunched { exe_path: _ } => {
                         if game_launched_at_sig2().is_none() {
                             let launched_at = Instant::now();
                             game_launched_at_sig2.set(Some(launched_at));
 
+                            let Some(delay) = settings::load_settings()
+                                .unwrap_or_default()
+                                .game
+                                .auto_close_after_secs()
+                            else {
+                                continue;
+                            };
+                            auto_close_left.set(Some(delay));
+
                             let mut show_connect_modal_sig3 = show_connect_modal_sig2;
                             let connecting_sig3 = connecting_sig2;
                             let connect_success_sig3 = connect_success_sig2;
                             let game_launched_at_sig3 = game_launched_at_sig2;
-                            let last_activity_sig3 = last_activity_sig2;
+                            let mut auto_close_sig3 = auto_close_left;
                             spawn(async move {
-                                tokio::time::sleep(Duration::from_secs(10)).await;
-
-                                if !show_connect_modal_sig3() {
-                                    return;
-                                }
-
-                                // Only close if connection finished successfully,
-                                // and the user didn't interact with the launcher after the game started.
-                                if !connecting_sig3()
-                                    && connect_success_sig3()
-                                    && game_launched_at_sig3() == Some(launched_at)
-                                    && last_activity_sig3() <= launched_at
-                                {
-                                    show_connect_modal_sig3.set(false);
+                                loop {
+                                    // "Не закрывать" clears the countdown; a new connect replaces it.
+                                    let Some(left) = auto_close_sig3() else {
+                                        return;
+                                    };
+                                    if !show_connect_modal_sig3()
+                                        || game_launched_at_sig3() != Some(launched_at)
+                                    {
+                                        return;
+                                    }
+                                    if left == 0 && !connecting_sig3() {
+                                        auto_close_sig3.set(None);
+                                        // Only close if the connection finished successfully.
+                                        if connect_success_sig3() {
+                                            show_connect_modal_sig3.set(false);
+                                        }
+                                        return;
+                                    }
+                                    tokio::time::sleep(Duration::from_secs(1)).await;
+                                    if let Some(left) = auto_close_sig3() {
+                                        auto_close_sig3.set(Some(left.saturating_sub(1)));
+                                    }
                                 }
                             });
                         }
@@ -804,12 +2125,19 @@ fn start_connect_task(
         });
 
         let res = tokio::task::spawn_blocking(move || {
-            crate::connect::connect_to_ss14_address(
+            // Closing the window while this runs cancels it instead of abandoning it.
+            let _shutdown_guard = crate::core::shutdown::track_connect(cancel_flag.clone());
+            let user_id = account.as_ref().map(|a| a.user_id);
+            let res = crate::connect::connect_to_ss14_address(
                 &address,
                 account,
                 Some(tx),
                 Some(cancel_flag),
-            )
+            );
+            if matches!(&res, Ok(ok) if ok.launched) {
+                let _ = last_server::save_last_server(&address, user_id);
+            }
+            res
         })
         .await;
 
@@ -818,28 +2146,28 @@ fn start_connect_task(
                 connect_success_sig.set(ok.launched);
                 msg_sig.set(Some(ok.message));
             }
-            Ok(Err(e)) => msg_sig.set(Some(format!("ошибка подключения: {e}"))),
-            Err(e) => msg_sig.set(Some(format!("ошибка задачи: {e}"))),
+            Ok(Err(e)) if e.stopped_by_user() => {
+                // Questions the cancelled attempt asked no longer have anyone waiting.
+                guest_prompt.set(None);
+                engine_prompt.set(None);
+                // A force stop of a launched game reports its own result.
+                if game_launched_at.peek().is_none() {
+                    msg_sig.set(Some("подключение отменено".to_string()));
+                }
+            }
+            Ok(Err(e)) => {
+                msg_sig.set(None);
+                connect_error.set(Some(format!(
+                    "ошибка подключения: {e}\nсессия: {session_id} (logs/connect-{session_id}.log)"
+                )));
+            }
+            Err(e) => {
+                msg_sig.set(None);
+                connect_error.set(Some(format!("ошибка задачи: {e}")));
+            }
         }
 
         connecting_sig.set(false);
         cancel_sig.set(None);
     });
 }
-
-fn format_bytes(bytes: u64) -> String {
-    const KB: f64 = 1024.0;
-    const MB: f64 = 1024.0 * 1024.0;
-    const GB: f64 = 1024.0 * 1024.0 * 1024.0;
-
-    let b = bytes as f64;
-    if b >= GB {
-        format!("{:.1} GiB", b / GB)
-    } else if b >= MB {
-        format!("{:.1} MiB", b / MB)
-    } else if b >= KB {
-        format!("{:.1} KiB", b / KB)
-    } else {
-        format!("{} B", bytes)
-    }
-}
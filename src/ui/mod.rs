@@ -1,20 +1,36 @@
+use std::collections::HashSet;
+
 use dioxus::prelude::*;
+use dioxus_desktop::tao::event::{Event, WindowEvent};
 
+pub mod avatar;
+pub mod clipboard;
+pub mod error_box;
 pub mod icons;
 pub mod home;
 pub mod news;
 pub mod patches;
 pub mod settings;
+pub mod startup_banner;
+pub mod tasks;
 pub mod window;
 
 use crate::account_store;
-use crate::auth::{AuthApi, AuthenticateResult, LoginInfo};
-use crate::constants::{APP_TITLE, STYLE};
-use crate::ui::home::tab_home;
+use crate::core::shutdown;
+use crate::ui_state;
+use crate::auth::{
+    AuthApi, AuthenticateDenyResponseCode, AuthenticateResult, LoginInfo, TokenCheck,
+};
+use crate::constants::{APP_TITLE, APP_VERSION_LABEL, STYLE};
+use crate::ui::avatar::AccountBadge;
+use crate::ui::error_box::ErrorBox;
+use crate::ui::home::{tab_home, use_home_store};
 use crate::open_url;
 use crate::ui::patches::PatchesState;
 use crate::ui::news::tab_news;
 use crate::ui::settings::tab_settings;
+use crate::ui::startup_banner::StartupBanner;
+use crate::ui::tasks::TasksButton;
 
 const DISCORD_INVITE_URL: &str = "https://discord.gg/HWvEa6KRYb";
 const ACCOUNT_REGISTER_URL: &str = "https://account.spacestation14.com/Identity/Account/Register";
@@ -26,15 +42,130 @@ enum Tab {
     Settings,
 }
 
+impl Tab {
+    fn as_key(self) -> &'static str {
+        match self {
+            Tab::Home => "home",
+            Tab::News => "news",
+            Tab::Settings => "settings",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "home" => Some(Tab::Home),
+            "news" => Some(Tab::News),
+            "settings" => Some(Tab::Settings),
+            _ => None,
+        }
+    }
+}
+
 pub fn app() -> Element {
     let auth_api = use_signal(AuthApi::new);
     let mut show_login = use_signal(|| true);
     let menu_open = use_signal(|| false);
     let mut active_account: Signal<Option<LoginInfo>> = use_signal(|| None);
+    // Guest name while playing without an account by choice.
+    let mut guest_name: Signal<Option<String>> = use_signal(|| None);
+    // Saved accounts whose token the auth server no longer accepts.
+    let mut needs_relogin: Signal<HashSet<uuid::Uuid>> = use_signal(HashSet::new);
     let saved_accounts: Signal<Vec<LoginInfo>> = use_signal(Vec::new);
-    let mut active_tab = use_signal(|| Tab::Home);
+    // Picking an account in any way (login, account menu, undo) ends guest mode.
+    use_effect(move || {
+        if active_account.read().is_some() && guest_name.peek().is_some() {
+            guest_name.set(None);
+            let _ = crate::settings::set_guest_mode(false);
+        }
+    });
+    let mut active_tab = use_signal(|| {
+        crate::cli_args::take_startup_tab()
+            .and_then(Tab::from_key)
+            .or_else(|| Tab::from_key(&ui_state::load_ui_state().main_tab))
+            .unwrap_or(Tab::Home)
+    });
+
+    use_effect(move || {
+        let key = active_tab().as_key();
+        let _ = ui_state::update_ui_state(|s| s.main_tab = key.to_string());
+    });
+
+    // Leftovers of downloads interrupted by a crash or a killed process.
+    use_future(move || async move {
+        if let Ok(data_dir) = crate::app_paths::data_dir() {
+            let _ = tokio::task::spawn_blocking(move || {
+                crate::cache_cleanup::sweep_temp_files(&data_dir)
+            })
+            .await;
+        }
+    });
+
+    // Logs and history past their retention window.
+    use_future(crate::cache_cleanup::prune_expired_data_periodically);
 
     let patches_state: Signal<PatchesState> = use_signal(PatchesState::default);
+    // Set by "Добавить хаб" on the home tab; the settings tab opens the hub list and clears it.
+    let mut open_hub_settings = use_signal(|| false);
+    let home_store = use_home_store();
+    let mut palette_open = use_signal(|| false);
+
+    // Ctrl+K opens the quick-connect palette from any tab.
+    use_future(move || async move {
+        let mut js = eval(
+            r#"
+            document.addEventListener("keydown", (e) => {
+                if ((e.ctrlKey || e.metaKey) && e.code === "KeyK") {
+                    e.preventDefault();
+                    dioxus.send(true);
+                }
+            });
+            "#,
+        );
+        while js.recv().await.is_ok() {
+            active_tab.set(Tab::Home);
+            palette_open.set(true);
+        }
+    });
+
+    // Closing only hides the window (see `app_window`): the process exits once background work
+    // is wound down, off the UI thread, or the window comes back if the user changed their mind.
+    let close_requests = use_hook(|| {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<bool>();
+        spawn(async move {
+            while let Some(confirmed) = rx.recv().await {
+                let desktop = dioxus_desktop::window();
+                if !confirmed {
+                    desktop.set_visible(true);
+                    continue;
+                }
+                let _ = tokio::task::spawn_blocking(shutdown::shutdown).await;
+                desktop.close();
+            }
+        });
+        tx
+    });
+    dioxus_desktop::use_wry_event_handler(move |event, _| {
+        if let Event::WindowEvent {
+            event: WindowEvent::CloseRequested,
+            ..
+        } = event
+        {
+            let warn = crate::settings::load_settings()
+                .map(|s| s.game.warn_on_close_while_preparing)
+                .unwrap_or(true);
+            let confirmed = !(warn && shutdown::preparation_in_progress())
+                || rfd::MessageDialog::new()
+                    .set_level(rfd::MessageLevel::Warning)
+                    .set_title(APP_TITLE)
+                    .set_description(
+                        "Идёт подготовка игры — она будет прервана. Уже скачанное сохранится и пригодится при следующем подключении. Закрыть лаунчер?",
+                    )
+                    .set_buttons(rfd::MessageButtons::OkCancel)
+                    .show()
+                    == rfd::MessageDialogResult::Ok;
+            let _ = close_requests.send(confirmed);
+        }
+    });
 
     let mut toggle_menu = menu_open;
     let mut close_menu = menu_open;
@@ -44,6 +175,10 @@ pub fn app() -> Element {
     let menu_state = menu_open;
     let current_account = active_account();
     let can_close_login = !saved_accounts().is_empty();
+    let saved_accounts_len = saved_accounts().len();
+    let mut rename_target: Signal<Option<LoginInfo>> = use_signal(|| None);
+    let mut sign_out_target: Signal<Option<LoginInfo>> = use_signal(|| None);
+    let mut undo_removal: Signal<Option<UndoRemoval>> = use_signal(|| None);
 
     {
         let mut saved_accounts = saved_accounts;
@@ -58,14 +193,25 @@ pub fn app() -> Element {
         let mut active_account = active_account;
         let mut show_login = show_login;
         use_future(move || async move {
-            let allow_auto_login = crate::settings::load_settings()
-                .ok()
+            let settings = crate::settings::load_settings().ok();
+            let allow_auto_login = settings
+                .as_ref()
                 .map(|s| s.security.auto_login)
                 .unwrap_or(true);
 
             if allow_auto_login && let Ok(Some(info)) = account_store::load_saved_login() {
+                let token = info.token.clone();
+                let user_id = info.user_id;
                 active_account.set(Some(info));
                 show_login.set(false);
+                // Offline or an unreachable auth server is not a reason to ask for a login.
+                let api = auth_api.peek().clone();
+                if let Ok(TokenCheck::Revoked) = api.check_token(&token).await {
+                    needs_relogin.write().insert(user_id);
+                }
+            } else if let Some(game) = settings.map(|s| s.game).filter(|g| g.guest_mode) {
+                guest_name.set(Some(game.guest_username));
+                show_login.set(false);
             }
         });
     }
@@ -77,6 +223,10 @@ pub fn app() -> Element {
         });
     }
 
+    use_future(|| async {
+        crate::ss14_loader::warm_loader_in_background();
+    });
+
     rsx! {
         Fragment {
             style { {STYLE} }
@@ -89,22 +239,37 @@ pub fn app() -> Element {
                         }
                         div { class: "title-right",
                             div { class: "title-right-links",
+                                TasksButton {}
                                 button {
                                     class: "pill discord-pill",
                                     onclick: move |_| open_url::open(DISCORD_INVITE_URL),
                                     DiscordIcon {}
                                     span { "Discord" }
                                 }
-                                span { class: "badge", "1.0.0-release" }
+                                span { class: "badge", {APP_VERSION_LABEL} }
                             }
                         }
                     }
 
+                    StartupBanner {
+                        on_open_settings: move |_| active_tab.set(Tab::Settings),
+                        on_login: move |_| show_login.set(true),
+                    }
+
                     div { class: "tab-panel",
                         match active_tab() {
-                            Tab::Home => rsx!(tab_home { active_account }),
+                            Tab::Home => rsx!(tab_home {
+                                store: home_store,
+                                active_account,
+                                palette_open,
+                                on_open_settings: move |_| active_tab.set(Tab::Settings),
+                                on_add_hub: move |_| {
+                                    open_hub_settings.set(true);
+                                    active_tab.set(Tab::Settings);
+                                },
+                            }),
                             Tab::News => rsx!(tab_news {}),
-                            Tab::Settings => rsx!(tab_settings { patches_state }),
+                            Tab::Settings => rsx!(tab_settings { patches_state, open_hub_settings }),
                         }
                     }
 
@@ -129,17 +294,29 @@ pub fn app() -> Element {
 
                         div { class: "account-menu tabs-account",
                             button {
-                                class: "tab tab-outline",
+                                class: "tab tab-outline account-tab",
                                 onclick: move |_| toggle_menu.set(!toggle_menu()),
-                                {current_account.as_ref().map(|a| a.username.clone()).unwrap_or_else(|| "Войти".to_string())}
+                                if let Some(account) = current_account.as_ref() {
+                                    AccountBadge { name: account.display_name().to_string(), user_id: account.user_id }
+                                    {account.display_name().to_string()}
+                                    if needs_relogin().contains(&account.user_id) {
+                                        span { class: "needs-relogin", title: "вход устарел — войдите заново", "⚠" }
+                                    }
+                                } else if let Some(name) = guest_name() {
+                                    span { title: "игра без аккаунта", {format!("Гость: {name}")} }
+                                } else {
+                                    "Войти"
+                                }
                             }
 
                             if menu_state() {
                                 div { class: "dropdown up",
-                                    for account in saved_accounts().into_iter() {
+                                    for (idx, account) in saved_accounts().into_iter().enumerate() {
                                         {
                                             let account_id = account.user_id;
-                                            let account_name = account.username.clone();
+                                            let account_name = account.display_name().to_string();
+                                            let account_username = account.alias.as_ref().map(|_| account.username.clone());
+                                            let stale = needs_relogin().contains(&account.user_id);
                                             let is_current = current_account
                                                 .as_ref()
                                                 .map(|cur| cur.user_id == account_id)
@@ -149,25 +326,80 @@ pub fn app() -> Element {
                                             } else {
                                                 "dropdown-item"
                                             };
+                                            let is_first = idx == 0;
+                                            let is_last = idx + 1 == saved_accounts_len;
 
                                             let mut active_account_sig = active_account_sig;
                                             let mut close_menu = close_menu;
                                             let mut login_open = login_open;
                                             let mut saved_accounts_sig = saved_accounts_sig;
+                                            let mut rename_target = rename_target;
                                             let account_clone = account.clone();
+                                            let account_for_rename = account.clone();
                                             rsx! {
-                                                button {
-                                                    class: class_name,
-                                                    onclick: move |_| {
-                                                        close_menu.set(false);
-                                                        let _ = account_store::set_active_login(account_id);
-                                                        active_account_sig.set(Some(account_clone.clone()));
-                                                        login_open.set(false);
-                                                        if let Ok(list) = account_store::load_saved_logins() {
-                                                            saved_accounts_sig.set(list);
+                                                div { class: "dropdown-row",
+                                                    button {
+                                                        class: class_name,
+                                                        onclick: move |_| {
+                                                            close_menu.set(false);
+                                                            let _ = account_store::set_active_login(account_id);
+                                                            active_account_sig.set(Some(account_clone.clone()));
+                                                            login_open.set(stale);
+                                                            if let Ok(list) = account_store::load_saved_logins() {
+                                                                saved_accounts_sig.set(list);
+                                                            }
+                                                        },
+                                                        div { class: "account-line",
+                                                            AccountBadge { name: account_name.clone(), user_id: account_id }
+                                                            div { class: "account-names",
+                                                                {account_name}
+                                                                if let Some(username) = account_username {
+                                                                    span { class: "dropdown-item-sub", {username} }
+                                                                }
+                                                                if stale {
+                                                                    span { class: "dropdown-item-sub needs-relogin", "нужно войти заново" }
+                                                                }
+                                                            }
                                                         }
-                                                    },
-                                                    {account_name}
+                                                    }
+                                                    button {
+                                                        class: "dropdown-mini",
+                                                        title: "выше",
+                                                        disabled: is_first,
+                                                        onclick: move |evt| {
+                                                            evt.stop_propagation();
+                                                            if account_store::move_login(account_id, -1).is_ok()
+                                                                && let Ok(list) = account_store::load_saved_logins()
+                                                            {
+                                                                saved_accounts_sig.set(list);
+                                                            }
+                                                        },
+                                                        "↑"
+                                                    }
+                                                    button {
+                                                        class: "dropdown-mini",
+                                                        title: "ниже",
+                                                        disabled: is_last,
+                                                        onclick: move |evt| {
+                                                            evt.stop_propagation();
+                                                            if account_store::move_login(account_id, 1).is_ok()
+                                                                && let Ok(list) = account_store::load_saved_logins()
+                                                            {
+                                                                saved_accounts_sig.set(list);
+                                                            }
+                                                        },
+                                                        "↓"
+                                                    }
+                                                    button {
+                                                        class: "dropdown-mini",
+                                                        title: "переименовать",
+                                                        onclick: move |evt| {
+                                                            evt.stop_propagation();
+                                                            close_menu.set(false);
+                                                            rename_target.set(Some(account_for_rename.clone()));
+                                                        },
+                                                        "✎"
+                                                    }
                                                 }
                                             }
                                         }
@@ -186,38 +418,14 @@ pub fn app() -> Element {
 
                                     if let Some(account) = current_account {
                                         {
-                                            let user_id = account.user_id;
                                             let mut close_menu = close_menu;
-                                            let mut active_account_sig = active_account_sig;
-                                            let mut saved_accounts_sig = saved_accounts_sig;
-                                            let mut login_open = login_open;
+                                            let mut sign_out_target = sign_out_target;
                                             rsx! {
                                                 button {
                                                     class: "dropdown-item",
                                                     onclick: move |_| {
                                                         close_menu.set(false);
-                                                        let before = saved_accounts_sig();
-                                                        let removed_index = before.iter().position(|a| a.user_id == user_id);
-
-                                                        let _ = account_store::remove_login(user_id);
-                                                        let list = account_store::load_saved_logins().unwrap_or_default();
-                                                        saved_accounts_sig.set(list.clone());
-
-                                                        if list.is_empty() {
-                                                            active_account_sig.set(None);
-                                                            login_open.set(true);
-                                                            return;
-                                                        }
-
-                                                        let mut pick_index = removed_index.unwrap_or(0);
-                                                        if pick_index >= list.len() {
-                                                            pick_index = list.len() - 1;
-                                                        }
-
-                                                        let picked = list[pick_index].clone();
-                                                        let _ = account_store::set_active_login(picked.user_id);
-                                                        active_account_sig.set(Some(picked));
-                                                        login_open.set(false);
+                                                        sign_out_target.set(Some(account.clone()));
                                                     },
                                                     "Выйти"
                                                 }
@@ -230,11 +438,101 @@ pub fn app() -> Element {
                     }
                 }
 
+                if let Some(target) = rename_target() {
+                    AccountAliasModal {
+                        user_id: target.user_id,
+                        username: target.username.clone(),
+                        current_alias: target.alias.clone(),
+                        on_saved: move |_| {
+                            let list = account_store::load_saved_logins().unwrap_or_default();
+                            if let Some(cur) = active_account() {
+                                let refreshed = list.iter().find(|a| a.user_id == cur.user_id).cloned();
+                                if refreshed.is_some() {
+                                    active_account.set(refreshed);
+                                }
+                            }
+                            saved_accounts_sig.set(list);
+                            rename_target.set(None);
+                        },
+                        on_close: move |_| rename_target.set(None),
+                    }
+                }
+
+                if let Some(target) = sign_out_target() {
+                    {
+                        let user_id = target.user_id;
+                        let name = target.display_name().to_string();
+                        let name_for_remove = name.clone();
+                        rsx! {
+                            SignOutModal {
+                                name: name.clone(),
+                                on_sign_out: move |_| {
+                                    sign_out_target.set(None);
+                                    let removed = account_store::sign_out_login(user_id);
+                                    let message = format!("Вы вышли из {name}, имя сохранено");
+                                    finish_account_removal(
+                                        removed,
+                                        message,
+                                        AccountSignals {
+                                            saved: saved_accounts_sig,
+                                            active: active_account,
+                                            login_open: show_login,
+                                            undo: undo_removal,
+                                        },
+                                    );
+                                },
+                                on_remove: move |_| {
+                                    sign_out_target.set(None);
+                                    let removed = account_store::remove_login(user_id);
+                                    let message = format!("Аккаунт {name_for_remove} удалён");
+                                    finish_account_removal(
+                                        removed,
+                                        message,
+                                        AccountSignals {
+                                            saved: saved_accounts_sig,
+                                            active: active_account,
+                                            login_open: show_login,
+                                            undo: undo_removal,
+                                        },
+                                    );
+                                },
+                                on_close: move |_| sign_out_target.set(None),
+                            }
+                        }
+                    }
+                }
+
+                if let Some(undo) = undo_removal() {
+                    div { class: "undo-toast",
+                        span { {undo.message.clone()} }
+                        if undo.removed.is_some() {
+                            button {
+                                class: "ghost small",
+                                onclick: move |_| {
+                                    let Some(removed) = undo_removal.take().and_then(|u| u.removed) else {
+                                        return;
+                                    };
+                                    let _ = account_store::restore_login(&removed);
+                                    let list = account_store::load_saved_logins().unwrap_or_default();
+                                    if let Some(restored) = list.iter().find(|a| a.user_id == removed.user_id()) {
+                                        let _ = account_store::set_active_login(restored.user_id);
+                                        active_account.set(Some(restored.clone()));
+                                        show_login.set(false);
+                                    }
+                                    saved_accounts_sig.set(list);
+                                },
+                                "Отменить"
+                            }
+                        }
+                    }
+                }
+
                 if show_login() {
                     LoginOverlay {
                         auth_api: auth_api,
                         can_close: can_close_login,
-                        on_success: move |info| {
+                        on_success: move |info: LoginInfo| {
+                            needs_relogin.write().remove(&info.user_id);
                             let _ = account_store::save_login(&info);
                             if let Ok(list) = account_store::load_saved_logins() {
                                 saved_accounts_sig.set(list);
@@ -244,7 +542,131 @@ pub fn app() -> Element {
                         },
                         on_close: move |_| {
                             show_login.set(false);
-                        }
+                        },
+                        on_guest: move |_| {
+                            let name = crate::settings::set_guest_mode(true).unwrap_or_else(|_| {
+                                crate::settings::DEFAULT_GUEST_USERNAME.to_string()
+                            });
+                            guest_name.set(Some(name));
+                            active_account.set(None);
+                            show_login.set(false);
+                        },
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// How long the undo toast stays after an account is signed out or removed.
+const UNDO_REMOVAL_WINDOW: std::time::Duration = std::time::Duration::from_secs(6);
+
+#[derive(Clone)]
+struct UndoRemoval {
+    /// Tells toasts apart, so the timer of an older one does not hide a newer one.
+    id: u64,
+    /// `None` when the removal itself failed and the toast only reports the error.
+    removed: Option<account_store::RemovedLogin>,
+    message: String,
+}
+
+#[derive(Clone, Copy)]
+struct AccountSignals {
+    saved: Signal<Vec<LoginInfo>>,
+    active: Signal<Option<LoginInfo>>,
+    login_open: Signal<bool>,
+    undo: Signal<Option<UndoRemoval>>,
+}
+
+/// After a sign-out or removal: refreshes the list and the active account and shows the
+/// undo toast.
+fn finish_account_removal(
+    result: Result<account_store::RemovedLogin, String>,
+    message: String,
+    mut signals: AccountSignals,
+) {
+    let (removed, message) = match result {
+        Ok(removed) => (Some(removed), message),
+        Err(e) => (None, format!("не удалось выйти из аккаунта: {e}")),
+    };
+    if let Some(removed) = &removed {
+        let removed_index = signals
+            .saved
+            .peek()
+            .iter()
+            .position(|a| a.user_id == removed.user_id());
+        pick_account_after_removal(removed_index, signals);
+    }
+
+    let id = signals.undo.peek().as_ref().map_or(0, |u| u.id) + 1;
+    signals.undo.set(Some(UndoRemoval {
+        id,
+        removed,
+        message,
+    }));
+    let mut undo = signals.undo;
+    spawn(async move {
+        tokio::time::sleep(UNDO_REMOVAL_WINDOW).await;
+        if undo.peek().as_ref().is_some_and(|u| u.id == id) {
+            undo.set(None);
+        }
+    });
+}
+
+/// Makes the account that took the removed one's place active, or opens the login form
+/// when none is left.
+fn pick_account_after_removal(removed_index: Option<usize>, mut signals: AccountSignals) {
+    let list = account_store::load_saved_logins().unwrap_or_default();
+    signals.saved.set(list.clone());
+    if list.is_empty() {
+        signals.active.set(None);
+        signals.login_open.set(true);
+    } else {
+        let pick_index = removed_index.unwrap_or(0).min(list.len() - 1);
+        let picked = list[pick_index].clone();
+        let _ = account_store::set_active_login(picked.user_id);
+        signals.active.set(Some(picked));
+        signals.login_open.set(false);
+    }
+}
+
+#[component]
+fn SignOutModal(
+    name: String,
+    on_sign_out: EventHandler<()>,
+    on_remove: EventHandler<()>,
+    on_close: EventHandler<()>,
+) -> Element {
+    rsx! {
+        div { class: "modal-backdrop", onclick: move |_| on_close.call(()),
+            div { class: "modal login-modal", onclick: move |evt| evt.stop_propagation(),
+                div { class: "modal-header",
+                    div {
+                        h3 { "выход из аккаунта" }
+                        p { class: "muted", {name} }
+                    }
+                }
+
+                div { class: "modal-body",
+                    p { "«Выйти» забывает токен, но оставляет имя пользователя — при следующем входе нужен только пароль." }
+                    p { "«Удалить» убирает аккаунт из лаунчера полностью." }
+                }
+
+                div { class: "modal-actions",
+                    button {
+                        class: "ghost modal-actions-left",
+                        onclick: move |_| on_close.call(()),
+                        "отмена"
+                    }
+                    button {
+                        class: "ghost",
+                        onclick: move |_| on_remove.call(()),
+                        "удалить"
+                    }
+                    button {
+                        class: "primary",
+                        onclick: move |_| on_sign_out.call(()),
+                        "выйти"
                     }
                 }
             }
@@ -270,119 +692,371 @@ fn DiscordIcon() -> Element {
 }
 
 #[component]
-fn LoginOverlay(
-    auth_api: Signal<AuthApi>,
-    on_success: EventHandler<LoginInfo>,
+fn AccountAliasModal(
+    user_id: uuid::Uuid,
+    username: String,
+    current_alias: Option<String>,
+    on_saved: EventHandler<()>,
     on_close: EventHandler<()>,
-    can_close: bool,
 ) -> Element {
-    let mut username = use_signal(String::new);
-    let mut password = use_signal(String::new);
-    let mut busy = use_signal(|| false);
+    let mut alias = use_signal(move || current_alias.unwrap_or_default());
     let mut error_message: Signal<Option<String>> = use_signal(|| None::<String>);
 
-    let button_disabled = move || busy() || username().trim().is_empty() || password().is_empty();
-
     rsx! {
-        div { class: "modal-backdrop locked",
-            div { class: "modal login-modal",
+        div { class: "modal-backdrop", onclick: move |_| on_close.call(()),
+            div { class: "modal login-modal", onclick: move |evt| evt.stop_propagation(),
                 div { class: "modal-header",
                     div {
-                        h3 { "авторизация" }
-                        p { class: "muted", "введите данные учетной записи" }
+                        h3 { "имя аккаунта" }
+                        p { class: "muted", {format!("как показывать {username} в списке")} }
                     }
                 }
 
                 div { class: "modal-body",
                     div { class: "form",
-                        label { "имя пользователя" }
+                        label { "отображаемое имя" }
                         input {
                             r#type: "text",
-                            value: username(),
-                            placeholder: "username",
-                            oninput: move |evt| username.set(evt.value())
-                        }
-
-                        label { "пароль" }
-                        input {
-                            r#type: "password",
-                            value: password(),
-                            placeholder: "********",
-                            oninput: move |evt| password.set(evt.value())
+                            value: alias(),
+                            placeholder: "например: main, alt",
+                            oninput: move |evt| {
+                                alias.set(evt.value());
+                                error_message.set(None);
+                            }
                         }
+                        p { class: "muted", "оставьте пустым, чтобы показывать имя пользователя" }
                     }
 
                     if let Some(message) = error_message() {
-                        p { class: "status status-error", {message} }
+                        ErrorBox { operation: "изменение аккаунта", message: message }
                     }
                 }
 
                 div { class: "modal-actions",
-                    button {
-                        class: "ghost modal-actions-left",
-                        onclick: move |_| open_url::open(ACCOUNT_REGISTER_URL),
-                        "создать аккаунт"
-                    }
                     button {
                         class: "ghost",
-                        disabled: busy() || !can_close,
-                        onclick: move |_| {
-                            if !can_close {
-                                return;
-                            }
-                            on_close.call(());
-                        },
+                        onclick: move |_| on_close.call(()),
                         "закрыть"
                     }
                     button {
                         class: "primary",
-                        disabled: button_disabled(),
                         onclick: move |_| {
-                            if busy() {
-                                return;
+                            match account_store::set_login_alias(user_id, &alias()) {
+                                Ok(()) => on_saved.call(()),
+                                Err(e) => error_message.set(Some(e)),
                             }
+                        },
+                        "сохранить"
+                    }
+                }
+            }
+        }
+    }
+}
 
-                            let user = username().trim().to_string();
-                            let pass = password();
+#[component]
+fn LoginOverlay(
+    auth_api: Signal<AuthApi>,
+    on_success: EventHandler<LoginInfo>,
+    on_close: EventHandler<()>,
+    /// "Play without account": servers that do not require auth only.
+    on_guest: EventHandler<()>,
+    can_close: bool,
+) -> Element {
+    // Signed-out accounts: their names are offered so only the password has to be typed.
+    let known_usernames =
+        use_signal(|| account_store::signed_out_usernames().unwrap_or_default());
+    let mut username =
+        use_signal(move || known_usernames.peek().first().cloned().unwrap_or_default());
+    let mut password = use_signal(String::new);
+    let mut busy = use_signal(|| false);
+    let mut error_message: Signal<Option<String>> = use_signal(|| None::<String>);
+    let mut login_task: Signal<Option<Task>> = use_signal(|| None);
+    // Second step for accounts with two-factor auth: the server asked for a code.
+    let mut tfa_step = use_signal(|| false);
+    let mut tfa_code = use_signal(String::new);
+    let mut reset_step = use_signal(|| false);
+
+    let button_disabled = move || {
+        busy()
+            || username().trim().is_empty()
+            || password().is_empty()
+            || (tfa_step() && tfa_code().trim().is_empty())
+    };
+
+    rsx! {
+        div { class: "modal-backdrop locked",
+            div { class: "modal login-modal",
+                div { class: "modal-header",
+                    div {
+                        h3 { "авторизация" }
+                        p { class: "muted", "введите данные учетной записи" }
+                    }
+                }
 
-                            if user.is_empty() || pass.is_empty() {
-                                error_message.set(Some("введите имя пользователя и пароль".to_string()));
-                                return;
+                if reset_step() {
+                    PasswordResetForm {
+                        auth_api,
+                        email: if username().contains('@') { username().trim().to_string() } else { String::new() },
+                        on_back: move |_| reset_step.set(false),
+                    }
+                } else {
+                    div { class: "modal-body",
+                        if tfa_step() {
+                            div { class: "form",
+                                p { class: "muted",
+                                    {format!("для {} включена двухфакторная аутентификация", username().trim())}
+                                }
+                                label { "код из приложения-аутентификатора" }
+                                input {
+                                    r#type: "text",
+                                    inputmode: "numeric",
+                                    autocomplete: "one-time-code",
+                                    value: tfa_code(),
+                                    placeholder: "123456",
+                                    oninput: move |evt| tfa_code.set(evt.value())
+                                }
+                                button {
+                                    class: "ghost small",
+                                    disabled: busy(),
+                                    onclick: move |_| {
+                                        tfa_step.set(false);
+                                        tfa_code.set(String::new());
+                                        error_message.set(None);
+                                    },
+                                    "назад"
+                                }
                             }
+                        } else {
+                            div { class: "form",
+                                label { "имя пользователя" }
+                                input {
+                                    r#type: "text",
+                                    value: username(),
+                                    placeholder: "username",
+                                    oninput: move |evt| username.set(evt.value())
+                                }
+                                if known_usernames().len() > 1 {
+                                    div { class: "known-logins",
+                                        for name in known_usernames() {
+                                            {
+                                                let selected = name == username();
+                                                rsx! {
+                                                    button {
+                                                        class: if selected { "ghost small selected" } else { "ghost small" },
+                                                        onclick: move |_| username.set(name.clone()),
+                                                        {name.clone()}
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
 
-                            busy.set(true);
-                            error_message.set(None);
+                                label { "пароль" }
+                                input {
+                                    r#type: "password",
+                                    value: password(),
+                                    placeholder: "********",
+                                    oninput: move |evt| password.set(evt.value())
+                                }
+                                button {
+                                    class: "ghost small",
+                                    onclick: move |_| {
+                                        error_message.set(None);
+                                        reset_step.set(true);
+                                    },
+                                    "забыли пароль?"
+                                }
+                            }
+                        }
 
-                            let api = auth_api();
-                            let mut busy_done = busy;
-                            let mut error_done: Signal<Option<String>> = error_message;
-                            let success_cb = on_success;
+                        if let Some(message) = error_message() {
+                            ErrorBox { operation: "вход в аккаунт", message: message }
+                        }
+                    }
 
-                            spawn(async move {
-                                match api.authenticate(user, pass).await {
-                                    Ok(AuthenticateResult::Success(info)) => {
-                                        success_cb.call(info);
-                                    }
-                                    Ok(AuthenticateResult::Failure { errors, code }) => {
-                                        let message = if errors.is_empty() {
-                                            format!("ошибка: {:?}", code)
-                                        } else {
-                                            errors.join("\n")
-                                        };
-                                        error_done.set(Some(message));
+                    div { class: "modal-actions",
+                        button {
+                            class: "ghost modal-actions-left",
+                            onclick: move |_| open_url::open(ACCOUNT_REGISTER_URL),
+                            "создать аккаунт"
+                        }
+                        button {
+                            class: "ghost",
+                            disabled: busy(),
+                            title: "только серверы, где авторизация не обязательна",
+                            onclick: move |_| on_guest.call(()),
+                            "играть без аккаунта"
+                        }
+                        if busy() {
+                            button {
+                                class: "ghost",
+                                onclick: move |_| {
+                                    if let Some(task) = login_task.take() {
+                                        task.cancel();
                                     }
-                                    Err(err) => {
-                                        error_done.set(Some(err.to_string()));
+                                    busy.set(false);
+                                    error_message.set(Some("вход отменён".to_string()));
+                                },
+                                "отмена"
+                            }
+                        } else {
+                            button {
+                                class: "ghost",
+                                disabled: !can_close,
+                                onclick: move |_| {
+                                    if !can_close {
+                                        return;
                                     }
+                                    on_close.call(());
+                                },
+                                "закрыть"
+                            }
+                        }
+                        button {
+                            class: "primary",
+                            disabled: button_disabled(),
+                            onclick: move |_| {
+                                if busy() {
+                                    return;
                                 }
 
-                                busy_done.set(false);
-                            });
-                        },
-                        {if busy() { "входим..." } else { "войти" }}
+                                let user = username().trim().to_string();
+                                let pass = password();
+                                let code = tfa_step()
+                                    .then(|| tfa_code().split_whitespace().collect::<String>());
+
+                                if user.is_empty() || pass.is_empty() {
+                                    error_message.set(Some("введите имя пользователя и пароль".to_string()));
+                                    return;
+                                }
+
+                                busy.set(true);
+                                error_message.set(None);
+
+                                let api = auth_api();
+                                let mut busy_done = busy;
+                                let mut error_done: Signal<Option<String>> = error_message;
+                                let success_cb = on_success;
+
+                                let mut login_task_done = login_task;
+                                let task = spawn(async move {
+                                    match api.authenticate(user, pass, code).await {
+                                        Ok(AuthenticateResult::Success(info)) => {
+                                            success_cb.call(info);
+                                        }
+                                        Ok(AuthenticateResult::Failure {
+                                            code: AuthenticateDenyResponseCode::TfaRequired,
+                                            ..
+                                        }) => {
+                                            tfa_step.set(true);
+                                            tfa_code.set(String::new());
+                                        }
+                                        Ok(AuthenticateResult::Failure {
+                                            code: AuthenticateDenyResponseCode::TfaInvalid,
+                                            ..
+                                        }) => {
+                                            tfa_code.set(String::new());
+                                            error_done.set(Some("неверный код двухфакторной аутентификации, попробуйте ещё раз".to_string()));
+                                        }
+                                        Ok(AuthenticateResult::Failure { errors, code }) => {
+                                            let message = if errors.is_empty() {
+                                                format!("ошибка: {:?}", code)
+                                            } else {
+                                                errors.join("\n")
+                                            };
+                                            error_done.set(Some(message));
+                                        }
+                                        Err(err) => {
+                                            error_done.set(Some(err.to_string()));
+                                        }
+                                    }
+
+                                    busy_done.set(false);
+                                    login_task_done.set(None);
+                                });
+                                login_task.set(Some(task));
+                            },
+                            {if busy() { "входим..." } else if tfa_step() { "подтвердить" } else { "войти" }}
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// "Forgot password" step of the login modal: the auth server mails a reset link.
+#[component]
+fn PasswordResetForm(
+    auth_api: Signal<AuthApi>,
+    email: String,
+    on_back: EventHandler<()>,
+) -> Element {
+    let mut email = use_signal(move || email);
+    let mut busy = use_signal(|| false);
+    let mut sent_to: Signal<Option<String>> = use_signal(|| None);
+    let mut error_message: Signal<Option<String>> = use_signal(|| None::<String>);
+
+    let address_invalid = move || {
+        let value = email();
+        let value = value.trim();
+        value.is_empty() || !value.contains('@')
+    };
+
+    rsx! {
+        div { class: "modal-body",
+            div { class: "form",
+                p { class: "muted", "на почту аккаунта придёт ссылка для смены пароля" }
+                label { "email" }
+                input {
+                    r#type: "email",
+                    value: email(),
+                    placeholder: "user@example.com",
+                    oninput: move |evt| {
+                        email.set(evt.value());
+                        sent_to.set(None);
                     }
                 }
             }
+
+            if let Some(address) = sent_to() {
+                div { class: "status status-info status-block",
+                    {format!("если {address} привязан к аккаунту, письмо уже отправлено; проверьте также папку «спам»")}
+                }
+            }
+            if let Some(message) = error_message() {
+                ErrorBox { operation: "восстановление пароля", message: message }
+            }
+        }
+
+        div { class: "modal-actions",
+            button {
+                class: "ghost",
+                disabled: busy(),
+                onclick: move |_| on_back.call(()),
+                "назад"
+            }
+            button {
+                class: "primary",
+                disabled: busy() || address_invalid(),
+                onclick: move |_| {
+                    let address = email().trim().to_string();
+                    busy.set(true);
+                    error_message.set(None);
+                    sent_to.set(None);
+                    let api = auth_api();
+                    spawn(async move {
+                        match api.reset_password(address.clone()).await {
+                            Ok(()) => sent_to.set(Some(address)),
+                            Err(err) => error_message.set(Some(err.to_string())),
+                        }
+                        busy.set(false);
+                    });
+                },
+                {if busy() { "отправляем..." } else { "отправить ссылку" }}
+            }
         }
     }
 }
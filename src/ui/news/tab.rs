@@ -1,6 +1,9 @@
 use dioxus::prelude::*;
 
+use crate::constants::APP_VERSION_LABEL;
 use crate::net::news;
+use crate::open_url;
+use crate::ui::error_box::ErrorBox;
 
 fn format_time(ts: chrono::DateTime<chrono::Utc>) -> String {
     // Simple, locale-neutral formatting.
@@ -11,8 +14,161 @@ async fn load_posts() -> Result<Vec<news::NewsPost>, String> {
     news::fetch_news(50).await
 }
 
+async fn load_releases() -> Result<Vec<news::LauncherRelease>, String> {
+    news::fetch_launcher_releases(30).await
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Feed {
+    Project,
+    Launcher,
+}
+
 #[component]
 pub fn tab_news() -> Element {
+    let mut feed = use_signal(|| Feed::Project);
+    // The releases feed is fetched the first time its tab is opened.
+    let mut launcher_opened = use_signal(|| false);
+
+    // Once mounted, feeds stay mounted so switching does not refetch; each refreshes on its own.
+    rsx! {
+        div { class: "news-page",
+            div { class: "filter-pills settings-tabs",
+                button {
+                    class: format_args!("pill {}", if feed() == Feed::Project { "active" } else { "" }),
+                    onclick: move |_| feed.set(Feed::Project),
+                    "Новости проекта"
+                }
+                button {
+                    class: format_args!("pill {}", if feed() == Feed::Launcher { "active" } else { "" }),
+                    onclick: move |_| {
+                        feed.set(Feed::Launcher);
+                        launcher_opened.set(true);
+                    },
+                    "Обновления лаунчера"
+                }
+            }
+            div { class: "news-feed", hidden: feed() != Feed::Project, ProjectNewsFeed {} }
+            if launcher_opened() {
+                div { class: "news-feed", hidden: feed() != Feed::Launcher, LauncherUpdatesFeed {} }
+            }
+        }
+    }
+}
+
+#[component]
+fn LauncherUpdatesFeed() -> Element {
+    let mut releases: Signal<Vec<news::LauncherRelease>> = use_signal(Vec::new);
+    let mut loading = use_signal(|| true);
+    let mut error: Signal<Option<String>> = use_signal(|| None);
+    let mut open_tag: Signal<Option<String>> = use_signal(|| None);
+
+    let mut reload = move || {
+        loading.set(true);
+        error.set(None);
+        spawn(async move {
+            match load_releases().await {
+                Ok(list) => {
+                    // The newest release that is ahead of us starts expanded.
+                    if open_tag().is_none()
+                        && let Some(next) = list.iter().find(|r| r.is_newer_than_current())
+                    {
+                        open_tag.set(Some(next.tag_name.clone()));
+                    }
+                    releases.set(list);
+                }
+                Err(e) => error.set(Some(e)),
+            }
+            loading.set(false);
+        });
+    };
+
+    use_hook(reload);
+
+    rsx! {
+        button {
+            class: "ghost news-refresh",
+            disabled: loading(),
+            onclick: move |_| {
+                if !loading() {
+                    reload();
+                }
+            },
+            "Обновить"
+        }
+
+        if loading() {
+            p { class: "status status-info", "Загрузка релизов..." }
+        }
+
+        if let Some(msg) = error() {
+            ErrorBox { operation: "загрузка релизов лаунчера", message: msg }
+        }
+
+        if !loading() && error().is_none() {
+            if releases().is_empty() {
+                p { class: "status status-info", "Релизов пока нет." }
+            } else if let Some(next) = releases().iter().find(|r| r.is_newer_than_current()) {
+                p { class: "status status-info",
+                    {format!("Доступна новая версия: {} (у вас {APP_VERSION_LABEL})", next.title())}
+                }
+            }
+
+            for release in releases().into_iter() {
+                {
+                    let tag = release.tag_name.clone();
+                    let is_open = open_tag().as_deref() == Some(tag.as_str());
+                    let url = release.html_url.clone();
+                    let mut meta = release
+                        .published_at
+                        .map(format_time)
+                        .unwrap_or_else(|| release.tag_name.clone());
+                    if release.prerelease {
+                        meta.push_str(" · предварительная версия");
+                    }
+                    if release.is_newer_than_current() {
+                        meta.push_str(" · новее текущей");
+                    }
+                    rsx! {
+                        div { class: "section news-post",
+                            div { class: "news-post-header",
+                                div { class: "news-post-meta",
+                                    h2 { class: "news-title", {release.title().to_string()} }
+                                    p { class: "news-date", {meta} }
+                                }
+                                button {
+                                    class: "ghost news-open",
+                                    onclick: move |_| {
+                                        if open_tag().as_deref() == Some(tag.as_str()) {
+                                            open_tag.set(None);
+                                        } else {
+                                            open_tag.set(Some(tag.clone()));
+                                        }
+                                    },
+                                    if is_open { "Скрыть" } else { "Открыть" }
+                                }
+                            }
+
+                            if is_open {
+                                p { class: "news-text selectable",
+                                    {release.body.clone().filter(|b| !b.trim().is_empty()).unwrap_or_else(|| "Описание изменений не указано.".to_string())}
+                                }
+                                button {
+                                    class: "ghost small news-open",
+                                    onclick: move |_| open_url::open(&url),
+                                    "Открыть на GitHub"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn ProjectNewsFeed() -> Element {
     let posts: Signal<Vec<news::NewsPost>> = use_signal(Vec::new);
     let mut loading = use_signal(|| true);
     let mut error: Signal<Option<String>> = use_signal(|| None);
@@ -36,89 +192,87 @@ pub fn tab_news() -> Element {
     }
 
     rsx! {
-        div { class: "news-page",
-            button {
-                class: "ghost news-refresh",
-                disabled: loading(),
-                onclick: move |_| {
-                    if loading() {
-                        return;
-                    }
-                    loading.set(true);
-                    error.set(None);
-                    let mut posts2 = posts;
-                    let mut loading2 = loading;
-                    let mut error2 = error;
-                    spawn(async move {
-                        match load_posts().await {
-                            Ok(list) => {
-                                posts2.set(list);
-                                error2.set(None);
-                            }
-                            Err(e) => error2.set(Some(e)),
+        button {
+            class: "ghost news-refresh",
+            disabled: loading(),
+            onclick: move |_| {
+                if loading() {
+                    return;
+                }
+                loading.set(true);
+                error.set(None);
+                let mut posts2 = posts;
+                let mut loading2 = loading;
+                let mut error2 = error;
+                spawn(async move {
+                    match load_posts().await {
+                        Ok(list) => {
+                            posts2.set(list);
+                            error2.set(None);
                         }
-                        loading2.set(false);
-                    });
-                },
-                "Обновить"
-            }
+                        Err(e) => error2.set(Some(e)),
+                    }
+                    loading2.set(false);
+                });
+            },
+            "Обновить"
+        }
 
-            if loading() {
-                p { class: "status status-info", "Загрузка новостей..." }
-            }
+        if loading() {
+            p { class: "status status-info", "Загрузка новостей..." }
+        }
 
-            if let Some(msg) = error() {
-                p { class: "status status-error selectable", {msg} }
-            }
+        if let Some(msg) = error() {
+            ErrorBox { operation: "загрузка новостей", message: msg }
+        }
 
-            if !loading() && error().is_none() {
-                if posts().is_empty() {
-                    p { class: "status status-info", "Новостей пока нет." }
-                }
+        if !loading() && error().is_none() {
+            if posts().is_empty() {
+                p { class: "status status-info", "Новостей пока нет." }
+            }
 
-                for post in posts().into_iter() {
-                    div { class: "section news-post",
-                        div { class: "news-post-header",
-                            div { class: "news-post-meta",
-                                h2 { class: "news-title", {post.title} }
-                                p { class: "news-date", {format_time(post.created_at)} }
-                            }
-                            button {
-                                class: "ghost news-open",
-                                onclick: {
-                                    let post_id = post.id.clone();
-                                    move |_| {
-                                        let is_open = open_post_id().as_deref() == Some(post_id.as_str());
-                                        if is_open {
-                                            open_post_id.set(None);
-                                        } else {
-                                            open_post_id.set(Some(post_id.clone()));
-                                        }
+            for post in posts().into_iter() {
+                div { class: "section news-post",
+                    div { class: "news-post-header",
+                        div { class: "news-post-meta",
+                            h2 { class: "news-title", {post.title} }
+                            p { class: "news-date", {format_time(post.created_at)} }
+                        }
+                        button {
+                            class: "ghost news-open",
+                            onclick: {
+                                let post_id = post.id.clone();
+                                move |_| {
+                                    let is_open = open_post_id().as_deref() == Some(post_id.as_str());
+                                    if is_open {
+                                        open_post_id.set(None);
+                                    } else {
+                                        open_post_id.set(Some(post_id.clone()));
                                     }
-                                },
-                                if open_post_id().as_deref() == Some(post.id.as_str()) {
-                                    "Скрыть"
-                                } else {
-                                    "Открыть"
                                 }
+                            },
+                            if open_post_id().as_deref() == Some(post.id.as_str()) {
+                                "Скрыть"
+                            } else {
+                                "Открыть"
                             }
                         }
+                    }
 
-                        if open_post_id().as_deref() == Some(post.id.as_str()) {
-                            for block in post.blocks.into_iter() {
-                                match block {
-                                    news::NewsBlock::Text { text } => rsx!(
-                                        p { class: "news-text selectable", {text} }
-                                    ),
-                                    news::NewsBlock::Image { media_id, alt } => {
-                                        if news::is_safe_media_id(&media_id) {
-                                            let src = news::media_url(&media_id);
-                                            rsx!(
-                                                img { class: "news-image", src: "{src}", alt: "{alt}" }
-                                            )
-                                        } else {
-                                            rsx!(Fragment {})
-                                        }
+                    if open_post_id().as_deref() == Some(post.id.as_str()) {
+                        for block in post.blocks.into_iter() {
+                            match block {
+                                news::NewsBlock::Text { text } => rsx!(
+                                    p { class: "news-text selectable", {text} }
+                                ),
+                                news::NewsBlock::Image { media_id, alt } => {
+                                    if news::is_safe_media_id(&media_id) {
+                                        let src = news::media_url(&media_id);
+                                        rsx!(
+                                            img { class: "news-image", src: "{src}", alt: "{alt}" }
+                                        )
+                                    } else {
+                                        rsx!(Fragment {})
                                     }
                                 }
                             }
@@ -5,6 +5,7 @@ use crate::{app_paths, marsey};
 #[derive(Clone, Debug, PartialEq)]
 pub struct PatchRow {
     pub filename: String,
+    pub path: PathBuf,
     pub enabled: bool,
     pub name: String,
     pub description: String,
@@ -15,6 +16,7 @@ pub struct PatchRow {
 pub struct PatchesState {
     pub mods_dir: Option<PathBuf>,
     pub patches: Vec<PatchRow>,
+    pub duplicates: Vec<marsey::DuplicatePatch>,
     pub error: Option<String>,
 }
 
@@ -36,6 +38,7 @@ impl PatchesState {
                     .into_iter()
                     .map(|p| PatchRow {
                         filename: p.filename,
+                        path: p.path,
                         enabled: p.enabled,
                         name: p.name,
                         description: p.description,
@@ -43,9 +46,13 @@ impl PatchesState {
                     })
                     .collect();
 
+                // Duplicate detection is advisory; a failure here shouldn't hide the list.
+                let duplicates = marsey::find_duplicate_patches(&data_dir).unwrap_or_default();
+
                 Self {
                     mods_dir: Some(mods_dir),
                     patches,
+                    duplicates,
                     error: None,
                 }
             }
@@ -0,0 +1,170 @@
+use dioxus::prelude::*;
+
+use crate::app_paths;
+use crate::client_install::{self, InstalledEngine};
+use crate::constants::{APP_TITLE, APP_VERSION_LABEL};
+use crate::net::redial_pipe::{self, RedialPipeStatus};
+use crate::ss14_loader::{self, LoaderProvenance};
+use crate::ui::clipboard;
+use crate::ui::error_box::ErrorBox;
+
+/// Launcher version, data dir, where the installed SS14.Loader came from and which engine
+/// builds are installed.
+#[component]
+pub fn AboutPanel() -> Element {
+    let mut provenance: Signal<Option<LoaderProvenance>> = use_signal(|| None);
+    let mut redial: Signal<RedialPipeStatus> = use_signal(redial_pipe::global_redial_status);
+    let mut redial_error: Signal<Option<String>> = use_signal(|| None);
+    let mut engines: Signal<Vec<InstalledEngine>> = use_signal(Vec::new);
+    // Result of the last "Проверить подпись" dry run.
+    let mut signature_check: Signal<Option<Result<String, String>>> = use_signal(|| None);
+
+    use_future(move || async move {
+        let Ok(data_dir) = app_paths::data_dir() else {
+            return;
+        };
+        let loaded = tokio::task::spawn_blocking(move || {
+            (
+                ss14_loader::load_loader_provenance(&data_dir),
+                client_install::installed_engines(&data_dir),
+            )
+        })
+        .await
+        .ok();
+        if let Some((loaded, installed)) = loaded {
+            provenance.set(loaded);
+            engines.set(installed);
+        }
+    });
+
+    let data_dir = app_paths::data_dir()
+        .map(|d| d.display().to_string())
+        .unwrap_or_else(|e| e);
+
+    rsx! {
+        div { class: "form",
+            label { "Лаунчер" }
+            p { class: "muted selectable", {format!("{APP_TITLE} {APP_VERSION_LABEL}")} }
+
+            label { "Каталог данных" }
+            p { class: "muted selectable", {data_dir} }
+
+            label { "SS14.Loader" }
+            if let Some(p) = provenance() {
+                div { class: "muted selectable",
+                    p { {format!("источник: {}", p.source.label_ru())} }
+                    p { {format!("сборка: {} ({})", p.build_id, p.rid)} }
+                    p {
+                        {format!("коммит: {}", p.commit.as_deref().unwrap_or("неизвестен"))}
+                    }
+                    if let Some(key) = p.signed_by.clone() {
+                        p { {format!("подпись: проверена (ключ {key}…)")} }
+                    }
+                    if let Some(at) = chrono::DateTime::from_timestamp(p.installed_at, 0) {
+                        p {
+                            {format!(
+                                "установлен: {}",
+                                at.with_timezone(&chrono::Local).format("%d.%m.%Y %H:%M"),
+                            )}
+                        }
+                    }
+                }
+            } else {
+                p { class: "muted", "ещё не установлен (подготовится при запуске или первом подключении)" }
+            }
+
+            div { class: "hub-actions",
+                button {
+                    class: "ghost small",
+                    title: "проверить ed25519-подпись архива SS14.Loader (рядом должен лежать <файл>.sig); ничего не устанавливается и не запускается",
+                    onclick: move |_| {
+                        spawn(async move {
+                            let Some(path) = clipboard::pick_file("Архив SS14.Loader").await else {
+                                return;
+                            };
+                            let res = tokio::task::spawn_blocking(move || ss14_loader::dry_run_verify_loader(&path))
+                                .await
+                                .map_err(|e| format!("ошибка задачи: {e}"))
+                                .and_then(|r| r);
+                            signature_check.set(Some(res));
+                        });
+                    },
+                    "Проверить подпись…"
+                }
+            }
+            match signature_check() {
+                Some(Ok(msg)) => rsx! { p { class: "status status-info", {msg} } },
+                Some(Err(e)) => rsx! { ErrorBox { operation: "проверка подписи SS14.Loader", message: e } },
+                None => rsx! {},
+            }
+
+            label { "Движки" }
+            div { class: "muted selectable",
+                p { {format!("платформа: {}", crate::platform::host_rid())} }
+                if engines().is_empty() {
+                    p { "не установлены" }
+                }
+                for engine in engines() {
+                    p {
+                        key: "{engine.version}",
+                        {format!(
+                            "{}: {}",
+                            engine.version,
+                            engine
+                                .rids
+                                .iter()
+                                .map(String::as_str)
+                                .chain(engine.legacy.then_some("без платформы (старая установка)"))
+                                .collect::<Vec<_>>()
+                                .join(", "),
+                        )}
+                    }
+                }
+            }
+
+            label { "Redial" }
+            div { class: "muted selectable",
+                match redial().pipe_name {
+                    Some(name) => rsx! {
+                        p {
+                            {format!(
+                                "{}: {name}",
+                                if redial().running { "работает" } else { "остановлен" },
+                            )}
+                        }
+                        p { {format!("переподключений: {}", redial().redials)} }
+                    },
+                    None => rsx! {
+                        p { "не запущен (запускается при первом запуске игры)" }
+                    },
+                }
+                if let Some(e) = redial().last_error {
+                    p { class: "field-error", {format!("последняя ошибка: {e}")} }
+                }
+            }
+            div { class: "hub-actions",
+                button {
+                    class: "ghost small",
+                    onclick: move |_| redial.set(redial_pipe::global_redial_status()),
+                    "Обновить"
+                }
+                button {
+                    class: "ghost small",
+                    onclick: move |_| {
+                        let restarted = crate::settings::load_settings()
+                            .and_then(|s| redial_pipe::restart_global_redial_pipe(s.security.disable_redial));
+                        match restarted {
+                            Ok(_) => redial_error.set(None),
+                            Err(e) => redial_error.set(Some(e)),
+                        }
+                        redial.set(redial_pipe::global_redial_status());
+                    },
+                    "Перезапустить"
+                }
+            }
+            if let Some(msg) = redial_error() {
+                ErrorBox { operation: "перезапуск redial", message: msg }
+            }
+        }
+    }
+}
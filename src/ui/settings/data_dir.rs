@@ -0,0 +1,118 @@
+use dioxus::prelude::*;
+
+use crate::app_paths::{self, DataDirHealth};
+use crate::ui::clipboard;
+use crate::ui::error_box::ErrorBox;
+
+/// Data dir location with OneDrive / write-access diagnostics and relocation.
+#[component]
+pub fn DataDirSection() -> Element {
+    let mut health: Signal<Option<DataDirHealth>> = use_signal(|| None);
+    let mut error: Signal<Option<String>> = use_signal(|| None);
+    let mut info: Signal<Option<String>> = use_signal(|| None);
+    let mut busy = use_signal(|| false);
+
+    use_future(move || async move {
+        let Ok(dir) = app_paths::data_dir() else {
+            return;
+        };
+        if let Ok(h) = tokio::task::spawn_blocking(move || app_paths::check_data_dir(&dir)).await {
+            health.set(Some(h));
+        }
+    });
+
+    let relocated = app_paths::data_dir_relocated();
+    let current = health();
+
+    rsx! {
+        div { class: "form",
+            label { "Каталог данных" }
+            div { class: "hub-row",
+                span { class: "muted selectable",
+                    {
+                        app_paths::data_dir()
+                            .map(|d| d.display().to_string())
+                            .unwrap_or_else(|e| e)
+                    }
+                }
+                button {
+                    class: "ghost small",
+                    onclick: move |_| {
+                        if let Ok(dir) = app_paths::data_dir() {
+                            let _ = app_paths::open_in_file_manager(&dir);
+                        }
+                    },
+                    "Открыть"
+                }
+                button {
+                    class: "ghost small",
+                    disabled: busy(),
+                    onclick: move |_| {
+                        busy.set(true);
+                        error.set(None);
+                        info.set(None);
+                        spawn(async move {
+                            if let Some(target) = clipboard::pick_folder("Новый каталог данных").await {
+                                let res = tokio::task::spawn_blocking(move || {
+                                    app_paths::relocate_data_dir(Some(&target))
+                                })
+                                .await
+                                .map_err(|e| format!("ошибка задачи: {e}"))
+                                .and_then(|r| r);
+                                match res {
+                                    Ok(()) => info.set(Some(
+                                        "каталог перенесён, данные скопированы. Перезапустите лаунчер; движки и контент скачаются заново"
+                                            .to_string(),
+                                    )),
+                                    Err(e) => error.set(Some(e)),
+                                }
+                            }
+                            busy.set(false);
+                        });
+                    },
+                    "Перенести…"
+                }
+                if relocated {
+                    button {
+                        class: "ghost small",
+                        disabled: busy(),
+                        onclick: move |_| {
+                            match app_paths::relocate_data_dir(None) {
+                                Ok(()) => {
+                                    error.set(None);
+                                    info.set(Some("после перезапуска будет использован каталог по умолчанию".to_string()));
+                                }
+                                Err(e) => error.set(Some(e)),
+                            }
+                        },
+                        "По умолчанию"
+                    }
+                }
+            }
+
+            if let Some(h) = current.filter(|h| h.has_issues()) {
+                div { class: "status status-error selectable",
+                    if let Some(provider) = h.cloud_sync {
+                        p {
+                            {format!(
+                                "Каталог находится в папке {provider}. Синхронизация блокирует и замедляет запись кэша — лучше перенести его."
+                            )}
+                        }
+                    }
+                    if let Some(elapsed) = h.slow_write {
+                        p { {format!("Тестовая запись заняла {:.1} с — запись в каталог кто-то тормозит (синхронизация или антивирус).", elapsed.as_secs_f32())} }
+                    }
+                    if let Some(err) = h.write_error {
+                        p { {err} }
+                    }
+                }
+            }
+
+            if let Some(msg) = error() {
+                ErrorBox { operation: "каталог данных", message: msg }
+            } else if let Some(msg) = info() {
+                p { class: "status status-info", {msg} }
+            }
+        }
+    }
+}
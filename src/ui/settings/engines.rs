@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+
+use dioxus::prelude::*;
+
+use crate::client_install::{self, InstalledEngine};
+use crate::tasks::{self, TaskKind};
+use crate::ui::error_box::ErrorBox;
+use crate::ui::home::helpers::format_bytes;
+use crate::{app_paths, favorites, known_good, servers};
+
+/// Installed engine versions with size and last use, each removable on its own. Versions a
+/// favorite runs ask for confirmation first.
+#[component]
+pub fn EnginesSection() -> Element {
+    let mut engines: Signal<Vec<InstalledEngine>> = use_signal(Vec::new);
+    // Engine version -> favorites running it: per their `/info`, else their last launch.
+    let mut users: Signal<HashMap<String, Vec<String>>> = use_signal(HashMap::new);
+    let mut confirm: Signal<Option<String>> = use_signal(|| None);
+    let mut error: Signal<Option<String>> = use_signal(|| None);
+    let mut busy = use_signal(|| false);
+
+    let reload = move || {
+        spawn(async move {
+            let Ok(data_dir) = app_paths::data_dir() else {
+                return;
+            };
+            let loaded = tokio::task::spawn_blocking(move || {
+                let favs = favorites::load_favorites().unwrap_or_default();
+                (
+                    client_install::installed_engines(&data_dir),
+                    known_good::engine_users(&favs, servers::announced_engine_version),
+                )
+            })
+            .await;
+            if let Ok((installed, in_use)) = loaded {
+                engines.set(installed);
+                users.set(in_use);
+            }
+        });
+    };
+
+    use_future(move || async move { reload() });
+
+    let mut remove = move |version: String| {
+        busy.set(true);
+        confirm.set(None);
+        error.set(None);
+        spawn(async move {
+            let res = match app_paths::data_dir() {
+                Ok(data_dir) => tokio::task::spawn_blocking(move || {
+                    let task = tasks::register(
+                        TaskKind::CacheCleanup,
+                        format!("Удаление движка {version}"),
+                        false,
+                    );
+                    let r = crate::core::cache_cleanup::clear_engine_version(&data_dir, &version);
+                    task.finish(&r);
+                    r
+                })
+                .await
+                .unwrap_or_else(|e| Err(format!("ошибка задачи: {e}"))),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = res {
+                error.set(Some(e));
+            }
+            busy.set(false);
+            reload();
+        });
+    };
+
+    let list = engines();
+    let total: u64 = list.iter().map(|e| e.size_bytes).sum();
+
+    rsx! {
+        div { class: "form",
+            label { "Установленные движки" }
+            if list.is_empty() {
+                span { class: "muted", "нет скачанных версий" }
+            } else {
+                span { class: "muted",
+                    {format!("версий: {} · всего {}", list.len(), format_bytes(total))}
+                }
+            }
+            for engine in list {
+                {
+                    let version = engine.version.clone();
+                    let version_confirm = engine.version.clone();
+                    let needed_by = users().get(&engine.version).cloned().unwrap_or_default();
+                    let last_used = engine
+                        .last_used
+                        .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))
+                        .map(|at| at.with_timezone(&chrono::Local).format("%d.%m.%Y %H:%M").to_string())
+                        .unwrap_or_else(|| "неизвестно".to_string());
+                    let confirming = confirm().as_deref() == Some(engine.version.as_str());
+                    rsx! {
+                        div { key: "{engine.version}", class: "hub-row engine-row",
+                            span { class: "selectable", {engine.version.clone()} }
+                            span { class: "muted",
+                                {format!("{} · использован: {last_used}", format_bytes(engine.size_bytes))}
+                            }
+                            if !needed_by.is_empty() {
+                                span {
+                                    class: "muted engine-in-use",
+                                    title: needed_by.join("\n"),
+                                    {format!("нужен избранным: {}", needed_by.len())}
+                                }
+                            }
+                            if confirming {
+                                button {
+                                    class: "ghost small",
+                                    disabled: busy(),
+                                    onclick: move |_| remove(version_confirm.clone()),
+                                    "Удалить всё равно"
+                                }
+                                button {
+                                    class: "ghost small",
+                                    onclick: move |_| confirm.set(None),
+                                    "Отмена"
+                                }
+                            } else {
+                                button {
+                                    class: "ghost small",
+                                    disabled: busy(),
+                                    title: if needed_by.is_empty() { "" } else { "избранные серверы скачают его заново при подключении" },
+                                    onclick: move |_| {
+                                        if needed_by.is_empty() {
+                                            remove(version.clone());
+                                        } else {
+                                            confirm.set(Some(version.clone()));
+                                        }
+                                    },
+                                    "Удалить"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            if let Some(msg) = error() {
+                ErrorBox { operation: "удаление движка", message: msg }
+            }
+        }
+    }
+}
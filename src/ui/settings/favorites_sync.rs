@@ -0,0 +1,157 @@
+use dioxus::prelude::*;
+
+use crate::favorites_sync::{self, FavoritesSyncState};
+use crate::settings::{self, LauncherSettings};
+use crate::ui::error_box::ErrorBox;
+
+/// Endpoint, credentials and status for syncing favorites between machines.
+#[component]
+pub fn FavoritesSyncSection(launcher_settings: Signal<LauncherSettings>) -> Element {
+    let mut state: Signal<FavoritesSyncState> = use_signal(favorites_sync::load_sync_state);
+    let mut secret_input = use_signal(String::new);
+    let mut error: Signal<Option<String>> = use_signal(|| None);
+    let mut info: Signal<Option<String>> = use_signal(|| None);
+    let mut busy = use_signal(|| false);
+
+    let sync = launcher_settings().favorites_sync;
+    let has_secret = !sync.secret_encrypted.is_empty();
+    let last_synced = state()
+        .synced_at
+        .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0));
+
+    rsx! {
+        div { class: "form",
+            label { "Синхронизация избранного" }
+            span { class: "muted",
+                "WebDAV или любой адрес, принимающий GET/PUT одного JSON-файла; пустой адрес отключает синхронизацию с ним"
+            }
+            input {
+                class: "input",
+                placeholder: "https://dav.example.com/sgloader/favorites.json",
+                value: "{sync.url}",
+                onchange: move |evt| {
+                    let mut next = launcher_settings();
+                    next.favorites_sync.url = evt.value().trim().to_string();
+                    match settings::save_settings(&next) {
+                        Ok(()) => error.set(None),
+                        Err(e) => error.set(Some(e)),
+                    }
+                    launcher_settings.set(next);
+                }
+            }
+            input {
+                class: "input",
+                placeholder: "пользователь (пусто — токен как Bearer)",
+                value: "{sync.username}",
+                onchange: move |evt| {
+                    let mut next = launcher_settings();
+                    next.favorites_sync.username = evt.value().trim().to_string();
+                    match settings::save_settings(&next) {
+                        Ok(()) => error.set(None),
+                        Err(e) => error.set(Some(e)),
+                    }
+                    launcher_settings.set(next);
+                }
+            }
+            div { class: "hub-row",
+                input {
+                    class: "input",
+                    r#type: "password",
+                    placeholder: if has_secret { "пароль/токен сохранён" } else { "пароль или токен" },
+                    value: "{secret_input}",
+                    oninput: move |evt| secret_input.set(evt.value()),
+                }
+                button {
+                    class: "ghost small",
+                    onclick: move |_| {
+                        let mut next = launcher_settings();
+                        let cleared = secret_input().trim().is_empty();
+                        if let Err(e) = next.favorites_sync.set_secret(secret_input().trim()) {
+                            error.set(Some(e));
+                            return;
+                        }
+                        match settings::save_settings(&next) {
+                            Ok(()) => {
+                                error.set(None);
+                                info.set(Some(
+                                    if cleared { "пароль/токен удалён" } else { "пароль/токен сохранён" }.to_string(),
+                                ));
+                            }
+                            Err(e) => error.set(Some(e)),
+                        }
+                        secret_input.set(String::new());
+                        launcher_settings.set(next);
+                    },
+                    { if secret_input().trim().is_empty() && has_secret { "Забыть пароль" } else { "Сохранить пароль" } }
+                }
+            }
+            div { class: "hub-row",
+                input {
+                    r#type: "checkbox",
+                    checked: sync.hub_account,
+                    onchange: move |_| {
+                        let mut next = launcher_settings();
+                        next.favorites_sync.hub_account = !next.favorites_sync.hub_account;
+                        match settings::save_settings(&next) {
+                            Ok(()) => error.set(None),
+                            Err(e) => error.set(Some(e)),
+                        }
+                        launcher_settings.set(next);
+                    }
+                }
+                span { class: "muted",
+                    {
+                        match (sync.hub_account, state().hub_url) {
+                            (true, Some(hub)) => format!("хранить избранное в аккаунте на хабе ({hub})"),
+                            (true, None) if state().hub_checked_at.is_some() => {
+                                "хранить избранное в аккаунте на хабе — ни один из хабов этого не поддерживает".to_string()
+                            }
+                            _ => "хранить избранное в аккаунте на хабе, если хаб это поддерживает".to_string(),
+                        }
+                    }
+                }
+            }
+            div { class: "hub-row",
+                button {
+                    class: "ghost small",
+                    disabled: busy() || !sync.is_enabled(),
+                    onclick: move |_| {
+                        let sync = launcher_settings().favorites_sync;
+                        busy.set(true);
+                        error.set(None);
+                        info.set(None);
+                        spawn(async move {
+                            let res = tokio::task::spawn_blocking(move || favorites_sync::sync_favorites(&sync))
+                                .await
+                                .map_err(|e| format!("ошибка задачи: {e}"))
+                                .and_then(|r| r);
+                            match res {
+                                Ok(outcome) => info.set(Some(outcome.summary())),
+                                Err(e) => error.set(Some(e)),
+                            }
+                            state.set(favorites_sync::load_sync_state());
+                            busy.set(false);
+                        });
+                    },
+                    { if busy() { "синхронизация..." } else { "Синхронизировать сейчас" } }
+                }
+                span { class: "muted",
+                    {
+                        match last_synced {
+                            Some(at) => format!(
+                                "последняя синхронизация: {}",
+                                at.with_timezone(&chrono::Local).format("%d.%m.%Y %H:%M")
+                            ),
+                            None => "ещё не синхронизировалось".to_string(),
+                        }
+                    }
+                }
+            }
+            if let Some(msg) = error().or_else(|| state().last_error) {
+                ErrorBox { operation: "синхронизация избранного", message: msg }
+            } else if let Some(msg) = info() {
+                p { class: "status status-info", {msg} }
+            }
+        }
+    }
+}
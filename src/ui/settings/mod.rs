@@ -1,3 +1,7 @@
+mod about;
+mod data_dir;
+mod engines;
+mod favorites_sync;
 mod tab;
 
 pub use tab::tab_settings;
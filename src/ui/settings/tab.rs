@@ -1,27 +1,92 @@
 use dioxus::prelude::*;
 
+use super::about::AboutPanel;
+use super::data_dir::DataDirSection;
+use super::engines::EnginesSection;
+use super::favorites_sync::FavoritesSyncSection;
 use crate::storage::hub_urls;
+use crate::ui::home::helpers::format_bytes;
+use crate::ui::clipboard;
+use crate::ui::error_box::ErrorBox;
 use crate::ui::patches::{truncate_ellipsis, PatchesState};
-use crate::{app_paths, marsey, settings};
+use crate::tasks::{self, TaskKind};
+use crate::marsey::patch_repo;
+use crate::{app_paths, cache_stats, hub_latency, marsey, settings, ui_state};
 
 #[component]
-pub fn tab_settings(patches_state: Signal<PatchesState>) -> Element {
+pub fn tab_settings(
+    patches_state: Signal<PatchesState>,
+    open_hub_settings: Signal<bool>,
+) -> Element {
     #[derive(Clone, Copy, PartialEq)]
     enum SettingsTab {
         Patches,
         Game,
         Security,
+        About,
     }
 
-    let mut active_tab = use_signal(|| SettingsTab::Patches);
+    impl SettingsTab {
+        fn as_key(self) -> &'static str {
+            match self {
+                SettingsTab::Patches => "patches",
+                SettingsTab::Game => "game",
+                SettingsTab::Security => "security",
+                SettingsTab::About => "about",
+            }
+        }
+
+        fn from_key(key: &str) -> Option<Self> {
+            match key {
+                "patches" => Some(SettingsTab::Patches),
+                "game" => Some(SettingsTab::Game),
+                "security" => Some(SettingsTab::Security),
+                "about" => Some(SettingsTab::About),
+                _ => None,
+            }
+        }
+    }
+
+    let mut active_tab = use_signal(|| {
+        if crate::cli_args::take_open_patches() {
+            return SettingsTab::Patches;
+        }
+        SettingsTab::from_key(&ui_state::load_ui_state().settings_tab)
+            .unwrap_or(SettingsTab::Patches)
+    });
+
+    use_effect(move || {
+        let key = active_tab().as_key();
+        let _ = ui_state::update_ui_state(|s| s.settings_tab = key.to_string());
+    });
 
     let mut show_hub_settings = use_signal(|| false);
+    let mut show_patch_repo = use_signal(|| false);
+    // (patch name, report) of the last "Анализ" click.
+    let mut patch_analysis: Signal<Option<(String, String)>> = use_signal(|| None);
+    // Result of the last export/import of the patch setup.
+    let mut patch_setup_info: Signal<Option<String>> = use_signal(|| None);
     let mut hub_list: Signal<Vec<String>> = use_signal(Vec::new);
+    let mut hub_disabled: Signal<Vec<String>> = use_signal(Vec::new);
     let mut hub_error: Signal<Option<String>> = use_signal(|| None::<String>);
 
+    // Arrived via "Добавить хаб" on the home tab: go straight to the hub list.
+    use_hook(move || {
+        let mut open_hub_settings = open_hub_settings;
+        if *open_hub_settings.peek() {
+            open_hub_settings.set(false);
+            active_tab.set(SettingsTab::Game);
+            hub_list.set(hub_urls::load_hub_urls());
+            hub_disabled.set(hub_urls::load_disabled_hub_urls());
+            show_hub_settings.set(true);
+        }
+    });
+
     let mut game_error: Signal<Option<String>> = use_signal(|| None::<String>);
     let mut game_info: Signal<Option<String>> = use_signal(|| None::<String>);
     let mut game_cache_cleaning: Signal<bool> = use_signal(|| false);
+    let mut cache_totals: Signal<cache_stats::CacheStatsTotals> =
+        use_signal(cache_stats::load_cache_stats);
 
     let mut launcher_settings: Signal<settings::LauncherSettings> =
         use_signal(settings::LauncherSettings::default);
@@ -64,6 +129,11 @@ pub fn tab_settings(patches_state: Signal<PatchesState>) -> Element {
                     onclick: move |_| active_tab.set(SettingsTab::Security),
                     "Безопасность"
                 }
+                button {
+                    class: format_args!("pill {}", if active_tab() == SettingsTab::About { "active" } else { "" }),
+                    onclick: move |_| active_tab.set(SettingsTab::About),
+                    "О программе"
+                }
             }
 
             div { class: "settings-divider" }
@@ -89,10 +159,167 @@ pub fn tab_settings(patches_state: Signal<PatchesState>) -> Element {
                                 },
                                 "Директория патчей"
                             }
+                            button {
+                                class: "ghost",
+                                onclick: move |_| show_patch_repo.set(true),
+                                "Репозиторий патчей"
+                            }
+                            button {
+                                class: "ghost",
+                                title: "список включённых патчей с хешами и настройка отправки, без самих DLL",
+                                onclick: move |_| {
+                                    let categories = launcher_settings().patches.categories;
+                                    spawn(async move {
+                                        let exported = tokio::task::spawn_blocking(move || {
+                                            let data_dir = app_paths::data_dir()?;
+                                            marsey::patch_setup::export_patch_setup(&data_dir, categories)
+                                        })
+                                        .await
+                                        .map_err(|e| format!("ошибка задачи: {e}"))
+                                        .and_then(|r| r);
+                                        let saved = match exported {
+                                            Ok(json) => clipboard::save_json_as("patch-setup.json", json).await,
+                                            Err(e) => Err(e),
+                                        };
+                                        match saved {
+                                            Ok(Some(path)) => patch_setup_info.set(Some(format!("набор патчей сохранён: {path}"))),
+                                            Ok(None) => {}
+                                            Err(e) => patches_state.set(PatchesState { error: Some(e), ..patches_state() }),
+                                        }
+                                    });
+                                },
+                                "Экспорт набора"
+                            }
+                            button {
+                                class: "ghost",
+                                onclick: move |_| {
+                                    spawn(async move {
+                                        let json = match clipboard::open_json_file("Набор патчей").await {
+                                            Ok(Some(json)) => json,
+                                            Ok(None) => return,
+                                            Err(e) => {
+                                                patches_state.set(PatchesState { error: Some(e), ..patches_state() });
+                                                return;
+                                            }
+                                        };
+                                        let imported = tokio::task::spawn_blocking(move || {
+                                            let data_dir = app_paths::data_dir()?;
+                                            marsey::patch_setup::import_patch_setup(&data_dir, &json)
+                                        })
+                                        .await
+                                        .map_err(|e| format!("ошибка задачи: {e}"))
+                                        .and_then(|r| r);
+                                        match imported {
+                                            Ok((categories, report)) => {
+                                                let mut next = launcher_settings();
+                                                next.patches.categories = categories;
+                                                let mut state = PatchesState::refresh();
+                                                if let Err(e) = settings::save_settings(&next) {
+                                                    state.error = Some(e);
+                                                }
+                                                launcher_settings.set(next);
+                                                patches_state.set(state);
+                                                patch_setup_info.set(Some(report.summary()));
+                                            }
+                                            Err(e) => patches_state.set(PatchesState { error: Some(e), ..patches_state() }),
+                                        }
+                                    });
+                                },
+                                "Импорт набора"
+                            }
+                        }
+
+                        if let Some(msg) = patch_setup_info() {
+                            p { class: "status status-info status-block selectable", {msg} }
+                        }
+
+                        div { class: "hub-row",
+                            input {
+                                r#type: "checkbox",
+                                checked: launcher_settings().debug.dump_marsey_pipes,
+                                onchange: move |_| {
+                                    let mut next = launcher_settings();
+                                    next.debug.dump_marsey_pipes = !next.debug.dump_marsey_pipes;
+                                    if let Err(e) = settings::save_settings(&next) {
+                                        patches_state.set(PatchesState { error: Some(e), ..patches_state() });
+                                        return;
+                                    }
+                                    launcher_settings.set(next);
+                                }
+                            }
+                            span { class: "muted", "отладка: сохранять данные Marsey pipes каждого запуска в logs/marsey-pipes" }
+                        }
+
+                        div { class: "hub-row",
+                            span { class: "muted", "Отправлять в игру:" }
+                            select {
+                                class: "select",
+                                value: launcher_settings().patches.categories.as_key(),
+                                onchange: move |evt| {
+                                    let Some(categories) = settings::PatchCategories::from_key(&evt.value()) else {
+                                        return;
+                                    };
+                                    let mut next = launcher_settings();
+                                    next.patches.categories = categories;
+                                    if let Err(e) = settings::save_settings(&next) {
+                                        patches_state.set(PatchesState { error: Some(e), ..patches_state() });
+                                        return;
+                                    }
+                                    launcher_settings.set(next);
+                                },
+                                for categories in settings::PatchCategories::ALL {
+                                    option {
+                                        value: categories.as_key(),
+                                        selected: launcher_settings().patches.categories == categories,
+                                        {categories.label_ru()}
+                                    }
+                                }
+                            }
+                        }
+
+                        if show_patch_repo() {
+                            PatchRepoModal {
+                                launcher_settings,
+                                patches_state,
+                                on_close: move |_| show_patch_repo.set(false),
+                            }
                         }
 
                         if let Some(err) = &patches_state_value.error {
-                            p { class: "status status-error selectable", {err.clone()} }
+                            ErrorBox { operation: "сканирование патчей", message: err.clone() }
+                        }
+
+                        for dup in patches_state_value.duplicates.iter().cloned() {
+                            {
+                                let file_name = |p: &std::path::Path| {
+                                    p.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+                                };
+                                let text = format!(
+                                    "{} загружается дважды: {} и {} — одинаковые копии, останется {}",
+                                    dup.assembly,
+                                    file_name(&dup.original),
+                                    file_name(&dup.duplicate),
+                                    file_name(&dup.original),
+                                );
+                                let duplicate = dup.duplicate.clone();
+                                rsx! {
+                                    div { class: "hub-row status status-error",
+                                        span { class: "selectable", {text} }
+                                        button {
+                                            class: "ghost small",
+                                            onclick: move |_| {
+                                                let res = app_paths::data_dir()
+                                                    .and_then(|dir| marsey::remove_duplicate_patch(&dir, &duplicate));
+                                                match res {
+                                                    Ok(()) => patches_state.set(PatchesState::refresh()),
+                                                    Err(e) => patches_state.set(PatchesState { error: Some(e), ..patches_state() }),
+                                                }
+                                            },
+                                            "Удалить дубликат"
+                                        }
+                                    }
+                                }
+                            }
                         }
 
                         div { class: "patch-header",
@@ -100,6 +327,15 @@ pub fn tab_settings(patches_state: Signal<PatchesState>) -> Element {
                             div { class: "patch-cell patch-cell-name", "Имя" }
                             div { class: "patch-cell patch-cell-desc", "Описание" }
                             div { class: "patch-cell patch-cell-rdnn", "RDNN" }
+                            div { class: "patch-cell" }
+                        }
+
+                        if let Some((title, report)) = patch_analysis() {
+                            PatchAnalysisModal {
+                                title,
+                                report,
+                                on_close: move |_| patch_analysis.set(None),
+                            }
                         }
 
                         div { class: "patch-scroll",
@@ -114,6 +350,8 @@ pub fn tab_settings(patches_state: Signal<PatchesState>) -> Element {
                                             let name = patch.name.clone();
                                             let desc = truncate_ellipsis(&patch.description, 100);
                                             let rdnn = patch.rdnn.clone();
+                                            let analyze_path = patch.path.clone();
+                                            let analyze_name = patch.name.clone();
                                             rsx! {
                                                 div { class: "patch-row",
                                                     div { class: "patch-cell patch-cell-toggle",
@@ -141,6 +379,26 @@ pub fn tab_settings(patches_state: Signal<PatchesState>) -> Element {
                                                     div { class: "patch-cell patch-cell-name", {name} }
                                                     div { class: "patch-cell patch-cell-desc", {desc} }
                                                     div { class: "patch-cell patch-cell-rdnn", {rdnn} }
+                                                    div { class: "patch-cell",
+                                                        button {
+                                                            class: "ghost small",
+                                                            title: "Показать, что внутри DLL, не загружая её",
+                                                            onclick: move |_| {
+                                                                let path = analyze_path.clone();
+                                                                let name = analyze_name.clone();
+                                                                spawn(async move {
+                                                                    let res = tokio::task::spawn_blocking(move || marsey::analyze_patch(&path)).await;
+                                                                    let report = match res {
+                                                                        Ok(Ok(analysis)) => analysis.report(),
+                                                                        Ok(Err(e)) => format!("не удалось разобрать: {e}"),
+                                                                        Err(e) => format!("ошибка задачи: {e}"),
+                                                                    };
+                                                                    patch_analysis.set(Some((name, report)));
+                                                                });
+                                                            },
+                                                            "Анализ"
+                                                        }
+                                                    }
                                                 }
                                             }
                                         }
@@ -158,6 +416,7 @@ pub fn tab_settings(patches_state: Signal<PatchesState>) -> Element {
                                 onclick: move |_| {
                                     hub_error.set(None);
                                     hub_list.set(hub_urls::load_hub_urls());
+                                    hub_disabled.set(hub_urls::load_disabled_hub_urls());
                                     show_hub_settings.set(true);
                                 },
                                 "Настройка хаба"
@@ -190,7 +449,10 @@ pub fn tab_settings(patches_state: Signal<PatchesState>) -> Element {
                                         };
 
                                         let res = tokio::task::spawn_blocking(move || {
-                                            crate::core::cache_cleanup::clear_engines_cache(&data_dir)
+                                            let task = tasks::register(TaskKind::CacheCleanup, "Очистка движков", false);
+                                            let r = crate::core::cache_cleanup::clear_engines_cache(&data_dir);
+                                            task.finish(&r);
+                                            r
                                         })
                                         .await;
 
@@ -225,12 +487,17 @@ pub fn tab_settings(patches_state: Signal<PatchesState>) -> Element {
 
                                     game_cache_cleaning.set(true);
                                     game_error.set(None);
-                                    game_info.set(Some("очистка...".to_string()));
+                                    game_info.set(None);
 
                                     let mut game_error2 = game_error;
                                     let mut game_info2 = game_info;
                                     let mut game_cache_cleaning2 = game_cache_cleaning;
                                     spawn(async move {
+                                        if !clipboard::confirm(clipboard::CLEAR_CONTENT_QUESTION).await {
+                                            game_cache_cleaning2.set(false);
+                                            return;
+                                        }
+                                        game_info2.set(Some("очистка...".to_string()));
                                         let data_dir = match app_paths::data_dir() {
                                             Ok(d) => d,
                                             Err(e) => {
@@ -242,7 +509,10 @@ pub fn tab_settings(patches_state: Signal<PatchesState>) -> Element {
                                         };
 
                                         let res = tokio::task::spawn_blocking(move || {
-                                            crate::core::cache_cleanup::clear_server_content_cache(&data_dir)
+                                            let task = tasks::register(TaskKind::CacheCleanup, "Очистка контента серверов", false);
+                                            let r = crate::core::cache_cleanup::clear_server_content_cache(&data_dir);
+                                            task.finish(&r);
+                                            r
                                         })
                                         .await;
 
@@ -266,27 +536,475 @@ pub fn tab_settings(patches_state: Signal<PatchesState>) -> Element {
                                 },
                                 "Очистить контент серверов"
                             }
+
+                            button {
+                                class: "ghost",
+                                disabled: game_cache_cleaning(),
+                                title: "удалить временные файлы, оставшиеся от прерванных загрузок (старше часа)",
+                                onclick: move |_| {
+                                    if game_cache_cleaning() {
+                                        return;
+                                    }
+                                    let data_dir = match app_paths::data_dir() {
+                                        Ok(d) => d,
+                                        Err(e) => {
+                                            game_error.set(Some(e));
+                                            return;
+                                        }
+                                    };
+
+                                    game_cache_cleaning.set(true);
+                                    game_error.set(None);
+                                    game_info.set(Some("очистка...".to_string()));
+
+                                    let mut game_error2 = game_error;
+                                    let mut game_info2 = game_info;
+                                    let mut game_cache_cleaning2 = game_cache_cleaning;
+                                    spawn(async move {
+                                        let res = tokio::task::spawn_blocking(move || crate::cache_cleanup::sweep_temp_files(&data_dir)).await;
+                                        match res {
+                                            Ok(Ok(sweep)) if sweep.files == 0 => {
+                                                game_info2.set(Some("временных файлов не найдено".to_string()));
+                                            }
+                                            Ok(Ok(sweep)) => game_info2.set(Some(format!(
+                                                "удалено временных файлов: {}, освобождено {}",
+                                                sweep.files,
+                                                format_bytes(sweep.bytes)
+                                            ))),
+                                            Ok(Err(e)) => {
+                                                game_info2.set(None);
+                                                game_error2.set(Some(e));
+                                            }
+                                            Err(e) => {
+                                                game_info2.set(None);
+                                                game_error2.set(Some(format!("ошибка задачи: {e}")));
+                                            }
+                                        }
+                                        game_cache_cleaning2.set(false);
+                                    });
+                                },
+                                "Удалить временные файлы"
+                            }
                         }
 
                         if let Some(msg) = game_error() {
-                            p { class: "status status-error selectable", {msg} }
+                            ErrorBox { operation: "настройки игры", message: msg }
                         } else if let Some(msg) = game_info() {
                             p { class: "status status-info", {msg} }
                         }
+
+                        EnginesSection {}
+
+                        DataDirSection {}
+
+                        FavoritesSyncSection { launcher_settings }
+
+                        div { class: "form",
+                            label { "Кэш контента" }
+                            div { class: "hub-row",
+                                span { class: "muted",
+                                    {
+                                        let t = cache_totals();
+                                        format!(
+                                            "сборок: {} · blobs из кэша: {} · скачано: {} · сэкономлено {} (скачано {})",
+                                            t.builds,
+                                            t.blobs_from_cache,
+                                            t.blobs_downloaded,
+                                            format_bytes(t.bytes_saved),
+                                            format_bytes(t.bytes_downloaded),
+                                        )
+                                    }
+                                }
+                                button {
+                                    class: "ghost small",
+                                    onclick: move |_| {
+                                        match cache_stats::reset_cache_stats() {
+                                            Ok(()) => game_error.set(None),
+                                            Err(e) => game_error.set(Some(e)),
+                                        }
+                                        cache_totals.set(cache_stats::load_cache_stats());
+                                    },
+                                    "Сбросить"
+                                }
+                            }
+                        }
+
+                        div { class: "form",
+                            label { "Регион серверов" }
+                            div { class: "hub-row",
+                                input {
+                                    r#type: "checkbox",
+                                    checked: launcher_settings().server_list.geoip_regions,
+                                    onchange: move |_| {
+                                        let mut next = launcher_settings();
+                                        next.server_list.geoip_regions = !next.server_list.geoip_regions;
+                                        match settings::save_settings(&next) {
+                                            Ok(()) => game_error.set(None),
+                                            Err(e) => game_error.set(Some(e)),
+                                        }
+                                        launcher_settings.set(next);
+                                    }
+                                }
+                                span { class: "muted", "определять регион по GeoIP, если сервер его не указал (IP отправляется в api.country.is)" }
+                            }
+
+                            label { "Версия движка" }
+                            div { class: "hub-row",
+                                input {
+                                    r#type: "checkbox",
+                                    checked: launcher_settings().game.engine_version_fallback,
+                                    onchange: move |_| {
+                                        let mut next = launcher_settings();
+                                        next.game.engine_version_fallback = !next.game.engine_version_fallback;
+                                        match settings::save_settings(&next) {
+                                            Ok(()) => game_error.set(None),
+                                            Err(e) => game_error.set(Some(e)),
+                                        }
+                                        launcher_settings.set(next);
+                                    }
+                                }
+                                span { class: "muted", "предлагать ближайшую совместимую версию, если нужной нет в robust-builds" }
+                            }
+
+                            label { "Приоритет процесса игры" }
+                            select {
+                                class: "select",
+                                value: launcher_settings().game.process_priority.as_key(),
+                                onchange: move |evt| {
+                                    let Some(priority) = settings::ProcessPriority::from_key(&evt.value()) else {
+                                        return;
+                                    };
+                                    let mut next = launcher_settings();
+                                    next.game.process_priority = priority;
+                                    match settings::save_settings(&next) {
+                                        Ok(()) => game_error.set(None),
+                                        Err(e) => game_error.set(Some(e)),
+                                    }
+                                    launcher_settings.set(next);
+                                },
+                                for priority in settings::ProcessPriority::ALL {
+                                    option {
+                                        value: priority.as_key(),
+                                        selected: launcher_settings().game.process_priority == priority,
+                                        {priority.label_ru()}
+                                    }
+                                }
+                            }
+
+                            label { "Ядра процессора для игры" }
+                            input {
+                                class: "input",
+                                placeholder: "все (например: 0-3,6)",
+                                value: "{launcher_settings().game.cpu_affinity}",
+                                onchange: move |evt| {
+                                    let spec = evt.value().trim().to_string();
+                                    if let Err(e) = crate::core::process_priority::parse_cpu_list(&spec) {
+                                        game_error.set(Some(e));
+                                        return;
+                                    }
+                                    let mut next = launcher_settings();
+                                    next.game.cpu_affinity = spec;
+                                    match settings::save_settings(&next) {
+                                        Ok(()) => game_error.set(None),
+                                        Err(e) => game_error.set(Some(e)),
+                                    }
+                                    launcher_settings.set(next);
+                                }
+                            }
+
+                            label { "Ник без аккаунта" }
+                            input {
+                                class: "input",
+                                placeholder: settings::DEFAULT_GUEST_USERNAME,
+                                value: "{launcher_settings().game.guest_username}",
+                                onchange: move |evt| {
+                                    let name = match settings::validate_guest_username(&evt.value()) {
+                                        Ok(name) => name,
+                                        Err(e) => {
+                                            game_error.set(Some(e));
+                                            return;
+                                        }
+                                    };
+                                    let mut next = launcher_settings();
+                                    next.game.guest_username = name;
+                                    match settings::save_settings(&next) {
+                                        Ok(()) => game_error.set(None),
+                                        Err(e) => game_error.set(Some(e)),
+                                    }
+                                    launcher_settings.set(next);
+                                }
+                            }
+                            div { class: "hub-row",
+                                input {
+                                    r#type: "checkbox",
+                                    checked: launcher_settings().game.ask_guest_username,
+                                    onchange: move |_| {
+                                        let mut next = launcher_settings();
+                                        next.game.ask_guest_username = !next.game.ask_guest_username;
+                                        match settings::save_settings(&next) {
+                                            Ok(()) => game_error.set(None),
+                                            Err(e) => game_error.set(Some(e)),
+                                        }
+                                        launcher_settings.set(next);
+                                    }
+                                }
+                                span { class: "muted", "спрашивать ник при каждом подключении без аккаунта" }
+                            }
+
+                            label { "Закрытие лаунчера" }
+                            div { class: "hub-row",
+                                input {
+                                    r#type: "checkbox",
+                                    checked: launcher_settings().game.warn_on_close_while_preparing,
+                                    onchange: move |_| {
+                                        let mut next = launcher_settings();
+                                        next.game.warn_on_close_while_preparing = !next.game.warn_on_close_while_preparing;
+                                        match settings::save_settings(&next) {
+                                            Ok(()) => game_error.set(None),
+                                            Err(e) => game_error.set(Some(e)),
+                                        }
+                                        launcher_settings.set(next);
+                                    }
+                                }
+                                span { class: "muted", "предупреждать, если при закрытии идёт подготовка игры" }
+                            }
+
+                            label { "Дополнительные CVar" }
+                            textarea {
+                                class: "input cvar-input",
+                                placeholder: "по одному на строку, например: audio.master_volume=0.5",
+                                value: launcher_settings().game.extra_cvars.join("\n"),
+                                onchange: move |evt| {
+                                    let lines: Vec<String> = evt.value().lines().map(str::to_string).collect();
+                                    let cvars = match crate::ss14::cvars::validate_cvars(&lines) {
+                                        Ok(cvars) => cvars,
+                                        Err(e) => {
+                                            game_error.set(Some(format!("CVar не сохранены:\n{e}")));
+                                            return;
+                                        }
+                                    };
+                                    let mut next = launcher_settings();
+                                    next.game.extra_cvars = cvars;
+                                    match settings::save_settings(&next) {
+                                        Ok(()) => game_error.set(None),
+                                        Err(e) => game_error.set(Some(e)),
+                                    }
+                                    launcher_settings.set(next);
+                                }
+                            }
+
+                            label { "Окно подключения после запуска игры" }
+                            select {
+                                class: "select",
+                                value: launcher_settings().game.auto_close.as_key(),
+                                onchange: move |evt| {
+                                    let Some(mode) = settings::AutoClose::from_key(&evt.value()) else {
+                                        return;
+                                    };
+                                    let mut next = launcher_settings();
+                                    next.game.auto_close = mode;
+                                    match settings::save_settings(&next) {
+                                        Ok(()) => game_error.set(None),
+                                        Err(e) => game_error.set(Some(e)),
+                                    }
+                                    launcher_settings.set(next);
+                                },
+                                for mode in settings::AutoClose::ALL {
+                                    option {
+                                        value: mode.as_key(),
+                                        selected: launcher_settings().game.auto_close == mode,
+                                        {mode.label_ru()}
+                                    }
+                                }
+                            }
+                            if launcher_settings().game.auto_close == settings::AutoClose::Delay {
+                                label { "Закрыть через, с" }
+                                input {
+                                    class: "input",
+                                    r#type: "number",
+                                    min: "1",
+                                    value: "{launcher_settings().game.auto_close_delay_secs}",
+                                    onchange: move |evt| {
+                                        let Ok(secs) = evt.value().trim().parse::<u64>() else {
+                                            game_error.set(Some("нужно целое число секунд".to_string()));
+                                            return;
+                                        };
+                                        let mut next = launcher_settings();
+                                        next.game.auto_close_delay_secs = secs.clamp(1, 600);
+                                        match settings::save_settings(&next) {
+                                            Ok(()) => game_error.set(None),
+                                            Err(e) => game_error.set(Some(e)),
+                                        }
+                                        launcher_settings.set(next);
+                                    }
+                                }
+                            }
+
+                            label { "Таймаут соединения, с" }
+                            input {
+                                class: "input",
+                                r#type: "number",
+                                min: "1",
+                                value: "{launcher_settings().network.connect_timeout_secs}",
+                                onchange: move |evt| {
+                                    let Ok(secs) = evt.value().trim().parse::<u64>() else {
+                                        game_error.set(Some("нужно целое число секунд".to_string()));
+                                        return;
+                                    };
+                                    let mut next = launcher_settings();
+                                    next.network.connect_timeout_secs = secs;
+                                    next.network = next.network.clamped();
+                                    match settings::save_settings(&next) {
+                                        Ok(()) => game_error.set(None),
+                                        Err(e) => game_error.set(Some(e)),
+                                    }
+                                    launcher_settings.set(next);
+                                }
+                            }
+
+                            label { "Таймаут API запросов, с" }
+                            input {
+                                class: "input",
+                                r#type: "number",
+                                min: "1",
+                                value: "{launcher_settings().network.api_timeout_secs}",
+                                onchange: move |evt| {
+                                    let Ok(secs) = evt.value().trim().parse::<u64>() else {
+                                        game_error.set(Some("нужно целое число секунд".to_string()));
+                                        return;
+                                    };
+                                    let mut next = launcher_settings();
+                                    next.network.api_timeout_secs = secs;
+                                    next.network = next.network.clamped();
+                                    match settings::save_settings(&next) {
+                                        Ok(()) => game_error.set(None),
+                                        Err(e) => game_error.set(Some(e)),
+                                    }
+                                    launcher_settings.set(next);
+                                }
+                            }
+
+                            label { "Таймаут скачивания, с" }
+                            input {
+                                class: "input",
+                                r#type: "number",
+                                min: "1",
+                                value: "{launcher_settings().network.download_timeout_secs}",
+                                onchange: move |evt| {
+                                    let Ok(secs) = evt.value().trim().parse::<u64>() else {
+                                        game_error.set(Some("нужно целое число секунд".to_string()));
+                                        return;
+                                    };
+                                    let mut next = launcher_settings();
+                                    next.network.download_timeout_secs = secs;
+                                    next.network = next.network.clamped();
+                                    match settings::save_settings(&next) {
+                                        Ok(()) => game_error.set(None),
+                                        Err(e) => game_error.set(Some(e)),
+                                    }
+                                    launcher_settings.set(next);
+                                }
+                            }
+
+                            label { "Таймаут OPTIONS (ACZ), с" }
+                            input {
+                                class: "input",
+                                r#type: "number",
+                                min: "1",
+                                value: "{launcher_settings().network.options_timeout_secs}",
+                                onchange: move |evt| {
+                                    let Ok(secs) = evt.value().trim().parse::<u64>() else {
+                                        game_error.set(Some("нужно целое число секунд".to_string()));
+                                        return;
+                                    };
+                                    let mut next = launcher_settings();
+                                    next.network.options_timeout_secs = secs;
+                                    next.network = next.network.clamped();
+                                    match settings::save_settings(&next) {
+                                        Ok(()) => game_error.set(None),
+                                        Err(e) => game_error.set(Some(e)),
+                                    }
+                                    launcher_settings.set(next);
+                                }
+                            }
+                            button {
+                                class: "ghost small",
+                                onclick: move |_| {
+                                    let mut next = launcher_settings();
+                                    next.network = settings::NetworkSettings::default();
+                                    match settings::save_settings(&next) {
+                                        Ok(()) => game_error.set(None),
+                                        Err(e) => game_error.set(Some(e)),
+                                    }
+                                    launcher_settings.set(next);
+                                },
+                                "Таймауты по умолчанию"
+                            }
+
+                            label { "Хранить логи, дней (0 — всегда)" }
+                            input {
+                                class: "input",
+                                r#type: "number",
+                                min: "0",
+                                max: "{settings::MAX_RETENTION_DAYS}",
+                                value: "{launcher_settings().retention.logs_days}",
+                                onchange: move |evt| {
+                                    let Ok(days) = evt.value().trim().parse::<u32>() else {
+                                        game_error.set(Some("нужно целое число дней".to_string()));
+                                        return;
+                                    };
+                                    let mut next = launcher_settings();
+                                    next.retention.logs_days = days.min(settings::MAX_RETENTION_DAYS);
+                                    match settings::save_settings(&next) {
+                                        Ok(()) => game_error.set(None),
+                                        Err(e) => game_error.set(Some(e)),
+                                    }
+                                    launcher_settings.set(next);
+                                }
+                            }
+
+                            label { "Хранить историю серверов, дней (0 — всегда)" }
+                            input {
+                                class: "input",
+                                r#type: "number",
+                                min: "0",
+                                max: "{settings::MAX_RETENTION_DAYS}",
+                                value: "{launcher_settings().retention.history_days}",
+                                onchange: move |evt| {
+                                    let Ok(days) = evt.value().trim().parse::<u32>() else {
+                                        game_error.set(Some("нужно целое число дней".to_string()));
+                                        return;
+                                    };
+                                    let mut next = launcher_settings();
+                                    next.retention.history_days = days.min(settings::MAX_RETENTION_DAYS);
+                                    match settings::save_settings(&next) {
+                                        Ok(()) => game_error.set(None),
+                                        Err(e) => game_error.set(Some(e)),
+                                    }
+                                    launcher_settings.set(next);
+                                }
+                            }
+                        }
                     }
 
                     if show_hub_settings() {
                         HubSettingsModal {
                             urls: hub_list,
+                            disabled: hub_disabled,
                             error: hub_error,
                             on_close: move |_| show_hub_settings.set(false),
                         }
                     }
                 },
+                SettingsTab::About => rsx! {
+                    div { class: "patch-page",
+                        AboutPanel {}
+                    }
+                },
                 SettingsTab::Security => rsx! {
                     div { class: "patch-page",
                         if let Some(msg) = settings_error() {
-                            p { class: "status status-error selectable", {msg} }
+                            ErrorBox { operation: "настройки безопасности", message: msg }
                         }
 
                         div { class: "form",
@@ -331,6 +1049,31 @@ pub fn tab_settings(patches_state: Signal<PatchesState>) -> Element {
                                     selected: launcher_settings().security.hide_level == settings::HideLevel::Maximum,
                                     {settings::HideLevel::Maximum.label_ru()}
                                 }
+                                option {
+                                    value: settings::HideLevel::Auto.as_key(),
+                                    selected: launcher_settings().security.hide_level == settings::HideLevel::Auto,
+                                    {settings::HideLevel::Auto.label_ru()}
+                                }
+                            }
+
+                            if launcher_settings().security.hide_level == settings::HideLevel::Auto {
+                                label { "Правила авто-скрытия (JSON, fork_id → уровень)" }
+                                input {
+                                    class: "input",
+                                    r#type: "text",
+                                    placeholder: "https://example.com/hide_levels.json",
+                                    value: launcher_settings().security.hide_level_rules_url,
+                                    onchange: move |evt| {
+                                        let mut next = launcher_settings();
+                                        next.security.hide_level_rules_url = evt.value().trim().to_string();
+                                        match settings::save_settings(&next) {
+                                            Ok(()) => settings_error.set(None),
+                                            Err(e) => settings_error.set(Some(e)),
+                                        }
+                                        launcher_settings.set(next);
+                                    }
+                                }
+                                span { class: "muted", "уровень выбирается при подключении и пишется в лог; без правил используется средний" }
                             }
 
                             label { "Автоматический вход" }
@@ -386,6 +1129,24 @@ pub fn tab_settings(patches_state: Signal<PatchesState>) -> Element {
                                 }
                                 span { class: "muted", "автоудаление HWID" }
                             }
+
+                            label { "Передача токена" }
+                            div { class: "hub-row",
+                                input {
+                                    r#type: "checkbox",
+                                    checked: launcher_settings().security.token_via_pipe,
+                                    onchange: move |_| {
+                                        let mut next = launcher_settings();
+                                        next.security.token_via_pipe = !next.security.token_via_pipe;
+                                        match settings::save_settings(&next) {
+                                            Ok(()) => settings_error.set(None),
+                                            Err(e) => settings_error.set(Some(e)),
+                                        }
+                                        launcher_settings.set(next);
+                                    }
+                                }
+                                span { class: "muted", "через одноразовый pipe вместо переменной окружения (только Windows; если pipe не создать, используется переменная)" }
+                            }
                         }
                     }
                 },
@@ -394,13 +1155,61 @@ pub fn tab_settings(patches_state: Signal<PatchesState>) -> Element {
     }
 }
 
+#[component]
+fn PatchAnalysisModal(title: String, report: String, on_close: EventHandler<()>) -> Element {
+    let copy_report = report.clone();
+
+    rsx! {
+        div { class: "modal-backdrop",
+            div { class: "modal hub-modal",
+                div { class: "modal-header",
+                    div {
+                        h3 { {format!("анализ: {title}")} }
+                        p { class: "muted", "метаданные сборки; код патча не запускается" }
+                    }
+                }
+
+                div { class: "modal-body",
+                    pre { class: "patch-analysis selectable", {report} }
+                }
+
+                div { class: "modal-actions",
+                    button {
+                        class: "ghost",
+                        onclick: move |_| crate::ui::clipboard::copy_text(copy_report.clone()),
+                        "Копировать"
+                    }
+                    button {
+                        class: "primary",
+                        onclick: move |_| on_close.call(()),
+                        "закрыть"
+                    }
+                }
+            }
+        }
+    }
+}
+
 #[component]
 fn HubSettingsModal(
     urls: Signal<Vec<String>>,
+    /// Normalized URLs of hubs switched off without removing them.
+    disabled: Signal<Vec<String>>,
     error: Signal<Option<String>>,
     on_close: EventHandler<()>,
 ) -> Element {
     let mut saving = use_signal(|| false);
+    let mut latencies = use_signal(hub_latency::latencies);
+    let mut probing = use_signal(|| false);
+    let checks = hub_urls::check_hub_urls(&urls());
+    let valid_urls: Vec<String> = checks
+        .iter()
+        .filter_map(|c| match c {
+            hub_urls::HubUrlCheck::Valid(url) => Some(url.clone()),
+            _ => None,
+        })
+        .collect();
+    let has_invalid = checks.iter().any(|c| matches!(c, hub_urls::HubUrlCheck::Invalid(_)));
 
     rsx! {
         div { class: "modal-backdrop",
@@ -417,14 +1226,24 @@ fn HubSettingsModal(
                         label { "ссылки хаба" }
 
                         div { class: "hub-list",
-                            for (idx, item) in urls().iter().cloned().enumerate() {
+                            for (idx, (item, check)) in urls().iter().cloned().zip(checks.iter().cloned()).enumerate() {
                                 {
                                     let mut urls = urls;
+                                    let mut disabled = disabled;
+                                    let invalid = matches!(check, hub_urls::HubUrlCheck::Invalid(_));
+                                    let normalized = match &check {
+                                        hub_urls::HubUrlCheck::Valid(url) => Some(url.clone()),
+                                        _ => None,
+                                    };
+                                    let is_disabled = normalized
+                                        .as_ref()
+                                        .is_some_and(|url| disabled().contains(url));
                                     rsx! {
-                                        div { class: "hub-row",
+                                        div { class: if is_disabled { "hub-row hub-disabled" } else { "hub-row" },
                                             input {
                                                 r#type: "text",
-                                                value: item,
+                                                value: item.clone(),
+                                                class: if invalid { "invalid" } else { "" },
                                                 placeholder: "https://hub.example.com/",
                                                 oninput: move |evt| {
                                                     let mut list = urls();
@@ -434,6 +1253,22 @@ fn HubSettingsModal(
                                                     }
                                                 }
                                             }
+                                            if let Some(url) = normalized.clone() {
+                                                button {
+                                                    class: "ghost",
+                                                    title: "отключённый хаб остаётся в списке, но не опрашивается",
+                                                    onclick: move |_| {
+                                                        let mut list = disabled();
+                                                        if let Some(pos) = list.iter().position(|u| *u == url) {
+                                                            list.remove(pos);
+                                                        } else {
+                                                            list.push(url.clone());
+                                                        }
+                                                        disabled.set(list);
+                                                    },
+                                                    { if is_disabled { "Включить" } else { "Отключить" } }
+                                                }
+                                            }
                                             button {
                                                 class: "ghost",
                                                 onclick: move |_| {
@@ -446,6 +1281,31 @@ fn HubSettingsModal(
                                                 "Убрать"
                                             }
                                         }
+                                        match check.clone() {
+                                            hub_urls::HubUrlCheck::Invalid(e) => rsx! {
+                                                p { class: "field-error", {e} }
+                                            },
+                                            hub_urls::HubUrlCheck::Duplicate(first) => rsx! {
+                                                p { class: "muted field-note", {format!("совпадает со ссылкой {} — будет убрана при сохранении", first + 1)} }
+                                            },
+                                            hub_urls::HubUrlCheck::Valid(url) if url != item.trim() => rsx! {
+                                                p { class: "muted field-note", {format!("будет сохранено как {url}")} }
+                                            },
+                                            _ => rsx! {},
+                                        }
+                                        if is_disabled {
+                                            p { class: "muted field-note", "отключён: список серверов у него не запрашивается" }
+                                        } else if let hub_urls::HubUrlCheck::Valid(url) = &check {
+                                            match latencies().get(url).map(|l| l.rtt) {
+                                                Some(Some(rtt)) => rsx! {
+                                                    p { class: "muted field-note", {format!("отклик {} мс", rtt.as_millis())} }
+                                                },
+                                                Some(None) => rsx! {
+                                                    p { class: "field-error", "не отвечает" }
+                                                },
+                                                None => rsx! {},
+                                            }
+                                        }
                                     }
                                 }
                             }
@@ -460,10 +1320,25 @@ fn HubSettingsModal(
                             },
                             "Добавить ссылку"
                         }
+                        button {
+                            class: "ghost",
+                            disabled: probing() || valid_urls.is_empty(),
+                            onclick: move |_| {
+                                let to_probe = valid_urls.clone();
+                                probing.set(true);
+                                spawn(async move {
+                                    hub_latency::probe_hubs(&to_probe).await;
+                                    latencies.set(hub_latency::latencies());
+                                    probing.set(false);
+                                });
+                            },
+                            { if probing() { "Измеряем…" } else { "Измерить отклик" } }
+                        }
+                        p { class: "muted", "список серверов запрашивается сначала у самого быстрого хаба" }
                     }
 
                     if let Some(msg) = error() {
-                        p { class: "status status-error selectable", {msg} }
+                        ErrorBox { operation: "сохранение ссылок хаба", message: msg }
                     }
                 }
 
@@ -476,7 +1351,7 @@ fn HubSettingsModal(
                     }
                     button {
                         class: "primary",
-                        disabled: saving(),
+                        disabled: saving() || has_invalid,
                         onclick: move |_| {
                             if saving() {
                                 return;
@@ -486,7 +1361,7 @@ fn HubSettingsModal(
                             error.set(None);
 
                             let current = urls();
-                            match hub_urls::save_hub_urls(&current) {
+                            match hub_urls::save_hub_urls(&current, &disabled()) {
                                 Ok(normalized) => {
                                     urls.set(normalized);
                                     saving.set(false);
@@ -505,3 +1380,211 @@ fn HubSettingsModal(
         }
     }
 }
+
+#[component]
+fn PatchRepoModal(
+    launcher_settings: Signal<settings::LauncherSettings>,
+    patches_state: Signal<PatchesState>,
+    on_close: EventHandler<()>,
+) -> Element {
+    let mut index_url = use_signal(|| launcher_settings().patch_repo.index_url);
+    let mut new_key = use_signal(String::new);
+    let mut remote: Signal<Vec<patch_repo::RemotePatch>> = use_signal(Vec::new);
+    let mut busy = use_signal(|| false);
+    let mut error: Signal<Option<String>> = use_signal(|| None::<String>);
+    let mut info: Signal<Option<String>> = use_signal(|| None::<String>);
+
+    let mut save_repo = move |repo: settings::PatchRepoSettings| {
+        let mut next = launcher_settings();
+        next.patch_repo = repo;
+        match settings::save_settings(&next) {
+            Ok(()) => error.set(None),
+            Err(e) => error.set(Some(e)),
+        }
+        launcher_settings.set(next);
+    };
+
+    let trusted_keys = launcher_settings().patch_repo.trusted_keys;
+
+    rsx! {
+        div { class: "modal-backdrop",
+            div { class: "modal hub-modal",
+                div { class: "modal-header",
+                    div {
+                        h3 { "репозиторий патчей" }
+                        p { class: "muted", "индекс и каждый патч проверяются ed25519-подписью доверенного ключа" }
+                    }
+                }
+
+                div { class: "modal-body",
+                    div { class: "form",
+                        label { "ссылка на индекс (подпись: <ссылка>.sig)" }
+                        input {
+                            r#type: "text",
+                            value: index_url(),
+                            placeholder: "https://example.com/patches/index.json",
+                            oninput: move |evt| index_url.set(evt.value()),
+                            onchange: move |_| {
+                                let mut repo = launcher_settings().patch_repo;
+                                repo.index_url = index_url().trim().to_string();
+                                save_repo(repo);
+                            }
+                        }
+
+                        label { "доверенные ключи (hex, 32 байта)" }
+                        div { class: "hub-list",
+                            for (idx, key) in trusted_keys.iter().cloned().enumerate() {
+                                div { class: "hub-row",
+                                    span { class: "selectable patch-repo-key", {key} }
+                                    button {
+                                        class: "ghost",
+                                        onclick: move |_| {
+                                            let mut repo = launcher_settings().patch_repo;
+                                            if idx < repo.trusted_keys.len() {
+                                                repo.trusted_keys.remove(idx);
+                                                save_repo(repo);
+                                            }
+                                        },
+                                        "Убрать"
+                                    }
+                                }
+                            }
+                            div { class: "hub-row",
+                                input {
+                                    r#type: "text",
+                                    value: new_key(),
+                                    placeholder: "ed25519 public key",
+                                    oninput: move |evt| new_key.set(evt.value()),
+                                }
+                                button {
+                                    class: "ghost",
+                                    onclick: move |_| {
+                                        let key = new_key().trim().to_ascii_lowercase();
+                                        if let Err(e) = patch_repo::parse_trusted_key(&key) {
+                                            error.set(Some(e));
+                                            return;
+                                        }
+                                        let mut repo = launcher_settings().patch_repo;
+                                        if !repo.trusted_keys.contains(&key) {
+                                            repo.trusted_keys.push(key);
+                                        }
+                                        save_repo(repo);
+                                        new_key.set(String::new());
+                                    },
+                                    "Добавить ключ"
+                                }
+                            }
+                        }
+                    }
+
+                    if !remote().is_empty() {
+                        div { class: "hub-list patch-repo-list",
+                            for patch in remote().into_iter() {
+                                {
+                                    let title = patch.name.clone().unwrap_or_else(|| patch.filename.clone());
+                                    let desc = patch.description.clone().unwrap_or_default();
+                                    rsx! {
+                                        div { class: "hub-row",
+                                            div { class: "patch-repo-item",
+                                                span { {title} }
+                                                span { class: "muted", {desc} }
+                                            }
+                                            button {
+                                                class: "ghost",
+                                                disabled: busy(),
+                                                onclick: move |_| {
+                                                    if busy() {
+                                                        return;
+                                                    }
+                                                    busy.set(true);
+                                                    error.set(None);
+                                                    info.set(Some(format!("устанавливаем {}...", patch.filename)));
+                                                    let patch = patch.clone();
+                                                    let keys = launcher_settings().patch_repo.trusted_keys;
+                                                    spawn(async move {
+                                                        let res = tokio::task::spawn_blocking(move || {
+                                                            let data_dir = app_paths::data_dir()?;
+                                                            patch_repo::install_remote_patch(&data_dir, &patch, &keys)
+                                                        })
+                                                        .await;
+                                                        match res {
+                                                            Ok(Ok(path)) => {
+                                                                info.set(Some(format!("установлено: {}", path.display())));
+                                                                patches_state.set(PatchesState::refresh());
+                                                            }
+                                                            Ok(Err(e)) => {
+                                                                info.set(None);
+                                                                error.set(Some(e));
+                                                            }
+                                                            Err(e) => {
+                                                                info.set(None);
+                                                                error.set(Some(format!("ошибка задачи: {e}")));
+                                                            }
+                                                        }
+                                                        busy.set(false);
+                                                    });
+                                                },
+                                                "Установить"
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    if let Some(msg) = error() {
+                        ErrorBox { operation: "репозиторий патчей", message: msg }
+                    } else if let Some(msg) = info() {
+                        p { class: "status status-info", {msg} }
+                    }
+                }
+
+                div { class: "modal-actions",
+                    button {
+                        class: "ghost",
+                        disabled: busy(),
+                        onclick: move |_| on_close.call(()),
+                        "закрыть"
+                    }
+                    button {
+                        class: "primary",
+                        disabled: busy(),
+                        onclick: move |_| {
+                            if busy() {
+                                return;
+                            }
+                            busy.set(true);
+                            error.set(None);
+                            info.set(Some("загружаем индекс...".to_string()));
+                            let repo = launcher_settings().patch_repo;
+                            spawn(async move {
+                                let res = tokio::task::spawn_blocking(move || {
+                                    patch_repo::fetch_verified_index(&repo.index_url, &repo.trusted_keys)
+                                })
+                                .await;
+                                match res {
+                                    Ok(Ok(list)) => {
+                                        info.set(Some(format!("индекс проверен, патчей: {}", list.len())));
+                                        remote.set(list);
+                                    }
+                                    Ok(Err(e)) => {
+                                        info.set(None);
+                                        remote.set(Vec::new());
+                                        error.set(Some(e));
+                                    }
+                                    Err(e) => {
+                                        info.set(None);
+                                        error.set(Some(format!("ошибка задачи: {e}")));
+                                    }
+                                }
+                                busy.set(false);
+                            });
+                        },
+                        "загрузить индекс"
+                    }
+                }
+            }
+        }
+    }
+}
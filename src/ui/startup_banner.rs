@@ -0,0 +1,54 @@
+use dioxus::prelude::*;
+
+use crate::startup_checks::{self, StartupCheck, StartupWarning};
+
+/// Dismissible banner with the problems found by the startup checks.
+#[component]
+pub fn StartupBanner(on_open_settings: EventHandler<()>, on_login: EventHandler<()>) -> Element {
+    let mut warnings: Signal<Vec<StartupWarning>> = use_signal(Vec::new);
+
+    use_future(move || async move {
+        if let Ok(found) = tokio::task::spawn_blocking(startup_checks::run_startup_checks).await {
+            warnings.set(found);
+        }
+    });
+
+    if warnings().is_empty() {
+        return rsx! {};
+    }
+
+    rsx! {
+        div { class: "status status-error startup-banner",
+            for (idx, warning) in warnings().into_iter().enumerate() {
+                div { class: "hub-row",
+                    span { class: "selectable", {warning.message} }
+                    match warning.check {
+                        StartupCheck::DataDir | StartupCheck::Hub | StartupCheck::Settings => rsx! {
+                            button {
+                                class: "ghost small",
+                                onclick: move |_| on_open_settings.call(()),
+                                "Настройки"
+                            }
+                        },
+                        StartupCheck::Token => rsx! {
+                            button {
+                                class: "ghost small",
+                                onclick: move |_| on_login.call(()),
+                                "Войти"
+                            }
+                        },
+                        StartupCheck::Loader | StartupCheck::Wine | StartupCheck::Instance => rsx! {},
+                    }
+                    button {
+                        class: "ghost small",
+                        title: "скрыть",
+                        onclick: move |_| {
+                            warnings.write().remove(idx);
+                        },
+                        "✕"
+                    }
+                }
+            }
+        }
+    }
+}
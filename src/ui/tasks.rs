@@ -0,0 +1,173 @@
+use std::time::Duration;
+
+use dioxus::prelude::*;
+
+use crate::tasks::{self, TaskProgress, TaskSnapshot, TaskStatus};
+use crate::ui::home::helpers::format_bytes;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Title bar pill that opens the background tasks panel.
+#[component]
+pub fn TasksButton() -> Element {
+    let mut items: Signal<Vec<TaskSnapshot>> = use_signal(Vec::new);
+    let mut open = use_signal(|| false);
+
+    use_future(move || async move {
+        loop {
+            let next = tasks::snapshot();
+            if *items.peek() != next {
+                items.set(next);
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    });
+
+    let running = items()
+        .iter()
+        .filter(|t| t.status == TaskStatus::Running)
+        .count();
+
+    rsx! {
+        button {
+            class: format_args!("pill tasks-pill {}", if running > 0 { "active" } else { "" }),
+            title: "фоновые задачи",
+            onclick: move |_| open.set(true),
+            if running > 0 {
+                {format!("Задачи: {running}")}
+            } else {
+                "Задачи"
+            }
+        }
+
+        if open() {
+            TasksModal { items, on_close: move |_| open.set(false) }
+        }
+    }
+}
+
+#[component]
+fn TasksModal(items: Signal<Vec<TaskSnapshot>>, on_close: EventHandler<()>) -> Element {
+    let list = items();
+    let has_finished = list.iter().any(|t| t.status != TaskStatus::Running);
+
+    rsx! {
+        div { class: "modal-backdrop",
+            div { class: "modal tasks-modal",
+                div { class: "modal-header",
+                    div {
+                        h3 { "фоновые задачи" }
+                        p { class: "muted", "загрузки, сборка loader и очистка кэша" }
+                    }
+                }
+
+                div { class: "modal-body",
+                    if list.is_empty() {
+                        p { class: "muted", "сейчас ничего не выполняется" }
+                    } else {
+                        div { class: "task-list",
+                            for task in list.into_iter().rev() {
+                                TaskRow { key: "{task.id}", task }
+                            }
+                        }
+                    }
+                }
+
+                div { class: "modal-actions",
+                    button {
+                        class: "ghost modal-actions-left",
+                        disabled: !has_finished,
+                        onclick: move |_| {
+                            tasks::clear_finished();
+                            items.set(tasks::snapshot());
+                        },
+                        "убрать завершённые"
+                    }
+                    button {
+                        class: "ghost",
+                        onclick: move |_| on_close.call(()),
+                        "закрыть"
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn TaskRow(task: TaskSnapshot) -> Element {
+    let id = task.id;
+    let elapsed = task.started_at.elapsed().as_secs();
+    let (status_text, status_class) = match &task.status {
+        TaskStatus::Running => (format!("выполняется, {elapsed} с"), "status-info"),
+        TaskStatus::Done => ("готово".to_string(), "status-info"),
+        TaskStatus::Cancelled => ("отменено".to_string(), "status-info"),
+        TaskStatus::Failed(e) => (format!("ошибка: {e}"), "status-error"),
+    };
+    let running = task.status == TaskStatus::Running;
+
+    let (progress_text, fraction) = match task.progress {
+        Some(TaskProgress::Bytes { done, total }) => (
+            Some(format!(
+                "{}{}",
+                format_bytes(done),
+                total
+                    .map(|t| format!(" / {}", format_bytes(t)))
+                    .unwrap_or_default()
+            )),
+            total.filter(|t| *t > 0).map(|t| done as f64 / t as f64),
+        ),
+        Some(TaskProgress::Items { done, total }) => (
+            Some(format!(
+                "{done}{}",
+                total.map(|t| format!(" / {t}")).unwrap_or_default()
+            )),
+            total.filter(|t| *t > 0).map(|t| done as f64 / t as f64),
+        ),
+        None => (None, None),
+    };
+
+    rsx! {
+        div { class: "task-row",
+            div { class: "task-row-head",
+                div { class: "task-row-title",
+                    span { class: "tag", {task.kind.label_ru()} }
+                    span { {task.label.clone()} }
+                }
+                if running && task.cancellable {
+                    button {
+                        class: "ghost small",
+                        onclick: move |_| tasks::cancel(id),
+                        "Отменить"
+                    }
+                }
+            }
+
+            if let Some(detail) = task.detail.clone() {
+                p { class: "muted", {detail} }
+            }
+
+            if running {
+                if let Some(f) = fraction {
+                    div { class: "progress-determinate",
+                        div {
+                            class: "progress-determinate-bar",
+                            style: format!("width: {:.1}%;", (f * 100.0).clamp(0.0, 100.0)),
+                        }
+                    }
+                } else {
+                    div { class: "progress-indeterminate",
+                        div { class: "progress-indeterminate-bar" }
+                    }
+                }
+            }
+
+            p { class: "status {status_class} selectable",
+                {status_text}
+                if let Some(p) = progress_text {
+                    {format!(" · {p}")}
+                }
+            }
+        }
+    }
+}
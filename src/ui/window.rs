@@ -1,5 +1,5 @@
 use dioxus_desktop::tao::window::Icon;
-use dioxus_desktop::{Config, LogicalSize, WindowBuilder};
+use dioxus_desktop::{Config, LogicalSize, WindowBuilder, WindowCloseBehaviour};
 
 use crate::constants::{APP_TITLE, TASKBAR_ICON, TITLEBAR_ICON, WINDOW_SIZE};
 use crate::ui::icons::load_icon;
@@ -20,6 +20,8 @@ pub fn app_window() -> Config {
     let builder = apply_taskbar_icon(builder, taskbar_icon);
 
     Config::default()
+        // The app decides when to exit after a close; see the close handler in `ui::app`.
+        .with_close_behaviour(WindowCloseBehaviour::LastWindowHides)
         .with_menu(None)
         .with_disable_context_menu(true)
         .with_window(builder)